@@ -7,6 +7,10 @@ pub enum AlertType {
     OutOfStock,
     LowStock,
     CriticallyLow,
+    /// Forecast demand empties stock before a reorder could arrive
+    StockoutRisk,
+    /// Raised by an automation rule, with the rule's message
+    Rule(String),
 }
 
 impl std::fmt::Display for AlertType {
@@ -15,24 +19,20 @@ impl std::fmt::Display for AlertType {
             AlertType::OutOfStock => write!(f, "Out of Stock"),
             AlertType::LowStock => write!(f, "Low Stock"),
             AlertType::CriticallyLow => write!(f, "Critically Low"),
+            AlertType::StockoutRisk => write!(f, "Stock-out Risk"),
+            AlertType::Rule(message) => write!(f, "{}", message),
         }
     }
 }
 
 impl AlertType {
-    pub fn color(&self) -> iced::Color {
-        match self {
-            AlertType::OutOfStock => iced::Color::from_rgb(0.9, 0.3, 0.3),
-            AlertType::LowStock => iced::Color::from_rgb(0.9, 0.7, 0.3),
-            AlertType::CriticallyLow => iced::Color::from_rgb(0.9, 0.5, 0.2),
-        }
-    }
-
     pub fn icon(&self) -> &'static str {
         match self {
             AlertType::OutOfStock => "🚫",
             AlertType::LowStock => "⚠️",
             AlertType::CriticallyLow => "❗",
+            AlertType::StockoutRisk => "📉",
+            AlertType::Rule(_) => "📌",
         }
     }
 }
@@ -61,7 +61,7 @@ impl StockAlert {
             item_id: item.id.clone(),
             item_name: item.name.clone(),
             item_sku: item.sku.clone(),
-            current_quantity: item.quantity,
+            current_quantity: item.available(),
             alert_type,
             threshold,
             created_at: Utc::now().timestamp(),
@@ -127,17 +127,25 @@ impl AlertManager {
 
         let mut new_alerts = Vec::new();
 
+        // Alerts are raised against available stock (on hand minus allocated)
         for item in items {
-            // Check if alert already exists
-            let existing = self.active_alerts.iter().any(|a| a.item_id == item.id);
+            let available = item.available();
+
+            // Check if alert already exists; rule alerts stay until acknowledged
+            let is_stock_alert = |a: &StockAlert| a.item_id == item.id && !matches!(a.alert_type, AlertType::Rule(_));
+            let existing = self.active_alerts.iter().any(is_stock_alert);
 
             if existing {
                 // Update existing alert quantity
-                if let Some(alert) = self.active_alerts.iter_mut().find(|a| a.item_id == item.id) {
-                    alert.current_quantity = item.quantity;
+                if let Some(alert) = self.active_alerts.iter_mut().find(|a| is_stock_alert(a)) {
+                    alert.current_quantity = available;
                     
                     // Check if item is back in stock and remove alert
-                    if item.quantity >= self.settings.low_stock_threshold {
+                    let resolved = match alert.alert_type {
+                        AlertType::StockoutRisk => !Self::stockout_within_lead_time(item),
+                        _ => available >= self.settings.low_stock_threshold,
+                    };
+                    if resolved {
                         alert.acknowledged = true;
                     }
                 }
@@ -145,20 +153,26 @@ impl AlertManager {
             }
 
             // Create new alert if needed
-            if item.quantity == 0 {
+            if available == 0 {
                 new_alerts.push(StockAlert::new(item, AlertType::OutOfStock, 0));
-            } else if item.quantity <= self.settings.critically_low_threshold {
+            } else if available <= self.settings.critically_low_threshold {
                 new_alerts.push(StockAlert::new(
                     item,
                     AlertType::CriticallyLow,
                     self.settings.critically_low_threshold,
                 ));
-            } else if item.quantity <= self.settings.low_stock_threshold {
+            } else if available <= self.settings.low_stock_threshold {
                 new_alerts.push(StockAlert::new(
                     item,
                     AlertType::LowStock,
                     self.settings.low_stock_threshold,
                 ));
+            } else if Self::stockout_within_lead_time(item) {
+                new_alerts.push(StockAlert::new(
+                    item,
+                    AlertType::StockoutRisk,
+                    item.lead_time_days,
+                ));
             }
         }
 
@@ -176,6 +190,28 @@ impl AlertManager {
         new_alerts
     }
 
+    /// Whether the projected stock-out falls inside the supplier lead time
+    fn stockout_within_lead_time(item: &InventoryItem) -> bool {
+        item.lead_time_days > 0
+            && item
+                .days_of_cover()
+                .is_some_and(|days| days < item.lead_time_days as f64)
+    }
+
+    /// Adds an alert raised outside the stock checks, unless the same one is already active
+    pub fn raise(&mut self, alert: StockAlert) -> bool {
+        if !self.settings.enabled
+            || self
+                .active_alerts
+                .iter()
+                .any(|a| a.item_id == alert.item_id && a.alert_type == alert.alert_type)
+        {
+            return false;
+        }
+        self.active_alerts.push(alert);
+        true
+    }
+
     pub fn get_active_alerts(&self) -> &[StockAlert] {
         &self.active_alerts
     }
@@ -201,6 +237,12 @@ impl AlertManager {
         self.alert_history.extend(acknowledged);
     }
 
+    /// Drops active and past alerts that don't match `keep`
+    pub fn retain(&mut self, keep: impl Fn(&StockAlert) -> bool) {
+        self.active_alerts.retain(&keep);
+        self.alert_history.retain(&keep);
+    }
+
     pub fn get_alert_history(&self) -> &[StockAlert] {
         &self.alert_history
     }
@@ -243,3 +285,41 @@ impl<T> DrainFilter<T> for Vec<T> {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raises_and_resolves_stock_alerts() {
+        let mut items = vec![InventoryItem::new(
+            "Tape".to_string(),
+            "TAPE-1".to_string(),
+            "Consumables".to_string(),
+            String::new(),
+            String::new(),
+            8,
+            2.0,
+        )];
+        let mut alerts = AlertManager::new();
+
+        let raised = alerts.update_from_inventory(&items);
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].alert_type, AlertType::LowStock);
+        // An item already alerted for isn't alerted again
+        items[0].quantity = 2;
+        assert!(alerts.update_from_inventory(&items).is_empty());
+        assert_eq!(alerts.get_active_alerts()[0].current_quantity, 2);
+
+        let rule_alert = StockAlert::new(&items[0], AlertType::Rule("Check the seal".to_string()), 0);
+        assert!(alerts.raise(rule_alert.clone()));
+        assert!(!alerts.raise(rule_alert));
+
+        // Restocking resolves the stock alert but leaves the rule's
+        items[0].quantity = 50;
+        alerts.update_from_inventory(&items);
+        assert_eq!(alerts.get_active_alerts().len(), 1);
+        assert!(matches!(alerts.get_active_alerts()[0].alert_type, AlertType::Rule(_)));
+        assert_eq!(alerts.get_alert_history().len(), 1);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::InventoryItem;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days of outbound history used for consumption value
+pub const CONSUMPTION_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AbcClass {
+    A,
+    B,
+    C,
+}
+
+impl AbcClass {
+    pub const ALL: [AbcClass; 3] = [AbcClass::A, AbcClass::B, AbcClass::C];
+}
+
+impl std::fmt::Display for AbcClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbcClass::A => write!(f, "A"),
+            AbcClass::B => write!(f, "B"),
+            AbcClass::C => write!(f, "C"),
+        }
+    }
+}
+
+/// Cut-offs for the ABC and slow-moving reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisSettings {
+    /// Cumulative share of consumption value (percent) covered by class A
+    pub a_cutoff: f64,
+    /// Cumulative share of consumption value (percent) covered by classes A and B
+    pub b_cutoff: f64,
+    /// Days without outbound movement before an item counts as slow-moving
+    pub slow_moving_days: u32,
+    /// Days without outbound movement before an item counts as dead stock
+    pub dead_stock_days: u32,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        Self {
+            a_cutoff: 80.0,
+            b_cutoff: 95.0,
+            slow_moving_days: 90,
+            dead_stock_days: 180,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AbcRow {
+    pub item_id: String,
+    pub item_sku: String,
+    pub item_name: String,
+    pub units: u32,
+    pub consumption_value: f64,
+    /// Cumulative share of total consumption value up to and including this item (percent)
+    pub cumulative_share: f64,
+    pub class: AbcClass,
+}
+
+/// Ranks items by consumption value over the last year and assigns A/B/C classes.
+///
+/// Items are sorted by value, largest first, and classed by the cumulative
+/// share of value ranked above them. Items with no consumption are always C.
+pub fn abc_analysis(
+    items: &[InventoryItem],
+    history: &HashMap<String, Vec<(i64, u32)>>,
+    settings: &AnalysisSettings,
+    now: i64,
+) -> Vec<AbcRow> {
+    let since = now - CONSUMPTION_DAYS * SECONDS_PER_DAY;
+
+    let mut rows: Vec<AbcRow> = items
+        .iter()
+        .map(|item| {
+            let units: u32 = history
+                .get(&item.id)
+                .map(|samples| samples.iter().filter(|(ts, _)| *ts >= since).map(|(_, q)| q).sum())
+                .unwrap_or(0);
+            AbcRow {
+                item_id: item.id.clone(),
+                item_sku: item.sku.clone(),
+                item_name: item.name.clone(),
+                units,
+                consumption_value: units as f64 * item.price,
+                cumulative_share: 0.0,
+                class: AbcClass::C,
+            }
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        b.consumption_value
+            .total_cmp(&a.consumption_value)
+            .then_with(|| a.item_name.to_lowercase().cmp(&b.item_name.to_lowercase()))
+    });
+
+    let total: f64 = rows.iter().map(|row| row.consumption_value).sum();
+    let mut running = 0.0;
+    for row in &mut rows {
+        if total <= 0.0 || row.consumption_value <= 0.0 {
+            continue;
+        }
+        // The item that crosses a cut-off still belongs to the higher class
+        let before = running / total * 100.0;
+        running += row.consumption_value;
+        row.cumulative_share = running / total * 100.0;
+        row.class = if before < settings.a_cutoff {
+            AbcClass::A
+        } else if before < settings.b_cutoff {
+            AbcClass::B
+        } else {
+            AbcClass::C
+        };
+    }
+
+    rows
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    SlowMoving,
+    Dead,
+}
+
+impl std::fmt::Display for Movement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Movement::SlowMoving => write!(f, "Slow-moving"),
+            Movement::Dead => write!(f, "Dead Stock"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SlowMovingRow {
+    pub item_sku: String,
+    pub item_name: String,
+    pub last_outbound: Option<i64>,
+    pub days_idle: i64,
+    pub status: Movement,
+    pub quantity: u32,
+    /// Capital tied up in the idle stock
+    pub value: f64,
+}
+
+impl SlowMovingRow {
+    pub fn formatted_last_outbound(&self) -> String {
+        self.last_outbound
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "Never".to_string())
+    }
+}
+
+/// Items in stock with no outbound movement for the configured number of days.
+///
+/// Items that have never moved are measured from their creation date.
+pub fn slow_moving(
+    items: &[InventoryItem],
+    history: &HashMap<String, Vec<(i64, u32)>>,
+    settings: &AnalysisSettings,
+    now: i64,
+) -> Vec<SlowMovingRow> {
+    let mut rows: Vec<SlowMovingRow> = items
+        .iter()
+        .filter(|item| item.quantity > 0)
+        .filter_map(|item| {
+            let last_outbound = history
+                .get(&item.id)
+                .and_then(|samples| samples.iter().map(|(ts, _)| *ts).max());
+            let days_idle = (now - last_outbound.unwrap_or(item.created_at)).max(0) / SECONDS_PER_DAY;
+            let status = if days_idle >= settings.dead_stock_days as i64 {
+                Movement::Dead
+            } else if days_idle >= settings.slow_moving_days as i64 {
+                Movement::SlowMoving
+            } else {
+                return None;
+            };
+            Some(SlowMovingRow {
+                item_sku: item.sku.clone(),
+                item_name: item.name.clone(),
+                last_outbound,
+                days_idle,
+                status,
+                quantity: item.quantity,
+                value: item.total_value(),
+            })
+        })
+        .collect();
+
+    rows.sort_by(|a, b| b.value.total_cmp(&a.value));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classes_follow_cumulative_value() {
+        let now = 1_000 * SECONDS_PER_DAY;
+        let mut items = Vec::new();
+        let mut history = HashMap::new();
+        for (sku, units) in [("A1", 70), ("A2", 15), ("B1", 10), ("C1", 5), ("C2", 0)] {
+            let item = InventoryItem::new(
+                sku.to_string(),
+                sku.to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                1,
+                1.0,
+            );
+            history.insert(item.id.clone(), vec![(now, units)]);
+            items.push(item);
+        }
+
+        let rows = abc_analysis(&items, &history, &AnalysisSettings::default(), now);
+        let classes: Vec<(&str, AbcClass)> =
+            rows.iter().map(|r| (r.item_sku.as_str(), r.class)).collect();
+        assert_eq!(
+            classes,
+            vec![
+                ("A1", AbcClass::A),
+                ("A2", AbcClass::A),
+                ("B1", AbcClass::B),
+                ("C1", AbcClass::C),
+                ("C2", AbcClass::C),
+            ]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Settings for the app's opt-in HTTP API, and the tokens scripts use to call it.
+//!
+//! Every call needs an API token, which acts as the user it was issued for.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const DEFAULT_PORT: u16 = 8787;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub tokens: Vec<ApiToken>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            tokens: Vec::new(),
+        }
+    }
+}
+
+impl ApiSettings {
+    /// The stored token matching one presented in a request
+    pub fn find_token(&self, token: &str) -> Option<&ApiToken> {
+        let hash = hash_token(token);
+        self.tokens.iter().find(|stored| stored.token_hash == hash)
+    }
+}
+
+/// A token that lets a script act as a user. Only its hash is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub user_id: String,
+    pub token_hash: String,
+    pub created_at: i64,
+}
+
+impl ApiToken {
+    /// Creates a token for the user, returned with the secret to hand out once
+    pub fn generate(name: String, user_id: String) -> (Self, String) {
+        let secret = format!(
+            "inv_{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        let token = Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            user_id,
+            token_hash: hash_token(&secret),
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        (token, secret)
+    }
+
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.trim().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_tokens_by_hash() {
+        let (token, secret) = ApiToken::generate("Till".to_string(), "user-1".to_string());
+        assert!(!token.token_hash.contains(&secret));
+        let settings = ApiSettings {
+            tokens: vec![token],
+            ..Default::default()
+        };
+        assert_eq!(settings.find_token(&secret).map(|token| token.name.as_str()), Some("Till"));
+        assert!(settings.find_token("inv_wrong").is_none());
+    }
+}
@@ -23,10 +23,40 @@ pub enum AuditAction {
     // Settings actions
     SettingsChanged,
 
+    // Purchasing actions
+    PurchaseOrderCreated,
+
+    // Sales actions
+    CustomerCreated,
+    CustomerDeleted,
+    SalesOrderCreated,
+    SalesOrderFulfilled,
+    SalesOrderCancelled,
+
+    // Returns actions
+    ReturnCreated,
+    ReturnRestocked,
+    ReturnQuarantined,
+    ReturnedToVendor,
+    ReturnScrapped,
+
+    // Stocktake actions
+    StocktakeStarted,
+    StocktakeAdjusted,
+    StocktakePosted,
+    StocktakeCancelled,
+
+    // Alert actions
+    AlertAcknowledged,
+
+    // Automation actions
+    RuleFired,
+
     // Data actions
     DataExported,
     DataImported,
     DataCleared,
+    BackupRestored,
 }
 
 impl std::fmt::Display for AuditAction {
@@ -44,8 +74,26 @@ impl std::fmt::Display for AuditAction {
             AuditAction::UserUpdated => write!(f, "User Updated"),
             AuditAction::UserDeleted => write!(f, "User Deleted"),
             AuditAction::SettingsChanged => write!(f, "Settings Changed"),
+            AuditAction::PurchaseOrderCreated => write!(f, "Purchase Order Created"),
+            AuditAction::CustomerCreated => write!(f, "Customer Created"),
+            AuditAction::CustomerDeleted => write!(f, "Customer Deleted"),
+            AuditAction::SalesOrderCreated => write!(f, "Sales Order Created"),
+            AuditAction::SalesOrderFulfilled => write!(f, "Sales Order Fulfilled"),
+            AuditAction::SalesOrderCancelled => write!(f, "Sales Order Cancelled"),
+            AuditAction::ReturnCreated => write!(f, "Return Created"),
+            AuditAction::ReturnRestocked => write!(f, "Return Restocked"),
+            AuditAction::ReturnQuarantined => write!(f, "Return Quarantined"),
+            AuditAction::ReturnedToVendor => write!(f, "Returned to Vendor"),
+            AuditAction::ReturnScrapped => write!(f, "Return Scrapped"),
+            AuditAction::StocktakeStarted => write!(f, "Stocktake Started"),
+            AuditAction::StocktakeAdjusted => write!(f, "Stocktake Adjusted"),
+            AuditAction::StocktakePosted => write!(f, "Stocktake Posted"),
+            AuditAction::StocktakeCancelled => write!(f, "Stocktake Cancelled"),
+            AuditAction::AlertAcknowledged => write!(f, "Alert Acknowledged"),
+            AuditAction::RuleFired => write!(f, "Rule Fired"),
             AuditAction::DataExported => write!(f, "Data Exported"),
             AuditAction::DataImported => write!(f, "Data Imported"),
+            AuditAction::BackupRestored => write!(f, "Backup Restored"),
             AuditAction::DataCleared => write!(f, "Data Cleared"),
         }
     }
@@ -154,6 +202,18 @@ impl AuditLog {
             .collect()
     }
 
+    pub fn retain(&mut self, keep: impl FnMut(&AuditEntry) -> bool) {
+        self.entries.retain(keep);
+    }
+
+    /// Replaces who made each change with a placeholder, for sharing the log
+    pub fn redact_users(&mut self) {
+        for entry in &mut self.entries {
+            entry.user_id.clear();
+            entry.username = "redacted".to_string();
+        }
+    }
+
     pub fn get_recent(&self, count: usize) -> Vec<&AuditEntry> {
         let start_index = if self.entries.len() > count {
             self.entries.len() - count
@@ -182,3 +242,42 @@ impl AuditLog {
         csv
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(user_id: &str, action: AuditAction, entity_id: &str, details: &str) -> AuditEntry {
+        AuditEntry::new(
+            user_id.to_string(),
+            user_id.to_uppercase(),
+            action,
+            "item".to_string(),
+            Some(entity_id.to_string()),
+            details.to_string(),
+        )
+    }
+
+    #[test]
+    fn records_filters_and_caps_entries() {
+        let mut log = AuditLog::new();
+        log.add_entry(entry("ann", AuditAction::ItemCreated, "item-1", "Added Tape, 8 rolls"));
+        log.add_entry(entry("bo", AuditAction::ItemUpdated, "item-1", "Changed quantity"));
+        log.add_entry(entry("ann", AuditAction::ItemDeleted, "item-2", "Removed Glue"));
+
+        assert_eq!(log.filter_by_user("ann").len(), 2);
+        assert_eq!(log.filter_by_action(&AuditAction::ItemUpdated).len(), 1);
+        assert_eq!(log.filter_by_entity("item-1").len(), 2);
+        assert_eq!(log.get_recent(2)[0].details, "Removed Glue");
+        assert!(log.export_to_csv().lines().nth(1).unwrap().ends_with("Added Tape; 8 rolls"));
+
+        log.redact_users();
+        assert!(log.get_entries().iter().all(|entry| entry.username == "redacted"));
+
+        for index in 0..1000 {
+            log.add_entry(entry("bo", AuditAction::ItemUpdated, "item-3", &index.to_string()));
+        }
+        assert_eq!(log.get_entries().len(), 1000);
+        assert_eq!(log.get_entries()[0].details, "0");
+    }
+}
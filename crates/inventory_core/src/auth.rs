@@ -1,4 +1,4 @@
-use crate::user::{Session, User, UserRole};
+use crate::user::{Session, User, UserRole, UNUSABLE_PASSWORD_HASH};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -25,6 +25,11 @@ impl AuthStore {
         Self::default()
     }
 
+    /// A store with no accounts at all, not even the default admin
+    pub fn empty() -> Self {
+        Self { users: HashMap::new() }
+    }
+
     pub fn authenticate(&mut self, username: &str, password: &str) -> Option<Session> {
         let user = self
             .users
@@ -102,8 +107,8 @@ impl AuthStore {
     }
 
     /// Ensures the default admin user exists with correct credentials
-    /// This is needed because password_hash is not serialized for security,
-    /// so loaded users will have invalid password hashes
+    /// Files saved before password hashes were kept have no usable hash for
+    /// anyone, so the admin gets the default password back
     pub fn ensure_default_admin(&mut self) {
         // Check if admin user exists
         let admin_exists = self.users.values().any(|u| u.username == "admin");
@@ -116,15 +121,37 @@ impl AuthStore {
         } else {
             // Admin exists, but check if password_hash is valid
             // If it's the default invalid hash, recreate the admin user
-            if let Some(admin_user) = self.users.values_mut().find(|u| u.username == "admin") {
-                let default_hash = "$2b$12$XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX";
-                if admin_user.password_hash == default_hash {
-                    // Password hash is invalid, recreate admin user
-                    if let Ok(new_admin) = User::new("admin".to_string(), "admin123", UserRole::Admin) {
-                        admin_user.password_hash = new_admin.password_hash;
-                    }
+            if let Some(admin_user) = self.users.values_mut().find(|u| u.username == "admin")
+                && admin_user.password_hash == UNUSABLE_PASSWORD_HASH
+            {
+                // Password hash is invalid, recreate admin user
+                if let Ok(new_admin) = User::new("admin".to_string(), "admin123", UserRole::Admin) {
+                    admin_user.password_hash = new_admin.password_hash;
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authenticates_users_and_keeps_an_admin() {
+        let mut store = AuthStore::empty();
+        let admin = store.add_user("root".to_string(), "s3cret!", UserRole::Admin).unwrap();
+        let clerk = store.add_user("sam".to_string(), "hunter22", UserRole::User).unwrap();
+        assert!(store.add_user("sam".to_string(), "other", UserRole::Viewer).is_err());
+
+        let session = store.authenticate("sam", "hunter22").unwrap();
+        assert_eq!(session.user_id, clerk.id);
+        assert_eq!(session.role, UserRole::User);
+        store.update_user(&clerk.id, UserRole::User, false).unwrap();
+        assert!(store.authenticate("sam", "hunter22").is_none());
+
+        assert!(store.delete_user(&admin.id).is_err());
+        store.delete_user(&clerk.id).unwrap();
+        assert_eq!(store.get_all_users().len(), 1);
+    }
+}
@@ -205,6 +205,21 @@ pub fn validate_quantity(value_str: &str) -> ValidationResult<u32> {
     Ok(value)
 }
 
+/// Parses an optional whole-number field; an empty value means zero
+pub fn validate_optional_count(field: &str, value_str: &str) -> ValidationResult<u32> {
+    let trimmed = value_str.trim();
+    if trimmed.is_empty() {
+        return Ok(0);
+    }
+
+    trimmed
+        .parse()
+        .map_err(|_| ValidationError::InvalidInteger {
+            field: field.to_string(),
+            value: value_str.to_string(),
+        })
+}
+
 pub fn check_duplicate_sku(
     sku: &str,
     items: &[crate::inventory::InventoryItem],
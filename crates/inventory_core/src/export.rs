@@ -0,0 +1,635 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthStore;
+use crate::currency;
+use crate::inventory::InventoryItem;
+use crate::persistence::SavedState;
+use crate::scripting::{ComputedColumn, ComputedValues};
+use crate::schedule::Schedule;
+use crate::search::SearchFilter;
+use crate::settings::{AppSettings, View};
+use crate::spreadsheet::{self, SheetFormat};
+
+/// A kind of record that can be included in a data export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExportEntity {
+    Items,
+    Notes,
+    PurchaseOrders,
+    Customers,
+    SalesOrders,
+    StockMovements,
+    Returns,
+    Stocktakes,
+    AuditLog,
+    Alerts,
+    Settings,
+}
+
+impl ExportEntity {
+    pub const ALL: [ExportEntity; 11] = [
+        ExportEntity::Items,
+        ExportEntity::Notes,
+        ExportEntity::PurchaseOrders,
+        ExportEntity::Customers,
+        ExportEntity::SalesOrders,
+        ExportEntity::StockMovements,
+        ExportEntity::Returns,
+        ExportEntity::Stocktakes,
+        ExportEntity::AuditLog,
+        ExportEntity::Alerts,
+        ExportEntity::Settings,
+    ];
+}
+
+impl fmt::Display for ExportEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportEntity::Items => write!(f, "Items"),
+            ExportEntity::Notes => write!(f, "Notes"),
+            ExportEntity::PurchaseOrders => write!(f, "Purchase orders"),
+            ExportEntity::Customers => write!(f, "Customers"),
+            ExportEntity::SalesOrders => write!(f, "Sales orders"),
+            ExportEntity::StockMovements => write!(f, "Stock movements"),
+            ExportEntity::Returns => write!(f, "Returns"),
+            ExportEntity::Stocktakes => write!(f, "Stocktakes"),
+            ExportEntity::AuditLog => write!(f, "Audit log"),
+            ExportEntity::Alerts => write!(f, "Alerts"),
+            ExportEntity::Settings => write!(f, "Settings"),
+        }
+    }
+}
+
+/// Which items go into the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemScope {
+    #[default]
+    All,
+    /// The items matching the inventory search and filters
+    Filtered,
+    /// Items ticked in the export dialog
+    Selected,
+}
+
+impl ItemScope {
+    pub const ALL: [ItemScope; 3] = [ItemScope::All, ItemScope::Filtered, ItemScope::Selected];
+}
+
+impl fmt::Display for ItemScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemScope::All => write!(f, "All items"),
+            ItemScope::Filtered => write!(f, "Current filter"),
+            ItemScope::Selected => write!(f, "Selected items"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub entities: BTreeSet<ExportEntity>,
+    pub item_scope: ItemScope,
+    /// Limits audit entries and alerts to this range of UTC dates
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Leaves out users, audit usernames and prices
+    pub strip_sensitive: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            entities: ExportEntity::ALL.into_iter().collect(),
+            item_scope: ItemScope::All,
+            from: None,
+            to: None,
+            strip_sensitive: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn includes(&self, entity: ExportEntity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    pub fn toggle(&mut self, entity: ExportEntity) {
+        if !self.entities.remove(&entity) {
+            self.entities.insert(entity);
+        }
+    }
+
+    /// Whether everything is exported as is, like a full backup
+    pub fn is_full(&self) -> bool {
+        self.entities.len() == ExportEntity::ALL.len()
+            && self.item_scope == ItemScope::All
+            && self.from.is_none()
+            && self.to.is_none()
+            && !self.strip_sensitive
+    }
+
+    /// One line for the audit log
+    pub fn describe(&self) -> String {
+        if self.is_full() {
+            return "all data".to_string();
+        }
+        let mut parts: Vec<String> = self.entities.iter().map(|e| e.to_string().to_lowercase()).collect();
+        if parts.is_empty() {
+            parts.push("nothing".to_string());
+        }
+        let mut description = parts.join(", ");
+        if self.includes(ExportEntity::Items) && self.item_scope != ItemScope::All {
+            description.push_str(&format!(" ({})", self.item_scope.to_string().to_lowercase()));
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let bound = |date: Option<NaiveDate>| date.map(|d| d.to_string()).unwrap_or_else(|| "…".to_string());
+            description.push_str(&format!(", dated {} to {}", bound(self.from), bound(self.to)));
+        }
+        if self.strip_sensitive {
+            description.push_str(", sensitive data removed");
+        }
+        description
+    }
+
+    fn in_range(&self, timestamp: i64) -> bool {
+        let start = self
+            .from
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc().timestamp());
+        let end = self
+            .to
+            .and_then(|d| d.and_hms_opt(23, 59, 59))
+            .map(|d| d.and_utc().timestamp());
+        start.is_none_or(|start| timestamp >= start) && end.is_none_or(|end| timestamp <= end)
+    }
+}
+
+/// Parses an optional YYYY-MM-DD date typed in a form
+pub fn parse_optional_date(value: &str, name: &str) -> Result<Option<NaiveDate>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| format!("Enter the {} date as YYYY-MM-DD, or leave it empty", name))
+}
+
+/// Cuts a full saved state down to what the options ask for.
+///
+/// `item_ids` is the set of items in scope when not exporting all of them;
+/// stock movements and alerts are limited to the same items. The result is
+/// still a complete data file, so it can be imported again.
+pub fn scoped_state(mut state: SavedState, options: &ExportOptions, item_ids: Option<&HashSet<String>>) -> SavedState {
+    let include = |entity| options.includes(entity);
+
+    if !include(ExportEntity::Items) {
+        state.items.clear();
+    } else if let Some(ids) = item_ids {
+        state.items.retain(|item| ids.contains(&item.id));
+    }
+    if !include(ExportEntity::Notes) {
+        state.notes.clear();
+    }
+    if !include(ExportEntity::PurchaseOrders) {
+        state.purchase_orders.clear();
+    }
+    if !include(ExportEntity::Customers) {
+        state.customers.clear();
+    }
+    if !include(ExportEntity::SalesOrders) {
+        state.sales_orders.clear();
+    }
+    if !include(ExportEntity::StockMovements) {
+        state.stock_movements.clear();
+    } else if let Some(ids) = item_ids {
+        state.stock_movements.retain(|movement| ids.contains(&movement.item_id));
+    }
+    if !include(ExportEntity::Returns) {
+        state.returns.clear();
+    }
+    if !include(ExportEntity::Stocktakes) {
+        state.stocktakes.clear();
+    }
+
+    if include(ExportEntity::AuditLog) {
+        state.audit_log.retain(|entry| options.in_range(entry.timestamp));
+    } else {
+        state.audit_log = Default::default();
+    }
+
+    let alert_settings = state.alert_manager.settings().clone();
+    if include(ExportEntity::Alerts) {
+        state
+            .alert_manager
+            .retain(|alert| options.in_range(alert.created_at) && item_ids.is_none_or(|ids| ids.contains(&alert.item_id)));
+    } else {
+        state.alert_manager = Default::default();
+    }
+
+    if !include(ExportEntity::Settings) {
+        state.settings = AppSettings::default();
+        state.calculator_position = None;
+        state.sidebar_collapsed = false;
+        state.show_alerts_panel = false;
+        state.show_search_panel = false;
+        state.current_view = View::default();
+        *state.alert_manager.settings_mut() = Default::default();
+    } else {
+        *state.alert_manager.settings_mut() = alert_settings;
+    }
+
+    strip_credentials(&mut state);
+    if options.strip_sensitive {
+        strip_sensitive(&mut state);
+    }
+    state
+}
+
+/// Removes what could be used to log in or call in as someone: password
+/// hashes, API token hashes and webhook signing keys. Only backups keep them.
+fn strip_credentials(state: &mut SavedState) {
+    state.auth_store = AuthStore::empty();
+    state.settings.api.tokens.clear();
+    for webhook in &mut state.settings.webhooks {
+        webhook.secret.clear();
+    }
+}
+
+/// Removes who did what, the webhook targets, and every price
+fn strip_sensitive(state: &mut SavedState) {
+    state.settings.webhooks.clear();
+    state.audit_log.redact_users();
+
+    for item in &mut state.items {
+        item.price = 0.0;
+    }
+    for order in &mut state.purchase_orders {
+        order.created_by.clear();
+        for line in &mut order.lines {
+            line.unit_price = 0.0;
+        }
+    }
+    for order in &mut state.sales_orders {
+        order.created_by.clear();
+        for line in &mut order.lines {
+            line.unit_price = 0.0;
+        }
+    }
+    for rma in &mut state.returns {
+        rma.created_by.clear();
+        for line in &mut rma.lines {
+            line.unit_price = 0.0;
+        }
+    }
+    for session in &mut state.stocktakes {
+        session.created_by.clear();
+        for line in &mut session.lines {
+            line.unit_price = 0.0;
+        }
+    }
+    for movement in &mut state.stock_movements {
+        movement.created_by.clear();
+    }
+}
+
+/// What a scheduled export job writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobFormat {
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl JobFormat {
+    pub const ALL: [JobFormat; 3] = [JobFormat::Csv, JobFormat::Xlsx, JobFormat::Json];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            JobFormat::Csv => "csv",
+            JobFormat::Xlsx => "xlsx",
+            JobFormat::Json => "json",
+        }
+    }
+
+    /// Whether the job exports the inventory list, which can be filtered
+    pub fn is_item_list(self) -> bool {
+        self != JobFormat::Json
+    }
+}
+
+impl fmt::Display for JobFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobFormat::Csv => write!(f, "Inventory list (CSV)"),
+            JobFormat::Xlsx => write!(f, "Inventory list (Excel)"),
+            JobFormat::Json => write!(f, "Data backup (JSON)"),
+        }
+    }
+}
+
+/// Outcome of the last run of a scheduled export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub at: i64,
+    pub ok: bool,
+    pub message: String,
+}
+
+impl JobRun {
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.at, 0)
+            .map(|dt| dt.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// An export written to a folder on a schedule while the app is open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportJob {
+    pub id: String,
+    pub name: String,
+    pub format: JobFormat,
+    pub folder: PathBuf,
+    pub schedule: Schedule,
+    /// Number of files kept in the folder; older ones are deleted
+    pub keep: u32,
+    /// Inventory filter for item lists, saved when the job was created
+    pub filter: Option<SearchFilter>,
+    /// Leaves out users, audit usernames and prices from JSON backups
+    pub strip_sensitive: bool,
+    pub created_at: i64,
+    pub last_run: Option<JobRun>,
+}
+
+impl ExportJob {
+    /// Whether the schedule has fired since the job last ran, or since it was created
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Local>) -> bool {
+        let since = self.last_run.as_ref().map_or(self.created_at, |run| run.at);
+        self.schedule.is_due(since, now)
+    }
+
+    /// File names start with the job name so retention only touches this job's files
+    fn file_stem(&self) -> String {
+        let stem: String = self
+            .name
+            .trim()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        let stem = stem.trim_matches('_');
+        if stem.is_empty() { "export".to_string() } else { stem.to_string() }
+    }
+}
+
+/// Writes one export for the job into its folder and prunes old files.
+///
+/// Returns a one-line summary for the last-run status and the audit log.
+pub fn run_job(job: &ExportJob, state: SavedState) -> Result<String, String> {
+    std::fs::create_dir_all(&job.folder)
+        .map_err(|e| format!("Could not open {}: {}", job.folder.display(), e))?;
+
+    let currency_code = state.settings.preferred_currency.clone();
+    let items = match &job.filter {
+        Some(filter) => filter.apply(&state.items),
+        None => state.items.clone(),
+    };
+    let (bytes, what) = match job.format {
+        JobFormat::Csv => (
+            inventory_csv(&items, &currency_code, &state.settings.computed_columns)
+                .into_bytes(),
+            format!("{} items", items.len()),
+        ),
+        JobFormat::Xlsx => (
+            spreadsheet::inventory_workbook(&items, &currency_code, SheetFormat::Xlsx)?,
+            format!("{} items", items.len()),
+        ),
+        JobFormat::Json => {
+            let options = ExportOptions {
+                strip_sensitive: job.strip_sensitive,
+                ..Default::default()
+            };
+            let what = if options.is_full() { "all data".to_string() } else { options.describe() };
+            let state = scoped_state(state, &options, None);
+            let json = serde_json::to_string_pretty(&state)
+                .map_err(|e| format!("Could not serialize the export: {}", e))?;
+            (json.into_bytes(), what)
+        }
+    };
+
+    let stem = job.file_stem();
+    let extension = job.format.extension();
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let path = job.folder.join(format!("{}_{}.{}", stem, timestamp, extension));
+    std::fs::write(&path, bytes).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+
+    let removed = prune(&job.folder, &stem, extension, job.keep)
+        .map_err(|e| format!("Wrote {} but could not remove old files: {}", path.display(), e))?;
+    let mut summary = format!("Wrote {} to {}", what, path.display());
+    if removed > 0 {
+        summary.push_str(&format!(", removed {} old file(s)", removed));
+    }
+    Ok(summary)
+}
+
+/// Deletes all but the newest `keep` files named `<stem>_<timestamp>.<extension>`
+fn prune(folder: &Path, stem: &str, extension: &str, keep: u32) -> std::io::Result<usize> {
+    let prefix = format!("{}_", stem);
+    let suffix = format!(".{}", extension);
+    let mut files: Vec<(String, PathBuf)> = std::fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let timestamp = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+            // Only names this job wrote: YYYYMMDD_HHMMSS
+            let is_ours = timestamp.len() == 15
+                && timestamp.char_indices().all(|(i, c)| if i == 8 { c == '_' } else { c.is_ascii_digit() });
+            is_ours.then(|| (timestamp.to_string(), entry.path()))
+        })
+        .collect();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut removed = 0;
+    for (_, path) in files.into_iter().skip(keep.max(1) as usize) {
+        std::fs::remove_file(path)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// The inventory list as CSV, with prices formatted in the given currency and
+/// a column after the standard ones for each computed column
+pub fn inventory_csv(items: &[InventoryItem], currency_code: &str, columns: &[ComputedColumn]) -> String {
+    let computed = ComputedValues::compute(columns, items);
+    let mut csv = String::new();
+    let mut header = format!(
+        "Name,SKU,Category,Supplier,Description,Quantity,Price ({currency}),Total Value ({currency}),Created At,Updated At",
+        currency = currency_code
+    );
+    for column in columns {
+        header.push(',');
+        header.push_str(&csv_escape(&column.name));
+    }
+    header.push('\n');
+    csv.push_str(&header);
+
+    for item in items {
+        let price = currency::format_amount(item.price, currency_code);
+        let total = currency::format_amount(item.total_value(), currency_code);
+        let created_at = chrono::DateTime::from_timestamp(item.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let updated_at = chrono::DateTime::from_timestamp(item.updated_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let mut row = format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            csv_escape(&item.name),
+            csv_escape(&item.sku),
+            csv_escape(&item.category),
+            csv_escape(&item.supplier),
+            csv_escape(&item.description),
+            item.quantity,
+            csv_escape(&price),
+            csv_escape(&total),
+            csv_escape(&created_at),
+            csv_escape(&updated_at),
+        );
+        for index in 0..columns.len() {
+            row.push(',');
+            row.push_str(&csv_escape(computed.get(&item.id, index)));
+        }
+        row.push('\n');
+        csv.push_str(&row);
+    }
+
+    csv
+}
+
+pub fn csv_escape(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') || value.contains('\r') {
+        let escaped = value.replace('"', "\"\"");
+        format!("\"{}\"", escaped)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditAction, AuditEntry};
+
+    fn item(name: &str, sku: &str) -> InventoryItem {
+        InventoryItem::new(
+            name.to_string(),
+            sku.to_string(),
+            "General".to_string(),
+            "Acme".to_string(),
+            String::new(),
+            5,
+            12.5,
+        )
+    }
+
+    #[test]
+    fn scopes_and_strips_an_export() {
+        let mut state = SavedState {
+            items: vec![item("Bolt", "B-1"), item("Nut", "N-1")],
+            ..Default::default()
+        };
+        let mut entry = AuditEntry::new(
+            "u1".to_string(),
+            "alice".to_string(),
+            AuditAction::ItemCreated,
+            "item".to_string(),
+            None,
+            "Created Bolt".to_string(),
+        );
+        entry.timestamp = NaiveDate::from_ymd_opt(2024, 3, 10)
+            .and_then(|d| d.and_hms_opt(12, 0, 0))
+            .map(|d| d.and_utc().timestamp())
+            .unwrap_or_default();
+        state.audit_log.add_entry(entry.clone());
+        entry.timestamp += 86_400 * 30;
+        state.audit_log.add_entry(entry);
+
+        let mut options = ExportOptions {
+            from: NaiveDate::from_ymd_opt(2024, 3, 1),
+            to: NaiveDate::from_ymd_opt(2024, 3, 31),
+            strip_sensitive: true,
+            ..Default::default()
+        };
+        options.toggle(ExportEntity::Notes);
+        let ids: HashSet<String> = [state.items[0].id.clone()].into_iter().collect();
+
+        let exported = scoped_state(state, &options, Some(&ids));
+        assert_eq!(exported.items.len(), 1);
+        assert_eq!(exported.items[0].name, "Bolt");
+        assert_eq!(exported.items[0].price, 0.0);
+        assert_eq!(exported.audit_log.get_entries().len(), 1);
+        assert_eq!(exported.audit_log.get_entries()[0].username, "redacted");
+        assert!(exported.auth_store.get_all_users().is_empty());
+        assert!(!options.is_full());
+        assert!(options.describe().contains("sensitive data removed"));
+    }
+
+    #[test]
+    fn exports_never_contain_credentials() {
+        let mut state = SavedState::default();
+        state.auth_store.add_user("dana".to_string(), "dana-pass", crate::user::UserRole::Manager).unwrap();
+        let (token, _) = crate::api::ApiToken::generate("Till".to_string(), "user-1".to_string());
+        state.settings.api.tokens.push(token);
+        state.settings.webhooks.push(crate::webhooks::Webhook::new(
+            "Shop".to_string(),
+            "https://example.com/hook".to_string(),
+            Default::default(),
+        ));
+        let secret = state.settings.webhooks[0].secret.clone();
+
+        let options = ExportOptions::default();
+        assert!(options.is_full());
+        let json = serde_json::to_string(&scoped_state(state, &options, None)).unwrap();
+        assert!(!json.contains("password_hash"));
+        assert!(!json.contains("token_hash"));
+        assert!(!json.contains(&secret));
+    }
+
+    #[test]
+    fn prunes_only_this_jobs_old_files() {
+        let folder = std::env::temp_dir().join(format!("export_prune_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&folder).unwrap();
+        for name in [
+            "nightly_20240101_020000.csv",
+            "nightly_20240102_020000.csv",
+            "nightly_20240103_020000.csv",
+            "nightly_notes.csv",
+            "other_20240101_020000.csv",
+        ] {
+            std::fs::write(folder.join(name), "").unwrap();
+        }
+
+        assert_eq!(prune(&folder, "nightly", "csv", 2).unwrap(), 1);
+        let mut left: Vec<String> = std::fs::read_dir(&folder)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "nightly_20240102_020000.csv",
+                "nightly_20240103_020000.csv",
+                "nightly_notes.csv",
+                "other_20240101_020000.csv",
+            ]
+        );
+        std::fs::remove_dir_all(&folder).ok();
+    }
+}
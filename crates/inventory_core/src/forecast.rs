@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::audit::{AuditAction, AuditEntry};
+use crate::stock::{MovementKind, StockMovement};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days of history considered when estimating demand
+pub const HISTORY_DAYS: i64 = 56;
+
+/// Smoothing factor for the daily demand series; higher values favour recent days
+pub const SMOOTHING_ALPHA: f64 = 0.3;
+
+/// Outbound units per item id with the time they left stock.
+///
+/// Issues from the stock ledger are used where an item has any; otherwise
+/// quantity decreases recorded on manual item edits in the audit log stand in.
+pub fn demand_history(
+    movements: &[StockMovement],
+    audit_entries: &[AuditEntry],
+) -> HashMap<String, Vec<(i64, u32)>> {
+    let mut history: HashMap<String, Vec<(i64, u32)>> = HashMap::new();
+
+    for movement in movements.iter().filter(|m| m.kind == MovementKind::Issue) {
+        history
+            .entry(movement.item_id.clone())
+            .or_default()
+            .push((movement.created_at, movement.quantity.unsigned_abs() as u32));
+    }
+
+    let mut fallback: HashMap<String, Vec<(i64, u32)>> = HashMap::new();
+    for entry in audit_entries
+        .iter()
+        .filter(|e| e.action == AuditAction::ItemUpdated)
+    {
+        let Some(item_id) = &entry.entity_id else {
+            continue;
+        };
+        let old = entry.old_value.as_deref().and_then(parse_quantity);
+        let new = entry.new_value.as_deref().and_then(parse_quantity);
+        if let (Some(old), Some(new)) = (old, new)
+            && new < old
+        {
+            fallback
+                .entry(item_id.clone())
+                .or_default()
+                .push((entry.timestamp, old - new));
+        }
+    }
+    for (item_id, samples) in fallback {
+        history.entry(item_id).or_insert(samples);
+    }
+
+    history
+}
+
+/// Reads the `Qty: N` field written into item audit values
+fn parse_quantity(value: &str) -> Option<u32> {
+    let rest = &value[value.find("Qty: ")? + 5..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Exponentially smoothed units per day over the recent history window.
+///
+/// The series starts at the first recorded outbound day (or the start of the
+/// window) so that new items are not diluted by days before they existed.
+pub fn daily_demand(samples: &[(i64, u32)], now: i64) -> f64 {
+    let today = now.div_euclid(SECONDS_PER_DAY);
+    let window_start = today - HISTORY_DAYS + 1;
+
+    let mut totals: HashMap<i64, u32> = HashMap::new();
+    for (timestamp, quantity) in samples {
+        let day = timestamp.div_euclid(SECONDS_PER_DAY);
+        if day >= window_start && day <= today {
+            *totals.entry(day).or_insert(0) += quantity;
+        }
+    }
+    let Some(first_day) = totals.keys().min().copied() else {
+        return 0.0;
+    };
+
+    let mut smoothed: Option<f64> = None;
+    for day in first_day..=today {
+        let units = totals.get(&day).copied().unwrap_or(0) as f64;
+        smoothed = Some(match smoothed {
+            Some(previous) => SMOOTHING_ALPHA * units + (1.0 - SMOOTHING_ALPHA) * previous,
+            None => units,
+        });
+    }
+    smoothed.unwrap_or(0.0)
+}
+
+/// Estimated daily demand per item id
+pub fn demand_by_item(history: &HashMap<String, Vec<(i64, u32)>>, now: i64) -> HashMap<String, f64> {
+    history
+        .iter()
+        .map(|(item_id, samples)| (item_id.clone(), daily_demand(samples, now)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoothing_weights_recent_days() {
+        let now = 100 * SECONDS_PER_DAY;
+        let steady: Vec<(i64, u32)> = (0..10).map(|d| (now - d * SECONDS_PER_DAY, 4)).collect();
+        assert!((daily_demand(&steady, now) - 4.0).abs() < 1e-9);
+
+        // A single old spike decays towards zero
+        let spike = [(now - 20 * SECONDS_PER_DAY, 100)];
+        assert!(daily_demand(&spike, now) < 1.0);
+        assert_eq!(daily_demand(&[], now), 0.0);
+    }
+
+    #[test]
+    fn parses_audit_quantities() {
+        assert_eq!(parse_quantity("Widget | W-1 | Qty: 12 | $3.00"), Some(12));
+        assert_eq!(parse_quantity("Qty: 7"), Some(7));
+        assert_eq!(parse_quantity("no quantity"), None);
+    }
+}
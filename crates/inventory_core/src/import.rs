@@ -0,0 +1,590 @@
+use std::fmt;
+use std::io::Cursor;
+
+use calamine::Reader;
+
+use crate::errors::{
+    check_duplicate_sku, validate_optional_count, validate_price, validate_quantity, validate_required,
+    validate_sku_format,
+};
+use crate::inventory::InventoryItem;
+
+/// Delimiters tried when reading a text file, with how they are described
+const DELIMITERS: [(u8, &str); 4] = [
+    (b',', "comma-separated"),
+    (b';', "semicolon-separated"),
+    (b'\t', "tab-separated"),
+    (b'|', "pipe-separated"),
+];
+/// File extensions read as workbooks rather than delimited text
+pub const SPREADSHEET_EXTENSIONS: [&str; 4] = ["xlsx", "xlsm", "xls", "ods"];
+/// Records looked at to pick the delimiter
+const SAMPLE_RECORDS: usize = 20;
+
+/// A file of item rows read into cells, before the columns are mapped
+#[derive(Debug, Clone)]
+pub struct ImportTable {
+    pub file_name: String,
+    /// How the file was read, e.g. "UTF-8, comma-separated"
+    pub format: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ImportTable {
+    /// Takes the first row as column headers and drops rows with no values
+    pub fn from_rows(file_name: String, format: String, mut rows: Vec<Vec<String>>) -> Result<Self, String> {
+        rows.retain(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+        if rows.is_empty() {
+            return Err("The file has no rows".to_string());
+        }
+        let headers: Vec<String> = rows.remove(0).into_iter().map(|h| h.trim().to_string()).collect();
+        if rows.is_empty() {
+            return Err("The file only has a header row".to_string());
+        }
+        Ok(Self {
+            file_name,
+            format,
+            headers,
+            rows,
+        })
+    }
+
+    /// Reads CSV or TSV, working out the text encoding and the delimiter
+    pub fn read_delimited(file_name: String, bytes: &[u8]) -> Result<Self, String> {
+        let (text, encoding) = decode(bytes);
+        let (delimiter, delimiter_name) = detect_delimiter(&text);
+
+        let rows = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes())
+            .records()
+            .map(|record| record.map(|r| r.iter().map(str::to_string).collect()))
+            .collect::<Result<Vec<Vec<String>>, _>>()
+            .map_err(|e| format!("Could not read the file: {}", e))?;
+
+        Self::from_rows(file_name, format!("{}, {}", encoding, delimiter_name), rows)
+    }
+
+    /// Reads the first sheet of an Excel or OpenDocument workbook
+    pub fn read_spreadsheet(file_name: String, bytes: &[u8]) -> Result<Self, String> {
+        let mut workbook = calamine::open_workbook_auto_from_rs(Cursor::new(bytes))
+            .map_err(|e| format!("Could not open the workbook: {}", e))?;
+        let format = match &workbook {
+            calamine::Sheets::Ods(_) => "OpenDocument spreadsheet",
+            _ => "Excel workbook",
+        };
+        let sheet = workbook.sheet_names().first().cloned().unwrap_or_default();
+        let range = workbook
+            .worksheet_range_at(0)
+            .ok_or("The workbook has no sheets")?
+            .map_err(|e| format!("Could not read the workbook: {}", e))?;
+
+        let rows = range.rows().map(|row| row.iter().map(cell_text).collect()).collect();
+        Self::from_rows(file_name, format!("{}, sheet \"{}\"", format, sheet), rows)
+    }
+
+    /// Reads a workbook or a delimited text file, going by the extension
+    pub fn read_file(file_name: String, bytes: &[u8]) -> Result<Self, String> {
+        let extension = file_name.rsplit('.').next().unwrap_or_default().to_lowercase();
+        if SPREADSHEET_EXTENSIONS.contains(&extension.as_str()) {
+            Self::read_spreadsheet(file_name, bytes)
+        } else {
+            Self::read_delimited(file_name, bytes)
+        }
+    }
+}
+
+/// Cell as text; whole numbers lose their ".0" so they validate as counts
+fn cell_text(cell: &calamine::Data) -> String {
+    match cell {
+        calamine::Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", *value as i64),
+        calamine::Data::DateTime(datetime) => datetime
+            .as_datetime()
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default(),
+        calamine::Data::Error(_) => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Decodes using the byte order mark, then UTF-8, then Windows-1252 as
+/// written by older spreadsheet programs
+fn decode(bytes: &[u8]) -> (String, &'static str) {
+    if let Some((encoding, bom_length)) = encoding_rs::Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return (text.into_owned(), encoding.name());
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), "UTF-8"),
+        Err(_) => {
+            let (text, _, _) = encoding_rs::WINDOWS_1252.decode(bytes);
+            (text.into_owned(), "Windows-1252")
+        }
+    }
+}
+
+/// Picks the delimiter that splits the first records into the same number
+/// of columns, preferring more columns
+fn detect_delimiter(text: &str) -> (u8, &'static str) {
+    let mut best = DELIMITERS[0];
+    let mut best_columns = 1;
+    for (delimiter, name) in DELIMITERS {
+        let counts: Vec<usize> = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes())
+            .records()
+            .take(SAMPLE_RECORDS)
+            .filter_map(Result::ok)
+            .map(|record| record.len())
+            .collect();
+        let Some(&columns) = counts.first() else {
+            continue;
+        };
+        if columns > best_columns && counts.iter().all(|&count| count == columns) {
+            best = (delimiter, name);
+            best_columns = columns;
+        }
+    }
+    best
+}
+
+/// Item field a column is imported into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportField {
+    Ignore,
+    Name,
+    Sku,
+    Category,
+    Supplier,
+    Location,
+    Description,
+    Quantity,
+    Price,
+    ReorderPoint,
+    ReorderQuantity,
+    LeadTime,
+}
+
+impl ImportField {
+    pub const ALL: [ImportField; 12] = [
+        ImportField::Ignore,
+        ImportField::Name,
+        ImportField::Sku,
+        ImportField::Category,
+        ImportField::Supplier,
+        ImportField::Location,
+        ImportField::Description,
+        ImportField::Quantity,
+        ImportField::Price,
+        ImportField::ReorderPoint,
+        ImportField::ReorderQuantity,
+        ImportField::LeadTime,
+    ];
+
+    /// Field a column header most likely refers to, e.g. "Price (USD)" or "Qty"
+    pub fn guess(header: &str) -> Self {
+        let header = header.to_lowercase();
+        let header = header.trim();
+        let matches = |names: &[&str]| names.iter().any(|name| header.starts_with(name));
+        if matches(&["sku", "item code", "product code", "part"]) {
+            ImportField::Sku
+        } else if matches(&["reorder point", "min"]) {
+            ImportField::ReorderPoint
+        } else if matches(&["reorder qty", "reorder quantity", "order qty"]) {
+            ImportField::ReorderQuantity
+        } else if matches(&["lead time"]) {
+            ImportField::LeadTime
+        } else if matches(&["name", "item", "product", "title"]) {
+            ImportField::Name
+        } else if matches(&["category", "group", "type"]) {
+            ImportField::Category
+        } else if matches(&["supplier", "vendor"]) {
+            ImportField::Supplier
+        } else if matches(&["location", "bin", "aisle"]) {
+            ImportField::Location
+        } else if matches(&["description", "notes"]) {
+            ImportField::Description
+        } else if matches(&["quantity", "qty", "stock", "on hand"]) {
+            ImportField::Quantity
+        } else if matches(&["price", "unit price", "cost"]) {
+            ImportField::Price
+        } else {
+            ImportField::Ignore
+        }
+    }
+}
+
+impl fmt::Display for ImportField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportField::Ignore => write!(f, "Don't import"),
+            ImportField::Name => write!(f, "Name"),
+            ImportField::Sku => write!(f, "SKU"),
+            ImportField::Category => write!(f, "Category"),
+            ImportField::Supplier => write!(f, "Supplier"),
+            ImportField::Location => write!(f, "Location"),
+            ImportField::Description => write!(f, "Description"),
+            ImportField::Quantity => write!(f, "Quantity"),
+            ImportField::Price => write!(f, "Price"),
+            ImportField::ReorderPoint => write!(f, "Reorder Point"),
+            ImportField::ReorderQuantity => write!(f, "Reorder Quantity"),
+            ImportField::LeadTime => write!(f, "Lead Time (days)"),
+        }
+    }
+}
+
+/// Mapping guessed from the headers; each field is used by one column at most
+pub fn guess_mapping(headers: &[String]) -> Vec<ImportField> {
+    let mut mapping: Vec<ImportField> = Vec::with_capacity(headers.len());
+    for header in headers {
+        let field = ImportField::guess(header);
+        if field != ImportField::Ignore && mapping.contains(&field) {
+            mapping.push(ImportField::Ignore);
+        } else {
+            mapping.push(field);
+        }
+    }
+    mapping
+}
+
+/// What to do with rows whose SKU is already in the inventory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    #[default]
+    CreateAndUpdate,
+    CreateOnly,
+    UpdateOnly,
+}
+
+impl ImportMode {
+    pub const ALL: [ImportMode; 3] = [
+        ImportMode::CreateAndUpdate,
+        ImportMode::CreateOnly,
+        ImportMode::UpdateOnly,
+    ];
+}
+
+impl fmt::Display for ImportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportMode::CreateAndUpdate => write!(f, "Create new items and update matching SKUs"),
+            ImportMode::CreateOnly => write!(f, "Create new items, skip matching SKUs"),
+            ImportMode::UpdateOnly => write!(f, "Update matching SKUs, skip new ones"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowAction {
+    Create,
+    /// Update the item with this id
+    Update(String),
+    Skip,
+}
+
+/// Values read from one row; `None` where the column is not mapped
+#[derive(Debug, Clone, Default)]
+pub struct RowValues {
+    pub name: Option<String>,
+    pub sku: String,
+    pub category: Option<String>,
+    pub supplier: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub quantity: Option<u32>,
+    pub price: Option<f64>,
+    pub reorder_point: Option<u32>,
+    pub reorder_quantity: Option<u32>,
+    pub lead_time_days: Option<u32>,
+}
+
+/// Outcome of one row in the dry run
+#[derive(Debug, Clone)]
+pub struct RowPreview {
+    /// Line in the file, counting the header as line 1
+    pub line: usize,
+    pub values: RowValues,
+    pub action: RowAction,
+    pub errors: Vec<String>,
+}
+
+/// Strips currency symbols and thousands separators; a lone comma followed
+/// by one or two digits is read as a decimal comma
+fn clean_number(value: &str) -> String {
+    let value = value.trim();
+    let decimal_comma = !value.contains('.')
+        && value
+            .rsplit_once(',')
+            .is_some_and(|(_, decimals)| (1..=2).contains(&decimals.trim().len()));
+    value
+        .chars()
+        .filter_map(|c| match c {
+            ',' if decimal_comma => Some('.'),
+            '0'..='9' | '.' | '-' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Runs every row through the item validation without changing anything
+pub fn preview(
+    table: &ImportTable,
+    mapping: &[ImportField],
+    mode: ImportMode,
+    items: &[InventoryItem],
+) -> Vec<RowPreview> {
+    // SKUs created earlier in the file, to catch duplicates within it
+    let mut planned: Vec<InventoryItem> = Vec::new();
+
+    table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let mut values = RowValues::default();
+            let mut errors = Vec::new();
+            let cell = |field: ImportField| {
+                mapping
+                    .iter()
+                    .position(|&f| f == field)
+                    .map(|column| row.get(column).map(|v| v.trim()).unwrap_or_default())
+            };
+            let text = |field| cell(field).map(str::to_string);
+            let count = |field, name: &str, errors: &mut Vec<String>| {
+                cell(field).and_then(|value| {
+                    validate_optional_count(name, &clean_number(value))
+                        .map_err(|e| errors.push(e.to_string()))
+                        .ok()
+                })
+            };
+
+            values.sku = cell(ImportField::Sku).unwrap_or_default().to_string();
+            values.name = text(ImportField::Name);
+            values.category = text(ImportField::Category);
+            values.supplier = text(ImportField::Supplier);
+            values.location = text(ImportField::Location);
+            values.description = text(ImportField::Description);
+            values.quantity = cell(ImportField::Quantity).and_then(|value| {
+                validate_quantity(&clean_number(value))
+                    .map_err(|e| errors.push(e.to_string()))
+                    .ok()
+            });
+            values.price = cell(ImportField::Price).and_then(|value| {
+                validate_price(&clean_number(value))
+                    .map_err(|e| errors.push(e.to_string()))
+                    .ok()
+            });
+            values.reorder_point = count(ImportField::ReorderPoint, "Reorder point", &mut errors);
+            values.reorder_quantity = count(ImportField::ReorderQuantity, "Reorder quantity", &mut errors);
+            values.lead_time_days = count(ImportField::LeadTime, "Lead time", &mut errors);
+
+            if let Err(e) = validate_sku_format(&values.sku) {
+                errors.insert(0, e.to_string());
+            }
+            if let Err(e) = check_duplicate_sku(&values.sku, &planned, None) {
+                errors.push(format!("{} earlier in the file", e));
+            }
+
+            let existing = check_duplicate_sku(&values.sku, items, None)
+                .err()
+                .and_then(|_| items.iter().find(|item| item.sku.eq_ignore_ascii_case(&values.sku)));
+            let mut action = match (existing, mode) {
+                (Some(item), ImportMode::CreateAndUpdate | ImportMode::UpdateOnly) => {
+                    RowAction::Update(item.id.clone())
+                }
+                (None, ImportMode::CreateAndUpdate | ImportMode::CreateOnly) => RowAction::Create,
+                _ => RowAction::Skip,
+            };
+            if action == RowAction::Create
+                && let Err(e) = validate_required("Name", values.name.as_deref().unwrap_or_default())
+            {
+                errors.push(e.to_string());
+            }
+
+            if errors.is_empty() {
+                if action != RowAction::Skip {
+                    planned.push(InventoryItem::new(
+                        String::new(),
+                        values.sku.clone(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        0,
+                        0.0,
+                    ));
+                }
+            } else {
+                action = RowAction::Skip;
+            }
+
+            RowPreview {
+                line: index + 2,
+                values,
+                action,
+                errors,
+            }
+        })
+        .collect()
+}
+
+/// Rows created, updated and skipped by an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+impl ImportSummary {
+    pub fn of(rows: &[RowPreview]) -> Self {
+        let mut summary = Self::default();
+        for row in rows {
+            match row.action {
+                RowAction::Create => summary.created += 1,
+                RowAction::Update(_) => summary.updated += 1,
+                RowAction::Skip => summary.skipped += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} created, {} updated, {} skipped",
+            self.created, self.updated, self.skipped
+        )
+    }
+}
+
+/// Creates and updates items as planned by `preview`
+pub fn apply(rows: &[RowPreview], items: &mut Vec<InventoryItem>, default_category: &str) -> ImportSummary {
+    for row in rows {
+        let values = &row.values;
+        let item = match &row.action {
+            RowAction::Create => {
+                items.push(InventoryItem::new(
+                    values.name.clone().unwrap_or_default(),
+                    values.sku.clone(),
+                    values.category.clone().unwrap_or_else(|| default_category.to_string()),
+                    values.supplier.clone().unwrap_or_default(),
+                    values.description.clone().unwrap_or_default(),
+                    values.quantity.unwrap_or(0),
+                    values.price.unwrap_or(0.0),
+                ));
+                items.last_mut().expect("item pushed above")
+            }
+            RowAction::Update(id) => {
+                let Some(item) = items.iter_mut().find(|item| item.id == *id) else {
+                    continue;
+                };
+                if let Some(name) = &values.name {
+                    item.name = name.clone();
+                }
+                if let Some(category) = &values.category {
+                    item.category = category.clone();
+                }
+                if let Some(supplier) = &values.supplier {
+                    item.supplier = supplier.clone();
+                }
+                if let Some(description) = &values.description {
+                    item.description = description.clone();
+                }
+                if let Some(quantity) = values.quantity {
+                    item.quantity = quantity;
+                }
+                if let Some(price) = values.price {
+                    item.price = price;
+                }
+                item.update_timestamp();
+                item
+            }
+            RowAction::Skip => continue,
+        };
+        if let Some(location) = &values.location {
+            item.location = location.clone();
+        }
+        if let Some(reorder_point) = values.reorder_point {
+            item.reorder_point = reorder_point;
+        }
+        if let Some(reorder_quantity) = values.reorder_quantity {
+            item.reorder_quantity = reorder_quantity;
+        }
+        if let Some(lead_time_days) = values.lead_time_days {
+            item.lead_time_days = lead_time_days;
+        }
+    }
+    ImportSummary::of(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_semicolon_file_in_windows_encoding() {
+        // "Café" in Windows-1252
+        let bytes = b"SKU;Name;Qty\nA-1;Caf\xe9;3\nA-2;Tea;5\n";
+        let table = ImportTable::read_delimited("items.csv".to_string(), bytes).unwrap();
+        assert_eq!(table.format, "Windows-1252, semicolon-separated");
+        assert_eq!(table.headers, vec!["SKU", "Name", "Qty"]);
+        assert_eq!(table.rows[0][1], "Café");
+        assert_eq!(
+            guess_mapping(&table.headers),
+            vec![ImportField::Sku, ImportField::Name, ImportField::Quantity]
+        );
+    }
+
+    #[test]
+    fn preview_reports_errors_and_matches_skus() {
+        let existing = InventoryItem::new(
+            "Widget".to_string(),
+            "W-1".to_string(),
+            "General".to_string(),
+            String::new(),
+            String::new(),
+            1,
+            2.0,
+        );
+        let rows = [
+            ["w-1", "Widget", "10", "$1,250.00"],
+            ["N-1", "Nut", "5", "0,25"],
+            ["N-1", "Nut again", "5", "1"],
+            ["bad sku", "Bolt", "-2", "1"],
+        ];
+        let table = ImportTable {
+            file_name: "items.csv".to_string(),
+            format: String::new(),
+            headers: vec!["SKU".into(), "Name".into(), "Quantity".into(), "Price".into()],
+            rows: rows.iter().map(|row| row.iter().map(|c| c.to_string()).collect()).collect(),
+        };
+        let mapping = guess_mapping(&table.headers);
+
+        let plan = preview(&table, &mapping, ImportMode::CreateAndUpdate, std::slice::from_ref(&existing));
+        assert_eq!(plan[0].action, RowAction::Update(existing.id.clone()));
+        assert_eq!(plan[0].values.price, Some(1250.0));
+        assert_eq!(plan[1].action, RowAction::Create);
+        assert_eq!(plan[1].values.price, Some(0.25));
+        assert_eq!(plan[2].action, RowAction::Skip);
+        assert_eq!(plan[2].errors.len(), 1);
+        assert_eq!(plan[3].errors.len(), 2);
+        assert_eq!(plan[3].line, 5);
+
+        let mut items = vec![existing];
+        let summary = apply(&plan, &mut items, "General");
+        assert_eq!(summary, ImportSummary { created: 1, updated: 1, skipped: 2 });
+        assert_eq!(items[0].quantity, 10);
+        assert_eq!(items[1].sku, "N-1");
+
+        let plan = preview(&table, &mapping, ImportMode::CreateOnly, &items);
+        assert_eq!(plan[0].action, RowAction::Skip);
+        assert!(plan[0].errors.is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryItem {
+    pub id: String,
+    pub name: String,
+    pub sku: String,
+    pub category: String,
+    pub supplier: String,
+    pub description: String,
+    pub quantity: u32,
+    pub price: f64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Reorder when quantity falls to this level (0 = use alert thresholds)
+    pub reorder_point: u32,
+    /// Preferred order size; suggestions are rounded up to a multiple of it
+    pub reorder_quantity: u32,
+    /// Supplier lead time in days
+    pub lead_time_days: u32,
+    /// Storage location, e.g. aisle or bin
+    pub location: String,
+    /// Units reserved by open sales orders (derived, recomputed on load)
+    #[serde(skip)]
+    pub allocated: u32,
+    /// Forecast units per day from recent outbound history (derived, recomputed on load)
+    #[serde(skip)]
+    pub daily_demand: f64,
+    /// ABC class by consumption value (derived, recomputed on load)
+    #[serde(skip)]
+    pub abc_class: Option<crate::analysis::AbcClass>,
+}
+
+impl InventoryItem {
+    pub fn new(
+        name: String,
+        sku: String,
+        category: String,
+        supplier: String,
+        description: String,
+        quantity: u32,
+        price: f64,
+    ) -> Self {
+        let now = Utc::now().timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            sku,
+            category,
+            supplier,
+            description,
+            quantity,
+            price,
+            created_at: now,
+            updated_at: now,
+            reorder_point: 0,
+            reorder_quantity: 0,
+            lead_time_days: 0,
+            location: String::new(),
+            allocated: 0,
+            daily_demand: 0.0,
+            abc_class: None,
+        }
+    }
+
+    pub fn update_timestamp(&mut self) {
+        self.updated_at = Utc::now().timestamp();
+    }
+
+    /// On-hand quantity not yet promised to a sales order
+    pub fn available(&self) -> u32 {
+        self.quantity.saturating_sub(self.allocated)
+    }
+
+    /// Days the available stock lasts at forecast demand, if there is any demand
+    pub fn days_of_cover(&self) -> Option<f64> {
+        (self.daily_demand > f64::EPSILON).then(|| self.available() as f64 / self.daily_demand)
+    }
+
+    /// Projected date the available stock runs out
+    pub fn projected_stockout(&self) -> Option<chrono::NaiveDate> {
+        let days = self.days_of_cover()?;
+        let date = Utc::now().date_naive();
+        date.checked_add_days(chrono::Days::new(days.floor().min(36_500.0) as u64))
+    }
+
+    pub fn total_value(&self) -> f64 {
+        self.quantity as f64 * self.price
+    }
+}
+
+/// The fields needed to add an item outside the item dialog, from the
+/// command line or the HTTP API
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NewItem {
+    pub name: String,
+    pub sku: String,
+    /// Falls back to the default category
+    pub category: Option<String>,
+    pub supplier: String,
+    pub description: String,
+    pub location: String,
+    pub quantity: u32,
+    pub price: f64,
+}
+
+impl NewItem {
+    /// Validates the fields the way the item dialog does and builds the item
+    pub fn build(self, items: &[InventoryItem], default_category: &str) -> Result<InventoryItem, String> {
+        let name = self.name.trim().to_string();
+        let sku = self.sku.trim().to_string();
+        crate::errors::validate_required("Name", &name).map_err(|e| e.to_string())?;
+        crate::errors::validate_length("Name", &name, 1, 200).map_err(|e| e.to_string())?;
+        crate::errors::validate_sku_format(&sku).map_err(|e| e.to_string())?;
+        crate::errors::check_duplicate_sku(&sku, items, None).map_err(|e| e.to_string())?;
+        crate::errors::validate_quantity(&self.quantity.to_string()).map_err(|e| e.to_string())?;
+        if !self.price.is_finite() || self.price < 0.0 {
+            return Err("Price must be zero or more".to_string());
+        }
+
+        let category = self
+            .category
+            .map(|category| category.trim().to_string())
+            .filter(|category| !category.is_empty())
+            .unwrap_or_else(|| default_category.to_string());
+        let mut item = InventoryItem::new(
+            name,
+            sku,
+            category,
+            self.supplier.trim().to_string(),
+            self.description,
+            self.quantity,
+            self.price,
+        );
+        item.location = self.location.trim().to_string();
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_new_items_and_projects_cover() {
+        let existing = vec![InventoryItem::new(
+            "Hex bolt".to_string(),
+            "BOLT-1".to_string(),
+            "Hardware".to_string(),
+            String::new(),
+            String::new(),
+            5,
+            0.2,
+        )];
+        let new_item = |sku: &str| NewItem {
+            name: " Washer ".to_string(),
+            sku: sku.to_string(),
+            quantity: 40,
+            price: 0.05,
+            ..Default::default()
+        };
+
+        let mut item = new_item("WASH-1").build(&existing, "General").unwrap();
+        assert_eq!(item.name, "Washer");
+        assert_eq!(item.category, "General");
+        assert!(new_item("BOLT-1").build(&existing, "General").is_err());
+        assert!(NewItem { price: -1.0, ..new_item("WASH-2") }.build(&existing, "General").is_err());
+
+        item.allocated = 10;
+        item.daily_demand = 3.0;
+        assert_eq!(item.available(), 30);
+        assert_eq!(item.days_of_cover(), Some(10.0));
+        assert!((item.total_value() - 2.0).abs() < 1e-9);
+        item.daily_demand = 0.0;
+        assert!(item.projected_stockout().is_none());
+    }
+}
@@ -0,0 +1,46 @@
+//! The data and rules behind Inventory Manager, without the desktop UI.
+//!
+//! Tools that read or change the app's data can depend on this crate instead
+//! of copying code. The main entry points are:
+//!
+//! - [`inventory::InventoryItem`], a stocked item and its reorder settings
+//! - [`search::SearchFilter`], which filters and sorts items
+//! - [`alerts::AlertManager`], which raises and resolves stock alerts
+//! - [`audit::AuditLog`], the record of who changed what
+//! - [`auth::AuthStore`], users, passwords and sessions
+//! - [`persistence`], which loads and saves a [`persistence::SavedState`] in
+//!   the app's JSON or SQLite data file, encrypted or not
+//!
+//! Everything here is plain data and logic; it doesn't depend on iced, and
+//! colors, icons and layout are left to the app.
+
+pub mod alerts;
+pub mod analysis;
+pub mod api;
+pub mod audit;
+pub mod auth;
+pub mod currency;
+pub mod errors;
+pub mod export;
+pub mod forecast;
+pub mod import;
+pub mod inventory;
+pub mod merge;
+pub mod note;
+pub mod pdf;
+pub mod persistence;
+pub mod purchasing;
+pub mod reorder;
+pub mod reports;
+pub mod returns;
+pub mod rules;
+pub mod sales;
+pub mod schedule;
+pub mod scripting;
+pub mod search;
+pub mod settings;
+pub mod spreadsheet;
+pub mod stock;
+pub mod stocktake;
+pub mod user;
+pub mod webhooks;
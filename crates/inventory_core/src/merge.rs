@@ -0,0 +1,431 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::inventory::InventoryItem;
+use crate::persistence::SavedState;
+use crate::note::Note;
+
+/// Appends the records from `theirs` whose id is not in `mine`; returns how many
+pub fn add_missing<T>(mine: &mut Vec<T>, theirs: Vec<T>, id: fn(&T) -> &str) -> usize {
+    let existing: HashSet<String> = mine.iter().map(|record| id(record).to_string()).collect();
+    let before = mine.len();
+    mine.extend(theirs.into_iter().filter(|record| !existing.contains(id(record))));
+    mine.len() - before
+}
+
+/// How an imported record relates to the data already here
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeStatus {
+    New,
+    Identical,
+    /// Matches an existing record that differs in at least one field
+    Conflict,
+    Skipped(String),
+}
+
+/// What to do with a conflicting record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    #[default]
+    KeepMine,
+    TakeTheirs,
+    /// Adds the imported record alongside, with a new id and SKU
+    KeepBoth,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 3] = [Resolution::KeepMine, Resolution::TakeTheirs, Resolution::KeepBoth];
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resolution::KeepMine => write!(f, "Keep mine"),
+            Resolution::TakeTheirs => write!(f, "Take theirs"),
+            Resolution::KeepBoth => write!(f, "Keep both"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    Item,
+    Note,
+}
+
+/// One field that differs between the existing and the imported record
+#[derive(Debug, Clone)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub mine: String,
+    pub theirs: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeRecord<T> {
+    pub theirs: T,
+    /// Id of the existing record it matched
+    pub mine_id: Option<String>,
+    pub label: String,
+    pub status: MergeStatus,
+    pub diffs: Vec<FieldDiff>,
+    pub resolution: Resolution,
+}
+
+/// Parts of the import, besides records, that can be left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOption {
+    Settings,
+    AlertSettings,
+    Audit,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    pub settings: bool,
+    pub alert_settings: bool,
+    pub audit: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            settings: false,
+            alert_settings: false,
+            audit: true,
+        }
+    }
+}
+
+impl MergeOptions {
+    pub fn toggle(&mut self, option: MergeOption) {
+        match option {
+            MergeOption::Settings => self.settings = !self.settings,
+            MergeOption::AlertSettings => self.alert_settings = !self.alert_settings,
+            MergeOption::Audit => self.audit = !self.audit,
+        }
+    }
+}
+
+/// An exported data file compared against the current data, waiting for
+/// the conflicts to be resolved
+#[derive(Debug, Clone)]
+pub struct MergePlan {
+    pub file_name: String,
+    pub items: Vec<MergeRecord<InventoryItem>>,
+    pub notes: Vec<MergeRecord<Note>>,
+    /// The rest of the file; its items and notes have been moved into the records above
+    pub state: SavedState,
+    pub options: MergeOptions,
+}
+
+fn diff(field: &'static str, mine: impl ToString, theirs: impl ToString, diffs: &mut Vec<FieldDiff>) {
+    let (mine, theirs) = (mine.to_string(), theirs.to_string());
+    if mine != theirs {
+        diffs.push(FieldDiff { field, mine, theirs });
+    }
+}
+
+fn item_diffs(mine: &InventoryItem, theirs: &InventoryItem) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    diff("Name", &mine.name, &theirs.name, &mut diffs);
+    diff("SKU", &mine.sku, &theirs.sku, &mut diffs);
+    diff("Category", &mine.category, &theirs.category, &mut diffs);
+    diff("Supplier", &mine.supplier, &theirs.supplier, &mut diffs);
+    diff("Location", &mine.location, &theirs.location, &mut diffs);
+    diff("Description", &mine.description, &theirs.description, &mut diffs);
+    diff("Quantity", mine.quantity, theirs.quantity, &mut diffs);
+    diff("Price", mine.price, theirs.price, &mut diffs);
+    diff("Reorder Point", mine.reorder_point, theirs.reorder_point, &mut diffs);
+    diff("Reorder Quantity", mine.reorder_quantity, theirs.reorder_quantity, &mut diffs);
+    diff("Lead Time", mine.lead_time_days, theirs.lead_time_days, &mut diffs);
+    diffs
+}
+
+fn note_diffs(mine: &Note, theirs: &Note) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    diff("Title", &mine.title, &theirs.title, &mut diffs);
+    diff("Content", &mine.content, &theirs.content, &mut diffs);
+    diffs
+}
+
+impl MergePlan {
+    pub fn new(file_name: String, mut state: SavedState, items: &[InventoryItem], notes: &[Note]) -> Self {
+        let mut seen_skus: HashSet<String> = HashSet::new();
+        let item_records = std::mem::take(&mut state.items)
+            .into_iter()
+            .map(|theirs| {
+                let label = format!("{} ({})", theirs.name, theirs.sku);
+                let sku = theirs.sku.to_lowercase();
+                let by_id = items.iter().find(|item| item.id == theirs.id);
+                let by_sku = items.iter().find(|item| item.sku.to_lowercase() == sku);
+                let mut record = MergeRecord {
+                    mine_id: None,
+                    label,
+                    status: MergeStatus::New,
+                    diffs: Vec::new(),
+                    resolution: Resolution::default(),
+                    theirs,
+                };
+
+                if !seen_skus.insert(sku) {
+                    record.status = MergeStatus::Skipped("The SKU appears more than once in the file".to_string());
+                    return record;
+                }
+                let mine = match (by_id, by_sku) {
+                    (Some(a), Some(b)) if a.id != b.id => {
+                        record.status = MergeStatus::Skipped(format!("The SKU belongs to a different item, {}", b.name));
+                        return record;
+                    }
+                    (Some(mine), _) | (None, Some(mine)) => mine,
+                    (None, None) => return record,
+                };
+                record.mine_id = Some(mine.id.clone());
+                record.diffs = item_diffs(mine, &record.theirs);
+                record.status = if record.diffs.is_empty() {
+                    MergeStatus::Identical
+                } else {
+                    MergeStatus::Conflict
+                };
+                record
+            })
+            .collect();
+
+        let note_records = std::mem::take(&mut state.notes)
+            .into_iter()
+            .map(|theirs| {
+                let mut record = MergeRecord {
+                    mine_id: None,
+                    label: theirs.title.clone(),
+                    status: MergeStatus::New,
+                    diffs: Vec::new(),
+                    resolution: Resolution::default(),
+                    theirs,
+                };
+                if let Some(mine) = notes.iter().find(|note| note.id == record.theirs.id) {
+                    record.mine_id = Some(mine.id.clone());
+                    record.diffs = note_diffs(mine, &record.theirs);
+                    record.status = if record.diffs.is_empty() {
+                        MergeStatus::Identical
+                    } else {
+                        MergeStatus::Conflict
+                    };
+                }
+                record
+            })
+            .collect();
+
+        Self {
+            file_name,
+            items: item_records,
+            notes: note_records,
+            state,
+            options: MergeOptions::default(),
+        }
+    }
+
+    pub fn set_resolution(&mut self, kind: RecordKind, index: usize, resolution: Resolution) {
+        match kind {
+            RecordKind::Item => {
+                if let Some(record) = self.items.get_mut(index) {
+                    record.resolution = resolution;
+                }
+            }
+            RecordKind::Note => {
+                if let Some(record) = self.notes.get_mut(index) {
+                    record.resolution = resolution;
+                }
+            }
+        }
+    }
+
+    /// Applies one choice to every conflict
+    pub fn resolve_all(&mut self, resolution: Resolution) {
+        for record in &mut self.items {
+            record.resolution = resolution;
+        }
+        for record in &mut self.notes {
+            record.resolution = resolution;
+        }
+    }
+
+    /// Counts of each status across items and notes
+    pub fn count(&self, status: fn(&MergeStatus) -> bool) -> usize {
+        self.items.iter().filter(|r| status(&r.status)).count() + self.notes.iter().filter(|r| status(&r.status)).count()
+    }
+
+    /// Merges the items and notes into the current data following the
+    /// chosen resolutions
+    pub fn apply_records(&mut self, items: &mut Vec<InventoryItem>, notes: &mut Vec<Note>) -> MergeSummary {
+        let mut summary = MergeSummary::default();
+        let now = chrono::Utc::now().timestamp();
+
+        for record in std::mem::take(&mut self.items) {
+            match (&record.status, record.resolution) {
+                (MergeStatus::New, _) => {
+                    items.push(record.theirs);
+                    summary.added += 1;
+                }
+                (MergeStatus::Identical, _) => summary.identical += 1,
+                (MergeStatus::Skipped(_), _) => summary.skipped += 1,
+                (MergeStatus::Conflict, Resolution::KeepMine) => summary.kept += 1,
+                (MergeStatus::Conflict, Resolution::TakeTheirs) => {
+                    let theirs = record.theirs;
+                    if let Some(mine) = items.iter_mut().find(|item| Some(&item.id) == record.mine_id.as_ref()) {
+                        mine.name = theirs.name;
+                        mine.sku = theirs.sku;
+                        mine.category = theirs.category;
+                        mine.supplier = theirs.supplier;
+                        mine.location = theirs.location;
+                        mine.description = theirs.description;
+                        mine.quantity = theirs.quantity;
+                        mine.price = theirs.price;
+                        mine.reorder_point = theirs.reorder_point;
+                        mine.reorder_quantity = theirs.reorder_quantity;
+                        mine.lead_time_days = theirs.lead_time_days;
+                        mine.updated_at = now;
+                    }
+                    summary.replaced += 1;
+                }
+                (MergeStatus::Conflict, Resolution::KeepBoth) => {
+                    let mut theirs = record.theirs;
+                    theirs.id = uuid::Uuid::new_v4().to_string();
+                    theirs.sku = unique_sku(&theirs.sku, items);
+                    theirs.updated_at = now;
+                    items.push(theirs);
+                    summary.both += 1;
+                }
+            }
+        }
+
+        for record in std::mem::take(&mut self.notes) {
+            match (&record.status, record.resolution) {
+                (MergeStatus::New, _) => {
+                    notes.push(record.theirs);
+                    summary.added += 1;
+                }
+                (MergeStatus::Identical, _) => summary.identical += 1,
+                (MergeStatus::Skipped(_), _) => summary.skipped += 1,
+                (MergeStatus::Conflict, Resolution::KeepMine) => summary.kept += 1,
+                (MergeStatus::Conflict, Resolution::TakeTheirs) => {
+                    if let Some(mine) = notes.iter_mut().find(|note| Some(&note.id) == record.mine_id.as_ref()) {
+                        mine.title = record.theirs.title;
+                        mine.content = record.theirs.content;
+                        mine.updated_at = now;
+                    }
+                    summary.replaced += 1;
+                }
+                (MergeStatus::Conflict, Resolution::KeepBoth) => {
+                    let mut theirs = record.theirs;
+                    theirs.id = uuid::Uuid::new_v4().to_string();
+                    theirs.title = format!("{} (imported)", theirs.title);
+                    notes.push(theirs);
+                    summary.both += 1;
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+/// `sku` with the lowest "-N" suffix no item uses yet
+fn unique_sku(sku: &str, items: &[InventoryItem]) -> String {
+    let taken: HashSet<String> = items.iter().map(|item| item.sku.to_lowercase()).collect();
+    (2..)
+        .map(|n| format!("{}-{}", sku, n))
+        .find(|candidate| !taken.contains(&candidate.to_lowercase()))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub identical: usize,
+    pub skipped: usize,
+    pub kept: usize,
+    pub replaced: usize,
+    pub both: usize,
+    /// Other records, such as orders and movements, added by id
+    pub other: usize,
+}
+
+impl fmt::Display for MergeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} new, {} identical, {} skipped; conflicts: {} kept mine, {} took theirs, {} kept both; {} other records added",
+            self.added, self.identical, self.skipped, self.kept, self.replaced, self.both, self.other
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, sku: &str, quantity: u32) -> InventoryItem {
+        InventoryItem::new(
+            name.to_string(),
+            sku.to_string(),
+            "Parts".to_string(),
+            String::new(),
+            String::new(),
+            quantity,
+            1.0,
+        )
+    }
+
+    fn plan(theirs: Vec<InventoryItem>, mine: &[InventoryItem]) -> MergePlan {
+        let state = SavedState {
+            items: theirs,
+            ..SavedState::default()
+        };
+        MergePlan::new("export.json".to_string(), state, mine, &[])
+    }
+
+    #[test]
+    fn classifies_imported_items() {
+        let mine = vec![item("Bolt", "B-1", 5), item("Nut", "N-1", 5), item("Washer", "W-1", 5)];
+        let mut same = mine[0].clone();
+        same.updated_at += 100;
+        let mut changed = mine[1].clone();
+        changed.quantity = 9;
+        let other_sku = InventoryItem { sku: "w-1".to_string(), ..mine[1].clone() };
+
+        let plan = plan(
+            vec![same, changed, item("Screw", "S-1", 1), item("Screw", "s-1", 1), other_sku],
+            &mine,
+        );
+        let statuses: Vec<&MergeStatus> = plan.items.iter().map(|r| &r.status).collect();
+        assert_eq!(statuses[0], &MergeStatus::Identical);
+        assert_eq!(statuses[1], &MergeStatus::Conflict);
+        assert_eq!(plan.items[1].diffs.len(), 1);
+        assert_eq!(plan.items[1].diffs[0].field, "Quantity");
+        assert_eq!(statuses[2], &MergeStatus::New);
+        assert!(matches!(statuses[3], MergeStatus::Skipped(_)));
+        assert!(matches!(statuses[4], MergeStatus::Skipped(_)));
+    }
+
+    #[test]
+    fn applies_resolutions() {
+        let mut mine = vec![item("Bolt", "B-1", 5), item("Nut", "N-1", 5), item("Washer", "B-1-2", 5)];
+        let theirs: Vec<InventoryItem> = mine
+            .iter()
+            .map(|existing| InventoryItem { quantity: 9, ..existing.clone() })
+            .collect();
+        let mut plan = plan(theirs, &mine);
+        plan.resolve_all(Resolution::KeepBoth);
+        plan.set_resolution(RecordKind::Item, 1, Resolution::TakeTheirs);
+        plan.set_resolution(RecordKind::Item, 2, Resolution::KeepMine);
+
+        let summary = plan.apply_records(&mut mine, &mut Vec::new());
+        assert_eq!((summary.both, summary.replaced, summary.kept), (1, 1, 1));
+        assert_eq!(mine.len(), 4);
+        assert_eq!(mine[1].quantity, 9);
+        assert_eq!(mine[2].quantity, 5);
+        // B-1-2 is taken, so the copy of B-1 gets the next free suffix
+        assert_eq!(mine[3].sku, "B-1-3");
+        assert_ne!(mine[3].id, mine[0].id);
+    }
+}
@@ -0,0 +1,176 @@
+use printpdf::image_crate::DynamicImage;
+use printpdf::{
+    BuiltinFont, Image, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point,
+};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const LINE_HEIGHT: f32 = 6.0;
+/// Space taken by the page header, including the rule under it
+const HEADER_HEIGHT: f32 = 22.0;
+const LOGO_HEIGHT: f32 = 16.0;
+
+/// Letterhead repeated at the top of every page. Pages also get a footer
+/// with the title and page number when a header is set.
+pub struct PageHeader {
+    pub company: String,
+    pub title: String,
+    /// Shown under the title, e.g. when and by whom the document was made
+    pub subtitle: String,
+    pub logo: Option<DynamicImage>,
+}
+
+/// Minimal A4 document writer used for packing slips and reports.
+///
+/// Text flows top to bottom and a new page is started automatically when
+/// the bottom margin is reached.
+pub struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+    header: Option<PageHeader>,
+    pages: Vec<PdfLayerReference>,
+}
+
+impl PdfWriter {
+    pub fn new(title: &str) -> Result<Self, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let font = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| e.to_string())?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| e.to_string())?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            pages: vec![layer.clone()],
+            layer,
+            font,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+            header: None,
+        })
+    }
+
+    /// Draws the header on this and every following page
+    pub fn with_header(mut self, header: PageHeader) -> Self {
+        self.header = Some(header);
+        self.draw_header();
+        self
+    }
+
+    /// Starts a new page and resets the cursor to the top margin
+    pub fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.pages.push(self.layer.clone());
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.draw_header();
+    }
+
+    fn draw_header(&mut self) {
+        let Some(header) = &self.header else {
+            return;
+        };
+        let top = PAGE_HEIGHT - MARGIN;
+        let mut text_x = MARGIN;
+        if let Some(logo) = &header.logo {
+            // Scaled so the logo is LOGO_HEIGHT tall whatever its resolution
+            let dpi = logo.height() as f32 * 25.4 / LOGO_HEIGHT;
+            Image::from_dynamic_image(logo).add_to_layer(
+                self.layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(MARGIN)),
+                    translate_y: Some(Mm(top - LOGO_HEIGHT)),
+                    dpi: Some(dpi),
+                    ..Default::default()
+                },
+            );
+            text_x += logo.width() as f32 * 25.4 / dpi + 4.0;
+        }
+        self.layer.use_text(&header.company, 14.0, Mm(text_x), Mm(top - 5.0), &self.bold);
+        self.layer.use_text(&header.title, 11.0, Mm(text_x), Mm(top - 10.5), &self.font);
+        self.layer.use_text(&header.subtitle, 8.0, Mm(text_x), Mm(top - 15.0), &self.font);
+
+        self.y = top - HEADER_HEIGHT + LINE_HEIGHT / 2.0;
+        self.rule();
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    pub fn title(&mut self, value: &str) {
+        self.ensure_space(LINE_HEIGHT * 2.0);
+        self.y -= LINE_HEIGHT * 1.5;
+        self.layer.use_text(value, 18.0, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= LINE_HEIGHT;
+    }
+
+    pub fn heading(&mut self, value: &str) {
+        self.ensure_space(LINE_HEIGHT * 2.0);
+        self.y -= LINE_HEIGHT * 1.2;
+        self.layer.use_text(value, 12.0, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= LINE_HEIGHT * 0.4;
+    }
+
+    pub fn text(&mut self, value: &str) {
+        self.ensure_space(LINE_HEIGHT);
+        self.y -= LINE_HEIGHT;
+        self.layer.use_text(value, 10.0, Mm(MARGIN), Mm(self.y), &self.font);
+    }
+
+    /// Writes one table row; `columns` pairs each cell with its x offset (mm from the left margin)
+    pub fn row(&mut self, columns: &[(&str, f32)], bold: bool) {
+        self.ensure_space(LINE_HEIGHT);
+        self.y -= LINE_HEIGHT;
+        let font = if bold { &self.bold } else { &self.font };
+        for (value, offset) in columns {
+            self.layer.use_text(*value, 10.0, Mm(MARGIN + offset), Mm(self.y), font);
+        }
+    }
+
+    pub fn rule(&mut self) {
+        self.ensure_space(LINE_HEIGHT / 2.0);
+        self.y -= LINE_HEIGHT / 2.0;
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    pub fn spacer(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    pub fn into_bytes(self) -> Result<Vec<u8>, String> {
+        // Page numbers are only known once everything has been written
+        if let Some(header) = &self.header {
+            let total = self.pages.len();
+            for (index, layer) in self.pages.iter().enumerate() {
+                let footer = format!("{} - {}", header.company, header.title);
+                layer.use_text(footer, 8.0, Mm(MARGIN), Mm(MARGIN / 2.0), &self.font);
+                layer.use_text(
+                    format!("Page {} of {}", index + 1, total),
+                    8.0,
+                    Mm(PAGE_WIDTH - MARGIN - 22.0),
+                    Mm(MARGIN / 2.0),
+                    &self.font,
+                );
+            }
+        }
+        self.doc.save_to_bytes().map_err(|e| e.to_string())
+    }
+}
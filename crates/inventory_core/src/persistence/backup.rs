@@ -0,0 +1,216 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{crypto, data_dir, write_atomic};
+use crate::persistence::{LoadError, SavedState};
+
+const FILE_PREFIX: &str = "inventory-";
+const FILE_TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+
+/// How often a snapshot of the state is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BackupFrequency {
+    Off,
+    Hourly,
+    #[default]
+    Daily,
+}
+
+impl BackupFrequency {
+    pub const ALL: [BackupFrequency; 3] = [
+        BackupFrequency::Off,
+        BackupFrequency::Hourly,
+        BackupFrequency::Daily,
+    ];
+
+    /// Minimum seconds between two automatic backups
+    fn interval(self) -> Option<i64> {
+        match self {
+            BackupFrequency::Off => None,
+            BackupFrequency::Hourly => Some(3_600),
+            BackupFrequency::Daily => Some(86_400),
+        }
+    }
+}
+
+impl std::fmt::Display for BackupFrequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupFrequency::Off => write!(f, "Off"),
+            BackupFrequency::Hourly => write!(f, "Hourly"),
+            BackupFrequency::Daily => write!(f, "Daily"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    pub frequency: BackupFrequency,
+    /// Number of backups kept; older ones are deleted
+    pub keep: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            frequency: BackupFrequency::Daily,
+            keep: 7,
+        }
+    }
+}
+
+/// A backup file with a summary of what it contains
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created_at: i64,
+    pub items: usize,
+    pub notes: usize,
+    pub users: usize,
+    /// Set when the file cannot be read back
+    pub unreadable: bool,
+}
+
+impl BackupInfo {
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+fn backup_dir() -> PathBuf {
+    let dir = data_dir().join("backups");
+    std::fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Creation time encoded in a backup file name
+fn backup_timestamp(path: &Path) -> Option<i64> {
+    let stem = path.file_stem()?.to_str()?;
+    let stamp = stem.strip_prefix(FILE_PREFIX)?;
+    NaiveDateTime::parse_from_str(stamp, FILE_TIMESTAMP)
+        .ok()
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Backup files, newest first
+fn backup_files() -> Vec<(PathBuf, i64)> {
+    let mut files: Vec<(PathBuf, i64)> = std::fs::read_dir(backup_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|path| backup_timestamp(&path).map(|ts| (path, ts)))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by_key(|(_, ts)| std::cmp::Reverse(*ts));
+    files
+}
+
+/// Writes a snapshot of the state into the backup folder
+pub fn write_backup(state: &SavedState) -> std::io::Result<PathBuf> {
+    let name = format!("{}{}.json", FILE_PREFIX, Utc::now().format(FILE_TIMESTAMP));
+    let path = backup_dir().join(name);
+    let json = serde_json::to_string_pretty(state)?;
+    write_atomic(&path, crypto::encode(json).as_bytes())?;
+    Ok(path)
+}
+
+/// Takes a backup if the newest one is older than the configured frequency,
+/// then deletes all but the newest `keep` backups
+pub fn rotate(state: &SavedState) -> std::io::Result<()> {
+    let settings = &state.settings.backups;
+    let Some(interval) = settings.frequency.interval() else {
+        return Ok(());
+    };
+
+    let files = backup_files();
+    let due = files
+        .first()
+        .is_none_or(|(_, newest)| Utc::now().timestamp() - newest >= interval);
+    if !due {
+        return Ok(());
+    }
+
+    write_backup(state)?;
+    for (path, _) in backup_files().into_iter().skip(settings.keep.max(1) as usize) {
+        std::fs::remove_file(path).ok();
+    }
+    Ok(())
+}
+
+/// All backups, newest first, with the number of items, notes and users in each
+pub fn list_backups() -> Vec<BackupInfo> {
+    backup_files()
+        .into_iter()
+        .map(|(path, created_at)| {
+            let value: Option<Value> = std::fs::read_to_string(&path).ok().and_then(|contents| {
+                crypto::decode(&contents)
+                    .ok()
+                    .and_then(|json| serde_json::from_str(&json).ok())
+            });
+            let count = |value: Option<&Value>| match value {
+                Some(Value::Array(list)) => list.len(),
+                Some(Value::Object(map)) => map.len(),
+                _ => 0,
+            };
+            BackupInfo {
+                items: count(value.as_ref().and_then(|v| v.get("items"))),
+                notes: count(value.as_ref().and_then(|v| v.get("notes"))),
+                users: count(value.as_ref().and_then(|v| v.pointer("/auth_store/users"))),
+                unreadable: value.is_none(),
+                path,
+                created_at,
+            }
+        })
+        .collect()
+}
+
+pub fn read_backup(path: &Path) -> Result<SavedState, LoadError> {
+    let contents = std::fs::read_to_string(path).map_err(|_| LoadError::FileNotFound)?;
+    super::parse_state(&crypto::decode(&contents)?)
+}
+
+/// Encrypts every backup again with the key that is active now.
+///
+/// `previous` is the key the backups were written with; backups it cannot
+/// open are left alone. Returns the backups that could not be read or
+/// written, which still need `previous`.
+pub fn reencrypt(previous: Option<&crypto::Cipher>) -> Vec<PathBuf> {
+    let mut failed = Vec::new();
+    for (path, _) in backup_files() {
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            failed.push(path);
+            continue;
+        };
+        let plaintext = match (crypto::Envelope::parse(&contents), previous) {
+            (None, _) => contents,
+            (Some(envelope), Some(cipher)) => match cipher.open(&envelope) {
+                Ok(plaintext) => plaintext,
+                Err(_) => continue,
+            },
+            (Some(_), None) => continue,
+        };
+        if write_atomic(&path, crypto::encode(plaintext).as_bytes()).is_err() {
+            failed.push(path);
+        }
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_is_read_from_file_name() {
+        let path = Path::new("/tmp/backups/inventory-20240301-091500.json");
+        assert_eq!(backup_timestamp(path), Some(1_709_284_500));
+        assert_eq!(backup_timestamp(Path::new("/tmp/backups/notes.json")), None);
+    }
+}
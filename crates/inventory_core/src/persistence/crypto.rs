@@ -0,0 +1,255 @@
+use std::borrow::Cow;
+use std::sync::{Arc, RwLock};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::LoadError;
+
+/// Marks a file as an encrypted envelope rather than plain saved state
+const ENVELOPE_FORMAT: &str = "inventory-app-encrypted";
+/// Prefix of encrypted values stored inside the SQLite database
+const TEXT_PREFIX: &str = "enc:";
+/// Encrypted in the key file so a passphrase can be checked without the data
+const CHECK_TEXT: &str = "inventory-app";
+const NONCE_LEN: usize = 24;
+
+/// Argon2id settings and salt used to turn a passphrase into a key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// Current recommended settings with a fresh random salt
+    pub fn generate() -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            memory_kib: 19_456,
+            iterations: 2,
+            parallelism: 1,
+            salt: BASE64.encode(salt),
+        }
+    }
+}
+
+/// An encrypted document: the KDF settings needed to re-derive the key, and
+/// the XChaCha20-Poly1305 nonce and ciphertext
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    format: String,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Envelope {
+    /// Returns the envelope if `contents` is one
+    pub fn parse(contents: &str) -> Option<Self> {
+        serde_json::from_str::<Envelope>(contents)
+            .ok()
+            .filter(|envelope| envelope.format == ENVELOPE_FORMAT)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// The passphrase or key does not match the data
+    WrongPassphrase,
+    Corrupted,
+    InvalidParams,
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::WrongPassphrase => write!(f, "Incorrect passphrase"),
+            CryptoError::Corrupted => write!(f, "The encrypted data is damaged"),
+            CryptoError::InvalidParams => write!(f, "Unsupported encryption settings"),
+        }
+    }
+}
+
+/// A key derived from a passphrase
+pub struct Cipher {
+    aead: XChaCha20Poly1305,
+    kdf: KdfParams,
+}
+
+impl Cipher {
+    pub fn derive(passphrase: &str, kdf: KdfParams) -> Result<Self, CryptoError> {
+        let salt = BASE64.decode(&kdf.salt).map_err(|_| CryptoError::InvalidParams)?;
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+            .map_err(|_| CryptoError::InvalidParams)?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|_| CryptoError::InvalidParams)?;
+
+        Ok(Self {
+            aead: XChaCha20Poly1305::new(&key.into()),
+            kdf,
+        })
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut sealed = nonce.to_vec();
+        sealed.extend(
+            self.aead
+                .encrypt(&nonce, plaintext)
+                .expect("encryption into a Vec cannot fail"),
+        );
+        sealed
+    }
+
+    fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(CryptoError::Corrupted);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.aead
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| CryptoError::WrongPassphrase)
+    }
+
+    pub fn seal(&self, plaintext: &str) -> String {
+        let sealed = self.encrypt(plaintext.as_bytes());
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let envelope = Envelope {
+            format: ENVELOPE_FORMAT.to_string(),
+            kdf: self.kdf.clone(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        serde_json::to_string_pretty(&envelope).unwrap_or_default()
+    }
+
+    pub fn open(&self, envelope: &Envelope) -> Result<String, CryptoError> {
+        if envelope.kdf != self.kdf {
+            return Err(CryptoError::WrongPassphrase);
+        }
+        let mut sealed = BASE64.decode(&envelope.nonce).map_err(|_| CryptoError::Corrupted)?;
+        sealed.extend(BASE64.decode(&envelope.ciphertext).map_err(|_| CryptoError::Corrupted)?);
+        String::from_utf8(self.decrypt(&sealed)?).map_err(|_| CryptoError::Corrupted)
+    }
+
+    /// Compact form for single values
+    pub fn seal_text(&self, plaintext: &str) -> String {
+        format!("{}{}", TEXT_PREFIX, BASE64.encode(self.encrypt(plaintext.as_bytes())))
+    }
+
+    pub fn open_text(&self, data: &str) -> Result<String, CryptoError> {
+        let encoded = data.strip_prefix(TEXT_PREFIX).ok_or(CryptoError::Corrupted)?;
+        let sealed = BASE64.decode(encoded).map_err(|_| CryptoError::Corrupted)?;
+        String::from_utf8(self.decrypt(&sealed)?).map_err(|_| CryptoError::Corrupted)
+    }
+}
+
+pub fn is_sealed_text(data: &str) -> bool {
+    data.starts_with(TEXT_PREFIX)
+}
+
+/// Encrypts with a new key derived from `passphrase`, for files that leave
+/// the data folder
+pub fn seal_with_passphrase(plaintext: &str, passphrase: &str) -> Result<String, CryptoError> {
+    Ok(Cipher::derive(passphrase, KdfParams::generate())?.seal(plaintext))
+}
+
+pub fn open_with_passphrase(envelope: &Envelope, passphrase: &str) -> Result<String, CryptoError> {
+    Cipher::derive(passphrase, envelope.kdf.clone())?.open(envelope)
+}
+
+/// Stored next to the data so the passphrase can be checked at unlock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyFile {
+    kdf: KdfParams,
+    check: String,
+}
+
+impl KeyFile {
+    pub fn new(cipher: &Cipher) -> Self {
+        Self {
+            kdf: cipher.kdf.clone(),
+            check: cipher.seal_text(CHECK_TEXT),
+        }
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<Cipher, CryptoError> {
+        let cipher = Cipher::derive(passphrase, self.kdf.clone())?;
+        match cipher.open_text(&self.check) {
+            Ok(text) if text == CHECK_TEXT => Ok(cipher),
+            _ => Err(CryptoError::WrongPassphrase),
+        }
+    }
+}
+
+static ACTIVE: RwLock<Option<Arc<Cipher>>> = RwLock::new(None);
+
+/// Key of the unlocked data, if it is encrypted
+pub fn active() -> Option<Arc<Cipher>> {
+    ACTIVE.read().ok().and_then(|cipher| cipher.clone())
+}
+
+pub fn set_active(cipher: Option<Arc<Cipher>>) {
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = cipher;
+    }
+}
+
+/// Encrypts a saved file with the active key, or leaves it as is
+pub fn encode(plaintext: String) -> String {
+    match active() {
+        Some(cipher) => cipher.seal(&plaintext),
+        None => plaintext,
+    }
+}
+
+/// Decrypts a saved file with the active key; plain files pass through
+pub fn decode(contents: &str) -> Result<Cow<'_, str>, LoadError> {
+    let Some(envelope) = Envelope::parse(contents) else {
+        return Ok(Cow::Borrowed(contents));
+    };
+    let cipher = active().ok_or(LoadError::Locked)?;
+    match cipher.open(&envelope) {
+        Ok(plaintext) => Ok(Cow::Owned(plaintext)),
+        Err(CryptoError::WrongPassphrase) => Err(LoadError::Locked),
+        Err(_) => Err(LoadError::FormatError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_params() -> KdfParams {
+        KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            ..KdfParams::generate()
+        }
+    }
+
+    #[test]
+    fn sealed_data_needs_the_same_passphrase() {
+        let cipher = Cipher::derive("correct horse", fast_params()).unwrap();
+        let envelope = Envelope::parse(&cipher.seal(r#"{"items":[]}"#)).unwrap();
+        assert_eq!(cipher.open(&envelope).unwrap(), r#"{"items":[]}"#);
+
+        let wrong = Cipher::derive("battery staple", envelope.kdf.clone()).unwrap();
+        assert_eq!(wrong.open(&envelope), Err(CryptoError::WrongPassphrase));
+
+        let key_file = KeyFile::new(&cipher);
+        assert!(key_file.unlock("correct horse").is_ok());
+        assert!(key_file.unlock("battery staple").is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use super::{crypto, parse_state, write_atomic, Storage};
+use crate::persistence::{LoadError, SavedState};
+
+/// The whole state as one pretty-printed JSON document, replaced atomically on save
+pub struct JsonStorage {
+    path: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load(&self) -> Result<SavedState, LoadError> {
+        let path = &self.path;
+
+        if !path.exists() {
+            return Err(LoadError::FileNotFound);
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|_| LoadError::FileNotFound)?;
+
+        parse_state(&crypto::decode(&contents)?)
+    }
+
+    fn save(&self, state: &SavedState) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(state)?;
+        write_atomic(&self.path, crypto::encode(json).as_bytes())
+    }
+
+    fn location(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
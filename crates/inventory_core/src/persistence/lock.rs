@@ -0,0 +1,82 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Lock file of the workspace this instance writes to. The operating system
+/// releases the lock if the process exits without cleaning up.
+static HELD: Mutex<Option<(PathBuf, File)>> = Mutex::new(None);
+
+fn lock_path() -> PathBuf {
+    super::data_dir().join("inventory.lock")
+}
+
+/// Who holds the lock, as written into the lock file by the holder
+fn describe_owner(file: &mut File) -> String {
+    let mut owner = String::new();
+    file.read_to_string(&mut owner).ok();
+    let owner = owner.trim();
+    if owner.is_empty() {
+        "another Inventory Manager window".to_string()
+    } else {
+        owner.to_string()
+    }
+}
+
+/// Takes the advisory lock on the open workspace.
+///
+/// Returns who holds it when another instance already does.
+pub fn acquire() -> Result<(), String> {
+    let path = lock_path();
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    if held.as_ref().is_some_and(|(held_path, _)| *held_path == path) {
+        return Ok(());
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Err(describe_owner(&mut file)),
+        Err(TryLockError::Error(e)) => return Err(format!("Could not lock {}: {}", path.display(), e)),
+    }
+
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown user".to_string());
+    let owner = format!(
+        "{} (process {}), opened {}",
+        user,
+        std::process::id(),
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    );
+    file.set_len(0).ok();
+    file.rewind().ok();
+    file.write_all(owner.as_bytes()).ok();
+
+    // Taking a new lock lets go of the one on the previous workspace
+    *held = Some((path, file));
+    Ok(())
+}
+
+pub fn release() {
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    // The file itself stays; removing it could let two instances lock different files
+    if let Some((_, file)) = held.take() {
+        file.set_len(0).ok();
+    }
+}
+
+/// Whether this instance may write to the open workspace
+pub fn is_held() -> bool {
+    let path = lock_path();
+    HELD.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|(held_path, _)| *held_path == path)
+}
@@ -0,0 +1,280 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::alerts::AlertManager;
+use crate::analysis::AnalysisSettings;
+use crate::api::ApiSettings;
+use crate::audit::AuditLog;
+use crate::auth::AuthStore;
+use crate::persistence::{BackupSettings, LoadError, SavedState, StorageBackend};
+use crate::reports::ReportSettings;
+use crate::settings::{InventoryViewMode, LayoutStyle, View};
+use crate::user::UNUSABLE_PASSWORD_HASH;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// Upgrade steps in order; step `n` turns a version `n` file into version `n + 1`.
+///
+/// Files written before versioning was introduced have no `schema_version`
+/// and count as version 0. A field added to the saved state gets a step that
+/// fills it in for older files, rather than a `#[serde(default)]`. Append new
+/// steps at the end and never edit a released one.
+const MIGRATIONS: &[Migration] = &[
+    fill_release_defaults,
+    add_purchasing,
+    add_sales,
+    add_returns,
+    add_stocktakes,
+    add_demand_analysis,
+    add_storage_settings,
+    add_reports_and_exports,
+    add_integrations,
+    add_automation,
+    add_password_hashes,
+];
+
+/// Version written into every saved file
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Brings a saved state up to the current schema version in place
+pub fn migrate(value: &mut Value) -> Result<(), LoadError> {
+    let Value::Object(root) = value else {
+        return Err(LoadError::FormatError);
+    };
+
+    let version = root
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+    if version > SCHEMA_VERSION {
+        return Err(LoadError::NewerVersion(version));
+    }
+
+    for step in &MIGRATIONS[version as usize..] {
+        step(root);
+    }
+    root.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+    Ok(())
+}
+
+/// Parses and upgrades the contents of a data, backup or export file
+pub fn parse_state(contents: &str) -> Result<SavedState, LoadError> {
+    let mut value: Value = serde_json::from_str(contents).map_err(|_| LoadError::FormatError)?;
+    migrate(&mut value)?;
+    serde_json::from_value(value).map_err(|_| LoadError::FormatError)
+}
+
+fn settings(root: &mut Map<String, Value>) -> Option<&mut Map<String, Value>> {
+    match root.get_mut("settings") {
+        Some(Value::Object(settings)) => Some(settings),
+        _ => None,
+    }
+}
+
+fn items(root: &mut Map<String, Value>) -> impl Iterator<Item = &mut Map<String, Value>> {
+    root.get_mut("items")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+fn fill(map: &mut Map<String, Value>, key: &str, value: Value) {
+    map.entry(key).or_insert(value);
+}
+
+fn default_value<T: Default + Serialize>() -> Value {
+    serde_json::to_value(T::default()).unwrap_or(Value::Null)
+}
+
+/// 0 → 1: releases up to 0.2.3 treated a missing user store, audit log,
+/// alert list or panel state as empty, and settings added after the first
+/// release as empty or off. Give them the values a new installation starts
+/// with.
+fn fill_release_defaults(root: &mut Map<String, Value>) {
+    // Hashing the default admin's password is slow, so only do it when needed
+    if !root.contains_key("auth_store") {
+        root.insert("auth_store".to_string(), default_value::<AuthStore>());
+    }
+    fill(root, "audit_log", default_value::<AuditLog>());
+    fill(root, "alert_manager", default_value::<AlertManager>());
+    fill(root, "sidebar_collapsed", json!(false));
+    fill(root, "show_alerts_panel", json!(false));
+    fill(root, "show_search_panel", json!(false));
+    fill(root, "current_view", default_value::<View>());
+
+    let Some(settings) = settings(root) else {
+        return;
+    };
+    if settings
+        .get("preferred_currency")
+        .and_then(Value::as_str)
+        .is_none_or(str::is_empty)
+    {
+        settings.insert("preferred_currency".to_string(), json!("USD"));
+    }
+    fill(settings, "layout_style", default_value::<LayoutStyle>());
+    fill(settings, "inventory_view_mode", default_value::<InventoryViewMode>());
+    fill(settings, "device_notifications_enabled", json!(true));
+    fill(settings, "update_notifications_enabled", json!(true));
+    fill(settings, "notification_throttle_seconds", json!(30));
+}
+
+/// 1 → 2: reorder settings on items, and purchase orders
+fn add_purchasing(root: &mut Map<String, Value>) {
+    for item in items(root) {
+        fill(item, "reorder_point", json!(0));
+        fill(item, "reorder_quantity", json!(0));
+        fill(item, "lead_time_days", json!(0));
+    }
+    fill(root, "purchase_orders", json!([]));
+}
+
+/// 2 → 3: customers, sales orders and the stock movement history
+fn add_sales(root: &mut Map<String, Value>) {
+    fill(root, "customers", json!([]));
+    fill(root, "sales_orders", json!([]));
+    fill(root, "stock_movements", json!([]));
+}
+
+/// 3 → 4: customer returns
+fn add_returns(root: &mut Map<String, Value>) {
+    fill(root, "returns", json!([]));
+}
+
+/// 4 → 5: item locations and stocktake count sessions
+fn add_stocktakes(root: &mut Map<String, Value>) {
+    for item in items(root) {
+        fill(item, "location", json!(""));
+    }
+    fill(root, "stocktakes", json!([]));
+}
+
+/// 5 → 6: ABC analysis cut-offs and the slow-moving and dead stock thresholds
+fn add_demand_analysis(root: &mut Map<String, Value>) {
+    if let Some(settings) = settings(root) {
+        fill(settings, "analysis", default_value::<AnalysisSettings>());
+    }
+}
+
+/// 6 → 7: backup schedule and the chosen storage backend
+fn add_storage_settings(root: &mut Map<String, Value>) {
+    if let Some(settings) = settings(root) {
+        fill(settings, "backups", default_value::<BackupSettings>());
+        fill(settings, "storage_backend", default_value::<StorageBackend>());
+    }
+}
+
+/// 7 → 8: report letterhead and scheduled export jobs
+fn add_reports_and_exports(root: &mut Map<String, Value>) {
+    if let Some(settings) = settings(root) {
+        fill(settings, "reports", default_value::<ReportSettings>());
+        fill(settings, "export_jobs", json!([]));
+    }
+}
+
+/// 8 → 9: the local HTTP API and outgoing webhooks
+fn add_integrations(root: &mut Map<String, Value>) {
+    if let Some(settings) = settings(root) {
+        fill(settings, "api", default_value::<ApiSettings>());
+        fill(settings, "webhooks", json!([]));
+    }
+}
+
+/// 9 → 10: rules, their pending notices, computed columns and report scripts
+fn add_automation(root: &mut Map<String, Value>) {
+    if let Some(settings) = settings(root) {
+        fill(settings, "rules", json!([]));
+        fill(settings, "rule_notices", json!([]));
+        fill(settings, "computed_columns", json!([]));
+        fill(settings, "report_scripts", json!([]));
+    }
+}
+
+/// 10 → 11: password hashes weren't saved before, so users in older files
+/// get a hash that matches nothing, as they did when loaded then. The default
+/// admin gets its default password back on load.
+fn add_password_hashes(root: &mut Map<String, Value>) {
+    let Some(Value::Object(users)) = root.get_mut("auth_store").and_then(|store| store.get_mut("users")) else {
+        return;
+    };
+    for user in users.values_mut().filter_map(Value::as_object_mut) {
+        fill(user, "password_hash", json!(UNUSABLE_PASSWORD_HASH));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Saved by release 0.2.3, the last one without a schema version
+    const RELEASE_0_2_3: &str = include_str!("fixtures/v0_release_0_2_3.json");
+
+    #[test]
+    fn upgrades_release_file() {
+        let state = parse_state(RELEASE_0_2_3).unwrap();
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert_eq!(state.items.len(), 3);
+        assert_eq!(state.items[0].sku, "TOOL-001");
+        assert_eq!(state.items[0].reorder_point, 0);
+        assert_eq!(state.items[0].location, "");
+        assert_eq!(state.notes[0].content, "Acme: 555-0100");
+        assert_eq!(state.auth_store.get_all_users().len(), 2);
+        // 0.2.3 didn't save password hashes
+        assert!(state.auth_store.clone().authenticate("dana", "dana-pass").is_none());
+        assert_eq!(state.audit_log.get_entries().len(), 1);
+        assert_eq!(state.alert_manager.get_active_alerts().len(), 2);
+        assert_eq!(state.current_view, View::Alerts);
+        assert!(state.sidebar_collapsed);
+        assert!(state.purchase_orders.is_empty() && state.stocktakes.is_empty());
+
+        assert_eq!(state.settings.preferred_currency, "EUR");
+        assert!(!state.settings.device_notifications_enabled);
+        assert_eq!(state.settings.notification_throttle_seconds, 60);
+        assert_eq!(state.settings.analysis.a_cutoff, 80.0);
+        assert_eq!(state.settings.backups.keep, 7);
+        assert_eq!(state.settings.storage_backend, StorageBackend::Json);
+        assert!(state.settings.rules.is_empty());
+    }
+
+    #[test]
+    fn fills_settings_earlier_releases_left_out() {
+        let mut value: Value = serde_json::from_str(RELEASE_0_2_3).unwrap();
+        let settings = value["settings"].as_object_mut().unwrap();
+        settings.insert("preferred_currency".to_string(), json!(""));
+        for key in ["layout_style", "device_notifications_enabled", "notification_throttle_seconds"] {
+            settings.remove(key);
+        }
+        value.as_object_mut().unwrap().remove("current_view");
+
+        let state = parse_state(&value.to_string()).unwrap();
+        assert_eq!(state.settings.preferred_currency, "USD");
+        assert_eq!(state.settings.layout_style, LayoutStyle::Header);
+        assert!(state.settings.device_notifications_enabled);
+        assert_eq!(state.settings.notification_throttle_seconds, 30);
+        assert_eq!(state.current_view, View::Inventory);
+    }
+
+    #[test]
+    fn current_files_pass_through_unchanged() {
+        let mut state = parse_state(RELEASE_0_2_3).unwrap();
+        state.items[0].reorder_point = 5;
+        let saved = serde_json::to_string(&state).unwrap();
+        let mut value: Value = serde_json::from_str(&saved).unwrap();
+        let before = value.clone();
+        migrate(&mut value).unwrap();
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let contents = format!(
+            r#"{{"schema_version": {}, "items": [], "notes": [], "calculator_position": null}}"#,
+            SCHEMA_VERSION + 1
+        );
+        assert!(matches!(
+            parse_state(&contents),
+            Err(LoadError::NewerVersion(version)) if version == SCHEMA_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,523 @@
+mod backup;
+pub mod crypto;
+mod json;
+mod lock;
+mod migrations;
+mod sqlite;
+mod state;
+mod tracked;
+pub mod workspace;
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+pub use backup::{BackupFrequency, BackupInfo, BackupSettings};
+pub use json::JsonStorage;
+pub use migrations::{migrate, parse_state, SCHEMA_VERSION};
+pub use sqlite::SqliteStorage;
+pub use state::{LoadError, SavedState};
+pub use tracked::Tracked;
+pub use workspace::{configure, LaunchOptions, WorkspaceList};
+
+/// Where the application state is kept between runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StorageBackend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl StorageBackend {
+    pub const ALL: [StorageBackend; 2] = [StorageBackend::Json, StorageBackend::Sqlite];
+}
+
+impl std::fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageBackend::Json => write!(f, "JSON file"),
+            StorageBackend::Sqlite => write!(f, "SQLite database"),
+        }
+    }
+}
+
+/// A place the saved state can be loaded from and written to.
+///
+/// Implementations are blocking; the async wrappers below run them on the
+/// blocking thread pool.
+pub trait Storage: Send + Sync {
+    fn load(&self) -> Result<SavedState, LoadError>;
+    fn save(&self, state: &SavedState) -> std::io::Result<()>;
+    /// Writes the small fields and the named sections of the state (see
+    /// `SavedState::SECTIONS`), leaving the stored copy of the others alone
+    fn save_sections(&self, state: &SavedState, _sections: &[&str]) -> std::io::Result<()> {
+        self.save(state)
+    }
+    /// Writes everything again, not only what changed since the last save
+    fn rewrite(&self, state: &SavedState) -> std::io::Result<()> {
+        self.save(state)
+    }
+    /// Drops anything cached from the file, so the next load reads it again
+    fn forget(&self) {}
+    /// File the data is stored in
+    fn location(&self) -> PathBuf;
+}
+
+/// Folder of the open workspace, resolved on first use
+static DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = DATA_DIR.read().ok().and_then(|dir| dir.clone()) {
+        return dir;
+    }
+    let data_dir = match workspace::fixed_data_dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => WorkspaceList::load().active().directory(),
+    };
+
+    std::fs::create_dir_all(&data_dir).ok();
+    if let Ok(mut cached) = DATA_DIR.write() {
+        *cached = Some(data_dir.clone());
+    }
+    data_dir
+}
+
+pub fn data_file_path() -> PathBuf {
+    data_dir().join("inventory.json")
+}
+
+pub fn database_path() -> PathBuf {
+    data_dir().join("inventory.db")
+}
+
+/// Writes to a temporary file next to `path`, flushes it to disk and renames
+/// it over the original, so a crash mid-save leaves the old file intact. Each
+/// write has its own temporary file, so writes to the same path can't mix.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", uuid::Uuid::new_v4().simple()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let written = std::fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| std::fs::rename(&temp_path, path)) {
+        std::fs::remove_file(&temp_path).ok();
+        return Err(e);
+    }
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        std::fs::File::open(dir).and_then(|dir| dir.sync_all()).ok();
+    }
+    Ok(())
+}
+
+/// The backend choice lives outside the saved state so it is known before loading
+fn backend_config_path() -> PathBuf {
+    data_dir().join("storage.json")
+}
+
+/// Present while the data is encrypted; holds what is needed to check a passphrase
+fn key_file_path() -> PathBuf {
+    data_dir().join("encryption.json")
+}
+
+/// The new key while a passphrase change is under way. It replaces the key
+/// file once the data and backups are rewritten, so whichever key the data is
+/// under after an interruption, one of the two files can unlock it.
+fn pending_key_file_path() -> PathBuf {
+    data_dir().join("encryption-pending.json")
+}
+
+fn read_key_file(path: &Path) -> Option<crypto::KeyFile> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
+/// Derives the key from whichever key file the passphrase matches
+fn unlock_key_files(passphrase: &str) -> Result<crypto::Cipher, String> {
+    let key_files: Vec<_> = [key_file_path(), pending_key_file_path()]
+        .iter()
+        .filter_map(|path| read_key_file(path))
+        .collect();
+    if key_files.is_empty() {
+        return Err("The encryption key file is missing".to_string());
+    }
+    key_files
+        .iter()
+        .find_map(|key_file| key_file.unlock(passphrase).ok())
+        .ok_or_else(|| crypto::CryptoError::WrongPassphrase.to_string())
+}
+
+pub fn encryption_enabled() -> bool {
+    key_file_path().exists() || pending_key_file_path().exists()
+}
+
+/// Held while anything writes the data, so writers never interleave and a
+/// workspace switch waits before letting go of the folder, key and lock in use
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Backend of the open workspace, read from its folder on first use
+static BACKEND: RwLock<Option<StorageBackend>> = RwLock::new(None);
+
+pub fn configured_backend() -> StorageBackend {
+    if let Some(backend) = BACKEND.read().ok().and_then(|backend| *backend) {
+        return backend;
+    }
+    let backend = std::fs::read_to_string(backend_config_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    if let Ok(mut cached) = BACKEND.write() {
+        *cached = Some(backend);
+    }
+    backend
+}
+
+fn set_backend(backend: StorageBackend) -> std::io::Result<()> {
+    write_atomic(&backend_config_path(), serde_json::to_string(&backend)?.as_bytes())?;
+    if let Ok(mut current) = BACKEND.write() {
+        *current = Some(backend);
+    }
+    Ok(())
+}
+
+pub fn storage(backend: StorageBackend) -> Arc<dyn Storage> {
+    // The database stays open between saves; it is reopened when the
+    // workspace changes
+    static SQLITE: Mutex<Option<Arc<SqliteStorage>>> = Mutex::new(None);
+
+    match backend {
+        StorageBackend::Json => Arc::new(JsonStorage::new(data_file_path())),
+        StorageBackend::Sqlite => {
+            let path = database_path();
+            let mut cached = SQLITE.lock().unwrap_or_else(|e| e.into_inner());
+            match cached.as_ref() {
+                Some(storage) if storage.location() == path => storage.clone(),
+                _ => {
+                    let storage = Arc::new(SqliteStorage::new(path));
+                    *cached = Some(storage.clone());
+                    storage
+                }
+            }
+        }
+    }
+}
+
+/// File the active backend stores data in
+pub fn data_location() -> PathBuf {
+    storage(configured_backend()).location()
+}
+
+/// Size and modification time of the data file, and of the SQLite
+/// write-ahead log next to it
+type Fingerprint = [Option<(u64, std::time::SystemTime)>; 2];
+
+/// The data file as this instance last loaded or saved it
+static LAST_SEEN: Mutex<Option<Fingerprint>> = Mutex::new(None);
+
+fn fingerprint() -> Fingerprint {
+    let location = data_location();
+    let mut wal = location.clone().into_os_string();
+    wal.push("-wal");
+    [location.as_path(), Path::new(&wal)].map(|path| {
+        std::fs::metadata(path)
+            .ok()
+            .and_then(|meta| Some((meta.len(), meta.modified().ok()?)))
+    })
+}
+
+fn remember_data_file() {
+    *LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(fingerprint());
+}
+
+fn changed_externally() -> bool {
+    LAST_SEEN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some_and(|seen| seen != fingerprint())
+}
+
+/// Refuses writes from a read-only instance and over changes made elsewhere
+fn check_writable() -> std::io::Result<()> {
+    if !lock::is_held() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "The workspace is open read-only",
+        ));
+    }
+    if changed_externally() {
+        return Err(std::io::Error::other(
+            "The data file was changed outside this window",
+        ));
+    }
+    Ok(())
+}
+
+/// Takes the single-instance lock on the open workspace. On failure returns
+/// who holds it, so the user can choose to open it read-only.
+pub async fn lock_workspace() -> Result<(), String> {
+    tokio::task::spawn_blocking(lock::acquire)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Whether the data file changed on disk since this instance last read or wrote it
+pub async fn data_file_changed() -> bool {
+    tokio::task::spawn_blocking(changed_externally)
+        .await
+        .unwrap_or(false)
+}
+
+/// Reads the data file again after it was changed elsewhere
+pub async fn reload_state() -> Result<SavedState, LoadError> {
+    let backend = configured_backend();
+    tokio::task::spawn_blocking(move || {
+        let storage = storage(backend);
+        storage.forget();
+        let state = storage.load();
+        remember_data_file();
+        state
+    })
+    .await
+    .unwrap_or(Err(LoadError::FormatError))
+}
+
+/// Lets the next save overwrite changes made elsewhere
+pub fn accept_data_file() {
+    storage(configured_backend()).forget();
+    remember_data_file();
+}
+
+pub async fn load_state() -> Result<SavedState, LoadError> {
+    let backend = configured_backend();
+    tokio::task::spawn_blocking(move || {
+        let state = storage(backend).load();
+        remember_data_file();
+        state
+    })
+    .await
+    .unwrap_or(Err(LoadError::FormatError))
+}
+
+/// Writes the state, then makes a backup if one is due. A failed backup
+/// doesn't fail the save; its error is returned as a warning instead.
+pub async fn save_state(state: SavedState) -> Result<Option<String>, std::io::Error> {
+    save_sections(state, None).await
+}
+
+/// Like `save_state`, but only writes the sections of the state that changed
+/// since the last save, or everything when `changed` is `None`
+pub async fn save_sections(
+    state: SavedState,
+    changed: Option<Vec<&'static str>>,
+) -> Result<Option<String>, std::io::Error> {
+    let dir = data_dir();
+    let storage = storage(configured_backend());
+    tokio::task::spawn_blocking(move || {
+        let _writing = WRITING.lock().unwrap_or_else(|e| e.into_inner());
+        if data_dir() != dir {
+            return Err(std::io::Error::other("The workspace was switched before the data could be saved"));
+        }
+        check_writable()?;
+        match &changed {
+            Some(sections) => storage.save_sections(&state, sections)?,
+            None => storage.save(&state)?,
+        }
+        remember_data_file();
+        Ok(backup::rotate(&state).err().map(|e| format!("Backup failed: {}", e)))
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+pub async fn list_backups() -> Vec<BackupInfo> {
+    tokio::task::spawn_blocking(backup::list_backups)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn create_backup(state: SavedState) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || backup::write_backup(&state).map(|_| ()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Reads a backup and writes it over the active data.
+///
+/// When `current` is given it is backed up first, so the restore can be undone.
+pub async fn restore_backup(path: PathBuf, current: Option<SavedState>) -> Result<SavedState, String> {
+    let backend = configured_backend();
+    tokio::task::spawn_blocking(move || {
+        // A save finishing after the restore would write the old data back
+        let _writing = WRITING.lock().unwrap_or_else(|e| e.into_inner());
+        if !lock::is_held() {
+            return Err("The workspace is open read-only".to_string());
+        }
+        if let Some(current) = current {
+            backup::write_backup(&current).map_err(|e| format!("Could not back up current data: {}", e))?;
+        }
+        let state = backup::read_backup(&path).map_err(|e| e.to_string())?;
+        storage(backend).save(&state).map_err(|e| e.to_string())?;
+        remember_data_file();
+        Ok(state)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Checks the passphrase and keeps the derived key for loading and saving
+pub async fn unlock(passphrase: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let cipher = unlock_key_files(&passphrase)?;
+        crypto::set_active(Some(Arc::new(cipher)));
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Turns encryption on, changes the passphrase, or turns encryption off
+/// (`new` is `None`), then writes the data and all backups again with the new key.
+///
+/// The old key file is only replaced or removed after everything is
+/// rewritten, so an interrupted change leaves data that the old or the new
+/// passphrase still opens. It is kept when a backup could not be rewritten.
+/// Returns whether the data is encrypted afterwards.
+pub async fn change_passphrase(
+    state: SavedState,
+    current: Option<String>,
+    new: Option<String>,
+) -> Result<bool, String> {
+    let backend = configured_backend();
+    tokio::task::spawn_blocking(move || {
+        // A save finishing meanwhile would write with the key being replaced
+        let _writing = WRITING.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = if encryption_enabled() {
+            let current = current.ok_or("Enter the current passphrase")?;
+            Some(unlock_key_files(&current)?)
+        } else {
+            None
+        };
+        let next = match &new {
+            Some(passphrase) => Some(Arc::new(
+                crypto::Cipher::derive(passphrase, crypto::KdfParams::generate())
+                    .map_err(|e| e.to_string())?,
+            )),
+            None => None,
+        };
+
+        check_writable().map_err(|e| e.to_string())?;
+        if let Some(cipher) = &next {
+            let key_file = serde_json::to_string_pretty(&crypto::KeyFile::new(cipher))
+                .map_err(|e| e.to_string())?;
+            write_atomic(&pending_key_file_path(), key_file.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        let restore = crypto::active();
+        crypto::set_active(next.clone());
+        if let Err(e) = storage(backend).rewrite(&state) {
+            crypto::set_active(restore);
+            if next.is_some() {
+                std::fs::remove_file(pending_key_file_path()).ok();
+            }
+            return Err(e.to_string());
+        }
+        remember_data_file();
+        let failed = backup::reencrypt(previous.as_ref());
+        if !failed.is_empty() {
+            return Err(format!(
+                "The data was rewritten, but {} of the backups could not be and still need the old passphrase",
+                failed.len()
+            ));
+        }
+
+        if next.is_some() {
+            std::fs::rename(pending_key_file_path(), key_file_path()).map_err(|e| e.to_string())?;
+        } else {
+            std::fs::remove_file(key_file_path()).ok();
+            std::fs::remove_file(pending_key_file_path()).ok();
+        }
+        Ok(next.is_some())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Renames an unreadable JSON data file out of the way so starting fresh does
+/// not overwrite it. The SQLite database stays open and is left in place.
+pub fn set_aside_unreadable_data() -> Option<PathBuf> {
+    let location = data_location();
+    if configured_backend() != StorageBackend::Json || !location.exists() {
+        return None;
+    }
+    let mut name = location.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!("-unreadable-{}", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+    if let Some(extension) = location.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    let target = location.with_file_name(name);
+    std::fs::rename(&location, &target).ok()?;
+    Some(target)
+}
+
+/// Writes the state to another backend and makes it the active one
+pub async fn switch_backend(state: SavedState, backend: StorageBackend) -> Result<(), std::io::Error> {
+    tokio::task::spawn_blocking(move || {
+        // A save running meanwhile would go to the backend being left behind
+        let _writing = WRITING.lock().unwrap_or_else(|e| e.into_inner());
+        check_writable()?;
+        storage(backend).save(&state)?;
+        set_backend(backend)?;
+        remember_data_file();
+        Ok(())
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Makes `name` the open workspace. The next load reads from its folder with
+/// its own storage backend, and encrypted data has to be unlocked again.
+pub async fn switch_workspace(name: String) -> Result<WorkspaceList, String> {
+    if workspace::fixed_data_dir().is_some() {
+        return Err("The data folder was set with --data-dir".to_string());
+    }
+    tokio::task::spawn_blocking(move || {
+        let _writing = WRITING.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list = WorkspaceList::load();
+        if list.get(&name).is_none() {
+            return Err(format!("There is no workspace named '{}'", name));
+        }
+        list.active = name;
+        list.save().map_err(|e| e.to_string())?;
+
+        if let Ok(mut dir) = DATA_DIR.write() {
+            *dir = None;
+        }
+        if let Ok(mut backend) = BACKEND.write() {
+            *backend = None;
+        }
+        lock::release();
+        *LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        crypto::set_active(None);
+        Ok(list)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Saves a changed workspace list, e.g. after adding or removing one
+pub async fn save_workspaces(list: WorkspaceList) -> Result<WorkspaceList, String> {
+    tokio::task::spawn_blocking(move || {
+        list.save().map_err(|e| e.to_string())?;
+        Ok(list)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
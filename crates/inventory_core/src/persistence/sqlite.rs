@@ -0,0 +1,422 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+
+use super::crypto;
+use super::{migrate, JsonStorage, Storage};
+use crate::persistence::{LoadError, SavedState};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS records (
+        kind TEXT NOT NULL,
+        id TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (kind, id)
+    );
+    CREATE TABLE IF NOT EXISTS fields (
+        path TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS info (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+/// The state split into one row per entity in an embedded SQLite database.
+///
+/// Lists of records with distinct `id`s (items, notes, orders, audit entries,
+/// ...) are stored row by row under their path in the state, with their
+/// position in the list; everything else (settings, users, window flags) is
+/// stored as one value per field. Paths are JSON arrays of keys, so keys may
+/// contain any character. Each save only writes the rows that changed, in a
+/// single transaction, and only looks at the parts of the state it is given.
+/// When encryption is on, each stored value is encrypted on its own.
+pub struct SqliteStorage {
+    path: PathBuf,
+    inner: Mutex<Option<Inner>>,
+}
+
+/// A stored record: its position in the list and its data, decrypted
+type Record = (i64, String);
+
+struct Inner {
+    conn: Connection,
+    /// Last written record per (list path, id)
+    records: HashMap<(String, String), Record>,
+    /// Last written data per field path, decrypted
+    fields: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+enum Error {
+    Sql(rusqlite::Error),
+    /// Values are encrypted and no key is unlocked
+    Locked,
+    /// The JSON data file to migrate from could not be read
+    Import(LoadError),
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Sql(error)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Sql(error) => write!(f, "{}", error),
+            Error::Locked => write!(f, "The database is encrypted"),
+            Error::Import(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Decrypts a stored value if it was encrypted
+fn open_value(data: String) -> Result<String, Error> {
+    if !crypto::is_sealed_text(&data) {
+        return Ok(data);
+    }
+    crypto::active()
+        .ok_or(Error::Locked)?
+        .open_text(&data)
+        .map_err(|_| Error::Locked)
+}
+
+impl SqliteStorage {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            inner: Mutex::new(None),
+        }
+    }
+
+    fn with_inner<T>(&self, f: impl FnOnce(&mut Inner) -> Result<T, Error>) -> Result<T, Error> {
+        let mut guard = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if guard.is_none() {
+            *guard = Some(Inner::open(&self.path)?);
+        }
+        f(guard.as_mut().expect("connection opened above"))
+    }
+}
+
+impl Inner {
+    fn open(path: &Path) -> Result<Self, Error> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.execute_batch(SCHEMA)?;
+
+        let records = conn
+            .prepare("SELECT kind, id, position, data FROM records")?
+            .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?, row.get(3)?)))?
+            .collect::<rusqlite::Result<Vec<((String, String), i64, String)>>>()?
+            .into_iter()
+            .map(|(key, position, data)| Ok((key, (position, open_value(data)?))))
+            .collect::<Result<_, Error>>()?;
+        let fields = conn
+            .prepare("SELECT path, data FROM fields")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, String)>>>()?
+            .into_iter()
+            .map(|(path, data)| Ok((path, open_value(data)?)))
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self { conn, records, fields })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.records.is_empty() && self.fields.is_empty()
+    }
+
+    fn info(&self, key: &str) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row("SELECT value FROM info WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
+    }
+
+    fn set_info(&self, key: &str, value: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO info (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    fn read(&self) -> Value {
+        let mut root = Value::Object(Map::new());
+
+        for (path, data) in &self.fields {
+            if let (Some(path), Ok(value)) = (parse_path(path), serde_json::from_str(data)) {
+                set_path(&mut root, &path, value);
+            }
+        }
+
+        let mut records: Vec<(&(String, String), &Record)> = self.records.iter().collect();
+        records.sort_by_key(|((kind, _), (position, _))| (kind.as_str(), *position));
+        for ((kind, _), (_, data)) in records {
+            let (Some(path), Ok(value)) = (parse_path(kind), serde_json::from_str(data)) else {
+                continue;
+            };
+            if let Some(Value::Array(list)) = get_path_mut(&mut root, &path) {
+                list.push(value);
+            }
+        }
+
+        root
+    }
+
+    /// Writes the rows that changed under the top-level keys of `value`; rows
+    /// under other keys are left as they are. `full` rewrites every row,
+    /// e.g. after a key change.
+    fn write(&mut self, value: Value, full: bool) -> Result<(), Error> {
+        let Value::Object(root) = value else {
+            return Ok(());
+        };
+        let sections: HashSet<String> = root.keys().cloned().collect();
+        let mut records: HashMap<(String, String), Record> = HashMap::new();
+        let mut fields: HashMap<String, String> = HashMap::new();
+        split(&[], root, &mut records, &mut fields);
+
+        if full {
+            self.records.clear();
+            self.fields.clear();
+        }
+        let in_scope = |path: &str| {
+            parse_path(path).is_some_and(|path| path.first().is_some_and(|key| sections.contains(key)))
+        };
+        let cipher = crypto::active();
+        let stored = |data: &String| match &cipher {
+            Some(cipher) => cipher.seal_text(data),
+            None => data.clone(),
+        };
+
+        let tx = self.conn.transaction()?;
+        if full {
+            tx.execute_batch("DELETE FROM records; DELETE FROM fields;")?;
+        }
+        {
+            let mut upsert_record = tx.prepare(
+                "INSERT INTO records (kind, id, position, data) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(kind, id) DO UPDATE SET position = excluded.position, data = excluded.data",
+            )?;
+            let mut delete_record = tx.prepare("DELETE FROM records WHERE kind = ?1 AND id = ?2")?;
+            let mut upsert_field = tx.prepare(
+                "INSERT INTO fields (path, data) VALUES (?1, ?2)
+                 ON CONFLICT(path) DO UPDATE SET data = excluded.data",
+            )?;
+            let mut delete_field = tx.prepare("DELETE FROM fields WHERE path = ?1")?;
+
+            for (key, record) in &records {
+                if self.records.get(key) != Some(record) {
+                    upsert_record.execute(params![key.0, key.1, record.0, stored(&record.1)])?;
+                }
+            }
+            for key in self.records.keys() {
+                if in_scope(&key.0) && !records.contains_key(key) {
+                    delete_record.execute(params![key.0, key.1])?;
+                }
+            }
+            for (path, data) in &fields {
+                if self.fields.get(path) != Some(data) {
+                    upsert_field.execute(params![path, stored(data)])?;
+                }
+            }
+            for path in self.fields.keys() {
+                if in_scope(path) && !fields.contains_key(path) {
+                    delete_field.execute(params![path])?;
+                }
+            }
+        }
+        tx.commit()?;
+
+        self.records.retain(|key, _| !in_scope(&key.0));
+        self.records.extend(records);
+        self.fields.retain(|path, _| !in_scope(path));
+        self.fields.extend(fields);
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&self) -> Result<SavedState, LoadError> {
+        let value = self
+            .with_inner(|inner| {
+                // One-time migration from the JSON data file
+                if inner.is_empty() && inner.info("migrated_from_json")?.is_none() {
+                    // Only marked as done once there was nothing to import or it
+                    // was imported, so a locked or damaged file is tried again
+                    match JsonStorage::new(self.path.with_file_name("inventory.json")).load() {
+                        Ok(state) => {
+                            let value = serde_json::to_value(&state)
+                                .map_err(|_| Error::Import(LoadError::FormatError))?;
+                            inner.write(value, false)?;
+                        }
+                        Err(LoadError::FileNotFound) => {}
+                        Err(e) => return Err(Error::Import(e)),
+                    }
+                    inner.set_info("migrated_from_json", &chrono::Utc::now().to_rfc3339())?;
+                }
+                if inner.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(inner.read()))
+            })
+            .map_err(|e| match e {
+                Error::Locked => LoadError::Locked,
+                Error::Sql(_) => LoadError::FormatError,
+                Error::Import(e) => e,
+            })?;
+
+        let mut value = value.ok_or(LoadError::FileNotFound)?;
+        migrate(&mut value)?;
+        serde_json::from_value(value).map_err(|_| LoadError::FormatError)
+    }
+
+    fn save(&self, state: &SavedState) -> std::io::Result<()> {
+        let value = serde_json::to_value(state)?;
+        self.with_inner(|inner| inner.write(value, false))
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
+    fn save_sections(&self, state: &SavedState, sections: &[&str]) -> std::io::Result<()> {
+        let value = state.to_value_with(sections)?;
+        self.with_inner(|inner| inner.write(value, false))
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
+    fn rewrite(&self, state: &SavedState) -> std::io::Result<()> {
+        let value = serde_json::to_value(state)?;
+        self.with_inner(|inner| inner.write(value, true))
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
+    fn forget(&self) {
+        // Reopening reads every row again
+        *self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+
+    fn location(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// A list whose elements all carry a distinct string `id` is stored as
+/// records; any other list is stored whole
+fn is_record_list(value: &Value) -> bool {
+    let Value::Array(list) = value else {
+        return false;
+    };
+    let mut ids = HashSet::new();
+    list.iter()
+        .all(|element| element.get("id").and_then(Value::as_str).is_some_and(|id| ids.insert(id)))
+}
+
+fn path_key(path: &[String]) -> String {
+    serde_json::to_string(path).unwrap_or_default()
+}
+
+fn parse_path(key: &str) -> Option<Vec<String>> {
+    serde_json::from_str(key).ok()
+}
+
+fn split(
+    prefix: &[String],
+    map: Map<String, Value>,
+    records: &mut HashMap<(String, String), Record>,
+    fields: &mut HashMap<String, String>,
+) {
+    for (key, field) in map {
+        let mut path = prefix.to_vec();
+        path.push(key);
+
+        if is_record_list(&field) {
+            // The empty list marks where the records are appended when reading back
+            let kind = path_key(&path);
+            fields.insert(kind.clone(), "[]".to_string());
+            if let Value::Array(list) = field {
+                for (position, element) in list.into_iter().enumerate() {
+                    let id = element["id"].as_str().unwrap_or_default().to_string();
+                    records.insert((kind.clone(), id), (position as i64, element.to_string()));
+                }
+            }
+        } else if let Value::Object(object) = field {
+            if object.values().any(is_record_list) {
+                split(&path, object, records, fields);
+            } else {
+                fields.insert(path_key(&path), Value::Object(object).to_string());
+            }
+        } else {
+            fields.insert(path_key(&path), field.to_string());
+        }
+    }
+}
+
+fn set_path(root: &mut Value, path: &[String], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = root;
+    for part in parents {
+        let Value::Object(map) = current else {
+            return;
+        };
+        current = map.entry(part.clone()).or_insert_with(|| Value::Object(Map::new()));
+    }
+    if let Value::Object(map) = current {
+        map.insert(last.clone(), value);
+    }
+}
+
+fn get_path_mut<'a>(root: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter().try_fold(root, |current, part| current.get_mut(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_order_odd_keys_and_duplicate_ids() {
+        let path = std::env::temp_dir().join(format!("sqlite_storage_{}.db", uuid::Uuid::new_v4()));
+        let mut inner = Inner::open(&path).unwrap();
+
+        let mut state = json!({
+            "items": [{"id": "b", "n": 1}, {"id": "a", "n": 2}, {"id": "c", "n": 3}],
+            "settings": {
+                "jobs": [{"id": "j1"}],
+                "by_file": {"stock.csv": 1, "a@b.com": {"x.y": true}}
+            },
+            "notes": [{"id": "n1", "v": 1}, {"id": "n1", "v": 2}]
+        });
+        inner.write(state.clone(), false).unwrap();
+        assert_eq!(Inner::open(&path).unwrap().read(), state);
+
+        // Reordering moves rows without changing their data
+        state["items"].as_array_mut().unwrap().swap(0, 2);
+        inner.write(state.clone(), false).unwrap();
+        assert_eq!(Inner::open(&path).unwrap().read(), state);
+
+        // Only the sections given are looked at
+        inner.write(json!({"notes": []}), false).unwrap();
+        state["notes"] = json!([]);
+        assert_eq!(Inner::open(&path).unwrap().read(), state);
+
+        drop(inner);
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.clone().into_os_string();
+            file.push(suffix);
+            std::fs::remove_file(file).ok();
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Everything the app keeps between runs, as it is written to the data file
+
+use crate::settings::{AppSettings, View};
+
+#[derive(Debug, Clone)]
+pub enum LoadError {
+    FileNotFound,
+    FormatError,
+    /// Written by a newer version of the app with this schema version
+    NewerVersion(u32),
+    /// Encrypted, and the passphrase has not been entered yet
+    Locked,
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::FileNotFound => write!(f, "The data file was not found"),
+            LoadError::FormatError => write!(f, "The data file is damaged or incomplete"),
+            LoadError::NewerVersion(version) => write!(
+                f,
+                "The data was created by a newer version of Inventory Manager (data format {}, this version supports up to {}). Please update the app.",
+                version,
+                crate::persistence::SCHEMA_VERSION
+            ),
+            LoadError::Locked => write!(f, "The data is encrypted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct SavedState {
+    /// Data format version, see `persistence::migrations`
+    pub schema_version: u32,
+    pub items: Vec<crate::inventory::InventoryItem>,
+    pub notes: Vec<crate::note::Note>,
+    pub calculator_position: Option<(f32, f32)>,
+    pub settings: AppSettings,
+    pub auth_store: crate::auth::AuthStore,
+    pub audit_log: crate::audit::AuditLog,
+    pub alert_manager: crate::alerts::AlertManager,
+    pub sidebar_collapsed: bool,
+    pub show_alerts_panel: bool,
+    pub show_search_panel: bool,
+    pub current_view: View,
+    pub purchase_orders: Vec<crate::purchasing::PurchaseOrder>,
+    pub customers: Vec<crate::sales::Customer>,
+    pub sales_orders: Vec<crate::sales::SalesOrder>,
+    pub stock_movements: Vec<crate::stock::StockMovement>,
+    pub returns: Vec<crate::returns::ReturnAuthorization>,
+    pub stocktakes: Vec<crate::stocktake::CountSession>,
+}
+
+
+impl SavedState {
+    /// Fields holding lists or settings, which a save can skip when they
+    /// haven't changed. The other fields are small and always written.
+    pub const SECTIONS: [&'static str; 12] = [
+        "items",
+        "notes",
+        "settings",
+        "auth_store",
+        "audit_log",
+        "alert_manager",
+        "purchase_orders",
+        "customers",
+        "sales_orders",
+        "stock_movements",
+        "returns",
+        "stocktakes",
+    ];
+
+    /// The state as it is written to the data file, with only the named
+    /// sections and the small fields
+    pub fn to_value_with(&self, sections: &[&str]) -> serde_json::Result<serde_json::Value> {
+        use serde_json::to_value;
+
+        let mut map = serde_json::Map::new();
+        map.insert("schema_version".into(), to_value(self.schema_version)?);
+        map.insert("calculator_position".into(), to_value(self.calculator_position)?);
+        map.insert("sidebar_collapsed".into(), to_value(self.sidebar_collapsed)?);
+        map.insert("show_alerts_panel".into(), to_value(self.show_alerts_panel)?);
+        map.insert("show_search_panel".into(), to_value(self.show_search_panel)?);
+        map.insert("current_view".into(), to_value(&self.current_view)?);
+        for &section in sections {
+            let value = match section {
+                "items" => to_value(&self.items)?,
+                "notes" => to_value(&self.notes)?,
+                "settings" => to_value(&self.settings)?,
+                "auth_store" => to_value(&self.auth_store)?,
+                "audit_log" => to_value(&self.audit_log)?,
+                "alert_manager" => to_value(&self.alert_manager)?,
+                "purchase_orders" => to_value(&self.purchase_orders)?,
+                "customers" => to_value(&self.customers)?,
+                "sales_orders" => to_value(&self.sales_orders)?,
+                "stock_movements" => to_value(&self.stock_movements)?,
+                "returns" => to_value(&self.returns)?,
+                "stocktakes" => to_value(&self.stocktakes)?,
+                _ => continue,
+            };
+            map.insert(section.to_string(), value);
+        }
+        Ok(serde_json::Value::Object(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_sections_make_up_the_whole_state() {
+        let state = SavedState::default();
+        assert_eq!(
+            state.to_value_with(&SavedState::SECTIONS).unwrap(),
+            serde_json::to_value(&state).unwrap()
+        );
+    }
+}
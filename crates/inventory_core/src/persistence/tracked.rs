@@ -0,0 +1,70 @@
+//! Change tracking for the parts of the state that are saved separately
+
+use std::ops::{Deref, DerefMut};
+
+/// A value that counts how often it was borrowed mutably, so a save can tell
+/// which sections of the state may have changed without comparing them.
+///
+/// Every mutable borrow counts as a change, whether or not anything was
+/// modified, so a section is never missed.
+#[derive(Debug, Clone, Default)]
+pub struct Tracked<T> {
+    value: T,
+    version: u64,
+}
+
+impl<T> Tracked<T> {
+    pub fn new(value: T) -> Self {
+        Self { value, version: 0 }
+    }
+
+    /// Changes whenever the value is borrowed mutably
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl<T> From<T> for Tracked<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.version += 1;
+        &mut self.value
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Tracked<T>
+where
+    &'a T: IntoIterator,
+{
+    type Item = <&'a T as IntoIterator>::Item;
+    type IntoIter = <&'a T as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.value).into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Tracked<T>
+where
+    &'a mut T: IntoIterator,
+{
+    type Item = <&'a mut T as IntoIterator>::Item;
+    type IntoIter = <&'a mut T as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&mut **self).into_iter()
+    }
+}
@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::write_atomic;
+
+/// Name of the workspace that keeps its data in the top-level data folder
+pub const DEFAULT_WORKSPACE: &str = "Default";
+/// A file with this name next to the executable turns on portable mode
+const PORTABLE_MARKER: &str = "portable";
+
+/// Where data is kept, from the command line
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// `--data-dir <path>`: use this folder and nothing else
+    pub data_dir: Option<PathBuf>,
+    /// `--portable`: keep everything in a `data` folder next to the executable
+    pub portable: bool,
+}
+
+impl LaunchOptions {
+    pub fn from_args(args: &[String]) -> Self {
+        let mut options = Self::default();
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--portable" {
+                options.portable = true;
+            } else if arg == "--data-dir" {
+                options.data_dir = args.next().map(PathBuf::from);
+            } else if let Some(path) = arg.strip_prefix("--data-dir=") {
+                options.data_dir = Some(PathBuf::from(path));
+            }
+        }
+        options
+    }
+}
+
+static LAUNCH: OnceLock<LaunchOptions> = OnceLock::new();
+
+/// Records the command line options; call once before any data is read
+pub fn configure(options: LaunchOptions) {
+    LAUNCH.set(options).ok();
+}
+
+fn launch_options() -> &'static LaunchOptions {
+    LAUNCH.get_or_init(LaunchOptions::default)
+}
+
+fn executable_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+pub fn is_portable() -> bool {
+    launch_options().portable
+        || executable_dir().is_some_and(|dir| dir.join(PORTABLE_MARKER).exists())
+}
+
+/// Data folder fixed with `--data-dir`; workspaces cannot be switched
+pub fn fixed_data_dir() -> Option<&'static Path> {
+    launch_options().data_dir.as_deref()
+}
+
+/// Top-level folder holding the workspace list and the default workspace
+pub fn root_dir() -> PathBuf {
+    if is_portable()
+        && let Some(dir) = executable_dir()
+    {
+        return dir.join("data");
+    }
+    directories::ProjectDirs::from("com", "inventory", "app")
+        .expect("Failed to get data directory")
+        .data_dir()
+        .to_path_buf()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    /// Custom folder, e.g. on a shared drive; by default the data lives under
+    /// the top-level data folder
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+}
+
+impl Workspace {
+    pub fn directory(&self) -> PathBuf {
+        if let Some(dir) = &self.data_dir {
+            return dir.clone();
+        }
+        if self.name == DEFAULT_WORKSPACE {
+            return root_dir();
+        }
+        let folder: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        root_dir().join("workspaces").join(folder)
+    }
+}
+
+/// The known workspaces and the one opened last
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceList {
+    pub active: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+impl Default for WorkspaceList {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_WORKSPACE.to_string(),
+            workspaces: vec![Workspace {
+                name: DEFAULT_WORKSPACE.to_string(),
+                data_dir: None,
+            }],
+        }
+    }
+}
+
+impl WorkspaceList {
+    fn path() -> PathBuf {
+        root_dir().join("workspaces.json")
+    }
+
+    pub fn load() -> Self {
+        let mut list: Self = std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        if list.workspaces.is_empty() {
+            list = Self::default();
+        }
+        if list.get(&list.active).is_none() {
+            list.active = list.workspaces[0].name.clone();
+        }
+        list
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::create_dir_all(root_dir())?;
+        write_atomic(&Self::path(), serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.name == name)
+    }
+
+    pub fn active(&self) -> &Workspace {
+        self.get(&self.active).unwrap_or(&self.workspaces[0])
+    }
+
+    pub fn add(&mut self, name: &str, data_dir: Option<PathBuf>) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Enter a workspace name".to_string());
+        }
+        if self.workspaces.iter().any(|w| w.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("A workspace named '{}' already exists", name));
+        }
+        let workspace = Workspace {
+            name: name.to_string(),
+            data_dir,
+        };
+        // Names that differ only in punctuation map to the same folder
+        let directory = workspace.directory();
+        if let Some(existing) = self.workspaces.iter().find(|w| w.directory() == directory) {
+            return Err(format!("'{}' would share its folder with the workspace '{}'", name, existing.name));
+        }
+        self.workspaces.push(workspace);
+        Ok(())
+    }
+
+    /// Forgets a workspace; its files are left on disk
+    pub fn remove(&mut self, name: &str) -> Result<(), String> {
+        if name == self.active {
+            return Err("The open workspace cannot be removed".to_string());
+        }
+        self.workspaces.retain(|w| w.name != name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_data_dir_flag() {
+        let args: Vec<String> = ["app", "--data-dir", "/srv/shop", "--portable"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = LaunchOptions::from_args(&args);
+        assert_eq!(options.data_dir, Some(PathBuf::from("/srv/shop")));
+        assert!(options.portable);
+
+        let options = LaunchOptions::from_args(&["app".to_string(), "--data-dir=/mnt/a".to_string()]);
+        assert_eq!(options.data_dir, Some(PathBuf::from("/mnt/a")));
+    }
+
+    #[test]
+    fn rejects_names_sharing_a_folder() {
+        let mut list = WorkspaceList::default();
+        list.add("Shop A", None).unwrap();
+        assert!(list.add("shop_a", None).is_err());
+        assert!(list.add("Shop.A", None).is_err());
+        list.add("Shop-A", None).unwrap();
+
+        list.add("Remote", Some(PathBuf::from("/srv/shop"))).unwrap();
+        assert!(list.add("Other", Some(PathBuf::from("/srv/shop"))).is_err());
+        assert_eq!(list.workspaces.len(), 4);
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Sent,
+    Received,
+    Cancelled,
+}
+
+impl std::fmt::Display for PurchaseOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurchaseOrderStatus::Draft => write!(f, "Draft"),
+            PurchaseOrderStatus::Sent => write!(f, "Sent"),
+            PurchaseOrderStatus::Received => write!(f, "Received"),
+            PurchaseOrderStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurchaseOrderLine {
+    pub item_id: String,
+    pub item_name: String,
+    pub item_sku: String,
+    pub quantity: u32,
+    pub unit_price: f64,
+}
+
+impl PurchaseOrderLine {
+    pub fn total(&self) -> f64 {
+        self.quantity as f64 * self.unit_price
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurchaseOrder {
+    pub id: String,
+    pub number: String,
+    pub supplier: String,
+    pub status: PurchaseOrderStatus,
+    pub lines: Vec<PurchaseOrderLine>,
+    pub created_at: i64,
+    pub created_by: String,
+}
+
+impl PurchaseOrder {
+    pub fn new_draft(
+        number: String,
+        supplier: String,
+        lines: Vec<PurchaseOrderLine>,
+        created_by: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            number,
+            supplier,
+            status: PurchaseOrderStatus::Draft,
+            lines,
+            created_at: Utc::now().timestamp(),
+            created_by,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.lines.iter().map(|line| line.total()).sum()
+    }
+
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// Returns the next sequential purchase order number, e.g. `PO-000042`
+pub fn next_order_number(orders: &[PurchaseOrder]) -> String {
+    let last = orders
+        .iter()
+        .filter_map(|order| order.number.strip_prefix("PO-"))
+        .filter_map(|n| n.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("PO-{:06}", last + 1)
+}
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::alerts::{AlertSettings, AlertType, StockAlert};
+use crate::inventory::InventoryItem;
+
+#[derive(Debug, Clone)]
+pub struct ReorderSuggestion {
+    pub item_id: String,
+    pub item_name: String,
+    pub item_sku: String,
+    pub supplier: String,
+    pub current_quantity: u32,
+    pub reorder_point: u32,
+    pub suggested_quantity: u32,
+    pub unit_price: f64,
+    pub alert_type: Option<AlertType>,
+}
+
+/// Suggested order for a single item.
+///
+/// Available stock is brought back above the reorder point (or the low stock threshold
+/// when the item has none). With a reorder quantity set, the suggestion is
+/// rounded up to a multiple of it; otherwise we order up to twice the
+/// reorder point.
+pub fn suggested_quantity(item: &InventoryItem, settings: &AlertSettings) -> u32 {
+    let point = effective_reorder_point(item, settings);
+    let shortfall = (point + 1).saturating_sub(item.available()).max(1);
+
+    if item.reorder_quantity > 0 {
+        shortfall.div_ceil(item.reorder_quantity) * item.reorder_quantity
+    } else {
+        (point * 2).saturating_sub(item.available()).max(shortfall)
+    }
+}
+
+pub fn effective_reorder_point(item: &InventoryItem, settings: &AlertSettings) -> u32 {
+    if item.reorder_point > 0 {
+        item.reorder_point
+    } else {
+        settings.low_stock_threshold
+    }
+}
+
+/// Builds the reorder worksheet from the active stock alerts plus any item
+/// that has dropped to its own reorder point without raising an alert.
+pub fn build_worksheet(
+    alerts: &[StockAlert],
+    items: &[InventoryItem],
+    settings: &AlertSettings,
+) -> Vec<ReorderSuggestion> {
+    let items_by_id: HashMap<&str, &InventoryItem> =
+        items.iter().map(|item| (item.id.as_str(), item)).collect();
+
+    let mut suggestions: Vec<ReorderSuggestion> = Vec::new();
+
+    for alert in alerts
+        .iter()
+        .filter(|a| !a.acknowledged && !matches!(a.alert_type, AlertType::Rule(_)))
+    {
+        if let Some(item) = items_by_id.get(alert.item_id.as_str()) {
+            suggestions.push(make_suggestion(item, Some(alert.alert_type.clone()), settings));
+        }
+    }
+
+    for item in items {
+        let already_listed = suggestions.iter().any(|s| s.item_id == item.id);
+        if !already_listed && item.reorder_point > 0 && item.available() <= item.reorder_point {
+            suggestions.push(make_suggestion(item, None, settings));
+        }
+    }
+
+    suggestions.sort_by(|a, b| {
+        a.supplier
+            .to_lowercase()
+            .cmp(&b.supplier.to_lowercase())
+            .then_with(|| a.item_name.to_lowercase().cmp(&b.item_name.to_lowercase()))
+    });
+    suggestions
+}
+
+fn make_suggestion(
+    item: &InventoryItem,
+    alert_type: Option<AlertType>,
+    settings: &AlertSettings,
+) -> ReorderSuggestion {
+    ReorderSuggestion {
+        item_id: item.id.clone(),
+        item_name: item.name.clone(),
+        item_sku: item.sku.clone(),
+        supplier: item.supplier.clone(),
+        current_quantity: item.available(),
+        reorder_point: effective_reorder_point(item, settings),
+        suggested_quantity: suggested_quantity(item, settings),
+        unit_price: item.price,
+        alert_type,
+    }
+}
+
+/// Groups suggestions by supplier name; items without a supplier are
+/// collected under an empty key.
+pub fn group_by_supplier(
+    suggestions: &[ReorderSuggestion],
+) -> BTreeMap<String, Vec<&ReorderSuggestion>> {
+    let mut groups: BTreeMap<String, Vec<&ReorderSuggestion>> = BTreeMap::new();
+    for suggestion in suggestions {
+        groups
+            .entry(suggestion.supplier.trim().to_string())
+            .or_default()
+            .push(suggestion);
+    }
+    groups
+}
+
+pub fn supplier_label(supplier: &str) -> &str {
+    if supplier.is_empty() {
+        "No Supplier"
+    } else {
+        supplier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, supplier: &str, quantity: u32, reorder_point: u32, reorder_quantity: u32) -> InventoryItem {
+        let mut item = InventoryItem::new(
+            name.to_string(),
+            name.to_uppercase(),
+            "Parts".to_string(),
+            supplier.to_string(),
+            String::new(),
+            quantity,
+            2.5,
+        );
+        item.reorder_point = reorder_point;
+        item.reorder_quantity = reorder_quantity;
+        item
+    }
+
+    #[test]
+    fn suggestions_refill_available_stock() {
+        let settings = AlertSettings::default();
+
+        // No reorder settings: up to twice the low stock threshold
+        assert_eq!(suggested_quantity(&item("bolts", "", 4, 0, 0), &settings), 16);
+        // Rounded up to whole reorder quantities
+        assert_eq!(suggested_quantity(&item("bolts", "", 4, 0, 6), &settings), 12);
+        // Allocated stock doesn't count
+        let mut reserved = item("nuts", "", 8, 5, 0);
+        reserved.allocated = 5;
+        assert_eq!(effective_reorder_point(&reserved, &settings), 5);
+        assert_eq!(suggested_quantity(&reserved, &settings), 7);
+        // Always at least one
+        assert_eq!(suggested_quantity(&item("pins", "", 50, 5, 0), &settings), 1);
+    }
+
+    #[test]
+    fn worksheet_lists_open_alerts_and_items_at_their_reorder_point() {
+        let settings = AlertSettings::default();
+        let items = vec![
+            item("alpha", "Zeta", 2, 0, 0),
+            item("beta", "Zeta", 1, 0, 0),
+            item("gamma", "Zeta", 1, 0, 0),
+            item("delta", "acme", 5, 5, 0),
+            item("epsilon", "acme", 6, 5, 0),
+        ];
+        let mut acknowledged = StockAlert::new(&items[1], AlertType::LowStock, 10);
+        acknowledged.acknowledged = true;
+        let alerts = vec![
+            StockAlert::new(&items[0], AlertType::LowStock, 10),
+            acknowledged,
+            StockAlert::new(&items[2], AlertType::Rule("Check".to_string()), 0),
+        ];
+
+        let worksheet = build_worksheet(&alerts, &items, &settings);
+        let names: Vec<&str> = worksheet.iter().map(|s| s.item_name.as_str()).collect();
+        assert_eq!(names, ["delta", "alpha"]);
+        assert_eq!(worksheet[0].alert_type, None);
+        assert_eq!(worksheet[1].alert_type, Some(AlertType::LowStock));
+    }
+
+    #[test]
+    fn groups_ignore_surrounding_spaces() {
+        let settings = AlertSettings::default();
+        let items = [item("a", " Acme ", 0, 0, 0), item("b", "Acme", 0, 0, 0), item("c", "", 0, 0, 0)];
+        let suggestions: Vec<ReorderSuggestion> = items
+            .iter()
+            .map(|item| make_suggestion(item, None, &settings))
+            .collect();
+
+        let groups = group_by_supplier(&suggestions);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["Acme"].len(), 2);
+        assert_eq!(supplier_label(""), "No Supplier");
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, NaiveDate};
+use printpdf::image_crate::{self, DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::{AlertType, StockAlert};
+use crate::audit::AuditEntry;
+use crate::currency;
+use crate::inventory::InventoryItem;
+use crate::pdf::{PageHeader, PdfWriter};
+
+/// Printed when no company name has been set
+const DEFAULT_COMPANY: &str = "Inventory Manager";
+/// Largest logo width or height kept, enough for print at the header size
+const LOGO_MAX_PIXELS: u32 = 400;
+
+/// Letterhead used on printed reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportSettings {
+    pub company_name: String,
+    /// PNG or JPEG shown beside the company name
+    pub logo_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    Valuation,
+    StockList,
+    LowStock,
+    AuditTrail,
+}
+
+impl ReportKind {
+    pub const ALL: [ReportKind; 4] = [
+        ReportKind::Valuation,
+        ReportKind::StockList,
+        ReportKind::LowStock,
+        ReportKind::AuditTrail,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ReportKind::Valuation => "Stock value by category, with subtotals and a grand total.",
+            ReportKind::StockList => "Items matching the current inventory search and filters.",
+            ReportKind::LowStock => "Items that are out of stock, low or at risk of running out.",
+            ReportKind::AuditTrail => "Every audit log entry between two dates.",
+        }
+    }
+
+    /// Start of the suggested file name
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            ReportKind::Valuation => "inventory_valuation",
+            ReportKind::StockList => "stock_list",
+            ReportKind::LowStock => "low_stock",
+            ReportKind::AuditTrail => "audit_trail",
+        }
+    }
+}
+
+impl fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportKind::Valuation => write!(f, "Inventory Valuation"),
+            ReportKind::StockList => write!(f, "Stock List"),
+            ReportKind::LowStock => write!(f, "Low Stock Report"),
+            ReportKind::AuditTrail => write!(f, "Audit Trail"),
+        }
+    }
+}
+
+/// Snapshot of what a report prints, taken when it is requested
+#[derive(Debug, Clone)]
+pub enum ReportData {
+    Valuation(Vec<InventoryItem>),
+    StockList {
+        items: Vec<InventoryItem>,
+        /// The search filter the items were picked with
+        filter: String,
+    },
+    LowStock {
+        alerts: Vec<StockAlert>,
+        items: Vec<InventoryItem>,
+    },
+    AuditTrail {
+        entries: Vec<AuditEntry>,
+        from: NaiveDate,
+        to: NaiveDate,
+    },
+}
+
+impl ReportData {
+    pub fn kind(&self) -> ReportKind {
+        match self {
+            ReportData::Valuation(_) => ReportKind::Valuation,
+            ReportData::StockList { .. } => ReportKind::StockList,
+            ReportData::LowStock { .. } => ReportKind::LowStock,
+            ReportData::AuditTrail { .. } => ReportKind::AuditTrail,
+        }
+    }
+}
+
+/// Who the report is for and by
+#[derive(Debug, Clone)]
+pub struct ReportContext {
+    pub settings: ReportSettings,
+    pub generated_at: DateTime<Local>,
+    pub generated_by: String,
+    pub currency: String,
+}
+
+impl ReportContext {
+    fn header(&self, title: &str) -> Result<PageHeader, String> {
+        let company = match self.settings.company_name.trim() {
+            "" => DEFAULT_COMPANY.to_string(),
+            name => name.to_string(),
+        };
+        let logo = match &self.settings.logo_path {
+            Some(path) => Some(load_logo(path)?),
+            None => None,
+        };
+        Ok(PageHeader {
+            company,
+            title: title.to_string(),
+            subtitle: format!(
+                "Generated {} by {}",
+                self.generated_at.format("%Y-%m-%d %H:%M"),
+                self.generated_by
+            ),
+            logo,
+        })
+    }
+
+    fn money(&self, amount: f64) -> String {
+        currency::format_currency_with_exp(amount, &self.currency)
+    }
+}
+
+/// Reads the logo, shrinking it since it is embedded on every page and
+/// flattening any transparency onto white as PDF images here have no alpha
+fn load_logo(path: &PathBuf) -> Result<DynamicImage, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Could not read the logo {}: {}", path.display(), e))?;
+    let image = image_crate::load_from_memory(&bytes)
+        .map_err(|e| format!("Could not read the logo {}: {}", path.display(), e))?
+        .thumbnail(LOGO_MAX_PIXELS, LOGO_MAX_PIXELS)
+        .to_rgba8();
+    let flattened = RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let blend = |channel: u8| ((channel as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    });
+    Ok(DynamicImage::ImageRgb8(flattened))
+}
+
+/// Cuts a value to roughly fit a column `chars` characters wide
+fn fit(value: &str, chars: usize) -> String {
+    if value.chars().count() <= chars {
+        value.to_string()
+    } else {
+        let cut: String = value.chars().take(chars.saturating_sub(3)).collect();
+        format!("{}...", cut.trim_end())
+    }
+}
+
+/// Splits text into lines of at most `chars` characters at spaces
+fn wrap(value: &str, chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in value.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+        while line.chars().count() > chars {
+            let rest: String = line.chars().skip(chars).collect();
+            lines.push(line.chars().take(chars).collect());
+            line = rest;
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+pub fn render(context: &ReportContext, data: &ReportData) -> Result<Vec<u8>, String> {
+    let title = data.kind().to_string();
+    let mut pdf = PdfWriter::new(&title)?.with_header(context.header(&title)?);
+    match data {
+        ReportData::Valuation(items) => valuation(&mut pdf, context, items),
+        ReportData::StockList { items, filter } => stock_list(&mut pdf, items, filter),
+        ReportData::LowStock { alerts, items } => low_stock(&mut pdf, alerts, items),
+        ReportData::AuditTrail { entries, from, to } => audit_trail(&mut pdf, entries, *from, *to),
+    }
+    pdf.into_bytes()
+}
+
+fn valuation(pdf: &mut PdfWriter, context: &ReportContext, items: &[InventoryItem]) {
+    let mut categories: BTreeMap<&str, Vec<&InventoryItem>> = BTreeMap::new();
+    for item in items {
+        let category = if item.category.is_empty() { "Uncategorised" } else { &item.category };
+        categories.entry(category).or_default().push(item);
+    }
+
+    let columns = [0.0, 30.0, 108.0, 128.0, 155.0];
+    let mut total_units: u64 = 0;
+    let mut total_value = 0.0;
+    for (category, mut category_items) in categories {
+        category_items.sort_by_key(|item| item.name.to_lowercase());
+        pdf.heading(category);
+        pdf.row(
+            &[
+                ("SKU", columns[0]),
+                ("Item", columns[1]),
+                ("Quantity", columns[2]),
+                ("Unit Price", columns[3]),
+                ("Value", columns[4]),
+            ],
+            true,
+        );
+        pdf.rule();
+
+        let mut units: u64 = 0;
+        let mut value = 0.0;
+        for item in category_items {
+            units += item.quantity as u64;
+            value += item.total_value();
+            pdf.row(
+                &[
+                    (&fit(&item.sku, 14), columns[0]),
+                    (&fit(&item.name, 40), columns[1]),
+                    (&item.quantity.to_string(), columns[2]),
+                    (&context.money(item.price), columns[3]),
+                    (&context.money(item.total_value()), columns[4]),
+                ],
+                false,
+            );
+        }
+        pdf.rule();
+        pdf.row(
+            &[
+                (&format!("Subtotal: {}", fit(category, 50)), columns[0]),
+                (&units.to_string(), columns[2]),
+                (&context.money(value), columns[4]),
+            ],
+            true,
+        );
+        total_units += units;
+        total_value += value;
+    }
+
+    pdf.spacer(4.0);
+    pdf.rule();
+    pdf.row(
+        &[
+            (&format!("Total ({} items)", items.len()), columns[0]),
+            (&total_units.to_string(), columns[2]),
+            (&context.money(total_value), columns[4]),
+        ],
+        true,
+    );
+}
+
+fn stock_list(pdf: &mut PdfWriter, items: &[InventoryItem], filter: &str) {
+    let columns = [0.0, 26.0, 82.0, 116.0, 142.0, 162.0];
+    pdf.text(filter);
+    pdf.spacer(2.0);
+    pdf.row(
+        &[
+            ("SKU", columns[0]),
+            ("Item", columns[1]),
+            ("Category", columns[2]),
+            ("Location", columns[3]),
+            ("On Hand", columns[4]),
+            ("Available", columns[5]),
+        ],
+        true,
+    );
+    pdf.rule();
+    for item in items {
+        pdf.row(
+            &[
+                (&fit(&item.sku, 13), columns[0]),
+                (&fit(&item.name, 30), columns[1]),
+                (&fit(&item.category, 18), columns[2]),
+                (&fit(&item.location, 13), columns[3]),
+                (&item.quantity.to_string(), columns[4]),
+                (&item.available().to_string(), columns[5]),
+            ],
+            false,
+        );
+    }
+    pdf.rule();
+    let units: u64 = items.iter().map(|item| item.quantity as u64).sum();
+    pdf.row(
+        &[
+            (&format!("{} items", items.len()), columns[0]),
+            (&units.to_string(), columns[4]),
+        ],
+        true,
+    );
+}
+
+fn low_stock(pdf: &mut PdfWriter, alerts: &[StockAlert], items: &[InventoryItem]) {
+    if alerts.is_empty() {
+        pdf.text("No items are out of stock or running low.");
+        return;
+    }
+
+    let columns = [0.0, 28.0, 90.0, 128.0, 148.0, 163.0];
+    let order = [
+        AlertType::OutOfStock,
+        AlertType::CriticallyLow,
+        AlertType::LowStock,
+        AlertType::StockoutRisk,
+    ];
+    for alert_type in order {
+        let mut matching: Vec<&StockAlert> = alerts.iter().filter(|alert| alert.alert_type == alert_type).collect();
+        if matching.is_empty() {
+            continue;
+        }
+        matching.sort_by_key(|alert| (alert.current_quantity, alert.item_name.to_lowercase()));
+
+        pdf.heading(&format!("{} ({})", alert_type, matching.len()));
+        pdf.row(
+            &[
+                ("SKU", columns[0]),
+                ("Item", columns[1]),
+                ("Supplier", columns[2]),
+                ("Available", columns[3]),
+                ("Threshold", columns[4]),
+                ("Since", columns[5]),
+            ],
+            true,
+        );
+        pdf.rule();
+        for alert in matching {
+            let supplier = items
+                .iter()
+                .find(|item| item.id == alert.item_id)
+                .map(|item| item.supplier.as_str())
+                .unwrap_or_default();
+            let since = chrono::DateTime::from_timestamp(alert.created_at, 0)
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            pdf.row(
+                &[
+                    (&fit(&alert.item_sku, 14), columns[0]),
+                    (&fit(&alert.item_name, 32), columns[1]),
+                    (&fit(supplier, 20), columns[2]),
+                    (&alert.current_quantity.to_string(), columns[3]),
+                    (&alert.threshold.to_string(), columns[4]),
+                    (&since, columns[5]),
+                ],
+                false,
+            );
+        }
+    }
+}
+
+fn audit_trail(pdf: &mut PdfWriter, entries: &[AuditEntry], from: NaiveDate, to: NaiveDate) {
+    let columns = [0.0, 32.0, 58.0, 95.0];
+    pdf.text(&format!(
+        "{} to {} (UTC), {} entries",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d"),
+        entries.len()
+    ));
+    pdf.spacer(2.0);
+    pdf.row(
+        &[
+            ("Time", columns[0]),
+            ("User", columns[1]),
+            ("Action", columns[2]),
+            ("Details", columns[3]),
+        ],
+        true,
+    );
+    pdf.rule();
+    for entry in entries {
+        let time = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let mut details = wrap(&entry.details, 46).into_iter();
+        pdf.row(
+            &[
+                (&time, columns[0]),
+                (&fit(&entry.username, 12), columns[1]),
+                (&fit(&entry.action.to_string(), 19), columns[2]),
+                (&details.next().unwrap_or_default(), columns[3]),
+            ],
+            false,
+        );
+        for line in details {
+            pdf.row(&[(&line, columns[3])], false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_and_fits_text() {
+        assert_eq!(wrap("Updated quantity from 5 to 10", 12), vec!["Updated", "quantity", "from 5 to 10"]);
+        assert_eq!(wrap("", 12), vec![String::new()]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(fit("Widget", 10), "Widget");
+        assert_eq!(fit("A very long item name", 10), "A very...");
+    }
+
+    #[test]
+    fn renders_valuation_over_several_pages() {
+        let items: Vec<InventoryItem> = (0..120)
+            .map(|i| {
+                InventoryItem::new(
+                    format!("Item {}", i),
+                    format!("SKU-{:03}", i),
+                    ["Parts", "Tools", ""][i % 3].to_string(),
+                    String::new(),
+                    String::new(),
+                    i as u32,
+                    1.5,
+                )
+            })
+            .collect();
+        let context = ReportContext {
+            settings: ReportSettings::default(),
+            generated_at: Local::now(),
+            generated_by: "admin".to_string(),
+            currency: "USD".to_string(),
+        };
+        let bytes = render(&context, &ReportData::Valuation(items)).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::InventoryItem;
+use crate::stock::{MovementKind, StockMovement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReturnReason {
+    Damaged,
+    Defective,
+    WrongItem,
+    NotAsDescribed,
+    NoLongerNeeded,
+    Other,
+}
+
+impl ReturnReason {
+    pub const ALL: [ReturnReason; 6] = [
+        ReturnReason::Damaged,
+        ReturnReason::Defective,
+        ReturnReason::WrongItem,
+        ReturnReason::NotAsDescribed,
+        ReturnReason::NoLongerNeeded,
+        ReturnReason::Other,
+    ];
+}
+
+impl std::fmt::Display for ReturnReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnReason::Damaged => write!(f, "Damaged"),
+            ReturnReason::Defective => write!(f, "Defective"),
+            ReturnReason::WrongItem => write!(f, "Wrong Item"),
+            ReturnReason::NotAsDescribed => write!(f, "Not as Described"),
+            ReturnReason::NoLongerNeeded => write!(f, "No Longer Needed"),
+            ReturnReason::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// What happens to the returned units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disposition {
+    Restock,
+    Quarantine,
+    ReturnToVendor,
+    Scrap,
+}
+
+impl Disposition {
+    pub const ALL: [Disposition; 4] = [
+        Disposition::Restock,
+        Disposition::Quarantine,
+        Disposition::ReturnToVendor,
+        Disposition::Scrap,
+    ];
+
+    /// Ledger entry kind posted for this disposition
+    pub fn movement_kind(&self) -> MovementKind {
+        match self {
+            Disposition::Restock => MovementKind::Return,
+            Disposition::Quarantine => MovementKind::Quarantine,
+            Disposition::ReturnToVendor => MovementKind::ReturnToVendor,
+            Disposition::Scrap => MovementKind::Scrap,
+        }
+    }
+}
+
+impl std::fmt::Display for Disposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Disposition::Restock => write!(f, "Restock"),
+            Disposition::Quarantine => write!(f, "Quarantine"),
+            Disposition::ReturnToVendor => write!(f, "Return to Vendor"),
+            Disposition::Scrap => write!(f, "Scrap"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReturnLine {
+    pub item_id: String,
+    pub item_name: String,
+    pub item_sku: String,
+    pub quantity: u32,
+    pub unit_price: f64,
+    pub disposition: Disposition,
+}
+
+impl ReturnLine {
+    pub fn total(&self) -> f64 {
+        self.quantity as f64 * self.unit_price
+    }
+}
+
+/// A return merchandise authorization (RMA)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReturnAuthorization {
+    pub id: String,
+    pub number: String,
+    pub sales_order_id: Option<String>,
+    pub sales_order_number: Option<String>,
+    pub customer_name: String,
+    pub reason: ReturnReason,
+    pub notes: String,
+    pub lines: Vec<ReturnLine>,
+    pub created_at: i64,
+    pub created_by: String,
+}
+
+impl ReturnAuthorization {
+    pub fn new(
+        number: String,
+        customer_name: String,
+        reason: ReturnReason,
+        notes: String,
+        lines: Vec<ReturnLine>,
+        created_by: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            number,
+            sales_order_id: None,
+            sales_order_number: None,
+            customer_name,
+            reason,
+            notes,
+            lines,
+            created_at: Utc::now().timestamp(),
+            created_by,
+        }
+    }
+
+    pub fn total_units(&self) -> u32 {
+        self.lines.iter().map(|line| line.quantity).sum()
+    }
+
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// Returns the next sequential RMA number, e.g. `RMA-000042`
+pub fn next_return_number(returns: &[ReturnAuthorization]) -> String {
+    let last = returns
+        .iter()
+        .filter_map(|rma| rma.number.strip_prefix("RMA-"))
+        .filter_map(|n| n.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("RMA-{:06}", last + 1)
+}
+
+/// Units of an item already returned against a sales order
+pub fn returned_quantity(returns: &[ReturnAuthorization], sales_order_id: &str, item_id: &str) -> u32 {
+    returns
+        .iter()
+        .filter(|rma| rma.sales_order_id.as_deref() == Some(sales_order_id))
+        .flat_map(|rma| rma.lines.iter())
+        .filter(|line| line.item_id == item_id)
+        .map(|line| line.quantity)
+        .sum()
+}
+
+#[derive(Debug, Clone)]
+pub struct ReturnsReportRow {
+    pub item_sku: String,
+    pub item_name: String,
+    pub reason: ReturnReason,
+    pub returns: usize,
+    pub quantity: u32,
+    pub value: f64,
+}
+
+/// Returned units and value per item and reason, largest quantities first
+pub fn returns_report(returns: &[ReturnAuthorization]) -> Vec<ReturnsReportRow> {
+    let mut rows: BTreeMap<(String, String), ReturnsReportRow> = BTreeMap::new();
+
+    for rma in returns {
+        for line in &rma.lines {
+            let row = rows
+                .entry((line.item_id.clone(), rma.reason.to_string()))
+                .or_insert_with(|| ReturnsReportRow {
+                    item_sku: line.item_sku.clone(),
+                    item_name: line.item_name.clone(),
+                    reason: rma.reason,
+                    returns: 0,
+                    quantity: 0,
+                    value: 0.0,
+                });
+            row.returns += 1;
+            row.quantity += line.quantity;
+            row.value += line.total();
+        }
+    }
+
+    let mut rows: Vec<ReturnsReportRow> = rows.into_values().collect();
+    rows.sort_by(|a, b| {
+        b.quantity
+            .cmp(&a.quantity)
+            .then_with(|| a.item_name.to_lowercase().cmp(&b.item_name.to_lowercase()))
+    });
+    rows
+}
+
+/// Units currently held in quarantine per item id
+pub fn quarantined_by_item(movements: &[StockMovement]) -> BTreeMap<String, i64> {
+    let mut quarantined: BTreeMap<String, i64> = BTreeMap::new();
+    for movement in movements.iter().filter(|m| m.kind == MovementKind::Quarantine) {
+        *quarantined.entry(movement.item_id.clone()).or_insert(0) += movement.quantity;
+    }
+    quarantined.retain(|_, qty| *qty > 0);
+    quarantined
+}
+
+/// Takes inspected units out of quarantine: back into stock, back to the
+/// vendor, or written off. Returns the ledger entries to record, one taking
+/// the units out of quarantine and one for where they went.
+pub fn resolve_quarantine(
+    item: &mut InventoryItem,
+    movements: &[StockMovement],
+    quantity: u32,
+    disposition: Disposition,
+    created_by: String,
+) -> Result<[StockMovement; 2], String> {
+    if disposition == Disposition::Quarantine {
+        return Err("Choose where the quarantined units go".to_string());
+    }
+    let held = quarantined_by_item(movements).get(&item.id).copied().unwrap_or(0);
+    if quantity == 0 || i64::from(quantity) > held {
+        return Err(format!("{} of {} are held in quarantine", held, item.name));
+    }
+
+    let kind = disposition.movement_kind();
+    if kind.affects_on_hand() {
+        item.quantity += quantity;
+        item.update_timestamp();
+    }
+    let quantity = i64::from(quantity);
+    let reference = "Quarantine".to_string();
+    Ok([
+        StockMovement::new(item, MovementKind::Quarantine, -quantity, reference.clone(), created_by.clone()),
+        StockMovement::new(item, kind, quantity, reference, created_by),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantined_units_can_be_released_or_written_off() {
+        let mut item = InventoryItem::new(
+            "Drill".to_string(),
+            "TOOL-001".to_string(),
+            "Tools".to_string(),
+            String::new(),
+            String::new(),
+            2,
+            80.0,
+        );
+        let mut movements = vec![StockMovement::new(
+            &item,
+            MovementKind::Quarantine,
+            5,
+            "RMA-000001".to_string(),
+            "admin".to_string(),
+        )];
+
+        let admin = || "admin".to_string();
+        assert!(resolve_quarantine(&mut item, &movements, 6, Disposition::Restock, admin()).is_err());
+        assert!(resolve_quarantine(&mut item, &movements, 1, Disposition::Quarantine, admin()).is_err());
+
+        let released = resolve_quarantine(&mut item, &movements, 3, Disposition::Restock, admin()).unwrap();
+        movements.extend(released);
+        assert_eq!(item.quantity, 5);
+        assert_eq!(quarantined_by_item(&movements).get(&item.id), Some(&2));
+
+        let scrapped = resolve_quarantine(&mut item, &movements, 2, Disposition::Scrap, admin()).unwrap();
+        movements.extend(scrapped);
+        assert_eq!(item.quantity, 5);
+        assert!(quarantined_by_item(&movements).is_empty());
+    }
+}
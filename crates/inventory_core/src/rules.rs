@@ -0,0 +1,797 @@
+//! Automation rules: "when this happens to an item that matches, do that".
+//!
+//! A rule has a trigger, a condition written over the fields the inventory
+//! search knows about, and actions run for each item the rule fires for.
+//! Conditions are typed as clauses joined with `and`, for example
+//! `category is Consumables and quantity < 20` or `supplier is empty`.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::export::ExportJob;
+use crate::inventory::InventoryItem;
+use crate::schedule::{self, Frequency, Schedule};
+use crate::search::SearchFilter;
+
+/// User id recorded on audit entries for changes made by rules
+pub const AUTOMATION_USER_ID: &str = "automation";
+
+/// Username recorded on audit entries for changes made by rules
+pub const AUTOMATION_USERNAME: &str = "Automation";
+
+/// What makes a rule look at items
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RuleTrigger {
+    /// An item was added or changed so that it now meets the condition
+    ItemChanged,
+    /// A stock alert was raised for an item meeting the condition
+    AlertRaised,
+    /// On a schedule, for every item meeting the condition
+    Schedule(Schedule),
+}
+
+impl RuleTrigger {
+    pub fn kind(&self) -> TriggerKind {
+        match self {
+            RuleTrigger::ItemChanged => TriggerKind::ItemChanged,
+            RuleTrigger::AlertRaised => TriggerKind::AlertRaised,
+            RuleTrigger::Schedule(_) => TriggerKind::Schedule,
+        }
+    }
+}
+
+impl fmt::Display for RuleTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleTrigger::ItemChanged => write!(f, "When an item starts to match"),
+            RuleTrigger::AlertRaised => write!(f, "When a stock alert is raised"),
+            RuleTrigger::Schedule(schedule) => write!(f, "{}", schedule),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerKind {
+    ItemChanged,
+    AlertRaised,
+    Schedule,
+}
+
+impl TriggerKind {
+    pub const ALL: [TriggerKind; 3] = [TriggerKind::ItemChanged, TriggerKind::AlertRaised, TriggerKind::Schedule];
+}
+
+impl fmt::Display for TriggerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerKind::ItemChanged => write!(f, "Item changed"),
+            TriggerKind::AlertRaised => write!(f, "Alert raised"),
+            TriggerKind::Schedule => write!(f, "Schedule"),
+        }
+    }
+}
+
+/// Search fields a condition clause can test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionField {
+    /// Free text, matched like the search box
+    Text,
+    Category,
+    Supplier,
+    Class,
+    Quantity,
+    Price,
+}
+
+impl ConditionField {
+    fn parse(word: &str) -> Option<Self> {
+        match word.to_lowercase().as_str() {
+            "text" => Some(ConditionField::Text),
+            "category" => Some(ConditionField::Category),
+            "supplier" => Some(ConditionField::Supplier),
+            "class" | "abc" => Some(ConditionField::Class),
+            "quantity" | "qty" => Some(ConditionField::Quantity),
+            "price" => Some(ConditionField::Price),
+            _ => None,
+        }
+    }
+
+    fn allows(&self, comparison: Comparison) -> bool {
+        use Comparison::*;
+        match self {
+            ConditionField::Text => comparison == Contains,
+            ConditionField::Category | ConditionField::Supplier => {
+                matches!(comparison, Is | IsNot | Contains | IsEmpty | IsNotEmpty)
+            }
+            ConditionField::Class => matches!(comparison, Is | IsNot),
+            ConditionField::Quantity | ConditionField::Price => {
+                matches!(comparison, Is | IsNot | Below | AtMost | Above | AtLeast)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConditionField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionField::Text => write!(f, "text"),
+            ConditionField::Category => write!(f, "category"),
+            ConditionField::Supplier => write!(f, "supplier"),
+            ConditionField::Class => write!(f, "class"),
+            ConditionField::Quantity => write!(f, "quantity"),
+            ConditionField::Price => write!(f, "price"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Is,
+    IsNot,
+    Contains,
+    IsEmpty,
+    IsNotEmpty,
+    Below,
+    AtMost,
+    Above,
+    AtLeast,
+}
+
+/// One `field comparison value` test
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub field: ConditionField,
+    pub comparison: Comparison,
+    pub value: String,
+}
+
+impl Clause {
+    fn matches(&self, item: &InventoryItem) -> bool {
+        match self.field {
+            ConditionField::Text => SearchFilter {
+                query: self.value.clone(),
+                ..SearchFilter::default()
+            }
+            .matches(item),
+            ConditionField::Category => self.matches_text(&item.category),
+            ConditionField::Supplier => self.matches_text(&item.supplier),
+            ConditionField::Class => {
+                let same = item
+                    .abc_class
+                    .is_some_and(|class| class.to_string().eq_ignore_ascii_case(&self.value));
+                same == (self.comparison == Comparison::Is)
+            }
+            ConditionField::Quantity => self.matches_number(f64::from(item.quantity)),
+            ConditionField::Price => self.matches_number(item.price),
+        }
+    }
+
+    fn matches_text(&self, actual: &str) -> bool {
+        match self.comparison {
+            Comparison::Is => actual.eq_ignore_ascii_case(&self.value),
+            Comparison::IsNot => !actual.eq_ignore_ascii_case(&self.value),
+            Comparison::Contains => actual.to_lowercase().contains(&self.value.to_lowercase()),
+            Comparison::IsEmpty => actual.trim().is_empty(),
+            Comparison::IsNotEmpty => !actual.trim().is_empty(),
+            _ => false,
+        }
+    }
+
+    fn matches_number(&self, actual: f64) -> bool {
+        let Ok(value) = self.value.parse::<f64>() else {
+            return false;
+        };
+        match self.comparison {
+            Comparison::Is => actual == value,
+            Comparison::IsNot => actual != value,
+            Comparison::Below => actual < value,
+            Comparison::AtMost => actual <= value,
+            Comparison::Above => actual > value,
+            Comparison::AtLeast => actual >= value,
+            _ => false,
+        }
+    }
+}
+
+/// Clauses that must all hold; no clauses matches every item
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Condition {
+    pub clauses: Vec<Clause>,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut clauses = Vec::new();
+        let mut current = Vec::new();
+        for token in tokenize(source)? {
+            if !token.quoted && token.text.eq_ignore_ascii_case("and") {
+                clauses.push(parse_clause(&current)?);
+                current.clear();
+            } else {
+                current.push(token);
+            }
+        }
+        if !current.is_empty() || !clauses.is_empty() {
+            clauses.push(parse_clause(&current)?);
+        }
+        Ok(Self { clauses })
+    }
+
+    pub fn matches(&self, item: &InventoryItem) -> bool {
+        self.clauses.iter().all(|clause| clause.matches(item))
+    }
+}
+
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+/// Splits a condition into words, quoted values and comparison operators
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '"' | '\'' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some(other) => text.push(other),
+                        None => return Err(format!("A value starting with {} is not closed", c)),
+                    }
+                }
+                tokens.push(Token { text, quoted: true });
+            }
+            '<' | '>' | '=' | '!' => {
+                let mut text = c.to_string();
+                if chars.peek() == Some(&'=') {
+                    text.push('=');
+                    chars.next();
+                }
+                tokens.push(Token { text, quoted: false });
+            }
+            _ => {
+                let mut text = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '"' | '\'' | '<' | '>' | '=' | '!') {
+                        break;
+                    }
+                    text.push(next);
+                    chars.next();
+                }
+                tokens.push(Token { text, quoted: false });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_clause(tokens: &[Token]) -> Result<Clause, String> {
+    let Some((first, rest)) = tokens.split_first() else {
+        return Err("A condition is missing between two 'and's".to_string());
+    };
+    let field = ConditionField::parse(&first.text).ok_or_else(|| {
+        format!(
+            "Unknown field '{}'; use text, category, supplier, class, quantity or price",
+            first.text
+        )
+    })?;
+
+    let word = |index: usize| {
+        rest.get(index)
+            .filter(|token| !token.quoted)
+            .map(|token| token.text.to_lowercase())
+            .unwrap_or_default()
+    };
+    let (comparison, value_start) = match (word(0).as_str(), word(1).as_str(), word(2).as_str()) {
+        ("is", "not", "empty") if rest.len() == 3 => (Comparison::IsNotEmpty, 3),
+        ("is", "empty", _) if rest.len() == 2 => (Comparison::IsEmpty, 2),
+        ("is", "not", _) => (Comparison::IsNot, 2),
+        ("is", _, _) | ("=", _, _) | ("==", _, _) => (Comparison::Is, 1),
+        ("!=", _, _) => (Comparison::IsNot, 1),
+        ("contains", _, _) => (Comparison::Contains, 1),
+        ("<", _, _) => (Comparison::Below, 1),
+        ("<=", _, _) => (Comparison::AtMost, 1),
+        (">", _, _) => (Comparison::Above, 1),
+        (">=", _, _) => (Comparison::AtLeast, 1),
+        _ => {
+            return Err(format!(
+                "After '{}' use is, is not, contains, is empty, =, !=, <, <=, > or >=",
+                field
+            ));
+        }
+    };
+    if !field.allows(comparison) {
+        return Err(match field {
+            ConditionField::Text => "text only supports contains".to_string(),
+            ConditionField::Class => "class only supports is and is not".to_string(),
+            ConditionField::Quantity | ConditionField::Price => {
+                format!("{} is compared with =, !=, <, <=, > or >=", field)
+            }
+            _ => format!("{} is compared with is, is not, contains or is empty", field),
+        });
+    }
+
+    let value = rest[value_start..]
+        .iter()
+        .map(|token| token.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if matches!(comparison, Comparison::IsEmpty | Comparison::IsNotEmpty) {
+        return Ok(Clause { field, comparison, value });
+    }
+    if value.is_empty() {
+        return Err(format!("Give a value to compare {} with", field));
+    }
+    match field {
+        ConditionField::Quantity | ConditionField::Price if value.parse::<f64>().is_err() => {
+            Err(format!("{} must be compared with a number, not '{}'", field, value))
+        }
+        ConditionField::Class if !matches!(value.to_uppercase().as_str(), "A" | "B" | "C") => {
+            Err(format!("class is A, B or C, not '{}'", value))
+        }
+        _ => Ok(Clause { field, comparison, value }),
+    }
+}
+
+/// Item fields a rule can set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemField {
+    Category,
+    Supplier,
+    Location,
+    Description,
+    ReorderPoint,
+    ReorderQuantity,
+    LeadTimeDays,
+}
+
+impl ItemField {
+    pub const ALL: [ItemField; 7] = [
+        ItemField::Category,
+        ItemField::Supplier,
+        ItemField::Location,
+        ItemField::Description,
+        ItemField::ReorderPoint,
+        ItemField::ReorderQuantity,
+        ItemField::LeadTimeDays,
+    ];
+
+    fn is_number(&self) -> bool {
+        matches!(self, ItemField::ReorderPoint | ItemField::ReorderQuantity | ItemField::LeadTimeDays)
+    }
+
+    /// Sets the field, returning the old value, or `None` when it already held `value`
+    pub fn set(&self, item: &mut InventoryItem, value: &str) -> Result<Option<String>, String> {
+        let number = if self.is_number() {
+            value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("{} must be a whole number, not '{}'", self, value))?
+        } else {
+            0
+        };
+        let old = match self {
+            ItemField::Category => std::mem::replace(&mut item.category, value.to_string()),
+            ItemField::Supplier => std::mem::replace(&mut item.supplier, value.to_string()),
+            ItemField::Location => std::mem::replace(&mut item.location, value.to_string()),
+            ItemField::Description => std::mem::replace(&mut item.description, value.to_string()),
+            ItemField::ReorderPoint => std::mem::replace(&mut item.reorder_point, number).to_string(),
+            ItemField::ReorderQuantity => std::mem::replace(&mut item.reorder_quantity, number).to_string(),
+            ItemField::LeadTimeDays => std::mem::replace(&mut item.lead_time_days, number).to_string(),
+        };
+        let new = if self.is_number() { number.to_string() } else { value.to_string() };
+        Ok((old != new).then_some(old))
+    }
+}
+
+impl fmt::Display for ItemField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemField::Category => write!(f, "Category"),
+            ItemField::Supplier => write!(f, "Supplier"),
+            ItemField::Location => write!(f, "Location"),
+            ItemField::Description => write!(f, "Description"),
+            ItemField::ReorderPoint => write!(f, "Reorder point"),
+            ItemField::ReorderQuantity => write!(f, "Reorder quantity"),
+            ItemField::LeadTimeDays => write!(f, "Lead time (days)"),
+        }
+    }
+}
+
+/// Something a rule does. Texts may use `{name}`, `{sku}`, `{category}`,
+/// `{supplier}`, `{location}` and `{quantity}` for the item it fired for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RuleAction {
+    /// Desktop notification for a user, or for whoever is logged in when `user` is `None`
+    Notify { user: Option<String>, message: String },
+    SetField { field: ItemField, value: String },
+    CreateNote { title: String, content: String },
+    RaiseAlert { message: String },
+    /// Draft purchase order for the suggested reorder quantity, one per supplier
+    DraftPurchaseOrder,
+    /// Runs a scheduled export job once per firing
+    RunExport { job_id: String },
+}
+
+impl RuleAction {
+    pub fn kind(&self) -> ActionKind {
+        match self {
+            RuleAction::Notify { .. } => ActionKind::Notify,
+            RuleAction::SetField { .. } => ActionKind::SetField,
+            RuleAction::CreateNote { .. } => ActionKind::CreateNote,
+            RuleAction::RaiseAlert { .. } => ActionKind::RaiseAlert,
+            RuleAction::DraftPurchaseOrder => ActionKind::DraftPurchaseOrder,
+            RuleAction::RunExport { .. } => ActionKind::RunExport,
+        }
+    }
+
+    /// What the action does, for the item when there is one
+    pub fn describe(&self, item: Option<&InventoryItem>, jobs: &[ExportJob]) -> String {
+        let fill = |template: &str| item.map_or_else(|| template.to_string(), |item| expand(template, item));
+        match self {
+            RuleAction::Notify { user, message } => format!(
+                "notify {}: \"{}\"",
+                user.as_deref().unwrap_or("whoever is logged in"),
+                fill(message)
+            ),
+            RuleAction::SetField { field, value } => {
+                format!("set {} to \"{}\"", field.to_string().to_lowercase(), fill(value))
+            }
+            RuleAction::CreateNote { title, .. } => format!("create note \"{}\"", fill(title)),
+            RuleAction::RaiseAlert { message } => format!("raise alert \"{}\"", fill(message)),
+            RuleAction::DraftPurchaseOrder => "draft a purchase order".to_string(),
+            RuleAction::RunExport { job_id } => format!(
+                "run export \"{}\"",
+                jobs.iter()
+                    .find(|job| job.id == *job_id)
+                    .map_or("(removed job)", |job| job.name.as_str())
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Notify,
+    SetField,
+    CreateNote,
+    RaiseAlert,
+    DraftPurchaseOrder,
+    RunExport,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 6] = [
+        ActionKind::Notify,
+        ActionKind::SetField,
+        ActionKind::CreateNote,
+        ActionKind::RaiseAlert,
+        ActionKind::DraftPurchaseOrder,
+        ActionKind::RunExport,
+    ];
+}
+
+impl fmt::Display for ActionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionKind::Notify => write!(f, "Notify"),
+            ActionKind::SetField => write!(f, "Set field"),
+            ActionKind::CreateNote => write!(f, "Create note"),
+            ActionKind::RaiseAlert => write!(f, "Raise alert"),
+            ActionKind::DraftPurchaseOrder => write!(f, "Draft purchase order"),
+            ActionKind::RunExport => write!(f, "Run export"),
+        }
+    }
+}
+
+/// Fills an action text in for an item
+pub fn expand(template: &str, item: &InventoryItem) -> String {
+    template
+        .replace("{name}", &item.name)
+        .replace("{sku}", &item.sku)
+        .replace("{category}", &item.category)
+        .replace("{supplier}", &item.supplier)
+        .replace("{location}", &item.location)
+        .replace("{quantity}", &item.quantity.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+    /// Records what the rule would do in the audit log without doing it
+    pub dry_run: bool,
+    pub trigger: RuleTrigger,
+    /// Condition as typed; see [`Condition::parse`]
+    pub condition: String,
+    pub actions: Vec<RuleAction>,
+    pub created_at: i64,
+    /// Items meeting the condition when last checked, so item-changed rules
+    /// fire once per item as it starts to match
+    pub matching: BTreeSet<String>,
+    /// When a scheduled rule last ran
+    pub last_run: Option<i64>,
+    pub last_fired: Option<i64>,
+}
+
+impl Rule {
+    pub fn matches(&self, item: &InventoryItem) -> bool {
+        Condition::parse(&self.condition).is_ok_and(|condition| condition.matches(item))
+    }
+
+    pub fn matching_items<'a>(&self, items: &'a [InventoryItem]) -> Vec<&'a InventoryItem> {
+        match Condition::parse(&self.condition) {
+            Ok(condition) => items.iter().filter(|item| condition.matches(item)).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Records which items match now and returns those that did not before
+    pub fn update_matching(&mut self, items: &[InventoryItem]) -> Vec<String> {
+        let now: BTreeSet<String> = self.matching_items(items).into_iter().map(|item| item.id.clone()).collect();
+        let started = now.difference(&self.matching).cloned().collect();
+        self.matching = now;
+        started
+    }
+
+    /// Whether a scheduled rule's schedule has fired since it last ran
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Local>) -> bool {
+        match &self.trigger {
+            RuleTrigger::Schedule(schedule) => schedule.is_due(self.last_run.unwrap_or(self.created_at), now),
+            _ => false,
+        }
+    }
+
+    pub fn formatted_last_fired(&self) -> String {
+        self.last_fired
+            .and_then(|at| chrono::DateTime::from_timestamp(at, 0))
+            .map(|dt| dt.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Never".to_string())
+    }
+}
+
+/// A notification for a user who was not logged in when a rule fired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleNotice {
+    pub username: String,
+    pub rule_name: String,
+    pub message: String,
+    pub created_at: i64,
+}
+
+/// One action in the rules editor. `target` is the user to notify, the note
+/// title or the export job id; `text` is the message, value or note content.
+#[derive(Debug, Clone)]
+pub struct ActionDraft {
+    pub kind: ActionKind,
+    pub field: ItemField,
+    pub target: String,
+    pub text: String,
+}
+
+impl ActionDraft {
+    pub fn new(kind: ActionKind) -> Self {
+        Self {
+            kind,
+            field: ItemField::Category,
+            target: String::new(),
+            text: String::new(),
+        }
+    }
+
+    fn from_action(action: &RuleAction) -> Self {
+        let mut draft = Self::new(action.kind());
+        match action {
+            RuleAction::Notify { user, message } => {
+                draft.target = user.clone().unwrap_or_default();
+                draft.text = message.clone();
+            }
+            RuleAction::SetField { field, value } => {
+                draft.field = *field;
+                draft.text = value.clone();
+            }
+            RuleAction::CreateNote { title, content } => {
+                draft.target = title.clone();
+                draft.text = content.clone();
+            }
+            RuleAction::RaiseAlert { message } => draft.text = message.clone(),
+            RuleAction::DraftPurchaseOrder => {}
+            RuleAction::RunExport { job_id } => draft.target = job_id.clone(),
+        }
+        draft
+    }
+
+    fn build(&self) -> Result<RuleAction, String> {
+        let text = self.text.trim().to_string();
+        let target = self.target.trim().to_string();
+        match self.kind {
+            ActionKind::Notify if text.is_empty() => Err("Write the message to send".to_string()),
+            ActionKind::Notify => Ok(RuleAction::Notify {
+                user: (!target.is_empty()).then_some(target),
+                message: text,
+            }),
+            ActionKind::SetField => {
+                // Checked now unless it is filled in per item
+                if self.field.is_number() && !text.contains('{') && text.parse::<u32>().is_err() {
+                    return Err(format!("{} must be a whole number, not '{}'", self.field, text));
+                }
+                Ok(RuleAction::SetField { field: self.field, value: text })
+            }
+            ActionKind::CreateNote if target.is_empty() => Err("Give the note a title".to_string()),
+            ActionKind::CreateNote => Ok(RuleAction::CreateNote {
+                title: target,
+                content: self.text.clone(),
+            }),
+            ActionKind::RaiseAlert if text.is_empty() => Err("Write the alert message".to_string()),
+            ActionKind::RaiseAlert => Ok(RuleAction::RaiseAlert { message: text }),
+            ActionKind::DraftPurchaseOrder => Ok(RuleAction::DraftPurchaseOrder),
+            ActionKind::RunExport if target.is_empty() => Err("Choose the export job to run".to_string()),
+            ActionKind::RunExport => Ok(RuleAction::RunExport { job_id: target }),
+        }
+    }
+}
+
+/// Contents of the rules editor
+#[derive(Debug, Clone)]
+pub struct RuleDraft {
+    /// Id of the rule being edited; `None` for a new one
+    pub editing: Option<String>,
+    pub name: String,
+    pub trigger: TriggerKind,
+    pub frequency: Frequency,
+    pub time: String,
+    pub weekday: chrono::Weekday,
+    pub condition: String,
+    pub dry_run: bool,
+    pub actions: Vec<ActionDraft>,
+}
+
+impl Default for RuleDraft {
+    fn default() -> Self {
+        Self {
+            editing: None,
+            name: String::new(),
+            trigger: TriggerKind::ItemChanged,
+            frequency: Frequency::Daily,
+            time: "08:00".to_string(),
+            weekday: chrono::Weekday::Mon,
+            condition: String::new(),
+            dry_run: false,
+            actions: vec![ActionDraft::new(ActionKind::Notify)],
+        }
+    }
+}
+
+impl RuleDraft {
+    pub fn from_rule(rule: &Rule) -> Self {
+        let mut draft = Self {
+            editing: Some(rule.id.clone()),
+            name: rule.name.clone(),
+            trigger: rule.trigger.kind(),
+            condition: rule.condition.clone(),
+            dry_run: rule.dry_run,
+            actions: rule.actions.iter().map(ActionDraft::from_action).collect(),
+            ..Self::default()
+        };
+        if let RuleTrigger::Schedule(schedule) = &rule.trigger {
+            draft.frequency = schedule.frequency;
+            draft.weekday = schedule.weekday;
+            draft.time = match schedule.frequency {
+                Frequency::Hourly => schedule.minute.to_string(),
+                _ => format!("{:02}:{:02}", schedule.hour, schedule.minute),
+            };
+        }
+        draft
+    }
+
+    /// Checks the draft and turns it into a new, active rule
+    pub fn build(&self) -> Result<Rule, String> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Give the rule a name".to_string());
+        }
+        let trigger = match self.trigger {
+            TriggerKind::ItemChanged => RuleTrigger::ItemChanged,
+            TriggerKind::AlertRaised => RuleTrigger::AlertRaised,
+            TriggerKind::Schedule => {
+                let (hour, minute) = schedule::parse_when(self.frequency, &self.time)?;
+                RuleTrigger::Schedule(Schedule {
+                    frequency: self.frequency,
+                    hour,
+                    minute,
+                    weekday: self.weekday,
+                })
+            }
+        };
+        let condition = self.condition.trim().to_string();
+        Condition::parse(&condition)?;
+        if self.actions.is_empty() {
+            return Err("Add at least one action".to_string());
+        }
+        let actions = self
+            .actions
+            .iter()
+            .enumerate()
+            .map(|(index, action)| action.build().map_err(|e| format!("Action {}: {}", index + 1, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Rule {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            active: true,
+            dry_run: self.dry_run,
+            trigger,
+            condition,
+            actions,
+            created_at: chrono::Utc::now().timestamp(),
+            matching: BTreeSet::new(),
+            last_run: None,
+            last_fired: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, category: &str, supplier: &str, quantity: u32) -> InventoryItem {
+        InventoryItem::new(
+            name.to_string(),
+            name.to_uppercase(),
+            category.to_string(),
+            supplier.to_string(),
+            String::new(),
+            quantity,
+            2.5,
+        )
+    }
+
+    #[test]
+    fn parses_conditions_and_fires_on_new_matches() {
+        let gloves = item("Gloves", "Consumables", "Acme Ltd", 12);
+        let tape = item("Tape", "consumables", "", 40);
+
+        let low = Condition::parse("category is Consumables and quantity < 20").unwrap();
+        assert!(low.matches(&gloves));
+        assert!(!low.matches(&tape));
+        assert!(Condition::parse("supplier is empty").unwrap().matches(&tape));
+        assert!(Condition::parse("supplier is 'acme ltd'").unwrap().matches(&gloves));
+        assert!(Condition::parse("text contains glo and price<=2.5").unwrap().matches(&gloves));
+        assert!(Condition::parse("").unwrap().matches(&tape));
+        assert!(Condition::parse("colour is red").is_err());
+        assert!(Condition::parse("quantity < many").is_err());
+        assert!(Condition::parse("category is A and").is_err());
+
+        let mut draft = RuleDraft {
+            name: "Needs review".to_string(),
+            condition: "supplier is empty".to_string(),
+            actions: vec![ActionDraft {
+                text: "needs-review".to_string(),
+                ..ActionDraft::new(ActionKind::SetField)
+            }],
+            ..RuleDraft::default()
+        };
+        let mut rule = draft.build().unwrap();
+        assert_eq!(rule.update_matching(&[gloves.clone(), tape.clone()]), vec![tape.id.clone()]);
+        assert!(rule.update_matching(&[gloves.clone(), tape.clone()]).is_empty());
+
+        let mut tagged = tape.clone();
+        let value = expand("{category}-review", &tape);
+        assert_eq!(ItemField::Category.set(&mut tagged, &value), Ok(Some("consumables".to_string())));
+        assert_eq!(tagged.category, "consumables-review");
+
+        draft.actions[0].field = ItemField::ReorderPoint;
+        assert!(draft.build().is_err());
+        draft.trigger = TriggerKind::Schedule;
+        draft.time = "25:00".to_string();
+        assert!(draft.build().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Customer {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+    pub address: String,
+    pub created_at: i64,
+}
+
+impl Customer {
+    pub fn new(name: String, email: String, phone: String, address: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            email,
+            phone,
+            address,
+            created_at: Utc::now().timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SalesOrderStatus {
+    Open,
+    Fulfilled,
+    Cancelled,
+}
+
+impl std::fmt::Display for SalesOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SalesOrderStatus::Open => write!(f, "Open"),
+            SalesOrderStatus::Fulfilled => write!(f, "Fulfilled"),
+            SalesOrderStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SalesOrderLine {
+    pub item_id: String,
+    pub item_name: String,
+    pub item_sku: String,
+    pub quantity: u32,
+    pub unit_price: f64,
+}
+
+impl SalesOrderLine {
+    pub fn total(&self) -> f64 {
+        self.quantity as f64 * self.unit_price
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SalesOrder {
+    pub id: String,
+    pub number: String,
+    pub customer_id: String,
+    pub customer_name: String,
+    pub status: SalesOrderStatus,
+    pub lines: Vec<SalesOrderLine>,
+    pub created_at: i64,
+    pub created_by: String,
+    pub fulfilled_at: Option<i64>,
+}
+
+impl SalesOrder {
+    pub fn new(
+        number: String,
+        customer: &Customer,
+        lines: Vec<SalesOrderLine>,
+        created_by: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            number,
+            customer_id: customer.id.clone(),
+            customer_name: customer.name.clone(),
+            status: SalesOrderStatus::Open,
+            lines,
+            created_at: Utc::now().timestamp(),
+            created_by,
+            fulfilled_at: None,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.lines.iter().map(|line| line.total()).sum()
+    }
+
+    pub fn total_units(&self) -> u32 {
+        self.lines.iter().map(|line| line.quantity).sum()
+    }
+
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// Returns the next sequential sales order number, e.g. `SO-000042`
+pub fn next_order_number(orders: &[SalesOrder]) -> String {
+    let last = orders
+        .iter()
+        .filter_map(|order| order.number.strip_prefix("SO-"))
+        .filter_map(|n| n.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("SO-{:06}", last + 1)
+}
+
+/// Units reserved per item by open sales orders
+pub fn allocations(orders: &[SalesOrder]) -> HashMap<String, u32> {
+    let mut allocated: HashMap<String, u32> = HashMap::new();
+    for order in orders.iter().filter(|o| o.status == SalesOrderStatus::Open) {
+        for line in &order.lines {
+            *allocated.entry(line.item_id.clone()).or_insert(0) += line.quantity;
+        }
+    }
+    allocated
+}
+
+/// Sets each item's allocated quantity from open sales orders. It isn't
+/// saved, so this runs whenever the state is loaded.
+pub fn apply_allocations(items: &mut [crate::inventory::InventoryItem], orders: &[SalesOrder]) {
+    let allocated = allocations(orders);
+    for item in items {
+        item.allocated = allocated.get(&item.id).copied().unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::InventoryItem;
+
+    fn order(customer: &Customer, lines: &[(&InventoryItem, u32)], status: SalesOrderStatus) -> SalesOrder {
+        let lines = lines
+            .iter()
+            .map(|(item, quantity)| SalesOrderLine {
+                item_id: item.id.clone(),
+                item_name: item.name.clone(),
+                item_sku: item.sku.clone(),
+                quantity: *quantity,
+                unit_price: item.price,
+            })
+            .collect();
+        let mut order = SalesOrder::new("SO-000001".to_string(), customer, lines, "admin".to_string());
+        order.status = status;
+        order
+    }
+
+    fn item(sku: &str, quantity: u32) -> InventoryItem {
+        InventoryItem::new(
+            sku.to_string(),
+            sku.to_string(),
+            "Parts".to_string(),
+            String::new(),
+            String::new(),
+            quantity,
+            4.0,
+        )
+    }
+
+    #[test]
+    fn open_orders_reserve_stock() {
+        let customer = Customer::new("Acme".to_string(), String::new(), String::new(), String::new());
+        let mut items = vec![item("BOLT", 10), item("NUT", 3), item("PIN", 5)];
+        let orders = vec![
+            order(&customer, &[(&items[0], 4), (&items[1], 5)], SalesOrderStatus::Open),
+            order(&customer, &[(&items[0], 2)], SalesOrderStatus::Open),
+            order(&customer, &[(&items[2], 5)], SalesOrderStatus::Fulfilled),
+            order(&customer, &[(&items[2], 1)], SalesOrderStatus::Cancelled),
+        ];
+        assert_eq!(orders[0].total_units(), 9);
+        assert!((orders[0].total() - 36.0).abs() < 1e-9);
+
+        items[2].allocated = 7;
+        apply_allocations(&mut items, &orders);
+        assert_eq!(items.iter().map(|i| i.allocated).collect::<Vec<_>>(), [6, 5, 0]);
+        // Over-allocated items have nothing available rather than wrapping
+        assert_eq!(items.iter().map(|i| i.available()).collect::<Vec<_>>(), [4, 0, 5]);
+    }
+
+    #[test]
+    fn allocations_are_not_saved() {
+        let mut bolt = item("BOLT", 10);
+        bolt.allocated = 4;
+        let value = serde_json::to_value(&bolt).unwrap();
+        assert!(value.get("allocated").is_none());
+        let loaded: InventoryItem = serde_json::from_value(value).unwrap();
+        assert_eq!(loaded.allocated, 0);
+    }
+}
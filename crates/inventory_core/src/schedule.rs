@@ -0,0 +1,174 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+impl Frequency {
+    pub const ALL: [Frequency; 3] = [Frequency::Hourly, Frequency::Daily, Frequency::Weekly];
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frequency::Hourly => write!(f, "Hourly"),
+            Frequency::Daily => write!(f, "Daily"),
+            Frequency::Weekly => write!(f, "Weekly"),
+        }
+    }
+}
+
+/// Weekdays in the order they are offered in pick lists
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// When a recurring job fires, in local time.
+///
+/// Hourly schedules only use `minute`; `weekday` only matters for weekly ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub frequency: Frequency,
+    pub hour: u32,
+    pub minute: u32,
+    pub weekday: Weekday,
+}
+
+impl Schedule {
+    /// The latest time at or before `now` that the schedule fired
+    pub fn last_occurrence(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.frequency {
+            Frequency::Hourly => {
+                let at = now.with_minute(self.minute)?.with_second(0)?.with_nanosecond(0)?;
+                Some(if at > now { at - Duration::hours(1) } else { at })
+            }
+            Frequency::Daily => {
+                let at = self.at(now.date_naive())?;
+                if at > now {
+                    self.at(now.date_naive() - Duration::days(1))
+                } else {
+                    Some(at)
+                }
+            }
+            Frequency::Weekly => {
+                let back = (7 + now.weekday().num_days_from_monday() - self.weekday.num_days_from_monday()) % 7;
+                let date = now.date_naive() - Duration::days(i64::from(back));
+                let at = self.at(date)?;
+                if at > now {
+                    self.at(date - Duration::days(7))
+                } else {
+                    Some(at)
+                }
+            }
+        }
+    }
+
+    /// The first time after `now` that the schedule fires
+    pub fn next_occurrence(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let last = self.last_occurrence(now)?;
+        match self.frequency {
+            Frequency::Hourly => Some(last + Duration::hours(1)),
+            Frequency::Daily => self.at(last.date_naive() + Duration::days(1)),
+            Frequency::Weekly => self.at(last.date_naive() + Duration::days(7)),
+        }
+    }
+
+    /// Whether the schedule has fired since the `since` timestamp
+    pub fn is_due(&self, since: i64, now: DateTime<Local>) -> bool {
+        self.last_occurrence(now).is_some_and(|at| at.timestamp() > since)
+    }
+
+    fn at(&self, date: NaiveDate) -> Option<DateTime<Local>> {
+        let time = NaiveTime::from_hms_opt(self.hour, self.minute, 0)?;
+        // A time skipped by a daylight saving change falls back to the hour after
+        Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .or_else(|| Local.from_local_datetime(&date.and_time(time + Duration::hours(1))).earliest())
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.frequency {
+            Frequency::Hourly => write!(f, "Every hour at :{:02}", self.minute),
+            Frequency::Daily => write!(f, "Daily at {:02}:{:02}", self.hour, self.minute),
+            Frequency::Weekly => write!(f, "Weekly on {} at {:02}:{:02}", self.weekday, self.hour, self.minute),
+        }
+    }
+}
+
+/// Parses a time of day typed as HH:MM
+pub fn parse_time(value: &str) -> Result<(u32, u32), String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map(|time| (time.hour(), time.minute()))
+        .map_err(|_| "Enter the time as HH:MM, for example 02:00".to_string())
+}
+
+/// Parses the time typed for a schedule: a minute past the hour for hourly
+/// schedules, otherwise a time of day
+pub fn parse_when(frequency: Frequency, value: &str) -> Result<(u32, u32), String> {
+    match frequency {
+        Frequency::Hourly => match value.trim().parse::<u32>() {
+            Ok(minute) if minute < 60 => Ok((0, minute)),
+            _ => Err("Enter the minute past the hour, from 0 to 59".to_string()),
+        },
+        _ => parse_time(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_the_last_and_next_occurrence() {
+        let daily = Schedule {
+            frequency: Frequency::Daily,
+            hour: 2,
+            minute: 0,
+            weekday: Weekday::Mon,
+        };
+        // Wednesday 1 May 2024, 01:30
+        let now = local(2024, 5, 1, 1, 30);
+        assert_eq!(daily.last_occurrence(now), Some(local(2024, 4, 30, 2, 0)));
+        assert_eq!(daily.next_occurrence(now), Some(local(2024, 5, 1, 2, 0)));
+        assert!(daily.is_due(local(2024, 4, 29, 2, 0).timestamp(), now));
+        assert!(!daily.is_due(local(2024, 4, 30, 2, 0).timestamp(), now));
+
+        let weekly = Schedule {
+            frequency: Frequency::Weekly,
+            ..daily
+        };
+        assert_eq!(weekly.last_occurrence(now), Some(local(2024, 4, 29, 2, 0)));
+        assert_eq!(weekly.next_occurrence(now), Some(local(2024, 5, 6, 2, 0)));
+
+        let hourly = Schedule {
+            frequency: Frequency::Hourly,
+            minute: 45,
+            ..daily
+        };
+        assert_eq!(hourly.last_occurrence(now), Some(local(2024, 5, 1, 0, 45)));
+        assert_eq!(parse_time(" 02:05 "), Ok((2, 5)));
+        assert!(parse_time("25:00").is_err());
+    }
+}
@@ -0,0 +1,321 @@
+//! Scripts administrators write to extend the app: computed item columns and
+//! small custom reports, in the Rhai language.
+//!
+//! Scripts run in a sandbox. They can't load modules or reach files, the
+//! network or the rest of the app; they only see copies of the records they
+//! are given, and a script that runs too long is stopped.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::AuditEntry;
+use crate::inventory::InventoryItem;
+use crate::note::Note;
+
+/// Operations a computed column may take for one item
+const COLUMN_OPERATIONS: u64 = 100_000;
+
+/// Operations a report script may take in total
+const REPORT_OPERATIONS: u64 = 10_000_000;
+
+/// Output lines kept from a report script
+const REPORT_LINES: usize = 1_000;
+
+/// Shown in place of a computed value the script failed on
+pub const ERROR_VALUE: &str = "#ERROR";
+
+/// A column worked out for each item, e.g. `item.price * 1.08 + 0.35`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComputedColumn {
+    pub id: String,
+    pub name: String,
+    pub script: String,
+    pub created_at: i64,
+}
+
+impl ComputedColumn {
+    pub fn new(name: String, script: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            script,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// A script that prints a report from the items, notes and audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportScript {
+    pub id: String,
+    pub name: String,
+    pub script: String,
+    pub created_at: i64,
+}
+
+impl ReportScript {
+    pub fn new(name: String, script: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            script,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+fn engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(max_operations)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(100_000)
+        .disable_symbol("eval");
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+    engine
+}
+
+fn compile(engine: &Engine, script: &str) -> Result<AST, String> {
+    if script.trim().is_empty() {
+        return Err("The script is empty".to_string());
+    }
+    engine.compile(script).map_err(|e| format!("Syntax error: {}", e))
+}
+
+/// Checks a computed column against every item, returning the first failure
+pub fn check_column(script: &str, items: &[InventoryItem]) -> Result<(), String> {
+    let engine = engine(COLUMN_OPERATIONS);
+    let ast = compile(&engine, script)?;
+    for item in items {
+        evaluate(&engine, &ast, item).map_err(|e| format!("Fails for {} ({}): {}", item.name, item.sku, e))?;
+    }
+    Ok(())
+}
+
+fn evaluate(engine: &Engine, ast: &AST, item: &InventoryItem) -> Result<String, String> {
+    let mut scope = Scope::new();
+    scope.push_constant("item", item_map(item));
+    engine
+        .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+        .map(|value| display(&value))
+        .map_err(|e| e.to_string())
+}
+
+/// How a script's result is shown; decimals are rounded to two places
+fn display(value: &Dynamic) -> String {
+    if value.is_unit() {
+        String::new()
+    } else if let Ok(number) = value.as_float() {
+        format!("{:.2}", number)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Computed column values for a set of items
+#[derive(Debug, Clone, Default)]
+pub struct ComputedValues {
+    values: HashMap<String, Vec<String>>,
+    /// Per column: items it failed for, and the first error
+    pub errors: Vec<Option<(usize, String)>>,
+}
+
+impl ComputedValues {
+    pub fn compute(columns: &[ComputedColumn], items: &[InventoryItem]) -> Self {
+        let engine = engine(COLUMN_OPERATIONS);
+        let mut computed = Self::default();
+        let mut row_values: HashMap<String, Vec<String>> = HashMap::new();
+
+        for column in columns {
+            let mut failures = 0;
+            let mut first_error = None;
+            let ast = compile(&engine, &column.script);
+            for item in items {
+                let value = match &ast {
+                    Ok(ast) => evaluate(&engine, ast, item),
+                    Err(e) => Err(e.clone()),
+                };
+                let value = value.unwrap_or_else(|e| {
+                    failures += 1;
+                    first_error.get_or_insert_with(|| format!("{} ({}): {}", item.name, item.sku, e));
+                    ERROR_VALUE.to_string()
+                });
+                row_values.entry(item.id.clone()).or_default().push(value);
+            }
+            computed.errors.push(first_error.map(|error| (failures, error)));
+        }
+        computed.values = row_values;
+        computed
+    }
+
+    /// An item's value in a column, empty if it was not worked out
+    pub fn get(&self, item_id: &str, column: usize) -> &str {
+        self.values
+            .get(item_id)
+            .and_then(|values| values.get(column))
+            .map_or("", String::as_str)
+    }
+}
+
+/// Checks a report script compiles; what it finds depends on the data when it runs
+pub fn check_report(script: &str) -> Result<(), String> {
+    compile(&engine(REPORT_OPERATIONS), script).map(|_| ())
+}
+
+/// Runs a report script. Its output is whatever it prints, followed by the
+/// value of its last expression.
+pub fn run_report(
+    script: &str,
+    items: &[InventoryItem],
+    notes: &[Note],
+    audit: &[AuditEntry],
+) -> Result<Vec<String>, String> {
+    let mut engine = engine(REPORT_OPERATIONS);
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let printed = Rc::clone(&output);
+    engine.on_print(move |line| {
+        let mut printed = printed.borrow_mut();
+        if printed.len() < REPORT_LINES {
+            printed.push(line.to_string());
+        }
+    });
+    let ast = compile(&engine, script)?;
+
+    let mut scope = Scope::new();
+    scope.push_constant("items", items.iter().map(|item| Dynamic::from_map(item_map(item))).collect::<rhai::Array>());
+    scope.push_constant("notes", notes.iter().map(note_map).collect::<rhai::Array>());
+    scope.push_constant("audit", audit.iter().map(audit_map).collect::<rhai::Array>());
+    let result = engine
+        .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = output.take();
+    if !result.is_unit() {
+        lines.push(display(&result));
+    }
+    Ok(lines)
+}
+
+fn item_map(item: &InventoryItem) -> Map {
+    let mut map = Map::new();
+    map.insert("id".into(), item.id.clone().into());
+    map.insert("name".into(), item.name.clone().into());
+    map.insert("sku".into(), item.sku.clone().into());
+    map.insert("category".into(), item.category.clone().into());
+    map.insert("supplier".into(), item.supplier.clone().into());
+    map.insert("description".into(), item.description.clone().into());
+    map.insert("location".into(), item.location.clone().into());
+    map.insert("quantity".into(), i64::from(item.quantity).into());
+    map.insert("allocated".into(), i64::from(item.allocated).into());
+    map.insert("available".into(), i64::from(item.available()).into());
+    map.insert("price".into(), item.price.into());
+    map.insert("reorder_point".into(), i64::from(item.reorder_point).into());
+    map.insert("reorder_quantity".into(), i64::from(item.reorder_quantity).into());
+    map.insert("lead_time_days".into(), i64::from(item.lead_time_days).into());
+    map.insert("daily_demand".into(), item.daily_demand.into());
+    map.insert(
+        "class".into(),
+        item.abc_class.map(|class| class.to_string()).unwrap_or_default().into(),
+    );
+    map.insert("created_at".into(), item.created_at.into());
+    map.insert("updated_at".into(), item.updated_at.into());
+    map
+}
+
+fn note_map(note: &Note) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("id".into(), note.id.clone().into());
+    map.insert("title".into(), note.title.clone().into());
+    map.insert("content".into(), note.content.clone().into());
+    map.insert("created_at".into(), note.created_at.into());
+    map.insert("updated_at".into(), note.updated_at.into());
+    Dynamic::from_map(map)
+}
+
+fn audit_map(entry: &AuditEntry) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("timestamp".into(), entry.timestamp.into());
+    map.insert("user".into(), entry.username.clone().into());
+    map.insert("action".into(), entry.action.to_string().into());
+    map.insert("entity_type".into(), entry.entity_type.clone().into());
+    map.insert(
+        "entity_id".into(),
+        entry.entity_id.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map.insert("details".into(), entry.details.clone().into());
+    Dynamic::from_map(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_columns_and_runs_reports_in_a_sandbox() {
+        let items = vec![
+            InventoryItem::new(
+                "Gloves".to_string(),
+                "G-1".to_string(),
+                "Consumables".to_string(),
+                "Acme".to_string(),
+                String::new(),
+                12,
+                10.0,
+            ),
+            InventoryItem::new(
+                "Tape".to_string(),
+                "T-1".to_string(),
+                "Consumables".to_string(),
+                String::new(),
+                String::new(),
+                0,
+                2.0,
+            ),
+        ];
+        let landed = ComputedColumn {
+            id: "c1".to_string(),
+            name: "Landed cost".to_string(),
+            script: "item.price * 1.08 + 0.35".to_string(),
+            created_at: 0,
+        };
+        let packs = ComputedColumn {
+            id: "c2".to_string(),
+            name: "Packs of 100".to_string(),
+            script: "100 / item.quantity".to_string(),
+            created_at: 0,
+        };
+
+        let computed = ComputedValues::compute(&[landed.clone(), packs], &items);
+        assert_eq!(computed.get(&items[0].id, 0), "11.15");
+        assert_eq!(computed.get(&items[1].id, 0), "2.51");
+        assert!(computed.errors[0].is_none());
+        assert_eq!(computed.get(&items[0].id, 1), "8");
+        assert_eq!(computed.get(&items[1].id, 1), ERROR_VALUE);
+        assert!(computed.errors[1].as_ref().is_some_and(|(failures, error)| *failures == 1 && error.starts_with("Tape")));
+        assert!(check_column(&landed.script, &items).is_ok());
+        assert!(check_column("item.price *", &items).unwrap_err().starts_with("Syntax error"));
+        assert!(check_column("item.missing + 1", &items).unwrap_err().contains("Gloves"));
+        assert!(check_column("loop {}", &items).is_err());
+
+        let lines = run_report(
+            r#"
+                let low = items.filter(|i| i.quantity < 5);
+                for item in low { print(item.name); }
+                `${low.len()} of ${items.len()} low`
+            "#,
+            &items,
+            &[],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(lines, vec!["Tape".to_string(), "1 of 2 low".to_string()]);
+        assert!(run_report("eval(\"1\")", &items, &[], &[]).is_err());
+        assert!(check_report("for item in items {").is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::analysis::AbcClass;
 use crate::inventory::InventoryItem;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,7 @@ pub enum SortField {
     Price,
     CreatedAt,
     UpdatedAt,
+    DaysOfCover,
 }
 
 impl std::fmt::Display for SortField {
@@ -24,6 +26,7 @@ impl std::fmt::Display for SortField {
             SortField::Price => write!(f, "Price"),
             SortField::CreatedAt => write!(f, "Created Date"),
             SortField::UpdatedAt => write!(f, "Updated Date"),
+            SortField::DaysOfCover => write!(f, "Days of Cover"),
         }
     }
 }
@@ -49,6 +52,7 @@ pub struct SearchFilter {
     pub query: String,
     pub category_filter: Option<String>,
     pub supplier_filter: Option<String>,
+    pub abc_filter: Option<AbcClass>,
     pub min_quantity: Option<u32>,
     pub max_quantity: Option<u32>,
     pub min_price: Option<f64>,
@@ -63,6 +67,7 @@ impl SearchFilter {
             query: String::new(),
             category_filter: None,
             supplier_filter: None,
+            abc_filter: None,
             min_quantity: None,
             max_quantity: None,
             min_price: None,
@@ -76,6 +81,7 @@ impl SearchFilter {
         !self.query.is_empty()
             || self.category_filter.is_some()
             || self.supplier_filter.is_some()
+            || self.abc_filter.is_some()
             || self.min_quantity.is_some()
             || self.max_quantity.is_some()
             || self.min_price.is_some()
@@ -86,20 +92,56 @@ impl SearchFilter {
         self.query.clear();
         self.category_filter = None;
         self.supplier_filter = None;
+        self.abc_filter = None;
         self.min_quantity = None;
         self.max_quantity = None;
         self.min_price = None;
         self.max_price = None;
     }
 
+    /// Plain description of the active criteria, e.g. for report headings
+    pub fn describe(&self) -> String {
+        fn range<T: std::fmt::Display>(name: &str, min: &Option<T>, max: &Option<T>) -> Option<String> {
+            match (min, max) {
+                (Some(min), Some(max)) => Some(format!("{} {} to {}", name, min, max)),
+                (Some(min), None) => Some(format!("{} at least {}", name, min)),
+                (None, Some(max)) => Some(format!("{} at most {}", name, max)),
+                (None, None) => None,
+            }
+        }
+
+        let mut parts = Vec::new();
+        if !self.query.is_empty() {
+            parts.push(format!("matching \"{}\"", self.query));
+        }
+        if let Some(category) = &self.category_filter {
+            parts.push(format!("category {}", category));
+        }
+        if let Some(supplier) = &self.supplier_filter {
+            parts.push(format!("supplier {}", supplier));
+        }
+        if let Some(class) = &self.abc_filter {
+            parts.push(format!("class {}", class));
+        }
+        parts.extend(range("quantity", &self.min_quantity, &self.max_quantity));
+        parts.extend(range("price", &self.min_price, &self.max_price));
+
+        if parts.is_empty() {
+            "All items".to_string()
+        } else {
+            format!("Items {}", parts.join(", "))
+        }
+    }
+
     pub fn matches(&self, item: &InventoryItem) -> bool {
-        // Text search (searches in name, SKU, category, supplier, description)
+        // Text search (searches in name, SKU, category, supplier, location, description)
         if !self.query.is_empty() {
             let query_lower = self.query.to_lowercase();
             let matches = item.name.to_lowercase().contains(&query_lower)
                 || item.sku.to_lowercase().contains(&query_lower)
                 || item.category.to_lowercase().contains(&query_lower)
                 || item.supplier.to_lowercase().contains(&query_lower)
+                || item.location.to_lowercase().contains(&query_lower)
                 || item.description.to_lowercase().contains(&query_lower);
             
             if !matches {
@@ -121,6 +163,11 @@ impl SearchFilter {
             }
         }
 
+        // ABC class filter
+        if self.abc_filter.is_some_and(|class| item.abc_class != Some(class)) {
+            return false;
+        }
+
         // Quantity range
         if let Some(min) = self.min_quantity {
             if item.quantity < min {
@@ -167,6 +214,11 @@ impl SearchFilter {
                     SortField::Price => a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal),
                     SortField::CreatedAt => a.created_at.cmp(&b.created_at),
                     SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+                    // Items without demand never run out, so they sort as infinite cover
+                    SortField::DaysOfCover => a
+                        .days_of_cover()
+                        .unwrap_or(f64::INFINITY)
+                        .total_cmp(&b.days_of_cover().unwrap_or(f64::INFINITY)),
                 };
 
                 match self.sort_direction {
@@ -202,3 +254,48 @@ impl SearchFilter {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, category: &str, quantity: u32, price: f64) -> InventoryItem {
+        InventoryItem::new(
+            name.to_string(),
+            name.to_uppercase(),
+            category.to_string(),
+            "Acme".to_string(),
+            String::new(),
+            quantity,
+            price,
+        )
+    }
+
+    #[test]
+    fn filters_and_sorts_items() {
+        let items = vec![
+            item("Hammer", "Tools", 12, 15.0),
+            item("Drill", "Tools", 3, 80.0),
+            item("Gloves", "Safety", 40, 4.0),
+        ];
+
+        let mut filter = SearchFilter::new();
+        assert!(!filter.is_active());
+        assert_eq!(filter.apply(&items).len(), 3);
+
+        filter.category_filter = Some("tools".to_string());
+        filter.sort_field = Some(SortField::Quantity);
+        filter.sort_direction = SortDirection::Descending;
+        let names: Vec<String> = filter.apply(&items).into_iter().map(|item| item.name).collect();
+        assert_eq!(names, ["Hammer", "Drill"]);
+
+        filter.query = "dri".to_string();
+        filter.max_price = Some(50.0);
+        assert!(filter.apply(&items).is_empty());
+        assert_eq!(filter.describe(), "Items matching \"dri\", category tools, price at most 50");
+
+        filter.clear();
+        assert!(!filter.is_active());
+        assert_eq!(SearchFilter::get_unique_categories(&items), ["Safety", "Tools"]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Settings and preferences saved with the data
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppTheme {
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutStyle {
+    Header,
+    Sidebar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryViewMode {
+    Cards,
+    Table,
+}
+
+impl Default for InventoryViewMode {
+    fn default() -> Self {
+        InventoryViewMode::Cards
+    }
+}
+
+impl serde::Serialize for InventoryViewMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            InventoryViewMode::Cards => "cards",
+            InventoryViewMode::Table => "table",
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for InventoryViewMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "table" => InventoryViewMode::Table,
+            _ => InventoryViewMode::Cards,
+        })
+    }
+}
+
+impl Default for LayoutStyle {
+    fn default() -> Self {
+        LayoutStyle::Header
+    }
+}
+
+impl serde::Serialize for LayoutStyle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            LayoutStyle::Header => "header",
+            LayoutStyle::Sidebar => "sidebar",
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LayoutStyle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "sidebar" => LayoutStyle::Sidebar,
+            _ => LayoutStyle::Header,
+        })
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AppSettings {
+    pub auto_save_enabled: bool,
+    pub auto_save_interval: u32, // seconds
+    pub default_category: String,
+    pub preferred_currency: String,
+    pub theme: AppTheme,
+    pub show_loading_screen: bool,
+    pub layout_style: LayoutStyle,
+    pub inventory_view_mode: InventoryViewMode,
+    pub device_notifications_enabled: bool,
+    pub update_notifications_enabled: bool,
+    pub notification_throttle_seconds: u32,
+    pub analysis: crate::analysis::AnalysisSettings,
+    pub backups: crate::persistence::BackupSettings,
+    /// Mirrors the backend chosen in `storage.json`, which is read before loading
+    pub storage_backend: crate::persistence::StorageBackend,
+    pub reports: crate::reports::ReportSettings,
+    pub export_jobs: Vec<crate::export::ExportJob>,
+    pub api: crate::api::ApiSettings,
+    pub webhooks: Vec<crate::webhooks::Webhook>,
+    pub rules: Vec<crate::rules::Rule>,
+    /// Rule notifications waiting for their users to log in
+    pub rule_notices: Vec<crate::rules::RuleNotice>,
+    pub computed_columns: Vec<crate::scripting::ComputedColumn>,
+    pub report_scripts: Vec<crate::scripting::ReportScript>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            auto_save_enabled: true,
+            auto_save_interval: 5,
+            default_category: String::from("General"),
+            preferred_currency: String::from("USD"),
+            theme: AppTheme::Dark,
+            show_loading_screen: true,
+            layout_style: LayoutStyle::default(),
+            inventory_view_mode: InventoryViewMode::default(),
+            device_notifications_enabled: true,
+            update_notifications_enabled: true,
+            notification_throttle_seconds: 30,
+            analysis: crate::analysis::AnalysisSettings::default(),
+            backups: crate::persistence::BackupSettings::default(),
+            storage_backend: crate::persistence::StorageBackend::default(),
+            reports: crate::reports::ReportSettings::default(),
+            export_jobs: Vec::new(),
+            api: crate::api::ApiSettings::default(),
+            webhooks: Vec::new(),
+            rules: Vec::new(),
+            rule_notices: Vec::new(),
+            computed_columns: Vec::new(),
+            report_scripts: Vec::new(),
+        }
+    }
+}
+
+impl serde::Serialize for AppTheme {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            AppTheme::Dark => "dark",
+            AppTheme::Light => "light",
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AppTheme {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "light" => AppTheme::Light,
+            _ => AppTheme::Dark,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum View {
+    Inventory,
+    Editor,
+    Settings,
+    UserManagement,
+    AuditLog,
+    Alerts,
+    Reorder,
+    Sales,
+    Returns,
+    Stocktake,
+    Analysis,
+    Backups,
+    Import,
+    Reports,
+    MergeImport,
+    Webhooks,
+    Rules,
+    Scripts,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View::Inventory
+    }
+}
+
+impl serde::Serialize for View {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            View::Inventory => "inventory",
+            View::Editor => "editor",
+            View::Settings => "settings",
+            View::UserManagement => "user_management",
+            View::AuditLog => "audit_log",
+            View::Alerts => "alerts",
+            View::Reorder => "reorder",
+            View::Sales => "sales",
+            View::Returns => "returns",
+            View::Stocktake => "stocktake",
+            View::Analysis => "analysis",
+            View::Backups => "backups",
+            View::Import => "import",
+            View::Reports => "reports",
+            View::MergeImport => "merge_import",
+            View::Webhooks => "webhooks",
+            View::Rules => "rules",
+            View::Scripts => "scripts",
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for View {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "editor" => View::Editor,
+            "settings" => View::Settings,
+            "user_management" => View::UserManagement,
+            "audit_log" => View::AuditLog,
+            "alerts" => View::Alerts,
+            "reorder" => View::Reorder,
+            "sales" => View::Sales,
+            "returns" => View::Returns,
+            "stocktake" => View::Stocktake,
+            "analysis" => View::Analysis,
+            "backups" => View::Backups,
+            "import" => View::Import,
+            "reports" => View::Reports,
+            "merge_import" => View::MergeImport,
+            "webhooks" => View::Webhooks,
+            "rules" => View::Rules,
+            "scripts" => View::Scripts,
+            _ => View::Inventory,
+        })
+    }
+}
@@ -0,0 +1,357 @@
+use std::fmt;
+use std::io::{Cursor, Write};
+
+use rust_xlsxwriter::{ExcelDateTime, Format, Formula, Workbook, XlsxError};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::currency;
+use crate::inventory::InventoryItem;
+
+/// Name of the sheet items are written to
+const SHEET_NAME: &str = "Inventory";
+/// Columns summed on the totals row: quantity and total value
+const QUANTITY_COLUMN: usize = 6;
+const VALUE_COLUMN: usize = 8;
+
+/// Spreadsheet file the inventory can be exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetFormat {
+    Xlsx,
+    Ods,
+}
+
+impl SheetFormat {
+    /// Format matching a file name's extension; anything else is written as Excel
+    pub fn from_file_name(file_name: &str) -> Self {
+        if file_name.to_lowercase().ends_with(".ods") {
+            SheetFormat::Ods
+        } else {
+            SheetFormat::Xlsx
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SheetFormat::Xlsx => "xlsx",
+            SheetFormat::Ods => "ods",
+        }
+    }
+}
+
+impl fmt::Display for SheetFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SheetFormat::Xlsx => write!(f, "XLSX"),
+            SheetFormat::Ods => write!(f, "ODS"),
+        }
+    }
+}
+
+/// A cell of the exported sheet, kept typed so numbers stay numbers
+enum Cell<'a> {
+    Text(&'a str),
+    Number(f64),
+    Money(f64),
+    /// Unix timestamp in seconds
+    Date(i64),
+}
+
+fn headers(currency_code: &str) -> [String; 11] {
+    [
+        "Name".to_string(),
+        "SKU".to_string(),
+        "Category".to_string(),
+        "Supplier".to_string(),
+        "Location".to_string(),
+        "Description".to_string(),
+        "Quantity".to_string(),
+        format!("Price ({})", currency_code),
+        format!("Total Value ({})", currency_code),
+        "Created At".to_string(),
+        "Updated At".to_string(),
+    ]
+}
+
+fn cells(item: &InventoryItem) -> [Cell<'_>; 11] {
+    [
+        Cell::Text(&item.name),
+        // SKUs are text so leading zeros survive
+        Cell::Text(&item.sku),
+        Cell::Text(&item.category),
+        Cell::Text(&item.supplier),
+        Cell::Text(&item.location),
+        Cell::Text(&item.description),
+        Cell::Number(item.quantity as f64),
+        Cell::Money(item.price),
+        Cell::Money(item.total_value()),
+        Cell::Date(item.created_at),
+        Cell::Date(item.updated_at),
+    ]
+}
+
+/// Decimal places the currency is shown with
+fn currency_places(currency_code: &str) -> usize {
+    currency::currency_exp(currency_code)
+        .filter(|exp| *exp >= 0)
+        .map(|exp| exp as usize)
+        .unwrap_or(2)
+}
+
+/// Writes the items as a workbook with a bold header row, frozen header
+/// and name column, and a totals row underneath
+pub fn inventory_workbook(items: &[InventoryItem], currency_code: &str, format: SheetFormat) -> Result<Vec<u8>, String> {
+    match format {
+        SheetFormat::Xlsx => write_xlsx(items, currency_code).map_err(|e| format!("Could not write the workbook: {}", e)),
+        SheetFormat::Ods => write_ods(items, currency_code).map_err(|e| format!("Could not write the spreadsheet: {}", e)),
+    }
+}
+
+fn write_xlsx(items: &[InventoryItem], currency_code: &str) -> Result<Vec<u8>, XlsxError> {
+    let places = currency_places(currency_code);
+    let amount_format = if places == 0 {
+        "#,##0".to_string()
+    } else {
+        format!("#,##0.{}", "0".repeat(places))
+    };
+    let bold = Format::new().set_bold();
+    let money = Format::new().set_num_format(format!("\"{}\" {}", currency_code, amount_format));
+    let money_total = money.clone().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(SHEET_NAME)?;
+
+    for (column, header) in headers(currency_code).iter().enumerate() {
+        sheet.write_string_with_format(0, column as u16, header, &bold)?;
+    }
+    for (index, item) in items.iter().enumerate() {
+        let row = index as u32 + 1;
+        for (column, cell) in cells(item).into_iter().enumerate() {
+            let column = column as u16;
+            match cell {
+                Cell::Text(value) => sheet.write_string(row, column, value)?,
+                Cell::Number(value) => sheet.write_number(row, column, value)?,
+                Cell::Money(value) => sheet.write_number_with_format(row, column, value, &money)?,
+                Cell::Date(timestamp) => match ExcelDateTime::from_timestamp(timestamp) {
+                    Ok(datetime) => sheet.write_datetime_with_format(row, column, &datetime, &date)?,
+                    Err(_) => sheet.write_blank(row, column, &date)?,
+                },
+            };
+        }
+    }
+
+    let total_row = items.len() as u32 + 1;
+    let quantity: u64 = items.iter().map(|item| item.quantity as u64).sum();
+    let value: f64 = items.iter().map(InventoryItem::total_value).sum();
+    sheet.write_string_with_format(total_row, 0, "Total", &bold)?;
+    sheet.write_formula_with_format(
+        total_row,
+        QUANTITY_COLUMN as u16,
+        Formula::new(format!("=SUM(G2:G{})", total_row)).set_result(quantity.to_string()),
+        &bold,
+    )?;
+    sheet.write_formula_with_format(
+        total_row,
+        VALUE_COLUMN as u16,
+        Formula::new(format!("=SUM(I2:I{})", total_row)).set_result(value.to_string()),
+        &money_total,
+    )?;
+
+    sheet.set_freeze_panes(1, 1)?;
+    sheet.set_column_width(0, 30)?;
+    sheet.set_column_width(1, 16)?;
+    sheet.set_column_range_width(2, 4, 18)?;
+    sheet.set_column_width(5, 40)?;
+    sheet.set_column_range_width(6, 10, 18)?;
+
+    workbook.save_to_buffer()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn ods_text_cell(value: &str, style: Option<&str>) -> String {
+    let style = style.map(|s| format!(" table:style-name=\"{}\"", s)).unwrap_or_default();
+    format!(
+        "<table:table-cell office:value-type=\"string\"{}><text:p>{}</text:p></table:table-cell>",
+        style,
+        xml_escape(value)
+    )
+}
+
+fn write_ods(items: &[InventoryItem], currency_code: &str) -> zip::result::ZipResult<Vec<u8>> {
+    let places = currency_places(currency_code);
+    let currency_code = xml_escape(currency_code);
+
+    let mut content = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<office:document-content \
+xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\" \
+xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" \
+xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\" \
+xmlns:number=\"urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0\" \
+xmlns:fo=\"urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0\" \
+xmlns:of=\"urn:oasis:names:tc:opendocument:xmlns:of:1.2\" \
+office:version=\"1.2\">",
+    );
+    content.push_str(&format!(
+        "<office:automatic-styles>\
+<number:currency-style style:name=\"N1\">\
+<number:currency-symbol>{currency}</number:currency-symbol><number:text> </number:text>\
+<number:number number:decimal-places=\"{places}\" number:min-integer-digits=\"1\" number:grouping=\"true\"/>\
+</number:currency-style>\
+<number:date-style style:name=\"N2\">\
+<number:year/><number:text>-</number:text><number:month number:style=\"long\"/><number:text>-</number:text>\
+<number:day number:style=\"long\"/><number:text> </number:text>\
+<number:hours number:style=\"long\"/><number:text>:</number:text><number:minutes number:style=\"long\"/>\
+</number:date-style>\
+<style:style style:name=\"ce1\" style:family=\"table-cell\"><style:text-properties fo:font-weight=\"bold\"/></style:style>\
+<style:style style:name=\"ce2\" style:family=\"table-cell\" style:data-style-name=\"N1\"/>\
+<style:style style:name=\"ce3\" style:family=\"table-cell\" style:data-style-name=\"N2\"/>\
+<style:style style:name=\"ce4\" style:family=\"table-cell\" style:data-style-name=\"N1\">\
+<style:text-properties fo:font-weight=\"bold\"/></style:style>\
+</office:automatic-styles>\
+<office:body><office:spreadsheet><table:table table:name=\"{sheet}\">\
+<table:table-column table:number-columns-repeated=\"11\"/>",
+        currency = currency_code,
+        places = places,
+        sheet = SHEET_NAME,
+    ));
+
+    content.push_str("<table:table-row>");
+    for header in headers(&currency_code) {
+        content.push_str(&ods_text_cell(&header, Some("ce1")));
+    }
+    content.push_str("</table:table-row>");
+
+    for item in items {
+        content.push_str("<table:table-row>");
+        for cell in cells(item) {
+            match cell {
+                Cell::Text(value) => content.push_str(&ods_text_cell(value, None)),
+                Cell::Number(value) => content.push_str(&format!(
+                    "<table:table-cell office:value-type=\"float\" office:value=\"{0}\"><text:p>{0}</text:p></table:table-cell>",
+                    value
+                )),
+                Cell::Money(value) => content.push_str(&format!(
+                    "<table:table-cell table:style-name=\"ce2\" office:value-type=\"currency\" office:currency=\"{}\" office:value=\"{}\"><text:p>{}</text:p></table:table-cell>",
+                    currency_code,
+                    value,
+                    currency::format_amount(value, &currency_code)
+                )),
+                Cell::Date(timestamp) => match chrono::DateTime::from_timestamp(timestamp, 0) {
+                    Some(datetime) => content.push_str(&format!(
+                        "<table:table-cell table:style-name=\"ce3\" office:value-type=\"date\" office:date-value=\"{}\"><text:p>{}</text:p></table:table-cell>",
+                        datetime.format("%Y-%m-%dT%H:%M:%S"),
+                        datetime.format("%Y-%m-%d %H:%M")
+                    )),
+                    None => content.push_str("<table:table-cell/>"),
+                },
+            }
+        }
+        content.push_str("</table:table-row>");
+    }
+
+    let last_row = items.len() + 1;
+    let quantity: u64 = items.iter().map(|item| item.quantity as u64).sum();
+    let value: f64 = items.iter().map(InventoryItem::total_value).sum();
+    content.push_str("<table:table-row>");
+    content.push_str(&ods_text_cell("Total", Some("ce1")));
+    content.push_str(&format!("<table:table-cell table:number-columns-repeated=\"{}\"/>", QUANTITY_COLUMN - 1));
+    content.push_str(&format!(
+        "<table:table-cell table:style-name=\"ce1\" table:formula=\"of:=SUM([.G2:.G{0}])\" office:value-type=\"float\" office:value=\"{1}\"><text:p>{1}</text:p></table:table-cell>",
+        last_row, quantity
+    ));
+    content.push_str("<table:table-cell/>");
+    content.push_str(&format!(
+        "<table:table-cell table:style-name=\"ce4\" table:formula=\"of:=SUM([.I2:.I{}])\" office:value-type=\"currency\" office:currency=\"{}\" office:value=\"{}\"><text:p>{}</text:p></table:table-cell>",
+        last_row,
+        currency_code,
+        value,
+        currency::format_amount(value, &currency_code)
+    ));
+    content.push_str("</table:table-row>");
+    content.push_str("</table:table></office:spreadsheet></office:body></office:document-content>");
+
+    // Freezes the header row and the name column
+    let settings = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<office:document-settings \
+xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+xmlns:config=\"urn:oasis:names:tc:opendocument:xmlns:config:1.0\" office:version=\"1.2\">\
+<office:settings><config:config-item-set config:name=\"ooo:view-settings\">\
+<config:config-item-map-indexed config:name=\"Views\"><config:config-item-map-entry>\
+<config:config-item config:name=\"ViewId\" config:type=\"string\">view1</config:config-item>\
+<config:config-item-map-named config:name=\"Tables\"><config:config-item-map-entry config:name=\"{}\">\
+<config:config-item config:name=\"HorizontalSplitMode\" config:type=\"short\">2</config:config-item>\
+<config:config-item config:name=\"VerticalSplitMode\" config:type=\"short\">2</config:config-item>\
+<config:config-item config:name=\"HorizontalSplitPosition\" config:type=\"int\">1</config:config-item>\
+<config:config-item config:name=\"VerticalSplitPosition\" config:type=\"int\">1</config:config-item>\
+<config:config-item config:name=\"ActiveSplitRange\" config:type=\"short\">2</config:config-item>\
+<config:config-item config:name=\"PositionRight\" config:type=\"int\">1</config:config-item>\
+<config:config-item config:name=\"PositionBottom\" config:type=\"int\">1</config:config-item>\
+</config:config-item-map-entry></config:config-item-map-named>\
+</config:config-item-map-entry></config:config-item-map-indexed>\
+</config:config-item-set></office:settings></office:document-settings>",
+        SHEET_NAME
+    );
+    let manifest = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"1.2\">\
+<manifest:file-entry manifest:full-path=\"/\" manifest:version=\"1.2\" manifest:media-type=\"application/vnd.oasis.opendocument.spreadsheet\"/>\
+<manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/>\
+<manifest:file-entry manifest:full-path=\"settings.xml\" manifest:media-type=\"text/xml\"/>\
+</manifest:manifest>";
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    // The mimetype has to come first and uncompressed
+    zip.start_file("mimetype", SimpleFileOptions::default().compression_method(CompressionMethod::Stored))?;
+    zip.write_all(b"application/vnd.oasis.opendocument.spreadsheet")?;
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("META-INF/manifest.xml", deflated)?;
+    zip.write_all(manifest.as_bytes())?;
+    zip.start_file("content.xml", deflated)?;
+    zip.write_all(content.as_bytes())?;
+    zip.start_file("settings.xml", deflated)?;
+    zip.write_all(settings.as_bytes())?;
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::ImportTable;
+
+    #[test]
+    fn round_trips_through_import() {
+        let mut item = InventoryItem::new(
+            "Widget".to_string(),
+            "007".to_string(),
+            "Parts".to_string(),
+            "Acme".to_string(),
+            String::new(),
+            3,
+            2.5,
+        );
+        item.location = "A1".to_string();
+
+        for format in [SheetFormat::Xlsx, SheetFormat::Ods] {
+            let bytes = inventory_workbook(&[item.clone()], "USD", format).unwrap();
+            let table = ImportTable::read_spreadsheet(format!("items.{}", format.extension()), &bytes).unwrap();
+            assert_eq!(table.headers[1], "SKU");
+            // The item row and the totals row
+            assert_eq!(table.rows.len(), 2);
+            assert_eq!(table.rows[0][1], "007");
+            assert_eq!(table.rows[0][6], "3");
+            assert_eq!(table.rows[0][7], "2.5");
+            assert_eq!(table.rows[1][8], "7.5");
+        }
+    }
+}
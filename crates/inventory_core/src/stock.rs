@@ -0,0 +1,95 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::InventoryItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementKind {
+    Receipt,
+    Issue,
+    Adjustment,
+    /// Customer return put back into sellable stock
+    Return,
+    /// Customer return held aside pending inspection
+    Quarantine,
+    /// Customer return shipped back to the supplier
+    ReturnToVendor,
+    /// Customer return written off
+    Scrap,
+}
+
+impl MovementKind {
+    /// Whether this movement changes the item's on-hand quantity
+    pub fn affects_on_hand(&self) -> bool {
+        matches!(
+            self,
+            MovementKind::Receipt | MovementKind::Issue | MovementKind::Adjustment | MovementKind::Return
+        )
+    }
+}
+
+impl std::fmt::Display for MovementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MovementKind::Receipt => write!(f, "Receipt"),
+            MovementKind::Issue => write!(f, "Issue"),
+            MovementKind::Adjustment => write!(f, "Adjustment"),
+            MovementKind::Return => write!(f, "Return"),
+            MovementKind::Quarantine => write!(f, "Quarantine"),
+            MovementKind::ReturnToVendor => write!(f, "Return to Vendor"),
+            MovementKind::Scrap => write!(f, "Scrap"),
+        }
+    }
+}
+
+/// A single change to an item's stock.
+///
+/// `quantity` is signed: receipts and restocked returns are positive, issues
+/// negative. Kinds that do not affect on-hand stock (quarantine, return to
+/// vendor, scrap) record the number of units handled; quarantine entries are
+/// negative when units leave quarantine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockMovement {
+    pub id: String,
+    pub item_id: String,
+    pub item_sku: String,
+    pub kind: MovementKind,
+    pub quantity: i64,
+    pub reference: String,
+    pub created_at: i64,
+    pub created_by: String,
+}
+
+impl StockMovement {
+    pub fn new(
+        item: &InventoryItem,
+        kind: MovementKind,
+        quantity: i64,
+        reference: String,
+        created_by: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            item_id: item.id.clone(),
+            item_sku: item.sku.clone(),
+            kind,
+            quantity,
+            reference,
+            created_at: Utc::now().timestamp(),
+            created_by,
+        }
+    }
+}
+
+/// Changes an item's quantity by `delta` and returns the adjustment to record.
+///
+/// Fails without changing anything if it would take the quantity below zero.
+pub fn adjust(item: &mut InventoryItem, delta: i64, reason: String, created_by: String) -> Result<StockMovement, String> {
+    let new_quantity = i64::from(item.quantity) + delta;
+    if new_quantity < 0 {
+        return Err(format!("{} has only {} in stock", item.name, item.quantity));
+    }
+    item.quantity = u32::try_from(new_quantity).map_err(|_| format!("{} cannot hold that many", item.name))?;
+    item.update_timestamp();
+    Ok(StockMovement::new(item, MovementKind::Adjustment, delta, reason, created_by))
+}
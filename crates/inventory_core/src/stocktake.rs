@@ -0,0 +1,265 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::InventoryItem;
+
+/// Which kind of attribute a count session is restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    All,
+    Category,
+    Location,
+    Supplier,
+}
+
+impl ScopeKind {
+    pub const ALL: [ScopeKind; 4] = [
+        ScopeKind::All,
+        ScopeKind::Category,
+        ScopeKind::Location,
+        ScopeKind::Supplier,
+    ];
+
+    /// Distinct values of this attribute across the inventory, sorted
+    pub fn values(&self, items: &[InventoryItem]) -> Vec<String> {
+        let mut values: Vec<String> = items
+            .iter()
+            .filter_map(|item| match self {
+                ScopeKind::All => None,
+                ScopeKind::Category => Some(item.category.clone()),
+                ScopeKind::Location => Some(item.location.clone()),
+                ScopeKind::Supplier => Some(item.supplier.clone()),
+            })
+            .filter(|value| !value.trim().is_empty())
+            .collect();
+        values.sort_by_key(|v| v.to_lowercase());
+        values.dedup_by_key(|v| v.to_lowercase());
+        values
+    }
+
+    pub fn with_value(&self, value: String) -> CountScope {
+        match self {
+            ScopeKind::All => CountScope::All,
+            ScopeKind::Category => CountScope::Category(value),
+            ScopeKind::Location => CountScope::Location(value),
+            ScopeKind::Supplier => CountScope::Supplier(value),
+        }
+    }
+}
+
+impl std::fmt::Display for ScopeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopeKind::All => write!(f, "Full Count"),
+            ScopeKind::Category => write!(f, "Category"),
+            ScopeKind::Location => write!(f, "Location"),
+            ScopeKind::Supplier => write!(f, "Supplier"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CountScope {
+    All,
+    Category(String),
+    Location(String),
+    Supplier(String),
+}
+
+impl CountScope {
+    pub fn matches(&self, item: &InventoryItem) -> bool {
+        match self {
+            CountScope::All => true,
+            CountScope::Category(value) => item.category.eq_ignore_ascii_case(value),
+            CountScope::Location(value) => item.location.eq_ignore_ascii_case(value),
+            CountScope::Supplier(value) => item.supplier.eq_ignore_ascii_case(value),
+        }
+    }
+}
+
+impl std::fmt::Display for CountScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountScope::All => write!(f, "All items"),
+            CountScope::Category(value) => write!(f, "Category: {}", value),
+            CountScope::Location(value) => write!(f, "Location: {}", value),
+            CountScope::Supplier(value) => write!(f, "Supplier: {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CountStatus {
+    InProgress,
+    Posted,
+    Cancelled,
+}
+
+impl std::fmt::Display for CountStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountStatus::InProgress => write!(f, "In Progress"),
+            CountStatus::Posted => write!(f, "Posted"),
+            CountStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountLine {
+    pub item_id: String,
+    pub item_sku: String,
+    pub item_name: String,
+    pub location: String,
+    /// On-hand quantity when the session started
+    pub expected: u32,
+    pub counted: Option<u32>,
+    pub unit_price: f64,
+    pub approved: bool,
+}
+
+impl CountLine {
+    /// Counted minus expected, once the line has been counted
+    pub fn variance(&self) -> Option<i64> {
+        self.counted.map(|counted| counted as i64 - self.expected as i64)
+    }
+
+    pub fn variance_value(&self) -> f64 {
+        self.variance().unwrap_or(0) as f64 * self.unit_price
+    }
+
+    pub fn has_variance(&self) -> bool {
+        self.variance().is_some_and(|v| v != 0)
+    }
+}
+
+/// A stocktake or cycle count session with its snapshot of expected quantities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountSession {
+    pub id: String,
+    pub number: String,
+    pub scope: CountScope,
+    pub status: CountStatus,
+    pub lines: Vec<CountLine>,
+    pub created_at: i64,
+    pub created_by: String,
+    pub posted_at: Option<i64>,
+}
+
+impl CountSession {
+    pub fn new(number: String, scope: CountScope, items: &[InventoryItem], created_by: String) -> Self {
+        let mut lines: Vec<CountLine> = items
+            .iter()
+            .filter(|item| scope.matches(item))
+            .map(|item| CountLine {
+                item_id: item.id.clone(),
+                item_sku: item.sku.clone(),
+                item_name: item.name.clone(),
+                location: item.location.clone(),
+                expected: item.quantity,
+                counted: None,
+                unit_price: item.price,
+                approved: false,
+            })
+            .collect();
+        lines.sort_by(|a, b| {
+            a.location
+                .to_lowercase()
+                .cmp(&b.location.to_lowercase())
+                .then_with(|| a.item_sku.to_lowercase().cmp(&b.item_sku.to_lowercase()))
+        });
+
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            number,
+            scope,
+            status: CountStatus::InProgress,
+            lines,
+            created_at: Utc::now().timestamp(),
+            created_by,
+            posted_at: None,
+        }
+    }
+
+    /// Adds one unit to the line whose SKU matches a scanned code. Like a
+    /// typed count, the changed line has to be approved again.
+    pub fn record_scan(&mut self, code: &str) -> Result<&CountLine, String> {
+        let code = code.trim();
+        let line = self
+            .lines
+            .iter_mut()
+            .find(|line| line.item_sku.eq_ignore_ascii_case(code))
+            .ok_or_else(|| format!("SKU '{}' is not part of this count", code))?;
+        line.counted = Some(line.counted.unwrap_or(0) + 1);
+        line.approved = false;
+        Ok(line)
+    }
+
+    pub fn counted_lines(&self) -> usize {
+        self.lines.iter().filter(|line| line.counted.is_some()).count()
+    }
+
+    pub fn variance_units(&self) -> i64 {
+        self.lines.iter().filter_map(|line| line.variance()).sum()
+    }
+
+    pub fn variance_value(&self) -> f64 {
+        self.lines.iter().map(|line| line.variance_value()).sum()
+    }
+
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// Returns the next sequential count session number, e.g. `CNT-000042`
+pub fn next_session_number(sessions: &[CountSession]) -> String {
+    let last = sessions
+        .iter()
+        .filter_map(|session| session.number.strip_prefix("CNT-"))
+        .filter_map(|n| n.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("CNT-{:06}", last + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(sku: &str, category: &str, quantity: u32) -> InventoryItem {
+        InventoryItem::new(
+            sku.to_string(),
+            sku.to_string(),
+            category.to_string(),
+            String::new(),
+            String::new(),
+            quantity,
+            1.0,
+        )
+    }
+
+    #[test]
+    fn scanning_an_approved_line_needs_approval_again() {
+        let items = vec![item("TOOL-001", "Tools", 2)];
+        let mut session = CountSession::new("CNT-000001".to_string(), CountScope::All, &items, "admin".to_string());
+        session.lines[0].counted = Some(2);
+        session.lines[0].approved = true;
+
+        let line = session.record_scan(" tool-001 ").unwrap();
+        assert_eq!(line.counted, Some(3));
+        assert!(!line.approved);
+        assert!(session.record_scan("PPE-014").is_err());
+    }
+
+    #[test]
+    fn scope_values_ignore_case() {
+        let items = vec![item("A", "tools", 1), item("B", "Safety", 1), item("C", "Tools", 1), item("D", " ", 1)];
+        let values = ScopeKind::Category.values(&items);
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].to_lowercase(), "safety");
+        assert_eq!(values[1].to_lowercase(), "tools");
+    }
+}
@@ -34,11 +34,22 @@ impl UserRole {
     }
 }
 
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserRole::Admin => write!(f, "Admin"),
+            UserRole::Manager => write!(f, "Manager"),
+            UserRole::User => write!(f, "User"),
+            UserRole::Viewer => write!(f, "Viewer"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub username: String,
-    #[serde(skip_serializing, default = "default_password_hash")]
+    /// bcrypt hash; files saved before hashes were kept get [`UNUSABLE_PASSWORD_HASH`]
     pub password_hash: String,
     pub role: UserRole,
     pub created_at: i64,
@@ -46,12 +57,9 @@ pub struct User {
     pub active: bool,
 }
 
-fn default_password_hash() -> String {
-    // Default hash that will never match any password
-    // Users imported without password_hash will need to reset their password
-    "$2b$12$XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX"
-        .to_string()
-}
+/// A hash that never matches any password, for users whose hash was not saved
+pub const UNUSABLE_PASSWORD_HASH: &str =
+    "$2b$12$XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX";
 
 impl User {
     pub fn new(
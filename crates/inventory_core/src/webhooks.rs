@@ -0,0 +1,336 @@
+//! Outgoing webhooks: inventory events posted as signed JSON to URLs an
+//! administrator registers.
+//!
+//! Each payload is signed with the webhook's secret as
+//! `X-Inventory-Signature: sha256=<hex HMAC-SHA256 of the body>`. Failed
+//! deliveries are retried with growing delays, then given up on.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::audit::{AuditAction, AuditEntry};
+
+/// Attempts made before a delivery is marked as failed
+pub const MAX_ATTEMPTS: u32 = 6;
+
+/// Delivery log entries kept in memory
+pub const LOG_SIZE: usize = 200;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    ItemCreated,
+    ItemUpdated,
+    ItemDeleted,
+    AlertRaised,
+    AlertAcknowledged,
+    ImportCompleted,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 6] = [
+        WebhookEvent::ItemCreated,
+        WebhookEvent::ItemUpdated,
+        WebhookEvent::ItemDeleted,
+        WebhookEvent::AlertRaised,
+        WebhookEvent::AlertAcknowledged,
+        WebhookEvent::ImportCompleted,
+    ];
+
+    /// The name sent in payloads and the `X-Inventory-Event` header
+    pub fn key(&self) -> &'static str {
+        match self {
+            WebhookEvent::ItemCreated => "item.created",
+            WebhookEvent::ItemUpdated => "item.updated",
+            WebhookEvent::ItemDeleted => "item.deleted",
+            WebhookEvent::AlertRaised => "alert.raised",
+            WebhookEvent::AlertAcknowledged => "alert.acknowledged",
+            WebhookEvent::ImportCompleted => "import.completed",
+        }
+    }
+
+    /// The event an audit entry stands for, if it is one webhooks can follow
+    pub fn from_audit(action: &AuditAction) -> Option<Self> {
+        match action {
+            AuditAction::ItemCreated => Some(WebhookEvent::ItemCreated),
+            AuditAction::ItemUpdated => Some(WebhookEvent::ItemUpdated),
+            AuditAction::ItemDeleted => Some(WebhookEvent::ItemDeleted),
+            AuditAction::AlertAcknowledged => Some(WebhookEvent::AlertAcknowledged),
+            AuditAction::DataImported => Some(WebhookEvent::ImportCompleted),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookEvent::ItemCreated => write!(f, "Item created"),
+            WebhookEvent::ItemUpdated => write!(f, "Item updated"),
+            WebhookEvent::ItemDeleted => write!(f, "Item deleted"),
+            WebhookEvent::AlertRaised => write!(f, "Alert raised"),
+            WebhookEvent::AlertAcknowledged => write!(f, "Alert acknowledged"),
+            WebhookEvent::ImportCompleted => write!(f, "Import done"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// Key for the payload signature, shared with the receiver
+    pub secret: String,
+    pub events: BTreeSet<WebhookEvent>,
+    pub active: bool,
+    pub created_at: i64,
+}
+
+impl Webhook {
+    pub fn new(name: String, url: String, events: BTreeSet<WebhookEvent>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            url,
+            secret: format!("whsec_{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+            events,
+            active: true,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.active && self.events.contains(&event)
+    }
+}
+
+/// Checks a webhook URL typed by the user
+pub fn validate_url(url: &str) -> Result<(), String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => Ok(()),
+        _ => Err("Enter a full http:// or https:// URL".to_string()),
+    }
+}
+
+/// Something that happened, waiting to be sent to the webhooks following it
+#[derive(Debug, Clone)]
+pub struct PendingEvent {
+    pub event: WebhookEvent,
+    pub occurred_at: i64,
+    pub data: serde_json::Value,
+}
+
+impl PendingEvent {
+    pub fn new(event: WebhookEvent, data: serde_json::Value) -> Self {
+        Self {
+            event,
+            occurred_at: chrono::Utc::now().timestamp(),
+            data,
+        }
+    }
+
+    /// Describes an audit entry; `record` is the item or alert it is about
+    pub fn from_audit(event: WebhookEvent, entry: &AuditEntry, record: Option<serde_json::Value>) -> Self {
+        Self {
+            event,
+            occurred_at: entry.timestamp,
+            data: serde_json::json!({
+                "user": entry.username,
+                "entity_type": entry.entity_type,
+                "entity_id": entry.entity_id,
+                "details": entry.details,
+                "old_value": entry.old_value,
+                "new_value": entry.new_value,
+                "record": record,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryStatus {
+    Sending,
+    /// Failed; tried again at this timestamp
+    Retrying(i64),
+    Delivered,
+    Failed,
+}
+
+impl fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryStatus::Sending => write!(f, "Sending"),
+            DeliveryStatus::Retrying(at) => write!(
+                f,
+                "Retrying at {}",
+                chrono::DateTime::from_timestamp(*at, 0)
+                    .map(|dt| dt.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+                    .unwrap_or_default()
+            ),
+            DeliveryStatus::Delivered => write!(f, "Delivered"),
+            DeliveryStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// One event sent to one webhook, with its attempts so far
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub webhook_name: String,
+    pub event: WebhookEvent,
+    pub body: String,
+    pub attempts: u32,
+    pub status: DeliveryStatus,
+    /// Response status or error of the latest attempt
+    pub last_result: Option<String>,
+    pub created_at: i64,
+}
+
+impl Delivery {
+    pub fn new(webhook: &Webhook, pending: &PendingEvent) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        let body = serde_json::json!({
+            "id": id,
+            "event": pending.event.key(),
+            "occurred_at": pending.occurred_at,
+            "data": pending.data,
+        })
+        .to_string();
+        Self {
+            id,
+            webhook_id: webhook.id.clone(),
+            webhook_name: webhook.name.clone(),
+            event: pending.event,
+            body,
+            attempts: 0,
+            status: DeliveryStatus::Sending,
+            last_result: None,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// Records the outcome of an attempt and schedules the next one if needed
+    pub fn record(&mut self, result: Result<u16, String>, now: i64) {
+        self.attempts += 1;
+        match result {
+            Ok(status) => {
+                self.status = DeliveryStatus::Delivered;
+                self.last_result = Some(format!("HTTP {}", status));
+            }
+            Err(e) => {
+                self.status = if self.attempts >= MAX_ATTEMPTS {
+                    DeliveryStatus::Failed
+                } else {
+                    DeliveryStatus::Retrying(now + backoff(self.attempts).as_secs() as i64)
+                };
+                self.last_result = Some(e);
+            }
+        }
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        matches!(self.status, DeliveryStatus::Retrying(at) if at <= now)
+    }
+
+    pub fn formatted_timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|dt| dt.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// Wait before the attempt after `attempts` failures: 30 seconds, doubling each time
+pub fn backoff(attempts: u32) -> Duration {
+    Duration::from_secs(30 * 2u64.pow(attempts.saturating_sub(1).min(10)))
+}
+
+/// The `X-Inventory-Signature` value for a body
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body.as_bytes());
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// Posts a delivery; any 2xx response counts as delivered
+pub async fn send(url: String, secret: String, delivery: Delivery) -> Result<u16, String> {
+    let client = reqwest::Client::builder()
+        .user_agent("inventory-app")
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let response = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .header("X-Inventory-Event", delivery.event.key())
+        .header("X-Inventory-Delivery", &delivery.id)
+        .header("X-Inventory-Signature", sign(&secret, &delivery.body))
+        .body(delivery.body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(format!("HTTP {}", status.as_u16()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_and_retries_deliveries() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let webhook = Webhook::new(
+            "Bot".to_string(),
+            "http://localhost/hook".to_string(),
+            BTreeSet::from([WebhookEvent::AlertRaised]),
+        );
+        assert!(webhook.wants(WebhookEvent::AlertRaised));
+        assert!(!webhook.wants(WebhookEvent::ItemCreated));
+
+        let pending = PendingEvent::new(WebhookEvent::AlertRaised, serde_json::json!({ "sku": "B-1" }));
+        let mut delivery = Delivery::new(&webhook, &pending);
+        let body: serde_json::Value = serde_json::from_str(&delivery.body).unwrap();
+        assert_eq!(body["event"], "alert.raised");
+        assert_eq!(body["data"]["sku"], "B-1");
+
+        delivery.record(Err("HTTP 500".to_string()), 1_000);
+        assert_eq!(delivery.status, DeliveryStatus::Retrying(1_030));
+        assert!(!delivery.is_due(1_029));
+        assert!(delivery.is_due(1_030));
+        delivery.record(Err("timed out".to_string()), 1_030);
+        assert_eq!(delivery.status, DeliveryStatus::Retrying(1_090));
+        for _ in 2..MAX_ATTEMPTS {
+            delivery.record(Err("timed out".to_string()), 2_000);
+        }
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+
+        assert!(validate_url("https://chat.example.com/hooks/1").is_ok());
+        assert!(validate_url("chat.example.com").is_err());
+    }
+}
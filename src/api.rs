@@ -0,0 +1,281 @@
+//! Opt-in HTTP API on localhost for scripts such as tills and label printers.
+//!
+//! The server runs on its own thread and hands each request to the app as a
+//! message, so calls read and change the same data as the window. Every call
+//! needs an API token, which acts as the user it was issued for.
+
+use std::io::Read;
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use iced::Subscription;
+use iced::futures::SinkExt;
+use serde::Serialize;
+
+use crate::analysis::AbcClass;
+use crate::messages::Message;
+use crate::search::{SearchFilter, SortDirection, SortField};
+
+pub use inventory_core::api::{ApiToken, DEFAULT_PORT};
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// How long a request waits for the app before giving up
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request passed from the server thread to the app
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    /// Upper case, e.g. `GET`
+    pub method: String,
+    /// Path segments after decoding, e.g. `["api", "items", "SKU-1"]`
+    pub path: Vec<String>,
+    pub query: Vec<(String, String)>,
+    pub body: String,
+    /// The bearer token, if one was sent
+    pub token: Option<String>,
+    pub reply: ApiReply,
+}
+
+/// Where the app sends its answer; the first response sent wins
+#[derive(Clone)]
+pub struct ApiReply(Arc<Mutex<Option<SyncSender<ApiResponse>>>>);
+
+impl ApiReply {
+    pub fn send(&self, response: ApiResponse) {
+        if let Some(sender) = self.0.lock().ok().and_then(|mut sender| sender.take()) {
+            let _ = sender.send(response);
+        }
+    }
+}
+
+impl std::fmt::Debug for ApiReply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ApiReply")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+impl ApiResponse {
+    pub fn ok<T: Serialize + ?Sized>(body: &T) -> Self {
+        Self::with_status(200, body)
+    }
+
+    pub fn created<T: Serialize + ?Sized>(body: &T) -> Self {
+        Self::with_status(201, body)
+    }
+
+    /// An error in the shape `{"error": {"status": 404, "message": "..."}}`
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": { "status": status, "message": message.into() } }),
+        }
+    }
+
+    fn with_status<T: Serialize + ?Sized>(status: u16, body: &T) -> Self {
+        match serde_json::to_value(body) {
+            Ok(body) => Self { status, body },
+            Err(e) => Self::error(500, format!("could not encode the response: {}", e)),
+        }
+    }
+}
+
+/// Builds an inventory filter from query parameters such as
+/// `?query=bolt&category=Hardware&max_quantity=5&sort=quantity&direction=desc`
+pub fn parse_filter(query: &[(String, String)]) -> Result<SearchFilter, String> {
+    let mut filter = SearchFilter::new();
+    for (name, value) in query {
+        let value = value.trim();
+        match name.as_str() {
+            "query" | "q" => filter.query = value.to_string(),
+            "category" => filter.category_filter = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "supplier" => filter.supplier_filter = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "abc" => {
+                filter.abc_filter = Some(
+                    AbcClass::ALL
+                        .into_iter()
+                        .find(|class| class.to_string().eq_ignore_ascii_case(value))
+                        .ok_or_else(|| format!("abc must be A, B or C, not '{}'", value))?,
+                )
+            }
+            "min_quantity" => filter.min_quantity = Some(parse_number(name, value)?),
+            "max_quantity" => filter.max_quantity = Some(parse_number(name, value)?),
+            "min_price" => filter.min_price = Some(parse_number(name, value)?),
+            "max_price" => filter.max_price = Some(parse_number(name, value)?),
+            "sort" => {
+                filter.sort_field = Some(match value {
+                    "name" => SortField::Name,
+                    "sku" => SortField::Sku,
+                    "category" => SortField::Category,
+                    "supplier" => SortField::Supplier,
+                    "quantity" => SortField::Quantity,
+                    "price" => SortField::Price,
+                    "created_at" => SortField::CreatedAt,
+                    "updated_at" => SortField::UpdatedAt,
+                    "days_of_cover" => SortField::DaysOfCover,
+                    other => return Err(format!("cannot sort by '{}'", other)),
+                })
+            }
+            "direction" => {
+                filter.sort_direction = match value {
+                    "asc" => SortDirection::Ascending,
+                    "desc" => SortDirection::Descending,
+                    other => return Err(format!("direction must be asc or desc, not '{}'", other)),
+                }
+            }
+            other => return Err(format!("unknown parameter '{}'", other)),
+        }
+    }
+    Ok(filter)
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} must be a number, not '{}'", name, value))
+}
+
+/// Decodes `%XX` escapes, and `+` as a space when `plus_is_space` is set
+fn decode(value: &str, plus_is_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(if bytes[i] == b'+' && plus_is_space { b' ' } else { bytes[i] });
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name, true), decode(value, true))
+        })
+        .collect()
+}
+
+/// Runs the server on 127.0.0.1 while the subscription is active
+pub fn serve(port: u16) -> Subscription<Message> {
+    Subscription::run_with_id(("api", port), listen(port))
+}
+
+fn listen(port: u16) -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(16, move |mut output| async move {
+        let server = match tiny_http::Server::http(("127.0.0.1", port)) {
+            Ok(server) => Arc::new(server),
+            Err(e) => {
+                let _ = output
+                    .send(Message::ApiServerStarted(Err(format!("Could not listen on port {}: {}", port, e))))
+                    .await;
+                return;
+            }
+        };
+        let _ = output.send(Message::ApiServerStarted(Ok(port))).await;
+
+        let listener = Arc::clone(&server);
+        std::thread::spawn(move || {
+            for request in listener.incoming_requests() {
+                respond(request, &mut output);
+            }
+        });
+
+        // Stops the server thread once the subscription is dropped
+        let _guard = StopOnDrop(server);
+        std::future::pending::<()>().await;
+    })
+}
+
+struct StopOnDrop(Arc<tiny_http::Server>);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.unblock();
+    }
+}
+
+fn respond(mut request: tiny_http::Request, output: &mut iced::futures::channel::mpsc::Sender<Message>) {
+    let response = forward(&mut request, output);
+    let mut reply = tiny_http::Response::from_string(response.body.to_string()).with_status_code(response.status);
+    if let Ok(header) = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]) {
+        reply = reply.with_header(header);
+    }
+    let _ = request.respond(reply);
+}
+
+/// Passes the request to the app and waits for its answer
+fn forward(request: &mut tiny_http::Request, output: &mut iced::futures::channel::mpsc::Sender<Message>) -> ApiResponse {
+    let mut body = String::new();
+    if request.as_reader().take(MAX_BODY_BYTES + 1).read_to_string(&mut body).is_err() {
+        return ApiResponse::error(400, "the request body must be UTF-8 text");
+    }
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return ApiResponse::error(413, "the request body is larger than 1 MB");
+    }
+
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let token = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let api_request = ApiRequest {
+        method: request.method().as_str().to_ascii_uppercase(),
+        path: path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| decode(segment, false))
+            .collect(),
+        query: parse_query(query),
+        body,
+        token,
+        reply: ApiReply(Arc::new(Mutex::new(Some(sender)))),
+    };
+
+    if iced::futures::executor::block_on(output.send(Message::ApiRequest(api_request))).is_err() {
+        return ApiResponse::error(503, "the app is closing");
+    }
+    receiver
+        .recv_timeout(REPLY_TIMEOUT)
+        .unwrap_or_else(|_| ApiResponse::error(503, "the app did not answer in time"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_filters() {
+        let query = parse_query("q=hex+bolt&category=Hard%20ware&max_quantity=5&sort=quantity&direction=desc");
+        let filter = parse_filter(&query).unwrap();
+        assert_eq!(filter.query, "hex bolt");
+        assert_eq!(filter.category_filter.as_deref(), Some("Hard ware"));
+        assert_eq!(filter.max_quantity, Some(5));
+        assert_eq!(filter.sort_field, Some(SortField::Quantity));
+        assert_eq!(filter.sort_direction, SortDirection::Descending);
+        assert!(parse_filter(&parse_query("max_quantity=lots")).is_err());
+        assert!(parse_filter(&parse_query("colour=red")).is_err());
+        assert_eq!(decode("A%2FB%", false), "A/B%");
+    }
+}
@@ -2,24 +2,47 @@ use iced::keyboard::{self, Key};
 use iced::mouse;
 use iced::widget::{markdown, text_editor};
 use iced::{Element, Subscription, Task};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 use crate::alerts::AlertManager;
+use crate::analysis::AbcClass;
 use crate::audit::AuditLog;
 use crate::auth::AuthStore;
 use crate::calculator::Calculator;
 use crate::inventory::InventoryItem;
 use crate::messages::{AppSettings, ItemDialogMode, LoadError, Message, SavedState, View};
 use crate::note::Note;
-use crate::persistence;
+use crate::persistence::{self, Tracked};
+use crate::purchasing::PurchaseOrder;
+use crate::returns::{Disposition, ReturnAuthorization, ReturnLine, ReturnReason};
+use crate::sales::{Customer, SalesOrder, SalesOrderLine};
 use crate::search::SearchFilter;
+use crate::stock::StockMovement;
+use crate::stocktake::{CountSession, ScopeKind};
 use crate::update_checker;
 use crate::user::Session;
 
+/// How often the data file is checked for changes made by other programs
+const DATA_FILE_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+/// How often scheduled export jobs are checked for being due
+const EXPORT_JOBS_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// How often failed webhook deliveries are checked for a retry
+const WEBHOOK_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// How often scheduled rules are checked for being due
+const RULES_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 pub enum AppState {
     Loading,
+    /// The data is encrypted; waiting for the passphrase
+    Locked,
     Login,
+    /// The data file could not be read; offering to restore a backup
+    Recovery,
+    /// Picking which workspace to open
+    ChoosingWorkspace,
+    /// Another instance has the workspace open; offering read-only access
+    InUse,
     Loaded,
 }
 
@@ -29,8 +52,26 @@ pub struct InventoryApp {
     pub show_about: bool,
     pub show_clear_confirm: bool,
 
+    // Save state
+    /// Counts changes; the data is dirty while it differs from `saved_change_count`
+    pub change_count: u64,
+    pub saved_change_count: u64,
+    /// Change count of the save in flight
+    pub saving: Option<u64>,
+    /// Versions of the saved sections as of the last save, see
+    /// `section_versions`; `None` until a save wrote everything
+    pub saved_versions: Option<[u64; 12]>,
+    /// Section versions of the save in flight
+    pub saving_versions: [u64; 12],
+    pub last_saved: Option<chrono::DateTime<chrono::Local>>,
+    pub save_error: Option<String>,
+    /// Set when the last save worked but something after it, like the backup, failed
+    pub save_warning: Option<String>,
+    /// Window waiting for unsaved changes to be written before it closes
+    pub closing_window: Option<iced::window::Id>,
+
     // Authentication state
-    pub auth_store: AuthStore,
+    pub auth_store: Tracked<AuthStore>,
     pub session: Option<Session>,
     pub username_input: String,
     pub password_input: String,
@@ -44,33 +85,170 @@ pub struct InventoryApp {
     pub user_operation_error: Option<String>,
 
     // Audit log state
-    pub audit_log: AuditLog,
+    pub audit_log: Tracked<AuditLog>,
 
     // Alert system state
-    pub alert_manager: AlertManager,
+    pub alert_manager: Tracked<AlertManager>,
     pub show_alerts_panel: bool,
     pub notification_timestamps: HashMap<String, Instant>,
 
     // Inventory state
-    pub items: Vec<InventoryItem>,
+    pub items: Tracked<Vec<InventoryItem>>,
     pub filtered_items: Vec<InventoryItem>,
     pub item_dialog_mode: Option<ItemDialogMode>,
     pub search_filter: SearchFilter,
     pub show_search_panel: bool,
+    pub inventory_export_error: Option<String>,
 
     // Item dialog inputs
     pub name_input: String,
     pub sku_input: String,
     pub category_input: String,
     pub supplier_input: String,
+    pub location_input: String,
     pub description_input: String,
     pub quantity_input: String,
     pub price_input: String,
+    pub reorder_point_input: String,
+    pub reorder_quantity_input: String,
+    pub lead_time_input: String,
     pub item_validation_error: Option<String>,
     pub similar_items_warning: Vec<String>,
 
+    // Reorder worksheet state
+    pub purchase_orders: Tracked<Vec<PurchaseOrder>>,
+    pub reorder_selection: HashSet<String>,
+    pub reorder_quantity_inputs: HashMap<String, String>,
+    pub reorder_status: Option<String>,
+
+    // Sales state
+    pub customers: Tracked<Vec<Customer>>,
+    pub sales_orders: Tracked<Vec<SalesOrder>>,
+    pub stock_movements: Tracked<Vec<StockMovement>>,
+    pub customer_name_input: String,
+    pub customer_email_input: String,
+    pub customer_phone_input: String,
+    pub customer_address_input: String,
+    pub sales_customer_id: Option<String>,
+    pub sales_line_item_id: Option<String>,
+    pub sales_line_quantity_input: String,
+    pub sales_draft_lines: Vec<SalesOrderLine>,
+    pub sales_error: Option<String>,
+
+    // Returns state
+    pub returns: Tracked<Vec<ReturnAuthorization>>,
+    pub return_sales_order_id: Option<String>,
+    pub return_customer_input: String,
+    pub return_reason: Option<ReturnReason>,
+    pub return_notes_input: String,
+    pub return_line_item_id: Option<String>,
+    pub return_line_quantity_input: String,
+    pub return_line_disposition: Option<Disposition>,
+    pub return_draft_lines: Vec<ReturnLine>,
+    pub returns_error: Option<String>,
+    /// Units typed per item in the quarantine list; empty means all held units
+    pub quarantine_quantity_inputs: HashMap<String, String>,
+
+    // Stocktake state
+    pub stocktakes: Tracked<Vec<CountSession>>,
+    pub stocktake_scope_kind: ScopeKind,
+    pub stocktake_scope_value: Option<String>,
+    pub active_stocktake_id: Option<String>,
+    pub stocktake_scan_input: String,
+    pub stocktake_message: Option<String>,
+
+    // Analysis state
+    pub abc_a_cutoff_input: String,
+    pub abc_b_cutoff_input: String,
+    pub slow_moving_days_input: String,
+    pub dead_stock_days_input: String,
+    pub analysis_error: Option<String>,
+
+    // Item import state
+    pub import_table: Option<crate::import::ImportTable>,
+    pub import_mapping: Vec<crate::import::ImportField>,
+    pub import_mode: crate::import::ImportMode,
+    pub import_preview: Vec<crate::import::RowPreview>,
+    pub import_status: Option<String>,
+
+    // Data import preview state
+    pub merge_plan: Option<crate::merge::MergePlan>,
+    pub merge_status: Option<String>,
+
+    // Report state
+    pub report_from_input: String,
+    pub report_to_input: String,
+    pub report_status: Option<String>,
+
+    // Data export state
+    pub show_export_dialog: bool,
+    pub export_options: crate::export::ExportOptions,
+    pub export_selection: HashSet<String>,
+    pub export_from_input: String,
+    pub export_to_input: String,
+    pub export_status: Option<String>,
+
+    // Scheduled export state
+    pub export_job_name_input: String,
+    pub export_job_format: crate::export::JobFormat,
+    pub export_job_frequency: crate::schedule::Frequency,
+    pub export_job_time_input: String,
+    pub export_job_weekday: chrono::Weekday,
+    pub export_job_keep_input: String,
+    pub export_job_use_filter: bool,
+    pub export_job_strip_sensitive: bool,
+    pub export_job_folder: Option<std::path::PathBuf>,
+    pub export_job_status: Option<String>,
+    pub running_export_jobs: HashSet<String>,
+
+    // HTTP API state
+    /// Whether the server is listening, or why it could not start
+    pub api_server_status: Option<Result<u16, String>>,
+    pub api_port_input: String,
+    pub api_token_name_input: String,
+    pub api_token_user: Option<String>,
+    /// A token just created, shown once so it can be copied
+    pub api_new_token: Option<String>,
+    pub api_form_status: Option<String>,
+
+    // Webhook state
+    pub webhook_name_input: String,
+    pub webhook_url_input: String,
+    pub webhook_events: std::collections::BTreeSet<crate::webhooks::WebhookEvent>,
+    pub webhook_status: Option<String>,
+    /// Deliveries made this session, oldest first
+    pub webhook_deliveries: Vec<crate::webhooks::Delivery>,
+    /// Id of the last audit entry checked for webhook events
+    pub webhook_audit_cursor: Option<String>,
+
+    // Rules state
+    pub rule_draft: crate::rules::RuleDraft,
+    pub rule_status: Option<String>,
+    /// What the draft would do to the current items, from the last preview
+    pub rule_preview: Option<Vec<String>>,
+    /// Stock alerts raised since rules and webhooks last looked at them
+    pub raised_alerts: Vec<crate::alerts::StockAlert>,
+    /// Change count item-changed rules were last checked at
+    pub rules_checked_change: Option<u64>,
+
+    // Scripting state
+    /// Computed column values for the current items
+    pub computed_values: crate::scripting::ComputedValues,
+    /// Change count the computed values were worked out at
+    pub computed_values_change: Option<u64>,
+    pub column_editing: Option<String>,
+    pub column_name_input: String,
+    pub column_script_input: String,
+    pub column_status: Option<String>,
+    pub report_script_editing: Option<String>,
+    pub report_script_name_input: String,
+    pub report_script_content: text_editor::Content,
+    pub report_script_status: Option<String>,
+    /// Name of the last report run and what it printed
+    pub report_script_output: Option<(String, Vec<String>)>,
+
     // Editor/Notes state
-    pub notes: Vec<Note>,
+    pub notes: Tracked<Vec<Note>>,
     pub selected_note_id: Option<String>,
     pub note_title_input: String,
     pub editor_content: text_editor::Content,
@@ -80,12 +258,48 @@ pub struct InventoryApp {
     pub calculator: Calculator,
 
     // Settings state
-    pub settings: AppSettings,
+    pub settings: Tracked<AppSettings>,
     pub settings_interval_input: String,
     pub settings_category_input: String,
     pub settings_notification_throttle_input: String,
     pub import_file_picker_open: bool,
     pub import_error: Option<String>,
+    pub storage_status: Option<String>,
+
+    // Encryption state
+    pub encryption_enabled: bool,
+    pub unlock_passphrase_input: String,
+    pub unlock_error: Option<String>,
+    pub unlocking: bool,
+    pub current_passphrase_input: String,
+    pub new_passphrase_input: String,
+    pub confirm_passphrase_input: String,
+    pub file_passphrase_input: String,
+    pub encryption_status: Option<String>,
+
+    // Backup state
+    pub backups: Option<Vec<crate::persistence::BackupInfo>>,
+    pub backup_keep_input: String,
+    pub backup_status: Option<String>,
+    pub restoring_backup: bool,
+    pub recovery_reason: Option<String>,
+
+    // Workspace state
+    pub workspaces: persistence::WorkspaceList,
+    /// Whether the open workspace's data is in memory and should be saved before switching
+    pub workspace_data_loaded: bool,
+    /// Workspace chosen while a save was running; opened once it finishes
+    pub pending_workspace: Option<String>,
+    pub new_workspace_name_input: String,
+    pub new_workspace_folder: Option<std::path::PathBuf>,
+    pub workspace_error: Option<String>,
+
+    // Data file state
+    /// Who holds the workspace lock when another instance has it open
+    pub lock_owner: Option<String>,
+    pub read_only: bool,
+    pub data_file_changed: bool,
+    pub data_file_status: Option<String>,
 
     // Update state
     pub update_checker: update_checker::UpdateChecker,
@@ -113,74 +327,232 @@ impl InventoryApp {
     }
 
     pub fn new() -> (Self, Task<Message>) {
-        (
-            Self {
-                state: AppState::Loading,
-                show_about: false,
-                show_clear_confirm: false,
-                auth_store: AuthStore::new(),
-                session: None,
-                username_input: String::new(),
-                password_input: String::new(),
-                login_error: None,
-                logging_in: false,
-                new_username_input: String::new(),
-                new_password_input: String::new(),
-                new_role_input: None,
-                user_operation_error: None,
-                audit_log: AuditLog::new(),
-                alert_manager: AlertManager::new(),
-                show_alerts_panel: false,
-                notification_timestamps: HashMap::new(),
-                items: Vec::new(),
-                filtered_items: Vec::new(),
-                item_dialog_mode: None,
-                search_filter: SearchFilter::new(),
-                show_search_panel: false,
-                name_input: String::new(),
-                sku_input: String::new(),
-                category_input: String::new(),
-                supplier_input: String::new(),
-                description_input: String::new(),
-                quantity_input: String::new(),
-                price_input: String::new(),
-                item_validation_error: None,
-                similar_items_warning: Vec::new(),
-                notes: Vec::new(),
-                selected_note_id: None,
-                note_title_input: String::new(),
-                editor_content: text_editor::Content::new(),
-                delete_note_confirm: None,
-                calculator: Calculator::new(),
-                settings: AppSettings::default(),
-                settings_interval_input: String::from("5"),
-                settings_category_input: String::from("General"),
-                settings_notification_throttle_input: String::from("30"),
-                import_file_picker_open: false,
-                import_error: None,
-                update_checker: update_checker::UpdateChecker::new(
-                    "ojutalayomi".to_string(),
-                    "inventory_app".to_string(),
-                ),
-                latest_version: None,
-                update_release_notes_items: None,
-                show_update_notification: false,
-                update_download_progress: None,
-                checking_for_updates: false,
-                downloading_update: false,
-                update_message: None,
-                current_view: View::Inventory,
-                sidebar_collapsed: false,
-            },
-            Task::perform(persistence::load_state(), Message::Loaded),
-        )
+        let mut app = Self::empty();
+        if persistence::workspace::fixed_data_dir().is_none() && app.workspaces.workspaces.len() > 1 {
+            app.state = AppState::ChoosingWorkspace;
+            return (app, Task::none());
+        }
+        (app, Self::load_workspace())
+    }
+
+    /// Nothing loaded yet, with the workspace list read from disk
+    pub(crate) fn empty() -> Self {
+        Self {
+            state: AppState::Loading,
+            show_about: false,
+            show_clear_confirm: false,
+            change_count: 0,
+            saved_change_count: 0,
+            saving: None,
+            saved_versions: None,
+            saving_versions: [0; 12],
+            last_saved: None,
+            save_error: None,
+            save_warning: None,
+            closing_window: None,
+            auth_store: AuthStore::new().into(),
+            session: None,
+            username_input: String::new(),
+            password_input: String::new(),
+            login_error: None,
+            logging_in: false,
+            new_username_input: String::new(),
+            new_password_input: String::new(),
+            new_role_input: None,
+            user_operation_error: None,
+            audit_log: AuditLog::new().into(),
+            alert_manager: AlertManager::new().into(),
+            show_alerts_panel: false,
+            notification_timestamps: HashMap::new(),
+            items: Vec::new().into(),
+            filtered_items: Vec::new(),
+            item_dialog_mode: None,
+            search_filter: SearchFilter::new(),
+            show_search_panel: false,
+            inventory_export_error: None,
+            name_input: String::new(),
+            sku_input: String::new(),
+            category_input: String::new(),
+            supplier_input: String::new(),
+            location_input: String::new(),
+            description_input: String::new(),
+            quantity_input: String::new(),
+            price_input: String::new(),
+            reorder_point_input: String::new(),
+            reorder_quantity_input: String::new(),
+            lead_time_input: String::new(),
+            item_validation_error: None,
+            similar_items_warning: Vec::new(),
+            purchase_orders: Vec::new().into(),
+            reorder_selection: HashSet::new(),
+            reorder_quantity_inputs: HashMap::new(),
+            reorder_status: None,
+            customers: Vec::new().into(),
+            sales_orders: Vec::new().into(),
+            stock_movements: Vec::new().into(),
+            customer_name_input: String::new(),
+            customer_email_input: String::new(),
+            customer_phone_input: String::new(),
+            customer_address_input: String::new(),
+            sales_customer_id: None,
+            sales_line_item_id: None,
+            sales_line_quantity_input: String::new(),
+            sales_draft_lines: Vec::new(),
+            sales_error: None,
+            returns: Vec::new().into(),
+            return_sales_order_id: None,
+            return_customer_input: String::new(),
+            return_reason: None,
+            return_notes_input: String::new(),
+            return_line_item_id: None,
+            return_line_quantity_input: String::new(),
+            return_line_disposition: Some(Disposition::Restock),
+            return_draft_lines: Vec::new(),
+            returns_error: None,
+            quarantine_quantity_inputs: HashMap::new(),
+            stocktakes: Vec::new().into(),
+            stocktake_scope_kind: ScopeKind::All,
+            stocktake_scope_value: None,
+            active_stocktake_id: None,
+            stocktake_scan_input: String::new(),
+            stocktake_message: None,
+            abc_a_cutoff_input: String::from("80"),
+            abc_b_cutoff_input: String::from("95"),
+            slow_moving_days_input: String::from("90"),
+            dead_stock_days_input: String::from("180"),
+            analysis_error: None,
+            import_table: None,
+            import_mapping: Vec::new(),
+            import_mode: crate::import::ImportMode::default(),
+            import_preview: Vec::new(),
+            import_status: None,
+            merge_plan: None,
+            merge_status: None,
+            report_from_input: (chrono::Utc::now() - chrono::Duration::days(30)).format("%Y-%m-%d").to_string(),
+            report_to_input: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            report_status: None,
+            show_export_dialog: false,
+            export_options: crate::export::ExportOptions::default(),
+            export_selection: HashSet::new(),
+            export_from_input: String::new(),
+            export_to_input: String::new(),
+            export_status: None,
+            export_job_name_input: String::new(),
+            export_job_format: crate::export::JobFormat::Csv,
+            export_job_frequency: crate::schedule::Frequency::Daily,
+            export_job_time_input: "02:00".to_string(),
+            export_job_weekday: chrono::Weekday::Mon,
+            export_job_keep_input: "7".to_string(),
+            export_job_use_filter: false,
+            export_job_strip_sensitive: false,
+            export_job_folder: None,
+            export_job_status: None,
+            running_export_jobs: HashSet::new(),
+            api_server_status: None,
+            api_port_input: crate::api::DEFAULT_PORT.to_string(),
+            api_token_name_input: String::new(),
+            api_token_user: None,
+            api_new_token: None,
+            api_form_status: None,
+            webhook_name_input: String::new(),
+            webhook_url_input: String::new(),
+            webhook_events: std::collections::BTreeSet::new(),
+            webhook_status: None,
+            webhook_deliveries: Vec::new(),
+            webhook_audit_cursor: None,
+            rule_draft: crate::rules::RuleDraft::default(),
+            rule_status: None,
+            rule_preview: None,
+            raised_alerts: Vec::new(),
+            rules_checked_change: None,
+            computed_values: crate::scripting::ComputedValues::default(),
+            computed_values_change: None,
+            column_editing: None,
+            column_name_input: String::new(),
+            column_script_input: String::new(),
+            column_status: None,
+            report_script_editing: None,
+            report_script_name_input: String::new(),
+            report_script_content: text_editor::Content::new(),
+            report_script_status: None,
+            report_script_output: None,
+            notes: Vec::new().into(),
+            selected_note_id: None,
+            note_title_input: String::new(),
+            editor_content: text_editor::Content::new(),
+            delete_note_confirm: None,
+            calculator: Calculator::new(),
+            settings: AppSettings::default().into(),
+            settings_interval_input: String::from("5"),
+            settings_category_input: String::from("General"),
+            settings_notification_throttle_input: String::from("30"),
+            import_file_picker_open: false,
+            import_error: None,
+            storage_status: None,
+            encryption_enabled: persistence::encryption_enabled(),
+            unlock_passphrase_input: String::new(),
+            unlock_error: None,
+            unlocking: false,
+            current_passphrase_input: String::new(),
+            new_passphrase_input: String::new(),
+            confirm_passphrase_input: String::new(),
+            file_passphrase_input: String::new(),
+            encryption_status: None,
+            backups: None,
+            backup_keep_input: String::from("7"),
+            backup_status: None,
+            restoring_backup: false,
+            recovery_reason: None,
+            workspaces: persistence::WorkspaceList::load(),
+            workspace_data_loaded: false,
+            pending_workspace: None,
+            new_workspace_name_input: String::new(),
+            new_workspace_folder: None,
+            workspace_error: None,
+            lock_owner: None,
+            read_only: false,
+            data_file_changed: false,
+            data_file_status: None,
+            update_checker: update_checker::UpdateChecker::new(
+                "ojutalayomi".to_string(),
+                "inventory_app".to_string(),
+            ),
+            latest_version: None,
+            update_release_notes_items: None,
+            show_update_notification: false,
+            update_download_progress: None,
+            checking_for_updates: false,
+            downloading_update: false,
+            update_message: None,
+            current_view: View::Inventory,
+            sidebar_collapsed: false,
+        }
+    }
+
+    /// Locks the open workspace, then loads it
+    pub(crate) fn load_workspace() -> Task<Message> {
+        Task::perform(persistence::lock_workspace(), Message::WorkspaceLocked)
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.dispatch(message);
+        // Rules react to the changes and alerts, then webhooks send them and the audit entries on,
+        // and computed columns catch up with the changes
+        let rules = self.run_rules();
+        let deliveries = self.queue_webhook_events();
+        self.refresh_computed_values();
+        Task::batch([task, rules, deliveries])
+    }
+
+    fn dispatch(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::Loaded(Ok(state)) => self.handle_loaded_success(state),
+            Message::Loaded(Ok(state)) => self.handle_loaded_success(*state),
             Message::Loaded(Err(LoadError::FileNotFound)) => self.handle_loaded_file_not_found(),
-            Message::Loaded(Err(LoadError::FormatError)) => self.handle_loaded_format_error(),
+            Message::Loaded(Err(LoadError::Locked)) => {
+                self.state = AppState::Locked;
+                Task::none()
+            }
+            Message::Loaded(Err(error)) => self.handle_loaded_error(error),
 
             // Authentication Messages
             Message::UsernameChanged(value) => {
@@ -240,6 +612,10 @@ impl InventoryApp {
                 self.handle_supplier_filter_changed(supplier);
                 Task::none()
             }
+            Message::AbcClassFilterChanged(class) => {
+                self.handle_abc_class_filter_changed(class);
+                Task::none()
+            }
             Message::MinQuantityChanged(value) => {
                 self.handle_min_quantity_changed(value);
                 Task::none()
@@ -312,6 +688,10 @@ impl InventoryApp {
                 self.handle_supplier_changed(value);
                 Task::none()
             }
+            Message::LocationChanged(value) => {
+                self.handle_location_changed(value);
+                Task::none()
+            }
             Message::DescriptionChanged(value) => {
                 self.handle_description_changed(value);
                 Task::none()
@@ -324,9 +704,23 @@ impl InventoryApp {
                 self.handle_price_changed(value);
                 Task::none()
             }
+            Message::ReorderPointChanged(value) => {
+                self.handle_reorder_point_changed(value);
+                Task::none()
+            }
+            Message::ReorderQuantityChanged(value) => {
+                self.handle_reorder_quantity_changed(value);
+                Task::none()
+            }
+            Message::LeadTimeChanged(value) => {
+                self.handle_lead_time_changed(value);
+                Task::none()
+            }
             Message::SubmitItem => self.handle_submit_item(),
             Message::DeleteItem(item_id) => self.handle_delete_item(item_id),
             Message::ExportInventoryCsv => self.handle_export_inventory_csv(),
+            Message::ExportInventorySpreadsheet => self.handle_export_inventory_spreadsheet(),
+            Message::InventorySpreadsheetExported(result) => self.handle_inventory_spreadsheet_exported(result),
             Message::InventoryViewModeChanged(mode) => {
                 self.handle_inventory_view_mode_changed(mode)
             }
@@ -388,7 +782,10 @@ impl InventoryApp {
             }
 
             // App Actions
-            Message::Save => self.auto_save(),
+            Message::Save => self.save_now(),
+            Message::AutoSaveTick => self.handle_auto_save_tick(),
+            Message::Saved(result) => self.handle_saved(result),
+            Message::WindowCloseRequested(id) => self.handle_window_close_requested(id),
             Message::ShowAbout => {
                 self.show_about = true;
                 Task::none()
@@ -398,6 +795,171 @@ impl InventoryApp {
                 Task::none()
             }
 
+            // Reorder Worksheet Messages
+            Message::ToggleReorderLine(item_id) => {
+                self.handle_toggle_reorder_line(item_id);
+                Task::none()
+            }
+            Message::ReorderLineQuantityChanged(item_id, value) => {
+                self.handle_reorder_line_quantity_changed(item_id, value);
+                Task::none()
+            }
+            Message::SelectAllReorderLines => {
+                self.handle_select_all_reorder_lines();
+                Task::none()
+            }
+            Message::ClearReorderSelection => {
+                self.handle_clear_reorder_selection();
+                Task::none()
+            }
+            Message::CreateDraftPurchaseOrders => self.handle_create_draft_purchase_orders(),
+            Message::ExportReorderCsv => self.handle_export_reorder_csv(),
+            Message::ReorderCsvExported(result) => self.handle_reorder_csv_exported(result),
+
+            // Sales Messages
+            Message::CustomerNameChanged(value) => {
+                self.handle_customer_name_changed(value);
+                Task::none()
+            }
+            Message::CustomerEmailChanged(value) => {
+                self.handle_customer_email_changed(value);
+                Task::none()
+            }
+            Message::CustomerPhoneChanged(value) => {
+                self.handle_customer_phone_changed(value);
+                Task::none()
+            }
+            Message::CustomerAddressChanged(value) => {
+                self.handle_customer_address_changed(value);
+                Task::none()
+            }
+            Message::CreateCustomer => self.handle_create_customer(),
+            Message::DeleteCustomer(customer_id) => self.handle_delete_customer(customer_id),
+            Message::SalesCustomerSelected(customer_id) => {
+                self.handle_sales_customer_selected(customer_id);
+                Task::none()
+            }
+            Message::SalesLineItemSelected(item_id) => {
+                self.handle_sales_line_item_selected(item_id);
+                Task::none()
+            }
+            Message::SalesLineQuantityChanged(value) => {
+                self.handle_sales_line_quantity_changed(value);
+                Task::none()
+            }
+            Message::AddSalesLine => {
+                self.handle_add_sales_line();
+                Task::none()
+            }
+            Message::RemoveSalesLine(index) => {
+                self.handle_remove_sales_line(index);
+                Task::none()
+            }
+            Message::CreateSalesOrder => self.handle_create_sales_order(),
+            Message::FulfillSalesOrder(order_id) => self.handle_fulfill_sales_order(order_id),
+            Message::CancelSalesOrder(order_id) => self.handle_cancel_sales_order(order_id),
+            Message::ExportPackingSlip(order_id) => self.handle_export_packing_slip(order_id),
+            Message::PackingSlipExported(result) => self.handle_packing_slip_exported(result),
+
+            // Returns Messages
+            Message::ReturnSalesOrderSelected(order_id) => {
+                self.handle_return_sales_order_selected(order_id);
+                Task::none()
+            }
+            Message::ReturnCustomerChanged(value) => {
+                self.handle_return_customer_changed(value);
+                Task::none()
+            }
+            Message::ReturnReasonSelected(reason) => {
+                self.handle_return_reason_selected(reason);
+                Task::none()
+            }
+            Message::ReturnNotesChanged(value) => {
+                self.handle_return_notes_changed(value);
+                Task::none()
+            }
+            Message::ReturnLineItemSelected(item_id) => {
+                self.handle_return_line_item_selected(item_id);
+                Task::none()
+            }
+            Message::ReturnLineQuantityChanged(value) => {
+                self.handle_return_line_quantity_changed(value);
+                Task::none()
+            }
+            Message::ReturnLineDispositionSelected(disposition) => {
+                self.handle_return_line_disposition_selected(disposition);
+                Task::none()
+            }
+            Message::AddReturnLine => {
+                self.handle_add_return_line();
+                Task::none()
+            }
+            Message::RemoveReturnLine(index) => {
+                self.handle_remove_return_line(index);
+                Task::none()
+            }
+            Message::CreateReturn => self.handle_create_return(),
+            Message::ExportReturnsReport => self.handle_export_returns_report(),
+            Message::ReturnsReportExported(result) => self.handle_returns_report_exported(result),
+            Message::QuarantineQuantityChanged(item_id, value) => {
+                self.handle_quarantine_quantity_changed(item_id, value);
+                Task::none()
+            }
+            Message::ResolveQuarantine(item_id, disposition) => self.handle_resolve_quarantine(item_id, disposition),
+
+            // Stocktake Messages
+            Message::StocktakeScopeKindSelected(kind) => {
+                self.handle_stocktake_scope_kind_selected(kind);
+                Task::none()
+            }
+            Message::StocktakeScopeValueSelected(value) => {
+                self.handle_stocktake_scope_value_selected(value);
+                Task::none()
+            }
+            Message::StartStocktake => self.handle_start_stocktake(),
+            Message::PrintBlankCountSheet => self.handle_print_blank_count_sheet(),
+            Message::CountSheetExported(result) => self.handle_count_sheet_exported(result),
+            Message::SelectStocktake(session_id) => {
+                self.handle_select_stocktake(session_id);
+                Task::none()
+            }
+            Message::StocktakeCountChanged(item_id, value) => {
+                self.handle_stocktake_count_changed(item_id, value)
+            }
+            Message::StocktakeScanInputChanged(value) => {
+                self.handle_stocktake_scan_input_changed(value);
+                Task::none()
+            }
+            Message::StocktakeScanSubmitted => self.handle_stocktake_scan_submitted(),
+            Message::ToggleStocktakeApproval(item_id) => {
+                self.handle_toggle_stocktake_approval(item_id)
+            }
+            Message::ApproveAllStocktakeVariances => self.handle_approve_all_stocktake_variances(),
+            Message::PostStocktake => self.handle_post_stocktake(),
+            Message::CancelStocktake => self.handle_cancel_stocktake(),
+
+            // Analysis Messages
+            Message::AbcACutoffChanged(value) => {
+                self.handle_abc_a_cutoff_changed(value);
+                Task::none()
+            }
+            Message::AbcBCutoffChanged(value) => {
+                self.handle_abc_b_cutoff_changed(value);
+                Task::none()
+            }
+            Message::SlowMovingDaysChanged(value) => {
+                self.handle_slow_moving_days_changed(value);
+                Task::none()
+            }
+            Message::DeadStockDaysChanged(value) => {
+                self.handle_dead_stock_days_changed(value);
+                Task::none()
+            }
+            Message::ApplyAnalysisSettings => self.handle_apply_analysis_settings(),
+            Message::ExportAbcReport => self.handle_export_abc_report(),
+            Message::AnalysisReportExported(result) => self.handle_analysis_report_exported(result),
+            Message::ExportSlowMovingReport => self.handle_export_slow_moving_report(),
+
             // Settings Messages
             Message::ToggleAutoSave => self.handle_toggle_auto_save(),
             Message::AutoSaveIntervalChanged(value) => self.handle_auto_save_interval_changed(value),
@@ -415,7 +977,10 @@ impl InventoryApp {
                 self.sidebar_collapsed = !self.sidebar_collapsed;
                 self.auto_save()
             }
-            Message::ExportData => self.handle_export_data(),
+            Message::ExportData => {
+                self.handle_export_data();
+                Task::none()
+            }
             Message::ImportData => self.handle_import_data(),
             Message::OpenImportFilePicker => self.handle_open_import_file_picker(),
             Message::ImportFileSelected(path) => self.handle_import_file_selected(path),
@@ -428,6 +993,398 @@ impl InventoryApp {
                 self.handle_cancel_clear_all_data();
                 Task::none()
             }
+            Message::StorageBackendChanged(backend) => self.handle_storage_backend_changed(backend),
+            Message::StorageBackendSwitched(result) => {
+                self.handle_storage_backend_switched(result);
+                Task::none()
+            }
+
+            // Encryption Messages
+            Message::UnlockPassphraseChanged(value) => {
+                self.handle_unlock_passphrase_changed(value);
+                Task::none()
+            }
+            Message::Unlock => self.handle_unlock(),
+            Message::Unlocked(result) => self.handle_unlocked(result),
+            Message::CurrentPassphraseChanged(value) => {
+                self.handle_current_passphrase_changed(value);
+                Task::none()
+            }
+            Message::NewPassphraseChanged(value) => {
+                self.handle_new_passphrase_changed(value);
+                Task::none()
+            }
+            Message::ConfirmPassphraseChanged(value) => {
+                self.handle_confirm_passphrase_changed(value);
+                Task::none()
+            }
+            Message::ApplyPassphrase => self.handle_apply_passphrase(),
+            Message::DisableEncryption => self.handle_disable_encryption(),
+            Message::EncryptionUpdated(result) => self.handle_encryption_updated(result),
+            Message::FilePassphraseChanged(value) => {
+                self.handle_file_passphrase_changed(value);
+                Task::none()
+            }
+
+            // Backup Messages
+            Message::ShowBackups => self.handle_show_backups(),
+            Message::BackupsListed(backups) => {
+                self.handle_backups_listed(backups);
+                Task::none()
+            }
+            Message::BackupFrequencyChanged(frequency) => self.handle_backup_frequency_changed(frequency),
+            Message::BackupKeepChanged(value) => self.handle_backup_keep_changed(value),
+            Message::CreateBackup => self.handle_create_backup(),
+            Message::BackupCreated(result) => self.handle_backup_created(result),
+            Message::RestoreBackup(path) => self.handle_restore_backup(path),
+            Message::BackupRestored(result) => self.handle_backup_restored(result),
+            Message::StartFresh => {
+                self.handle_start_fresh();
+                Task::none()
+            }
+
+            // Item Import Messages
+            Message::ImportItemsFile => self.handle_import_items_file(),
+            Message::ItemsFileRead(result) => {
+                self.handle_items_file_read(result);
+                Task::none()
+            }
+            Message::ImportColumnMapped(column, field) => {
+                self.handle_import_column_mapped(column, field);
+                Task::none()
+            }
+            Message::ImportModeChanged(mode) => {
+                self.handle_import_mode_changed(mode);
+                Task::none()
+            }
+            Message::ConfirmItemsImport => self.handle_confirm_items_import(),
+            Message::CancelItemsImport => {
+                self.handle_cancel_items_import();
+                Task::none()
+            }
+
+            // Data Import Preview Messages
+            Message::MergeResolutionChanged(kind, index, resolution) => {
+                self.handle_merge_resolution_changed(kind, index, resolution);
+                Task::none()
+            }
+            Message::ResolveAllConflicts(resolution) => {
+                self.handle_resolve_all_conflicts(resolution);
+                Task::none()
+            }
+            Message::MergeOptionToggled(option) => {
+                self.handle_merge_option_toggled(option);
+                Task::none()
+            }
+            Message::ConfirmMerge => self.handle_confirm_merge(),
+            Message::CloseMerge => {
+                self.handle_close_merge();
+                Task::none()
+            }
+
+            // Report Messages
+            Message::GenerateReport(kind) => self.handle_generate_report(kind),
+            Message::ReportSaved(result) => self.handle_report_saved(result),
+            Message::ReportFromChanged(value) => {
+                self.handle_report_from_changed(value);
+                Task::none()
+            }
+            Message::ReportToChanged(value) => {
+                self.handle_report_to_changed(value);
+                Task::none()
+            }
+            Message::ReportCompanyNameChanged(value) => self.handle_report_company_name_changed(value),
+            Message::ChooseReportLogo => self.handle_choose_report_logo(),
+            Message::ReportLogoChosen(path) => self.handle_report_logo_chosen(path),
+            Message::ClearReportLogo => self.handle_clear_report_logo(),
+
+            // Data Export Messages
+            Message::ExportEntityToggled(entity) => {
+                self.handle_export_entity_toggled(entity);
+                Task::none()
+            }
+            Message::ExportItemScopeChanged(scope) => {
+                self.handle_export_item_scope_changed(scope);
+                Task::none()
+            }
+            Message::ExportItemToggled(item_id) => {
+                self.handle_export_item_toggled(item_id);
+                Task::none()
+            }
+            Message::ExportFromChanged(value) => {
+                self.handle_export_from_changed(value);
+                Task::none()
+            }
+            Message::ExportToChanged(value) => {
+                self.handle_export_to_changed(value);
+                Task::none()
+            }
+            Message::ExportStripSensitiveToggled(strip) => {
+                self.handle_export_strip_sensitive_toggled(strip);
+                Task::none()
+            }
+            Message::ConfirmExport => self.handle_confirm_export(),
+            Message::CancelExport => {
+                self.handle_cancel_export();
+                Task::none()
+            }
+            Message::DataExported(result) => self.handle_data_exported(result),
+
+            // Scheduled Export Messages
+            Message::ExportJobsTick => self.handle_export_jobs_tick(),
+            Message::ExportJobNameChanged(value) => {
+                self.handle_export_job_name_changed(value);
+                Task::none()
+            }
+            Message::ExportJobFormatChanged(format) => {
+                self.handle_export_job_format_changed(format);
+                Task::none()
+            }
+            Message::ExportJobFrequencyChanged(frequency) => {
+                self.handle_export_job_frequency_changed(frequency);
+                Task::none()
+            }
+            Message::ExportJobTimeChanged(value) => {
+                self.handle_export_job_time_changed(value);
+                Task::none()
+            }
+            Message::ExportJobWeekdayChanged(weekday) => {
+                self.handle_export_job_weekday_changed(weekday);
+                Task::none()
+            }
+            Message::ExportJobKeepChanged(value) => {
+                self.handle_export_job_keep_changed(value);
+                Task::none()
+            }
+            Message::ToggleExportJobFilter => {
+                self.handle_toggle_export_job_filter();
+                Task::none()
+            }
+            Message::ToggleExportJobStripSensitive => {
+                self.handle_toggle_export_job_strip_sensitive();
+                Task::none()
+            }
+            Message::PickExportJobFolder => self.handle_pick_export_job_folder(),
+            Message::ExportJobFolderPicked(folder) => {
+                self.handle_export_job_folder_picked(folder);
+                Task::none()
+            }
+            Message::AddExportJob => self.handle_add_export_job(),
+            Message::RunExportJob(job_id) => self.handle_run_export_job(job_id),
+            Message::RemoveExportJob(job_id) => self.handle_remove_export_job(job_id),
+            Message::ExportJobFinished(job_id, result) => self.handle_export_job_finished(job_id, result),
+
+            // HTTP API Messages
+            Message::ApiServerStarted(result) => {
+                self.handle_api_server_started(result);
+                Task::none()
+            }
+            Message::ApiRequest(request) => self.handle_api_request(request),
+            Message::ToggleApi(enabled) => self.handle_toggle_api(enabled),
+            Message::ApiPortChanged(value) => {
+                self.handle_api_port_changed(value);
+                Task::none()
+            }
+            Message::ApplyApiPort => self.handle_apply_api_port(),
+            Message::ApiTokenNameChanged(value) => {
+                self.handle_api_token_name_changed(value);
+                Task::none()
+            }
+            Message::ApiTokenUserChanged(username) => {
+                self.handle_api_token_user_changed(username);
+                Task::none()
+            }
+            Message::CreateApiToken => self.handle_create_api_token(),
+            Message::CopyApiToken => self.handle_copy_api_token(),
+            Message::DismissApiToken => {
+                self.handle_dismiss_api_token();
+                Task::none()
+            }
+            Message::RevokeApiToken(token_id) => self.handle_revoke_api_token(token_id),
+
+            // Webhook Messages
+            Message::ShowWebhooks => {
+                self.handle_show_webhooks();
+                Task::none()
+            }
+            Message::WebhookNameChanged(value) => {
+                self.handle_webhook_name_changed(value);
+                Task::none()
+            }
+            Message::WebhookUrlChanged(value) => {
+                self.handle_webhook_url_changed(value);
+                Task::none()
+            }
+            Message::WebhookEventToggled(event) => {
+                self.handle_webhook_event_toggled(event);
+                Task::none()
+            }
+            Message::AddWebhook => self.handle_add_webhook(),
+            Message::ToggleWebhook(webhook_id, active) => self.handle_toggle_webhook(webhook_id, active),
+            Message::RemoveWebhook(webhook_id) => self.handle_remove_webhook(webhook_id),
+            Message::CopyWebhookSecret(webhook_id) => self.handle_copy_webhook_secret(webhook_id),
+            Message::WebhookDelivered(delivery_id, result) => {
+                self.handle_webhook_delivered(delivery_id, result);
+                Task::none()
+            }
+            Message::WebhookRetryTick => self.handle_webhook_retry_tick(),
+            Message::RetryWebhookDelivery(delivery_id) => self.handle_retry_webhook_delivery(delivery_id),
+
+            // Rule Messages
+            Message::RulesTick => self.handle_rules_tick(),
+            Message::NewRule => {
+                self.handle_new_rule();
+                Task::none()
+            }
+            Message::EditRule(rule_id) => {
+                self.handle_edit_rule(rule_id);
+                Task::none()
+            }
+            Message::RuleNameChanged(value) => {
+                self.handle_rule_name_changed(value);
+                Task::none()
+            }
+            Message::RuleTriggerChanged(trigger) => {
+                self.handle_rule_trigger_changed(trigger);
+                Task::none()
+            }
+            Message::RuleFrequencyChanged(frequency) => {
+                self.handle_rule_frequency_changed(frequency);
+                Task::none()
+            }
+            Message::RuleTimeChanged(value) => {
+                self.handle_rule_time_changed(value);
+                Task::none()
+            }
+            Message::RuleWeekdayChanged(weekday) => {
+                self.handle_rule_weekday_changed(weekday);
+                Task::none()
+            }
+            Message::RuleConditionChanged(value) => {
+                self.handle_rule_condition_changed(value);
+                Task::none()
+            }
+            Message::RuleDryRunToggled(dry_run) => {
+                self.handle_rule_dry_run_toggled(dry_run);
+                Task::none()
+            }
+            Message::AddRuleAction => {
+                self.handle_add_rule_action();
+                Task::none()
+            }
+            Message::RemoveRuleAction(index) => {
+                self.handle_remove_rule_action(index);
+                Task::none()
+            }
+            Message::RuleActionKindChanged(index, kind) => {
+                self.handle_rule_action_kind_changed(index, kind);
+                Task::none()
+            }
+            Message::RuleActionFieldChanged(index, field) => {
+                self.handle_rule_action_field_changed(index, field);
+                Task::none()
+            }
+            Message::RuleActionTargetChanged(index, value) => {
+                self.handle_rule_action_target_changed(index, value);
+                Task::none()
+            }
+            Message::RuleActionTextChanged(index, value) => {
+                self.handle_rule_action_text_changed(index, value);
+                Task::none()
+            }
+            Message::PreviewRule => {
+                self.handle_preview_rule();
+                Task::none()
+            }
+            Message::SaveRule => self.handle_save_rule(),
+            Message::ToggleRule(rule_id, active) => self.handle_toggle_rule(rule_id, active),
+            Message::RemoveRule(rule_id) => self.handle_remove_rule(rule_id),
+
+            // Scripting Messages
+            Message::EditComputedColumn(column_id) => {
+                self.handle_edit_computed_column(column_id);
+                Task::none()
+            }
+            Message::ComputedColumnNameChanged(value) => {
+                self.handle_computed_column_name_changed(value);
+                Task::none()
+            }
+            Message::ComputedColumnScriptChanged(value) => {
+                self.handle_computed_column_script_changed(value);
+                Task::none()
+            }
+            Message::SaveComputedColumn => self.handle_save_computed_column(),
+            Message::ClearComputedColumn => {
+                self.handle_clear_computed_column();
+                Task::none()
+            }
+            Message::RemoveComputedColumn(column_id) => self.handle_remove_computed_column(column_id),
+            Message::EditReportScript(script_id) => {
+                self.handle_edit_report_script(script_id);
+                Task::none()
+            }
+            Message::ReportScriptNameChanged(value) => {
+                self.handle_report_script_name_changed(value);
+                Task::none()
+            }
+            Message::ReportScriptAction(action) => {
+                self.handle_report_script_action(action);
+                Task::none()
+            }
+            Message::SaveReportScript => self.handle_save_report_script(),
+            Message::ClearReportScript => {
+                self.handle_clear_report_script();
+                Task::none()
+            }
+            Message::RemoveReportScript(script_id) => self.handle_remove_report_script(script_id),
+            Message::RunReportDraft => {
+                self.handle_run_report_draft();
+                Task::none()
+            }
+            Message::RunReportScript(script_id) => {
+                self.handle_run_report_script(script_id);
+                Task::none()
+            }
+
+            // Workspace Messages
+            Message::ShowWorkspaces => self.handle_show_workspaces(),
+            Message::WorkspaceSelected(name) => self.handle_workspace_selected(name),
+            Message::WorkspaceSwitched(result) => self.handle_workspace_switched(result),
+            Message::NewWorkspaceNameChanged(value) => {
+                self.handle_new_workspace_name_changed(value);
+                Task::none()
+            }
+            Message::PickWorkspaceFolder => self.handle_pick_workspace_folder(),
+            Message::WorkspaceFolderPicked(folder) => {
+                self.handle_workspace_folder_picked(folder);
+                Task::none()
+            }
+            Message::CreateWorkspace => self.handle_create_workspace(),
+            Message::RemoveWorkspace(name) => self.handle_remove_workspace(name),
+            Message::WorkspacesSaved(result) => {
+                self.handle_workspaces_saved(result);
+                Task::none()
+            }
+
+            // Data File Messages
+            Message::WorkspaceLocked(result) => self.handle_workspace_locked(result),
+            Message::OpenReadOnly => self.handle_open_read_only(),
+            Message::RetryWorkspaceLock => {
+                self.state = AppState::Loading;
+                Self::load_workspace()
+            }
+            Message::ReopenForEditing => self.handle_reopen_for_editing(),
+            Message::ReopenedForEditing(result) => self.handle_reopened_for_editing(result),
+            Message::CheckDataFile => self.handle_check_data_file(),
+            Message::DataFileChecked(changed) => {
+                self.handle_data_file_checked(changed);
+                Task::none()
+            }
+            Message::ReloadDataFile => self.handle_reload_data_file(),
+            Message::DataFileReloaded(result) => self.handle_data_file_reloaded(result),
+            Message::MergeDataFile => self.handle_merge_data_file(),
+            Message::DataFileMerged(result) => self.handle_data_file_merged(result),
+            Message::OverwriteDataFile => self.handle_overwrite_data_file(),
 
             // Update messages
             Message::CheckForUpdates => self.handle_check_for_updates(),
@@ -451,26 +1408,43 @@ impl InventoryApp {
         }
     }
 
-    fn handle_loaded_success(&mut self, state: SavedState) -> Task<Message> {
-        self.items = state.items;
-        self.filtered_items = self.search_filter.apply(&self.items);
-        self.alert_manager = state.alert_manager;
+    pub(crate) fn handle_loaded_success(&mut self, state: SavedState) -> Task<Message> {
+        *self.items = state.items;
+        *self.customers = state.customers;
+        *self.sales_orders = state.sales_orders;
+        *self.stock_movements = state.stock_movements;
+        *self.returns = state.returns;
+        *self.stocktakes = state.stocktakes;
+        *self.audit_log = state.audit_log;
+        *self.settings = state.settings;
+        self.settings.storage_backend = persistence::configured_backend();
+        self.refresh_allocations();
+        *self.alert_manager = state.alert_manager;
         let _ = self.alert_manager.update_from_inventory(&self.items);
-        self.notes = state.notes;
-        self.settings = state.settings;
-        self.auth_store = state.auth_store;
+        *self.notes = state.notes;
+        *self.auth_store = state.auth_store;
         // Ensure default admin user exists with valid password hash
-        // This is needed because password_hash is not serialized for security
+        // Files saved before password hashes were kept have none to log in with
         self.auth_store.ensure_default_admin();
-        self.audit_log = state.audit_log;
         self.sidebar_collapsed = state.sidebar_collapsed;
         self.show_alerts_panel = state.show_alerts_panel;
         self.show_search_panel = state.show_search_panel;
         self.current_view = state.current_view;
+        *self.purchase_orders = state.purchase_orders;
         self.settings_interval_input = self.settings.auto_save_interval.to_string();
         self.settings_category_input = self.settings.default_category.clone();
         self.settings_notification_throttle_input =
             self.settings.notification_throttle_seconds.to_string();
+        self.abc_a_cutoff_input = self.settings.analysis.a_cutoff.to_string();
+        self.abc_b_cutoff_input = self.settings.analysis.b_cutoff.to_string();
+        self.slow_moving_days_input = self.settings.analysis.slow_moving_days.to_string();
+        self.dead_stock_days_input = self.settings.analysis.dead_stock_days.to_string();
+        self.backup_keep_input = self.settings.backups.keep.to_string();
+        self.api_port_input = self.settings.api.port.to_string();
+        // Entries already in the loaded log are not sent to webhooks
+        self.webhook_audit_cursor = self.audit_log.get_entries().last().map(|entry| entry.id.clone());
+        self.computed_values_change = None;
+        self.saved_versions = None;
         if let Some(pos) = state.calculator_position {
             self.calculator.set_position(pos.0, pos.1);
         }
@@ -498,11 +1472,11 @@ impl InventoryApp {
         Task::none()
     }
 
-    fn handle_loaded_format_error(&mut self) -> Task<Message> {
-        // Corrupted file, start fresh
-        Self::delay(3);
-        self.state = AppState::Login;
-        Task::none()
+    fn handle_loaded_error(&mut self, error: LoadError) -> Task<Message> {
+        // Unreadable or newer file, offer to restore a backup before starting fresh
+        self.recovery_reason = Some(error.to_string());
+        self.state = AppState::Recovery;
+        self.list_backups()
     }
 
     pub fn clear_item_inputs(&mut self) {
@@ -510,37 +1484,81 @@ impl InventoryApp {
         self.sku_input.clear();
         self.category_input.clear();
         self.supplier_input.clear();
+        self.location_input.clear();
         self.description_input.clear();
         self.quantity_input.clear();
         self.price_input.clear();
+        self.reorder_point_input.clear();
+        self.reorder_quantity_input.clear();
+        self.lead_time_input.clear();
         self.item_validation_error = None;
         self.similar_items_warning.clear();
     }
 
-    pub fn auto_save(&self) -> Task<Message> {
-        if !self.settings.auto_save_enabled {
-            return Task::none();
-        }
-        
-        let state = SavedState {
-            items: self.items.clone(),
-            notes: self.notes.clone(),
+    /// Marks the data as changed; it is written by the next background save
+    pub fn auto_save(&mut self) -> Task<Message> {
+        self.change_count += 1;
+        Task::none()
+    }
+
+    /// Version of each of `SavedState::SECTIONS`, in that order
+    pub(crate) fn section_versions(&self) -> [u64; 12] {
+        [
+            self.items.version(),
+            self.notes.version(),
+            self.settings.version(),
+            self.auth_store.version(),
+            self.audit_log.version(),
+            self.alert_manager.version(),
+            self.purchase_orders.version(),
+            self.customers.version(),
+            self.sales_orders.version(),
+            self.stock_movements.version(),
+            self.returns.version(),
+            self.stocktakes.version(),
+        ]
+    }
+
+    /// Sections changed since the last save, or `None` when everything has to be written
+    pub(crate) fn changed_sections(&self) -> Option<Vec<&'static str>> {
+        let saved = self.saved_versions?;
+        let current = self.section_versions();
+        Some(
+            SavedState::SECTIONS
+                .iter()
+                .zip(saved.iter().zip(current))
+                .filter(|(_, (saved, current))| *saved != current)
+                .map(|(section, _)| *section)
+                .collect(),
+        )
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.change_count != self.saved_change_count
+    }
+
+    /// Snapshot of everything that gets persisted to the data file
+    pub(crate) fn saved_state(&self) -> SavedState {
+        SavedState {
+            schema_version: persistence::SCHEMA_VERSION,
+            items: (*self.items).clone(),
+            notes: (*self.notes).clone(),
             calculator_position: self.calculator.position,
-            settings: self.settings.clone(),
-            auth_store: self.auth_store.clone(),
-            audit_log: self.audit_log.clone(),
-            alert_manager: self.alert_manager.clone(),
+            settings: (*self.settings).clone(),
+            auth_store: (*self.auth_store).clone(),
+            audit_log: (*self.audit_log).clone(),
+            alert_manager: (*self.alert_manager).clone(),
             sidebar_collapsed: self.sidebar_collapsed,
             show_alerts_panel: self.show_alerts_panel,
             show_search_panel: self.show_search_panel,
             current_view: self.current_view.clone(),
-        };
-        Task::perform(
-            async move {
-                persistence::save_state(&state).await.ok();
-            },
-            |_| Message::Save,
-        )
+            purchase_orders: (*self.purchase_orders).clone(),
+            customers: (*self.customers).clone(),
+            sales_orders: (*self.sales_orders).clone(),
+            stock_movements: (*self.stock_movements).clone(),
+            returns: (*self.returns).clone(),
+            stocktakes: (*self.stocktakes).clone(),
+        }
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
@@ -590,7 +1608,79 @@ impl InventoryApp {
             Subscription::none()
         };
 
-        Subscription::batch([keyboard_sub, mouse_sub])
+        // Coalesce changes into one save per interval
+        let auto_save_sub = if self.settings.auto_save_enabled
+            && self.has_unsaved_changes()
+            && self.saving.is_none()
+            && !self.read_only
+            && !self.data_file_changed
+        {
+            let interval = Duration::from_secs(u64::from(self.settings.auto_save_interval.max(1)));
+            iced::time::every(interval).map(|_| Message::AutoSaveTick)
+        } else {
+            Subscription::none()
+        };
+
+        let close_sub = iced::window::close_requests().map(Message::WindowCloseRequested);
+
+        // Poll the data file for changes made outside this window
+        let data_file_sub = if matches!(self.state, AppState::Login | AppState::Loaded)
+            && !self.data_file_changed
+        {
+            iced::time::every(DATA_FILE_CHECK_INTERVAL).map(|_| Message::CheckDataFile)
+        } else {
+            Subscription::none()
+        };
+
+        // Scheduled exports only run while someone is logged in
+        let export_jobs_sub = if matches!(self.state, AppState::Loaded) && !self.settings.export_jobs.is_empty() {
+            iced::time::every(EXPORT_JOBS_CHECK_INTERVAL).map(|_| Message::ExportJobsTick)
+        } else {
+            Subscription::none()
+        };
+
+        // The API answers from the loaded data, whether or not anyone is logged in
+        let api_sub = if matches!(self.state, AppState::Login | AppState::Loaded) && self.settings.api.enabled {
+            crate::api::serve(self.settings.api.port)
+        } else {
+            Subscription::none()
+        };
+
+        // Failed deliveries wait for their next attempt
+        let webhook_retry_sub = if self
+            .webhook_deliveries
+            .iter()
+            .any(|delivery| matches!(delivery.status, crate::webhooks::DeliveryStatus::Retrying(_)))
+        {
+            iced::time::every(WEBHOOK_RETRY_INTERVAL).map(|_| Message::WebhookRetryTick)
+        } else {
+            Subscription::none()
+        };
+
+        // Like scheduled exports, scheduled rules only run while someone is logged in
+        let rules_sub = if matches!(self.state, AppState::Loaded)
+            && self
+                .settings
+                .rules
+                .iter()
+                .any(|rule| rule.active && matches!(rule.trigger, crate::rules::RuleTrigger::Schedule(_)))
+        {
+            iced::time::every(RULES_CHECK_INTERVAL).map(|_| Message::RulesTick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            keyboard_sub,
+            mouse_sub,
+            auto_save_sub,
+            close_sub,
+            data_file_sub,
+            export_jobs_sub,
+            api_sub,
+            webhook_retry_sub,
+            rules_sub,
+        ])
     }
 
     pub fn view(&self) -> Element<Message> {
@@ -602,6 +1692,35 @@ impl InventoryApp {
                 self.login_error.as_deref(),
                 &self.settings.theme,
                 self.logging_in,
+                persistence::workspace::fixed_data_dir()
+                    .is_none()
+                    .then_some(self.workspaces.active.as_str()),
+            ),
+            AppState::Locked => crate::views::unlock::view(
+                &self.unlock_passphrase_input,
+                self.unlock_error.as_deref(),
+                self.unlocking,
+                &self.settings.theme,
+            ),
+            AppState::Recovery => crate::views::backups::view_recovery(
+                self.recovery_reason.as_deref().unwrap_or_default(),
+                self.backups.as_deref(),
+                self.backup_status.as_deref(),
+                self.restoring_backup,
+                &self.settings.theme,
+            ),
+            AppState::InUse => crate::views::data_file::view_in_use(
+                self.lock_owner.as_deref().unwrap_or_default(),
+                persistence::workspace::fixed_data_dir().is_none(),
+                &self.settings.theme,
+            ),
+            AppState::ChoosingWorkspace => crate::views::workspaces::view(
+                &self.workspaces,
+                self.workspace_data_loaded,
+                &self.new_workspace_name_input,
+                self.new_workspace_folder.as_deref(),
+                self.workspace_error.as_deref(),
+                &self.settings.theme,
             ),
             AppState::Loaded => {
                 let main_content = self.view_loaded();
@@ -612,15 +1731,21 @@ impl InventoryApp {
                 if let Some(ref mode) = self.item_dialog_mode {
                     stack.push(crate::views::item_dialog::view(
                         mode,
-                        &self.name_input,
-                        &self.sku_input,
-                        &self.category_input,
-                        &self.supplier_input,
-                        &self.description_input,
-                        &self.quantity_input,
-                        &self.price_input,
-                        self.item_validation_error.as_deref(),
-                        &self.similar_items_warning,
+                        crate::views::item_dialog::ItemForm {
+                            name: &self.name_input,
+                            sku: &self.sku_input,
+                            category: &self.category_input,
+                            supplier: &self.supplier_input,
+                            location: &self.location_input,
+                            description: &self.description_input,
+                            quantity: &self.quantity_input,
+                            price: &self.price_input,
+                            reorder_point: &self.reorder_point_input,
+                            reorder_quantity: &self.reorder_quantity_input,
+                            lead_time: &self.lead_time_input,
+                            validation_error: self.item_validation_error.as_deref(),
+                            similar_items: &self.similar_items_warning,
+                        },
                         &self.settings.theme,
                     ));
                 }
@@ -629,6 +1754,18 @@ impl InventoryApp {
                     stack.push(crate::views::about::view(&self.settings.theme));
                 }
 
+                if self.show_export_dialog {
+                    stack.push(crate::views::export_dialog::view(
+                        &self.export_options,
+                        &self.filtered_items,
+                        &self.export_selection,
+                        &self.export_from_input,
+                        &self.export_to_input,
+                        self.export_status.as_deref(),
+                        &self.settings.theme,
+                    ));
+                }
+
                 if self.show_clear_confirm {
                     stack.push(crate::views::dialogs::view_clear_confirm(&self.settings.theme));
                 }
@@ -655,9 +1792,31 @@ impl InventoryApp {
         std::thread::sleep(std::time::Duration::from_secs(seconds));
     }
 
+    /// Recomputes each item's allocated quantity from open sales orders,
+    /// and its daily demand forecast and ABC class from outbound history
+    pub(crate) fn refresh_allocations(&mut self) {
+        let now = chrono::Utc::now().timestamp();
+        crate::sales::apply_allocations(&mut self.items, &self.sales_orders);
+        let history =
+            crate::forecast::demand_history(&self.stock_movements, self.audit_log.get_entries());
+        let demand = crate::forecast::demand_by_item(&history, now);
+        let classes: HashMap<String, AbcClass> =
+            crate::analysis::abc_analysis(&self.items, &history, &self.settings.analysis, now)
+                .into_iter()
+                .map(|row| (row.item_id, row.class))
+                .collect();
+        for item in &mut self.items {
+            item.daily_demand = demand.get(&item.id).copied().unwrap_or(0.0);
+            item.abc_class = classes.get(&item.id).copied();
+        }
+        self.filtered_items = self.search_filter.apply(&self.items);
+    }
+
     pub(crate) fn update_alerts_from_inventory(&mut self) {
+        self.refresh_allocations();
         let new_alerts = self.alert_manager.update_from_inventory(&self.items);
         self.notify_new_alerts(&new_alerts);
+        self.raised_alerts.extend(new_alerts);
     }
 
     pub(crate) fn maybe_send_device_notification(
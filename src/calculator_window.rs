@@ -4,7 +4,7 @@ use iced::{Element, Subscription, Task};
 
 use crate::calculator::Calculator;
 use crate::icon;
-use crate::messages::{AppTheme, CalculatorOp, LoadError};
+use crate::messages::{AppTheme, CalculatorOp};
 use crate::persistence;
 use crate::views;
 use crate::Message;
@@ -21,7 +21,9 @@ impl CalculatorWindow {
                 calculator: Calculator::new(),
                 theme: AppTheme::Dark,
             },
-            Task::perform(persistence::load_state(), Message::Loaded),
+            Task::perform(persistence::load_state(), |result| {
+                Message::Loaded(result.map(Box::new))
+            }),
         )
     }
 
@@ -31,8 +33,7 @@ impl CalculatorWindow {
                 self.theme = state.settings.theme;
                 Task::none()
             }
-            Message::Loaded(Err(LoadError::FileNotFound)) => Task::none(),
-            Message::Loaded(Err(LoadError::FormatError)) => Task::none(),
+            Message::Loaded(Err(_)) => Task::none(),
             Message::CalculatorInput(digit) => {
                 self.calculator.input_digit(digit);
                 Task::none()
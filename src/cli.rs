@@ -0,0 +1,566 @@
+//! Headless subcommands that work on the same data file as the app.
+//!
+//! Every command logs in as a user first, checks that user's role the same
+//! way the app does, and records changes in the audit log.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::audit::{AuditAction, AuditEntry};
+use crate::errors;
+use crate::export::{self, ExportOptions};
+use crate::import::{self, ImportMode, ImportTable};
+use crate::inventory::NewItem;
+use crate::messages::{LoadError, SavedState};
+use crate::persistence;
+use crate::sales;
+use crate::search::SearchFilter;
+use crate::stock;
+use crate::user::{Session, UserRole};
+
+const COMMANDS: [&str; 7] = ["items", "export", "import", "users", "audit", "alerts", "help"];
+
+/// Options that take a value; everything else starting with `--` is a switch
+const VALUE_OPTIONS: [&str; 20] = [
+    "data-dir",
+    "user",
+    "password",
+    "passphrase",
+    "format",
+    "filter",
+    "category",
+    "supplier",
+    "name",
+    "sku",
+    "quantity",
+    "price",
+    "location",
+    "description",
+    "reason",
+    "output",
+    "mode",
+    "role",
+    "new-password",
+    "lines",
+];
+
+const USAGE: &str = "\
+Usage: inventory_app <command> [options]
+
+Commands:
+  items list [--filter TEXT] [--category C] [--supplier S] [--format table|json|csv]
+  items add --name NAME --sku SKU [--category C] [--supplier S] [--quantity N]
+            [--price P] [--location L] [--description D]
+  items adjust SKU +N|-N [--reason TEXT]
+  export [--output FILE] [--strip-sensitive]
+  import FILE [--mode create-and-update|create-only|update-only]
+  users add USERNAME --role admin|manager|user|viewer [--new-password P]
+  audit tail [--lines N] [--format table|json]
+  alerts list [--format table|json]
+
+Every command logs in first. The user comes from --user or INVENTORY_USER,
+the password from --password or INVENTORY_PASSWORD, and is asked for when
+missing. Encrypted data needs --passphrase or INVENTORY_PASSPHRASE.
+--data-dir and --portable choose the data folder as for the app.";
+
+/// Whether the command line asks for a subcommand rather than the window
+pub fn is_command(args: &[String]) -> bool {
+    Args::parse(args).positionals.first().is_some_and(|first| COMMANDS.contains(&first.as_str()))
+}
+
+/// Runs a subcommand and returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let args = Args::parse(args);
+    match dispatch(&args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+struct Args {
+    positionals: Vec<String>,
+    options: HashMap<String, String>,
+    switches: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Self {
+        let mut parsed = Args {
+            positionals: Vec::new(),
+            options: HashMap::new(),
+            switches: Vec::new(),
+        };
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positionals.push(arg.clone());
+                continue;
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                parsed.options.insert(name.to_string(), value.to_string());
+            } else if VALUE_OPTIONS.contains(&name) {
+                let value = args.next().cloned().unwrap_or_default();
+                parsed.options.insert(name.to_string(), value);
+            } else {
+                parsed.switches.push(name.to_string());
+            }
+        }
+        parsed
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|switch| switch == name)
+    }
+
+    fn positional(&self, index: usize, what: &str) -> Result<&str, String> {
+        self.positionals
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing {}\n\n{}", what, USAGE))
+    }
+}
+
+fn dispatch(args: &Args) -> Result<(), String> {
+    let command = args.positional(0, "command")?;
+    let subcommand = args.positionals.get(1).map(String::as_str);
+    match (command, subcommand) {
+        ("help", _) => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        ("items", Some("list")) => Workspace::open(args, false)?.list_items(args),
+        ("items", Some("add")) => Workspace::open(args, true)?.add_item(args),
+        ("items", Some("adjust")) => Workspace::open(args, true)?.adjust_item(args),
+        ("export", _) => Workspace::open(args, true)?.export(args),
+        ("import", _) => Workspace::open(args, true)?.import(args),
+        ("users", Some("add")) => Workspace::open(args, true)?.add_user(args),
+        ("audit", Some("tail")) => Workspace::open(args, false)?.audit_tail(args),
+        ("alerts", Some("list")) => Workspace::open(args, false)?.list_alerts(args),
+        _ => Err(format!("unknown command\n\n{}", USAGE)),
+    }
+}
+
+/// The loaded data and the logged-in user
+struct Workspace {
+    runtime: tokio::runtime::Runtime,
+    state: SavedState,
+    session: Session,
+}
+
+impl Workspace {
+    /// Loads the data and logs in. Commands that write take the workspace
+    /// lock first, so they fail while the app has the workspace open.
+    fn open(args: &Args, writes: bool) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("could not start: {}", e))?;
+
+        if writes {
+            runtime.block_on(persistence::lock_workspace()).map_err(|owner| {
+                format!("the workspace is open in {}; close it there first", owner)
+            })?;
+        }
+        if persistence::encryption_enabled() {
+            let passphrase = value(args, "passphrase", "INVENTORY_PASSPHRASE", "Passphrase")?;
+            runtime.block_on(persistence::unlock(passphrase))?;
+        }
+
+        let mut state = match runtime.block_on(persistence::load_state()) {
+            Ok(state) => state,
+            Err(LoadError::FileNotFound) => SavedState::default(),
+            Err(e) => return Err(format!("could not read {}: {}", persistence::data_location().display(), e)),
+        };
+        state.auth_store.ensure_default_admin();
+        sales::apply_allocations(&mut state.items, &state.sales_orders);
+        let _ = state.alert_manager.update_from_inventory(&state.items);
+
+        let username = value(args, "user", "INVENTORY_USER", "User")?;
+        let password = value(args, "password", "INVENTORY_PASSWORD", "Password")?;
+        let session = state
+            .auth_store
+            .authenticate(&username, &password)
+            .ok_or("invalid username or password")?;
+
+        Ok(Self { runtime, state, session })
+    }
+
+    fn require(&self, allowed: bool, action: &str) -> Result<(), String> {
+        if allowed {
+            Ok(())
+        } else {
+            Err(format!("{} ({:?}) is not allowed to {}", self.session.username, self.session.role, action))
+        }
+    }
+
+    fn audit_entry(&self, action: AuditAction, entity_type: &str, entity_id: Option<String>, details: String) -> AuditEntry {
+        AuditEntry::new(
+            self.session.user_id.clone(),
+            self.session.username.clone(),
+            action,
+            entity_type.to_string(),
+            entity_id,
+            details,
+        )
+    }
+
+    fn save(mut self) -> Result<(), String> {
+        self.state.schema_version = persistence::SCHEMA_VERSION;
+        let _ = self.state.alert_manager.update_from_inventory(&self.state.items);
+        let warning = self
+            .runtime
+            .block_on(persistence::save_state(self.state))
+            .map_err(|e| format!("could not save: {}", e))?;
+        if let Some(warning) = warning {
+            eprintln!("warning: {}", warning);
+        }
+        Ok(())
+    }
+
+    fn list_items(self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_view(), "view items")?;
+        let filter = SearchFilter {
+            query: args.option("filter").unwrap_or_default().to_string(),
+            category_filter: args.option("category").map(str::to_string),
+            supplier_filter: args.option("supplier").map(str::to_string),
+            ..SearchFilter::new()
+        };
+        let items = filter.apply(&self.state.items);
+        let currency_code = &self.state.settings.preferred_currency;
+
+        match args.option("format").unwrap_or("table") {
+            "json" => print_json(&items),
+            "csv" => {
+                let columns = &self.state.settings.computed_columns;
+                print!("{}", crate::export::inventory_csv(&items, currency_code, columns));
+                Ok(())
+            }
+            "table" => {
+                let rows = items
+                    .iter()
+                    .map(|item| {
+                        vec![
+                            item.sku.clone(),
+                            item.name.clone(),
+                            item.category.clone(),
+                            item.quantity.to_string(),
+                            crate::currency::format_amount(item.price, currency_code),
+                        ]
+                    })
+                    .collect();
+                print_table(&["SKU", "Name", "Category", "Quantity", "Price"], rows);
+                Ok(())
+            }
+            other => Err(format!("unknown format '{}'; use table, json or csv", other)),
+        }
+    }
+
+    fn add_item(mut self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_create(), "add items")?;
+        let quantity = errors::validate_quantity(args.option("quantity").unwrap_or("0")).map_err(|e| e.to_string())?;
+        let price = errors::validate_price(args.option("price").unwrap_or("0")).map_err(|e| e.to_string())?;
+        let item = NewItem {
+            name: args.option("name").unwrap_or_default().to_string(),
+            sku: args.option("sku").unwrap_or_default().to_string(),
+            category: args.option("category").map(str::to_string),
+            supplier: args.option("supplier").unwrap_or_default().to_string(),
+            description: args.option("description").unwrap_or_default().to_string(),
+            location: args.option("location").unwrap_or_default().to_string(),
+            quantity,
+            price,
+        }
+        .build(&self.state.items, &self.state.settings.default_category)?;
+
+        let entry = self.audit_entry(
+            AuditAction::ItemCreated,
+            "item",
+            Some(item.id.clone()),
+            format!("Created item: {} (SKU: {})", item.name, item.sku),
+        );
+        self.state.audit_log.add_entry(entry);
+        println!("Added {} ({})", item.name, item.sku);
+        self.state.items.push(item);
+        self.save()
+    }
+
+    fn adjust_item(mut self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_edit(), "adjust stock")?;
+        let sku = args.positional(2, "SKU")?.to_string();
+        let delta_text = args.positional(3, "quantity change, e.g. +5 or -2")?;
+        let delta: i64 = delta_text
+            .trim_start_matches('+')
+            .parse()
+            .ok()
+            .filter(|delta| *delta != 0)
+            .ok_or_else(|| format!("'{}' is not a quantity change such as +5 or -2", delta_text))?;
+        let reason = args.option("reason").unwrap_or("Adjusted from the command line").to_string();
+
+        let username = self.session.username.clone();
+        let item = self
+            .state
+            .items
+            .iter_mut()
+            .find(|item| item.sku.eq_ignore_ascii_case(&sku))
+            .ok_or_else(|| format!("no item with SKU {}", sku))?;
+        let old_quantity = item.quantity;
+        let movement = stock::adjust(item, delta, reason.clone(), username)?;
+        let (item_id, name, new_quantity) = (item.id.clone(), item.name.clone(), item.quantity);
+        self.state.stock_movements.push(movement);
+
+        let entry = self
+            .audit_entry(
+                AuditAction::ItemUpdated,
+                "item",
+                Some(item_id),
+                format!("Adjusted stock of {} by {:+}: {}", name, delta, reason),
+            )
+            .with_values(Some(format!("Qty: {}", old_quantity)), Some(format!("Qty: {}", new_quantity)));
+        self.state.audit_log.add_entry(entry);
+        println!("{}: {} -> {}", name, old_quantity, new_quantity);
+        self.save()
+    }
+
+    fn export(mut self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_view_audit(), "export data")?;
+        let options = ExportOptions {
+            strip_sensitive: args.switch("strip-sensitive"),
+            ..Default::default()
+        };
+        let state = export::scoped_state(self.state.clone(), &options, None);
+        let json = serde_json::to_string_pretty(&state).map_err(|e| format!("could not serialize: {}", e))?;
+
+        let target = match args.option("output") {
+            Some(path) if path != "-" => {
+                persistence::write_atomic(Path::new(path), json.as_bytes())
+                    .map_err(|e| format!("could not write {}: {}", path, e))?;
+                eprintln!("Exported to {}", path);
+                path.to_string()
+            }
+            _ => {
+                println!("{}", json);
+                "standard output".to_string()
+            }
+        };
+        let entry = self.audit_entry(
+            AuditAction::DataExported,
+            "data",
+            None,
+            format!("Exported {} to {} from the command line", options.describe(), target),
+        );
+        self.state.audit_log.add_entry(entry);
+        self.save()
+    }
+
+    fn import(mut self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_edit(), "import items")?;
+        let path = Path::new(args.positional(1, "file to import")?);
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if file_name.to_ascii_lowercase().ends_with(".json") {
+            return Err("JSON data files are imported from Settings in the app, where conflicts can be reviewed".to_string());
+        }
+        let mode = match args.option("mode").unwrap_or("create-and-update") {
+            "create-and-update" => ImportMode::CreateAndUpdate,
+            "create-only" => ImportMode::CreateOnly,
+            "update-only" => ImportMode::UpdateOnly,
+            other => return Err(format!("unknown import mode '{}'", other)),
+        };
+
+        let bytes = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let table = ImportTable::read_file(file_name, &bytes)?;
+        let mapping = import::guess_mapping(&table.headers);
+        let rows = import::preview(&table, &mapping, mode, &self.state.items);
+        for row in rows.iter().filter(|row| !row.errors.is_empty()) {
+            eprintln!("line {}: {}", row.line, row.errors.join("; "));
+        }
+        let default_category = self.state.settings.default_category.clone();
+        let summary = import::apply(&rows, &mut self.state.items, &default_category);
+
+        let entry = self.audit_entry(
+            AuditAction::DataImported,
+            "inventory",
+            None,
+            format!("Imported items from {}: {}", table.file_name, summary),
+        );
+        self.state.audit_log.add_entry(entry);
+        println!("Imported {}: {}", table.file_name, summary);
+        self.save()
+    }
+
+    fn add_user(mut self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_manage_users(), "manage users")?;
+        let username = args.positional(2, "username")?.to_string();
+        let role = match args.option("role").unwrap_or_default().to_ascii_lowercase().as_str() {
+            "admin" => UserRole::Admin,
+            "manager" => UserRole::Manager,
+            "user" => UserRole::User,
+            "viewer" => UserRole::Viewer,
+            _ => return Err("--role must be admin, manager, user or viewer".to_string()),
+        };
+        let password = value(args, "new-password", "INVENTORY_NEW_PASSWORD", "New user's password")?;
+        if password.is_empty() {
+            return Err("the new user needs a password".to_string());
+        }
+
+        let user = self.state.auth_store.add_user(username, &password, role)?;
+        let entry = self.audit_entry(
+            AuditAction::UserCreated,
+            "user",
+            Some(user.id.clone()),
+            format!("Created user: {} with role: {:?}", user.username, user.role),
+        );
+        self.state.audit_log.add_entry(entry);
+        println!("Added user {} ({:?})", user.username, user.role);
+        self.save()
+    }
+
+    fn audit_tail(self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_view_audit(), "view the audit log")?;
+        let lines = match args.option("lines") {
+            Some(lines) => lines.parse().map_err(|_| "--lines must be a number".to_string())?,
+            None => 20,
+        };
+        let mut entries = self.state.audit_log.get_recent(lines);
+        entries.reverse();
+
+        match args.option("format").unwrap_or("table") {
+            "json" => print_json(&entries),
+            "table" => {
+                let rows = entries
+                    .iter()
+                    .map(|entry| {
+                        vec![
+                            entry.formatted_timestamp(),
+                            entry.username.clone(),
+                            entry.action.to_string(),
+                            entry.details.clone(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["Time", "User", "Action", "Details"], rows);
+                Ok(())
+            }
+            other => Err(format!("unknown format '{}'; use table or json", other)),
+        }
+    }
+
+    fn list_alerts(self, args: &Args) -> Result<(), String> {
+        self.require(self.session.role.can_view(), "view alerts")?;
+        let alerts = self.state.alert_manager.get_active_alerts();
+        match args.option("format").unwrap_or("table") {
+            "json" => print_json(&alerts),
+            "table" => {
+                let rows = alerts
+                    .iter()
+                    .map(|alert| {
+                        vec![
+                            alert.item_sku.clone(),
+                            alert.item_name.clone(),
+                            alert.alert_type.to_string(),
+                            alert.current_quantity.to_string(),
+                            alert.threshold.to_string(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["SKU", "Name", "Alert", "Quantity", "Threshold"], rows);
+                Ok(())
+            }
+            other => Err(format!("unknown format '{}'; use table or json", other)),
+        }
+    }
+}
+
+/// Reads an option, falling back to an environment variable and then a prompt
+fn value(args: &Args, option: &str, env: &str, prompt: &str) -> Result<String, String> {
+    if let Some(value) = args.option(option) {
+        return Ok(value.to_string());
+    }
+    if let Ok(value) = std::env::var(env) {
+        return Ok(value);
+    }
+    eprint!("{}: ", prompt);
+    std::io::stderr().flush().ok();
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("could not read {}: {}", prompt.to_lowercase(), e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| format!("could not serialize: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in &rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("inventory_app").chain(line.split_whitespace()).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn parses_subcommands_and_options() {
+        assert!(is_command(&args("--data-dir /tmp/x items list")));
+        assert!(!is_command(&args("--calculator")));
+        assert!(!is_command(&args("--data-dir items")));
+
+        let parsed = Args::parse(&args("items adjust AB-1 -3 --reason=damaged --user bob --strip-sensitive"));
+        assert_eq!(parsed.positionals, ["items", "adjust", "AB-1", "-3"]);
+        assert_eq!(parsed.option("reason"), Some("damaged"));
+        assert_eq!(parsed.option("user"), Some("bob"));
+        assert!(parsed.switch("strip-sensitive"));
+    }
+
+    #[test]
+    fn users_added_from_the_command_line_can_log_in() {
+        let dir = std::env::temp_dir().join(format!("inventory_cli_{}", uuid::Uuid::new_v4()));
+        persistence::configure(persistence::LaunchOptions {
+            data_dir: Some(dir.clone()),
+            ..Default::default()
+        });
+
+        let as_admin = "--user admin --password admin123";
+        assert_eq!(run(&args(&format!("users add bob --role viewer --new-password secret {}", as_admin))), 0);
+        assert_eq!(run(&args("alerts list --user bob --password secret")), 0);
+        assert_eq!(run(&args("alerts list --user bob --password wrong")), 1);
+        assert_eq!(run(&args("users add eve --role admin --new-password x --user bob --password secret")), 1);
+        assert_eq!(run(&args("export --output - --user bob --password secret")), 1);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
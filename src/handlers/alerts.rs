@@ -1,5 +1,6 @@
 use iced::Task;
 use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
 
 impl InventoryApp {
     pub fn handle_toggle_alerts_panel(&mut self) -> Task<Message> {
@@ -8,15 +9,48 @@ impl InventoryApp {
     }
 
     pub fn handle_acknowledge_alert(&mut self, alert_id: String) -> Task<Message> {
+        self.log_alert_acknowledged(&alert_id);
         self.alert_manager.acknowledge_alert(&alert_id);
         self.auto_save()
     }
 
     pub fn handle_acknowledge_all_alerts(&mut self) -> Task<Message> {
+        let alert_ids: Vec<String> = self
+            .alert_manager
+            .get_active_alerts()
+            .iter()
+            .map(|alert| alert.id.clone())
+            .collect();
+        for alert_id in &alert_ids {
+            self.log_alert_acknowledged(alert_id);
+        }
         self.alert_manager.acknowledge_all();
         self.auto_save()
     }
 
+    fn log_alert_acknowledged(&mut self, alert_id: &str) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let Some(alert) = self
+            .alert_manager
+            .get_active_alerts()
+            .iter()
+            .find(|alert| alert.id == alert_id && !alert.acknowledged)
+        else {
+            return;
+        };
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::AlertAcknowledged,
+            "alert".to_string(),
+            Some(alert.id.clone()),
+            format!("Acknowledged alert: {} for {} (SKU: {})", alert.alert_type, alert.item_name, alert.item_sku),
+        );
+        self.audit_log.add_entry(audit_entry);
+    }
+
     pub fn handle_clear_acknowledged_alerts(&mut self) -> Task<Message> {
         self.alert_manager.clear_acknowledged();
         self.auto_save()
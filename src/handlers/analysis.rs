@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::analysis::{self, AbcRow, SlowMovingRow};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::export::csv_escape;
+
+impl InventoryApp {
+    fn outbound_history(&self) -> HashMap<String, Vec<(i64, u32)>> {
+        crate::forecast::demand_history(&self.stock_movements, self.audit_log.get_entries())
+    }
+
+    pub(crate) fn abc_report(&self) -> Vec<AbcRow> {
+        analysis::abc_analysis(
+            &self.items,
+            &self.outbound_history(),
+            &self.settings.analysis,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    pub(crate) fn slow_moving_report(&self) -> Vec<SlowMovingRow> {
+        analysis::slow_moving(
+            &self.items,
+            &self.outbound_history(),
+            &self.settings.analysis,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    pub fn handle_abc_a_cutoff_changed(&mut self, value: String) {
+        self.abc_a_cutoff_input = value;
+    }
+
+    pub fn handle_abc_b_cutoff_changed(&mut self, value: String) {
+        self.abc_b_cutoff_input = value;
+    }
+
+    pub fn handle_slow_moving_days_changed(&mut self, value: String) {
+        self.slow_moving_days_input = value;
+    }
+
+    pub fn handle_dead_stock_days_changed(&mut self, value: String) {
+        self.dead_stock_days_input = value;
+    }
+
+    /// Applies the typed cut-offs and thresholds together, so they are
+    /// checked against each other rather than against the saved values
+    pub fn handle_apply_analysis_settings(&mut self) -> Task<Message> {
+        let (Ok(a_cutoff), Ok(b_cutoff)) =
+            (self.abc_a_cutoff_input.trim().parse::<f64>(), self.abc_b_cutoff_input.trim().parse::<f64>())
+        else {
+            self.analysis_error = Some("Enter the class cut-offs as percentages".to_string());
+            return Task::none();
+        };
+        if !(a_cutoff > 0.0 && a_cutoff < b_cutoff) {
+            self.analysis_error = Some("Class A cut-off must be above 0 and below the class B cut-off".to_string());
+            return Task::none();
+        }
+        if b_cutoff > 100.0 {
+            self.analysis_error = Some("Class B cut-off must be at most 100".to_string());
+            return Task::none();
+        }
+        let (Ok(slow_moving_days), Ok(dead_stock_days)) =
+            (self.slow_moving_days_input.trim().parse::<u32>(), self.dead_stock_days_input.trim().parse::<u32>())
+        else {
+            self.analysis_error = Some("Enter the slow-moving and dead stock periods in whole days".to_string());
+            return Task::none();
+        };
+        if slow_moving_days == 0 || slow_moving_days > dead_stock_days {
+            self.analysis_error =
+                Some("Slow-moving days must be at least 1 and no more than dead stock days".to_string());
+            return Task::none();
+        }
+
+        self.analysis_error = None;
+        let analysis = crate::analysis::AnalysisSettings { a_cutoff, b_cutoff, slow_moving_days, dead_stock_days };
+        if analysis == self.settings.analysis {
+            return Task::none();
+        }
+        self.settings.analysis = analysis;
+        self.refresh_allocations();
+        self.auto_save()
+    }
+
+    pub fn handle_export_abc_report(&mut self) -> Task<Message> {
+        let rows = self.abc_report();
+
+        Task::perform(
+            async move {
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+                let filename = format!("abc_analysis_{}.csv", timestamp);
+                let file_path = rfd::FileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("CSV", &["csv"])
+                    .save_file();
+
+                let Some(file_path) = file_path else {
+                    return Ok(None);
+                };
+                let count = rows.len();
+
+                let mut csv = String::from("SKU,Item,Units Consumed,Consumption Value,Cumulative %,Class\n");
+                for row in rows {
+                    csv.push_str(&format!(
+                        "{},{},{},{:.2},{:.1},{}\n",
+                        csv_escape(&row.item_sku),
+                        csv_escape(&row.item_name),
+                        row.units,
+                        row.consumption_value,
+                        row.cumulative_share,
+                        row.class,
+                    ));
+                }
+
+                std::fs::write(file_path, csv).map_err(|e| e.to_string())?;
+                Ok(Some(format!("Exported ABC analysis ({} items) to CSV", count)))
+            },
+            Message::AnalysisReportExported,
+        )
+    }
+
+    pub fn handle_export_slow_moving_report(&mut self) -> Task<Message> {
+        let rows = self.slow_moving_report();
+
+        Task::perform(
+            async move {
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+                let filename = format!("slow_moving_stock_{}.csv", timestamp);
+                let file_path = rfd::FileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("CSV", &["csv"])
+                    .save_file();
+
+                let Some(file_path) = file_path else {
+                    return Ok(None);
+                };
+                let count = rows.len();
+
+                let mut csv = String::from("SKU,Item,Status,Last Outbound,Days Idle,Quantity,Value\n");
+                for row in rows {
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{},{:.2}\n",
+                        csv_escape(&row.item_sku),
+                        csv_escape(&row.item_name),
+                        row.status,
+                        row.formatted_last_outbound(),
+                        row.days_idle,
+                        row.quantity,
+                        row.value,
+                    ));
+                }
+
+                std::fs::write(file_path, csv).map_err(|e| e.to_string())?;
+                Ok(Some(format!("Exported slow-moving stock report ({} items) to CSV", count)))
+            },
+            Message::AnalysisReportExported,
+        )
+    }
+
+    /// Logs a written analysis report; nothing is logged when the save dialog
+    /// was cancelled or the file could not be written
+    pub fn handle_analysis_report_exported(&mut self, result: Result<Option<String>, String>) -> Task<Message> {
+        let details = match result {
+            Ok(Some(details)) => details,
+            Ok(None) => return Task::none(),
+            Err(e) => {
+                self.analysis_error = Some(format!("Could not export the report: {}", e));
+                return Task::none();
+            }
+        };
+        self.analysis_error = None;
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "analysis".to_string(),
+                None,
+                details,
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+}
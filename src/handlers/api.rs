@@ -0,0 +1,373 @@
+use iced::Task;
+use serde::Deserialize;
+use crate::{InventoryApp, Message};
+use crate::api::{self, ApiRequest, ApiResponse, ApiToken};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::inventory::{InventoryItem, NewItem};
+use crate::note::Note;
+use crate::stock;
+use crate::user::Session;
+
+/// The user a request acts as, and the token it came with
+struct ApiCaller {
+    session: Session,
+    token_name: String,
+}
+
+impl ApiCaller {
+    fn require(&self, allowed: bool, action: &str) -> Result<(), ApiResponse> {
+        if allowed {
+            Ok(())
+        } else {
+            Err(ApiResponse::error(
+                403,
+                format!("{} ({:?}) is not allowed to {}", self.session.username, self.session.role, action),
+            ))
+        }
+    }
+
+    fn audit_entry(&self, action: AuditAction, entity_type: &str, entity_id: Option<String>, details: String) -> AuditEntry {
+        AuditEntry::new(
+            self.session.user_id.clone(),
+            self.session.username.clone(),
+            action,
+            entity_type.to_string(),
+            entity_id,
+            format!("{} (API token '{}')", details, self.token_name),
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct Adjustment {
+    delta: i64,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NewNote {
+    title: String,
+    #[serde(default)]
+    content: String,
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, ApiResponse> {
+    serde_json::from_str(body).map_err(|e| ApiResponse::error(400, format!("invalid JSON body: {}", e)))
+}
+
+impl InventoryApp {
+    /// Answers a call to the HTTP API from the app's own data
+    pub fn handle_api_request(&mut self, request: ApiRequest) -> Task<Message> {
+        let response = match self.api_caller(&request) {
+            Ok(caller) => self.route_api_request(&request, &caller).unwrap_or_else(|error| error),
+            Err(error) => error,
+        };
+        // Every successful POST changes the data
+        let changed = request.method == "POST" && (200..300).contains(&response.status);
+        request.reply.send(response);
+        if changed {
+            self.auto_save()
+        } else {
+            Task::none()
+        }
+    }
+
+    fn api_caller(&self, request: &ApiRequest) -> Result<ApiCaller, ApiResponse> {
+        let token = request
+            .token
+            .as_deref()
+            .ok_or_else(|| ApiResponse::error(401, "send an API token in an 'Authorization: Bearer <token>' header"))?;
+        let api_token = self
+            .settings
+            .api
+            .find_token(token)
+            .ok_or_else(|| ApiResponse::error(401, "the API token is not valid"))?;
+        let user = self
+            .auth_store
+            .get_user(&api_token.user_id)
+            .filter(|user| user.active)
+            .ok_or_else(|| ApiResponse::error(403, "the token's user has been removed or deactivated"))?;
+        Ok(ApiCaller {
+            session: Session::new(user),
+            token_name: api_token.name.clone(),
+        })
+    }
+
+    fn route_api_request(&mut self, request: &ApiRequest, caller: &ApiCaller) -> Result<ApiResponse, ApiResponse> {
+        let path: Vec<&str> = request.path.iter().map(String::as_str).collect();
+        if request.method == "POST" && self.read_only {
+            return Err(ApiResponse::error(409, "the workspace is open read-only, so changes cannot be saved"));
+        }
+
+        match (request.method.as_str(), path.as_slice()) {
+            ("GET", ["api", "items"]) => {
+                caller.require(caller.session.role.can_view(), "view items")?;
+                let filter = api::parse_filter(&request.query).map_err(|e| ApiResponse::error(400, e))?;
+                Ok(ApiResponse::ok(&filter.apply(&self.items)))
+            }
+            ("GET", ["api", "items", key]) => {
+                caller.require(caller.session.role.can_view(), "view items")?;
+                Ok(ApiResponse::ok(self.api_item(key)?))
+            }
+            ("POST", ["api", "items"]) => {
+                caller.require(caller.session.role.can_create(), "add items")?;
+                let new_item: NewItem = parse_body(&request.body)?;
+                let item = new_item
+                    .build(&self.items, &self.settings.default_category)
+                    .map_err(|e| ApiResponse::error(422, e))?;
+                let entry = caller.audit_entry(
+                    AuditAction::ItemCreated,
+                    "item",
+                    Some(item.id.clone()),
+                    format!("Created item: {} (SKU: {})", item.name, item.sku),
+                );
+                self.audit_log.add_entry(entry);
+                let response = ApiResponse::created(&item);
+                self.items.push(item);
+                self.update_alerts_from_inventory();
+                Ok(response)
+            }
+            ("POST", ["api", "items", key, "adjust"]) => {
+                caller.require(caller.session.role.can_edit(), "adjust stock")?;
+                let adjustment: Adjustment = parse_body(&request.body)?;
+                if adjustment.delta == 0 {
+                    return Err(ApiResponse::error(422, "delta must not be zero"));
+                }
+                let reason = adjustment
+                    .reason
+                    .filter(|reason| !reason.trim().is_empty())
+                    .unwrap_or_else(|| format!("Adjusted through API token '{}'", caller.token_name));
+                let id = self.api_item(key)?.id.clone();
+                let item = self
+                    .items
+                    .iter_mut()
+                    .find(|item| item.id == id)
+                    .ok_or_else(|| ApiResponse::error(404, "no such item"))?;
+                let old_quantity = item.quantity;
+                let movement = stock::adjust(item, adjustment.delta, reason.clone(), caller.session.username.clone())
+                    .map_err(|e| ApiResponse::error(422, e))?;
+                let response = ApiResponse::ok(&*item);
+                let entry = caller
+                    .audit_entry(
+                        AuditAction::ItemUpdated,
+                        "item",
+                        Some(item.id.clone()),
+                        format!("Adjusted stock of {} by {:+}: {}", item.name, adjustment.delta, reason),
+                    )
+                    .with_values(Some(format!("Qty: {}", old_quantity)), Some(format!("Qty: {}", item.quantity)));
+                self.stock_movements.push(movement);
+                self.audit_log.add_entry(entry);
+                self.update_alerts_from_inventory();
+                Ok(response)
+            }
+            ("GET", ["api", "alerts"]) => {
+                caller.require(caller.session.role.can_view(), "view alerts")?;
+                Ok(ApiResponse::ok(self.alert_manager.get_active_alerts()))
+            }
+            ("GET", ["api", "notes"]) => {
+                caller.require(caller.session.role.can_view(), "view notes")?;
+                Ok(ApiResponse::ok(&*self.notes))
+            }
+            ("GET", ["api", "notes", id]) => {
+                caller.require(caller.session.role.can_view(), "view notes")?;
+                let note = self
+                    .notes
+                    .iter()
+                    .find(|note| note.id == *id)
+                    .ok_or_else(|| ApiResponse::error(404, format!("no note with id {}", id)))?;
+                Ok(ApiResponse::ok(note))
+            }
+            ("POST", ["api", "notes"]) => {
+                caller.require(caller.session.role.can_create(), "add notes")?;
+                let new_note: NewNote = parse_body(&request.body)?;
+                let title = new_note.title.trim().to_string();
+                if title.is_empty() {
+                    return Err(ApiResponse::error(422, "title must not be empty"));
+                }
+                let mut note = Note::new(title);
+                note.update_content(new_note.content);
+                let entry = caller.audit_entry(
+                    AuditAction::NoteCreated,
+                    "note",
+                    Some(note.id.clone()),
+                    format!("Created note: {}", note.title),
+                );
+                self.audit_log.add_entry(entry);
+                let response = ApiResponse::created(&note);
+                self.notes.push(note);
+                Ok(response)
+            }
+            (_, ["api", "items"] | ["api", "items", _] | ["api", "items", _, "adjust"] | ["api", "alerts"])
+            | (_, ["api", "notes"] | ["api", "notes", _]) => Err(ApiResponse::error(
+                405,
+                format!("{} is not supported on /{}", request.method, request.path.join("/")),
+            )),
+            _ => Err(ApiResponse::error(404, format!("no endpoint at /{}", request.path.join("/")))),
+        }
+    }
+
+    /// Finds an item by id or, ignoring case, by SKU
+    fn api_item(&self, key: &str) -> Result<&InventoryItem, ApiResponse> {
+        self.items
+            .iter()
+            .find(|item| item.id == key)
+            .or_else(|| self.items.iter().find(|item| item.sku.eq_ignore_ascii_case(key)))
+            .ok_or_else(|| ApiResponse::error(404, format!("no item with id or SKU {}", key)))
+    }
+
+    pub fn handle_api_server_started(&mut self, result: Result<u16, String>) {
+        self.api_server_status = Some(result);
+    }
+
+    pub fn handle_toggle_api(&mut self, enabled: bool) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        self.settings.api.enabled = enabled;
+        if !enabled {
+            self.api_server_status = None;
+        }
+
+        let details = if enabled {
+            format!("Enabled the HTTP API on port {}", self.settings.api.port)
+        } else {
+            "Disabled the HTTP API".to_string()
+        };
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "api".to_string(),
+            None,
+            details,
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+
+    pub fn handle_api_port_changed(&mut self, value: String) {
+        if value.chars().all(|c| c.is_ascii_digit()) {
+            self.api_port_input = value;
+        }
+    }
+
+    pub fn handle_apply_api_port(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let port = match self.api_port_input.parse::<u16>() {
+            Ok(port) if port >= 1024 => port,
+            _ => {
+                self.api_form_status = Some("Choose a port from 1024 to 65535".to_string());
+                return Task::none();
+            }
+        };
+        if port == self.settings.api.port {
+            return Task::none();
+        }
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "api".to_string(),
+            None,
+            "Changed the HTTP API port".to_string(),
+        )
+        .with_values(Some(self.settings.api.port.to_string()), Some(port.to_string()));
+        self.audit_log.add_entry(audit_entry);
+        self.settings.api.port = port;
+        self.api_server_status = None;
+        self.api_form_status = None;
+        self.auto_save()
+    }
+
+    pub fn handle_api_token_name_changed(&mut self, value: String) {
+        self.api_token_name_input = value;
+    }
+
+    pub fn handle_api_token_user_changed(&mut self, username: String) {
+        self.api_token_user = Some(username);
+    }
+
+    pub fn handle_create_api_token(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let name = self.api_token_name_input.trim().to_string();
+        if name.is_empty() {
+            self.api_form_status = Some("Name the token after the script that will use it".to_string());
+            return Task::none();
+        }
+        let Some(user) = self
+            .api_token_user
+            .as_ref()
+            .and_then(|username| self.auth_store.get_all_users().into_iter().find(|user| &user.username == username))
+        else {
+            self.api_form_status = Some("Choose the user the token acts as".to_string());
+            return Task::none();
+        };
+
+        let (token, secret) = ApiToken::generate(name, user.id.clone());
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "api_token".to_string(),
+            Some(token.id.clone()),
+            format!("Created API token '{}' for {} ({:?})", token.name, user.username, user.role),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.settings.api.tokens.push(token);
+        self.api_new_token = Some(secret);
+        self.api_token_name_input.clear();
+        self.api_form_status = None;
+        self.auto_save()
+    }
+
+    pub fn handle_copy_api_token(&mut self) -> Task<Message> {
+        match &self.api_new_token {
+            Some(secret) => iced::clipboard::write(secret.clone()),
+            None => Task::none(),
+        }
+    }
+
+    pub fn handle_dismiss_api_token(&mut self) {
+        self.api_new_token = None;
+    }
+
+    pub fn handle_revoke_api_token(&mut self, token_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let Some(index) = self.settings.api.tokens.iter().position(|token| token.id == token_id) else {
+            return Task::none();
+        };
+        let token = self.settings.api.tokens.remove(index);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "api_token".to_string(),
+            Some(token.id),
+            format!("Revoked API token '{}'", token.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+}
@@ -32,6 +32,7 @@ impl InventoryApp {
             self.audit_log.add_entry(audit_entry);
             
             self.session = Some(session);
+            self.deliver_rule_notices();
             self.username_input.clear();
             self.password_input.clear();
             self.login_error = None;
@@ -80,7 +81,9 @@ impl InventoryApp {
         self.session = None;
         self.state = crate::AppState::Login;
         self.current_view = View::Inventory;
-        self.auto_save()
+        // Nothing is left waiting for the next background save
+        let _ = self.auto_save();
+        self.save_now()
     }
 }
 
@@ -0,0 +1,128 @@
+use iced::Task;
+use crate::{AppState, InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::messages::{SavedState, View};
+use crate::persistence::{self, BackupFrequency, BackupInfo};
+
+impl InventoryApp {
+    pub fn handle_show_backups(&mut self) -> Task<Message> {
+        self.current_view = View::Backups;
+        self.backup_status = None;
+        self.list_backups()
+    }
+
+    pub(crate) fn list_backups(&mut self) -> Task<Message> {
+        self.backups = None;
+        Task::perform(persistence::list_backups(), Message::BackupsListed)
+    }
+
+    pub fn handle_backups_listed(&mut self, backups: Vec<BackupInfo>) {
+        self.backups = Some(backups);
+    }
+
+    pub fn handle_backup_frequency_changed(&mut self, frequency: BackupFrequency) -> Task<Message> {
+        self.settings.backups.frequency = frequency;
+        self.auto_save()
+    }
+
+    pub fn handle_backup_keep_changed(&mut self, value: String) -> Task<Message> {
+        if !value.chars().all(|c| c.is_ascii_digit()) {
+            return Task::none();
+        }
+        self.backup_keep_input = value.clone();
+        if let Ok(keep) = value.parse::<u32>()
+            && (1..=100).contains(&keep)
+        {
+            self.settings.backups.keep = keep;
+            self.backup_status = None;
+            return self.auto_save();
+        }
+        self.backup_status = Some("Keep between 1 and 100 backups".to_string());
+        Task::none()
+    }
+
+    pub fn handle_create_backup(&mut self) -> Task<Message> {
+        self.backup_status = Some("Creating backup...".to_string());
+        Task::perform(persistence::create_backup(self.saved_state()), Message::BackupCreated)
+    }
+
+    pub fn handle_backup_created(&mut self, result: Result<(), String>) -> Task<Message> {
+        match result {
+            Ok(()) => {
+                self.backup_status = Some("Backup created".to_string());
+                self.list_backups()
+            }
+            Err(e) => {
+                self.backup_status = Some(format!("Backup failed: {}", e));
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_restore_backup(&mut self, path: std::path::PathBuf) -> Task<Message> {
+        // While recovering from an unreadable data file nobody can log in yet
+        let current = match self.state {
+            AppState::Recovery => None,
+            _ => {
+                if !self.session.as_ref().is_some_and(|s| s.role.can_manage_users()) {
+                    self.backup_status = Some("Only administrators can restore backups".to_string());
+                    return Task::none();
+                }
+                Some(self.saved_state())
+            }
+        };
+
+        self.restoring_backup = true;
+        self.backup_status = Some("Restoring backup...".to_string());
+        Task::perform(persistence::restore_backup(path, current), |result| {
+            Message::BackupRestored(result.map(Box::new))
+        })
+    }
+
+    pub fn handle_backup_restored(&mut self, result: Result<Box<SavedState>, String>) -> Task<Message> {
+        self.restoring_backup = false;
+        let state = match result {
+            Ok(state) => state,
+            Err(e) => {
+                self.backup_status = Some(format!("Restore failed: {}", e));
+                return Task::none();
+            }
+        };
+
+        let (user_id, username) = self
+            .session
+            .as_ref()
+            .map(|s| (s.user_id.clone(), s.username.clone()))
+            .unwrap_or_else(|| ("system".to_string(), "system".to_string()));
+        let summary = format!(
+            "Restored backup with {} items and {} notes",
+            state.items.len(),
+            state.notes.len()
+        );
+
+        // The restored user list may not contain the current user, so log in again
+        self.session = None;
+        self.backup_status = None;
+        let _ = self.handle_loaded_success(*state);
+        self.current_view = View::Inventory;
+
+        let audit_entry = AuditEntry::new(
+            user_id,
+            username,
+            AuditAction::BackupRestored,
+            "data".to_string(),
+            None,
+            summary,
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+
+    pub fn handle_start_fresh(&mut self) {
+        if let Some(path) = persistence::set_aside_unreadable_data() {
+            eprintln!("Unreadable data file moved to {}", path.display());
+        }
+        self.backup_status = None;
+        self.state = AppState::Login;
+    }
+}
@@ -5,8 +5,11 @@ use crate::messages::CalculatorOp;
 impl InventoryApp {
     pub fn handle_toggle_calculator(&mut self) {
         if let Ok(exe_path) = std::env::current_exe() {
+            // The calculator reads the open workspace, not whichever is active on disk
             let _ = std::process::Command::new(exe_path)
                 .arg("--calculator")
+                .arg("--data-dir")
+                .arg(crate::persistence::data_dir())
                 .spawn();
         }
     }
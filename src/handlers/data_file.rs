@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use iced::Task;
+use crate::{AppState, InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::merge::add_missing;
+use crate::messages::{LoadError, SavedState};
+use crate::persistence;
+
+impl InventoryApp {
+    pub fn handle_workspace_locked(&mut self, result: Result<(), String>) -> Task<Message> {
+        match result {
+            Ok(()) => {
+                self.read_only = false;
+                self.lock_owner = None;
+                Task::perform(persistence::load_state(), |result| {
+                    Message::Loaded(result.map(Box::new))
+                })
+            }
+            Err(owner) => {
+                self.lock_owner = Some(owner);
+                self.state = AppState::InUse;
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_open_read_only(&mut self) -> Task<Message> {
+        self.read_only = true;
+        self.state = AppState::Loading;
+        Task::perform(persistence::load_state(), |result| {
+            Message::Loaded(result.map(Box::new))
+        })
+    }
+
+    pub fn handle_reopen_for_editing(&mut self) -> Task<Message> {
+        Task::perform(persistence::lock_workspace(), Message::ReopenedForEditing)
+    }
+
+    pub fn handle_reopened_for_editing(&mut self, result: Result<(), String>) -> Task<Message> {
+        match result {
+            Ok(()) => {
+                self.read_only = false;
+                self.lock_owner = None;
+                // The other window may have saved since this one loaded
+                self.handle_reload_data_file()
+            }
+            Err(owner) => {
+                self.data_file_status = Some(format!("Still open in {}", owner));
+                self.lock_owner = Some(owner);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_check_data_file(&mut self) -> Task<Message> {
+        Task::perform(persistence::data_file_changed(), Message::DataFileChecked)
+    }
+
+    pub fn handle_data_file_checked(&mut self, changed: bool) {
+        if changed && !self.data_file_changed {
+            self.data_file_changed = true;
+            self.data_file_status = None;
+        }
+    }
+
+    pub fn handle_reload_data_file(&mut self) -> Task<Message> {
+        Task::perform(persistence::reload_state(), |result| {
+            Message::DataFileReloaded(result.map(Box::new))
+        })
+    }
+
+    pub fn handle_data_file_reloaded(&mut self, result: Result<Box<SavedState>, LoadError>) -> Task<Message> {
+        match result {
+            Ok(state) => {
+                self.apply_reloaded_state(*state);
+                self.data_file_status = Some("Reloaded the data from disk".to_string());
+            }
+            Err(e) => self.data_file_status = Some(format!("Could not reload the data: {}", e)),
+        }
+        Task::none()
+    }
+
+    /// Replaces the data with what is on disk, keeping the user logged in if
+    /// their account is still there
+    fn apply_reloaded_state(&mut self, mut state: SavedState) {
+        let session = self.session.take();
+        let previous_state = std::mem::replace(&mut self.state, AppState::Loading);
+        let current_view = self.current_view.clone();
+        let show_loading_screen = state.settings.show_loading_screen;
+        state.settings.show_loading_screen = false;
+
+        let _ = self.handle_loaded_success(state);
+        self.settings.show_loading_screen = show_loading_screen;
+        if let Some(session) = session
+            && self.auth_store.get_user(&session.user_id).is_some()
+        {
+            self.session = Some(session);
+            self.state = previous_state;
+            self.current_view = current_view;
+        }
+        self.filtered_items = self.search_filter.apply(&self.items);
+        self.data_file_changed = false;
+    }
+
+    pub fn handle_merge_data_file(&mut self) -> Task<Message> {
+        Task::perform(persistence::reload_state(), |result| {
+            Message::DataFileMerged(result.map(Box::new))
+        })
+    }
+
+    /// Adds the records that only exist on disk; where both sides have a
+    /// record, the one in this window is kept. Then saves the combined data.
+    pub fn handle_data_file_merged(&mut self, result: Result<Box<SavedState>, LoadError>) -> Task<Message> {
+        let theirs = match result {
+            Ok(state) => *state,
+            Err(e) => {
+                self.data_file_status = Some(format!("Could not read the changed data: {}", e));
+                return Task::none();
+            }
+        };
+
+        let added = add_missing(&mut self.items, theirs.items, |item| &item.id)
+            + add_missing(&mut self.notes, theirs.notes, |note| &note.id)
+            + add_missing(&mut self.purchase_orders, theirs.purchase_orders, |order| &order.id)
+            + add_missing(&mut self.customers, theirs.customers, |customer| &customer.id)
+            + add_missing(&mut self.sales_orders, theirs.sales_orders, |order| &order.id)
+            + add_missing(&mut self.stock_movements, theirs.stock_movements, |movement| &movement.id)
+            + add_missing(&mut self.returns, theirs.returns, |rma| &rma.id)
+            + add_missing(&mut self.stocktakes, theirs.stocktakes, |session| &session.id);
+
+        let existing_audit_ids: HashSet<String> =
+            self.audit_log.get_entries().iter().map(|e| e.id.clone()).collect();
+        for entry in theirs.audit_log.get_entries() {
+            if !existing_audit_ids.contains(&entry.id) {
+                self.audit_log.add_entry(entry.clone());
+            }
+        }
+        self.update_alerts_from_inventory();
+
+        let details = format!("Merged {} records changed outside this window", added);
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataImported,
+                "data".to_string(),
+                None,
+                details.clone(),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        // The file now holds their copy of what was not merged, so write all of ours
+        self.saved_versions = None;
+        self.data_file_changed = false;
+        self.data_file_status = Some(details);
+        self.auto_save()
+    }
+
+    /// Keeps the data in this window and lets the next save replace the file
+    pub fn handle_overwrite_data_file(&mut self) -> Task<Message> {
+        persistence::accept_data_file();
+        self.saved_versions = None;
+        self.data_file_changed = false;
+        self.data_file_status = Some("Kept the data in this window".to_string());
+        self.auto_save()
+    }
+}
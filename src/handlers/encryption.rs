@@ -0,0 +1,129 @@
+use iced::Task;
+use crate::{AppState, InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::persistence;
+
+/// Shortest passphrase accepted for encrypting the data
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+impl InventoryApp {
+    pub fn handle_unlock_passphrase_changed(&mut self, value: String) {
+        self.unlock_passphrase_input = value;
+        self.unlock_error = None;
+    }
+
+    pub fn handle_unlock(&mut self) -> Task<Message> {
+        if self.unlocking || self.unlock_passphrase_input.is_empty() {
+            return Task::none();
+        }
+        self.unlocking = true;
+        self.unlock_error = None;
+        let passphrase = std::mem::take(&mut self.unlock_passphrase_input);
+        Task::perform(persistence::unlock(passphrase), Message::Unlocked)
+    }
+
+    pub fn handle_unlocked(&mut self, result: Result<(), String>) -> Task<Message> {
+        self.unlocking = false;
+        match result {
+            Ok(()) => {
+                self.encryption_enabled = true;
+                self.state = AppState::Loading;
+                Task::perform(persistence::load_state(), |result| {
+                    Message::Loaded(result.map(Box::new))
+                })
+            }
+            Err(e) => {
+                self.unlock_error = Some(e);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_current_passphrase_changed(&mut self, value: String) {
+        self.current_passphrase_input = value;
+    }
+
+    pub fn handle_new_passphrase_changed(&mut self, value: String) {
+        self.new_passphrase_input = value;
+    }
+
+    pub fn handle_confirm_passphrase_changed(&mut self, value: String) {
+        self.confirm_passphrase_input = value;
+    }
+
+    pub fn handle_file_passphrase_changed(&mut self, value: String) {
+        self.file_passphrase_input = value;
+    }
+
+    pub fn handle_apply_passphrase(&mut self) -> Task<Message> {
+        if self.new_passphrase_input.chars().count() < MIN_PASSPHRASE_LEN {
+            self.encryption_status = Some(format!(
+                "The passphrase must be at least {} characters",
+                MIN_PASSPHRASE_LEN
+            ));
+            return Task::none();
+        }
+        if self.new_passphrase_input != self.confirm_passphrase_input {
+            self.encryption_status = Some("The passphrases do not match".to_string());
+            return Task::none();
+        }
+        let new = Some(self.new_passphrase_input.clone());
+        self.update_encryption(new)
+    }
+
+    pub fn handle_disable_encryption(&mut self) -> Task<Message> {
+        self.update_encryption(None)
+    }
+
+    fn update_encryption(&mut self, new: Option<String>) -> Task<Message> {
+        if !self.session.as_ref().is_some_and(|s| s.role.can_manage_users()) {
+            self.encryption_status = Some("Only administrators can change encryption".to_string());
+            return Task::none();
+        }
+        if self.encryption_enabled && self.current_passphrase_input.is_empty() {
+            self.encryption_status = Some("Enter the current passphrase".to_string());
+            return Task::none();
+        }
+
+        self.encryption_status = Some("Encrypting data and backups...".to_string());
+        let current = Some(self.current_passphrase_input.clone()).filter(|p| !p.is_empty());
+        Task::perform(
+            persistence::change_passphrase(self.saved_state(), current, new),
+            Message::EncryptionUpdated,
+        )
+    }
+
+    pub fn handle_encryption_updated(&mut self, result: Result<bool, String>) -> Task<Message> {
+        let enabled = match result {
+            Ok(enabled) => enabled,
+            Err(e) => {
+                self.encryption_status = Some(format!("Encryption change failed: {}", e));
+                return Task::none();
+            }
+        };
+
+        let details = match (self.encryption_enabled, enabled) {
+            (false, true) => "Enabled data encryption",
+            (true, true) => "Changed data encryption passphrase",
+            _ => "Disabled data encryption",
+        };
+        self.encryption_enabled = enabled;
+        self.encryption_status = Some(format!("{}. The data and all backups were rewritten.", details));
+        self.current_passphrase_input.clear();
+        self.new_passphrase_input.clear();
+        self.confirm_passphrase_input.clear();
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::SettingsChanged,
+                "settings".to_string(),
+                None,
+                details.to_string(),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::export::{self, ExportEntity, ExportOptions, ItemScope};
+use crate::persistence;
+
+impl InventoryApp {
+    /// Opens the export dialog, starting from a full export
+    pub fn handle_export_data(&mut self) {
+        self.export_options = ExportOptions::default();
+        self.export_selection.clear();
+        self.export_from_input.clear();
+        self.export_to_input.clear();
+        self.export_status = None;
+        self.show_export_dialog = true;
+    }
+
+    pub fn handle_export_entity_toggled(&mut self, entity: ExportEntity) {
+        self.export_options.toggle(entity);
+    }
+
+    pub fn handle_export_item_scope_changed(&mut self, scope: ItemScope) {
+        self.export_options.item_scope = scope;
+    }
+
+    pub fn handle_export_item_toggled(&mut self, item_id: String) {
+        if !self.export_selection.remove(&item_id) {
+            self.export_selection.insert(item_id);
+        }
+    }
+
+    pub fn handle_export_from_changed(&mut self, value: String) {
+        self.export_from_input = value;
+    }
+
+    pub fn handle_export_to_changed(&mut self, value: String) {
+        self.export_to_input = value;
+    }
+
+    pub fn handle_export_strip_sensitive_toggled(&mut self, strip: bool) {
+        self.export_options.strip_sensitive = strip;
+    }
+
+    pub fn handle_cancel_export(&mut self) {
+        self.show_export_dialog = false;
+        self.export_status = None;
+    }
+
+    pub fn handle_confirm_export(&mut self) -> Task<Message> {
+        // Exports can hold users, prices and the audit log, like scheduled exports
+        if !self.session.as_ref().is_some_and(|session| session.role.can_view_audit()) {
+            self.export_status = Some("Only managers and administrators can export data".to_string());
+            return Task::none();
+        }
+        let from = export::parse_optional_date(&self.export_from_input, "start");
+        let to = export::parse_optional_date(&self.export_to_input, "end");
+        let (from, to) = match (from, to) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(e), _) | (_, Err(e)) => {
+                self.export_status = Some(e);
+                return Task::none();
+            }
+        };
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            self.export_status = Some("The start date is after the end date".to_string());
+            return Task::none();
+        }
+        if self.export_options.entities.is_empty() {
+            self.export_status = Some("Choose at least one kind of data to export".to_string());
+            return Task::none();
+        }
+
+        let item_ids: Option<HashSet<String>> = match self.export_options.item_scope {
+            ItemScope::All => None,
+            ItemScope::Filtered => Some(self.filtered_items.iter().map(|item| item.id.clone()).collect()),
+            ItemScope::Selected => {
+                if self.export_options.includes(ExportEntity::Items) && self.export_selection.is_empty() {
+                    self.export_status = Some("Select the items to export".to_string());
+                    return Task::none();
+                }
+                Some(self.export_selection.clone())
+            }
+        };
+
+        self.export_options.from = from;
+        self.export_options.to = to;
+        let options = self.export_options.clone();
+        let state = export::scoped_state(self.saved_state(), &options, item_ids.as_ref());
+        let passphrase = self.file_passphrase_input.clone();
+        self.export_status = None;
+
+        Task::perform(
+            async move {
+                let mut json = serde_json::to_string_pretty(&state)
+                    .map_err(|e| format!("Could not serialize the export: {}", e))?;
+                if !passphrase.is_empty() {
+                    json = persistence::crypto::seal_with_passphrase(&json, &passphrase)
+                        .map_err(|e| format!("Could not encrypt the export: {}", e))?;
+                }
+
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+                let filename = format!("inventory_export_{}.json", timestamp);
+                let Some(file) = rfd::AsyncFileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("JSON files", &["json"])
+                    .save_file()
+                    .await
+                else {
+                    return Ok(None);
+                };
+
+                tokio::fs::write(file.path(), json)
+                    .await
+                    .map_err(|e| format!("Could not save the export: {}", e))?;
+                let mut details = format!("Exported {} to {}", options.describe(), file.file_name());
+                if !passphrase.is_empty() {
+                    details.push_str(" (encrypted)");
+                }
+                Ok(Some(details))
+            },
+            Message::DataExported,
+        )
+    }
+
+    /// Closes the dialog and records the export once the file has been written
+    pub fn handle_data_exported(&mut self, result: Result<Option<String>, String>) -> Task<Message> {
+        match result {
+            Ok(None) => Task::none(),
+            Ok(Some(details)) => {
+                self.show_export_dialog = false;
+                if let Some(session) = &self.session {
+                    let audit_entry = AuditEntry::new(
+                        session.user_id.clone(),
+                        session.username.clone(),
+                        AuditAction::DataExported,
+                        "data".to_string(),
+                        None,
+                        details,
+                    );
+                    self.audit_log.add_entry(audit_entry);
+                }
+                self.auto_save()
+            }
+            Err(e) => {
+                self.export_status = Some(e);
+                Task::none()
+            }
+        }
+    }
+}
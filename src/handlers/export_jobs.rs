@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::export::{self, ExportJob, JobFormat, JobRun};
+use crate::schedule::{self, Frequency, Schedule};
+
+impl InventoryApp {
+    /// Starts every job whose schedule has fired since it last ran
+    pub fn handle_export_jobs_tick(&mut self) -> Task<Message> {
+        let now = chrono::Local::now();
+        let due: Vec<String> = self
+            .settings
+            .export_jobs
+            .iter()
+            .filter(|job| job.is_due(now) && !self.running_export_jobs.contains(&job.id))
+            .map(|job| job.id.clone())
+            .collect();
+        Task::batch(due.into_iter().map(|job_id| self.start_export_job(job_id)))
+    }
+
+    pub fn handle_run_export_job(&mut self, job_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_view_audit() {
+            self.export_job_status = Some("Only managers and administrators can run exports".to_string());
+            return Task::none();
+        }
+        if self.running_export_jobs.contains(&job_id) {
+            return Task::none();
+        }
+        self.start_export_job(job_id)
+    }
+
+    pub(crate) fn start_export_job(&mut self, job_id: String) -> Task<Message> {
+        if self.session.is_none() {
+            return Task::none();
+        }
+        let Some(job) = self.settings.export_jobs.iter().find(|job| job.id == job_id).cloned() else {
+            return Task::none();
+        };
+        self.running_export_jobs.insert(job_id.clone());
+        let state = self.saved_state();
+
+        // Writing every format can take a while on a large inventory
+        let run = async move {
+            tokio::task::spawn_blocking(move || export::run_job(&job, state))
+                .await
+                .map_err(|e| e.to_string())?
+        };
+        Task::perform(run, move |result| Message::ExportJobFinished(job_id.clone(), result))
+    }
+
+    /// Records the run on the job and in the audit log
+    pub fn handle_export_job_finished(&mut self, job_id: String, result: Result<String, String>) -> Task<Message> {
+        self.running_export_jobs.remove(&job_id);
+        let Some(job) = self.settings.export_jobs.iter_mut().find(|job| job.id == job_id) else {
+            return Task::none();
+        };
+
+        let (ok, message) = match result {
+            Ok(summary) => (true, summary),
+            Err(e) => (false, e),
+        };
+        let details = if ok {
+            format!("Scheduled export '{}': {}", job.name, message)
+        } else {
+            format!("Scheduled export '{}' failed: {}", job.name, message)
+        };
+        job.last_run = Some(JobRun {
+            at: chrono::Utc::now().timestamp(),
+            ok,
+            message,
+        });
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "export_job".to_string(),
+                Some(job_id),
+                details,
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+
+    pub fn handle_export_job_name_changed(&mut self, value: String) {
+        self.export_job_name_input = value;
+    }
+
+    pub fn handle_export_job_format_changed(&mut self, format: JobFormat) {
+        self.export_job_format = format;
+    }
+
+    pub fn handle_export_job_frequency_changed(&mut self, frequency: Frequency) {
+        // Hourly jobs take a minute, the others a time of day
+        if (frequency == Frequency::Hourly) != (self.export_job_frequency == Frequency::Hourly) {
+            self.export_job_time_input = if frequency == Frequency::Hourly { "0" } else { "02:00" }.to_string();
+        }
+        self.export_job_frequency = frequency;
+    }
+
+    pub fn handle_export_job_time_changed(&mut self, value: String) {
+        self.export_job_time_input = value;
+    }
+
+    pub fn handle_export_job_weekday_changed(&mut self, weekday: chrono::Weekday) {
+        self.export_job_weekday = weekday;
+    }
+
+    pub fn handle_export_job_keep_changed(&mut self, value: String) {
+        self.export_job_keep_input = value;
+    }
+
+    pub fn handle_toggle_export_job_filter(&mut self) {
+        self.export_job_use_filter = !self.export_job_use_filter;
+    }
+
+    pub fn handle_toggle_export_job_strip_sensitive(&mut self) {
+        self.export_job_strip_sensitive = !self.export_job_strip_sensitive;
+    }
+
+    pub fn handle_pick_export_job_folder(&mut self) -> Task<Message> {
+        Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose the export folder")
+                    .pick_folder()
+                    .await
+                    .map(|folder| folder.path().to_path_buf())
+            },
+            Message::ExportJobFolderPicked,
+        )
+    }
+
+    pub fn handle_export_job_folder_picked(&mut self, folder: Option<PathBuf>) {
+        if folder.is_some() {
+            self.export_job_folder = folder;
+        }
+    }
+
+    pub fn handle_add_export_job(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_view_audit() {
+            self.export_job_status = Some("Only managers and administrators can schedule exports".to_string());
+            return Task::none();
+        }
+
+        let name = self.export_job_name_input.trim().to_string();
+        if name.is_empty() {
+            self.export_job_status = Some("Give the job a name".to_string());
+            return Task::none();
+        }
+        let Some(folder) = self.export_job_folder.clone() else {
+            self.export_job_status = Some("Choose the folder to export to".to_string());
+            return Task::none();
+        };
+        let (hour, minute) = match schedule::parse_when(self.export_job_frequency, &self.export_job_time_input) {
+            Ok(time) => time,
+            Err(e) => {
+                self.export_job_status = Some(e);
+                return Task::none();
+            }
+        };
+        let keep = match self.export_job_keep_input.trim().parse::<u32>() {
+            Ok(keep) if keep > 0 => keep,
+            _ => {
+                self.export_job_status = Some("Keep must be a whole number of files, at least 1".to_string());
+                return Task::none();
+            }
+        };
+
+        let job = ExportJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            format: self.export_job_format,
+            folder,
+            schedule: Schedule {
+                frequency: self.export_job_frequency,
+                hour,
+                minute,
+                weekday: self.export_job_weekday,
+            },
+            keep,
+            filter: (self.export_job_format.is_item_list() && self.export_job_use_filter)
+                .then(|| self.search_filter.clone()),
+            strip_sensitive: self.export_job_format == JobFormat::Json && self.export_job_strip_sensitive,
+            created_at: chrono::Utc::now().timestamp(),
+            last_run: None,
+        };
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "export_job".to_string(),
+            Some(job.id.clone()),
+            format!(
+                "Scheduled export '{}': {} to {}, {}",
+                job.name,
+                job.format,
+                job.folder.display(),
+                job.schedule
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.settings.export_jobs.push(job);
+        self.export_job_name_input.clear();
+        self.export_job_status = None;
+        self.auto_save()
+    }
+
+    pub fn handle_remove_export_job(&mut self, job_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_view_audit() {
+            return Task::none();
+        }
+        let Some(index) = self.settings.export_jobs.iter().position(|job| job.id == job_id) else {
+            return Task::none();
+        };
+        let job = self.settings.export_jobs.remove(index);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "export_job".to_string(),
+            Some(job.id),
+            format!("Removed scheduled export '{}'", job.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+}
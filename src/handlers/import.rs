@@ -0,0 +1,105 @@
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::import::{self, ImportField, ImportMode, ImportSummary, ImportTable};
+use crate::messages::View;
+
+impl InventoryApp {
+    pub fn handle_import_items_file(&mut self) -> Task<Message> {
+        if !self.session.as_ref().is_some_and(|s| s.role.can_edit()) {
+            return Task::none();
+        }
+        self.current_view = View::Import;
+        Task::perform(
+            async {
+                let file = rfd::AsyncFileDialog::new()
+                    .set_title("Import items")
+                    .add_filter("Spreadsheet or text", &["csv", "tsv", "txt", "xlsx", "xlsm", "xls", "ods"])
+                    .add_filter("Excel or OpenDocument", &import::SPREADSHEET_EXTENSIONS)
+                    .add_filter("CSV or TSV", &["csv", "tsv", "txt"])
+                    .pick_file()
+                    .await?;
+                let file_name = file.file_name();
+                let bytes = file.read().await;
+                Some(ImportTable::read_file(file_name, &bytes))
+            },
+            Message::ItemsFileRead,
+        )
+    }
+
+    pub fn handle_items_file_read(&mut self, result: Option<Result<ImportTable, String>>) {
+        match result {
+            None => {}
+            Some(Ok(table)) => {
+                self.import_mapping = import::guess_mapping(&table.headers);
+                self.import_table = Some(table);
+                self.import_status = None;
+                self.refresh_import_preview();
+            }
+            Some(Err(e)) => self.import_status = Some(e),
+        }
+    }
+
+    pub fn handle_import_column_mapped(&mut self, column: usize, field: ImportField) {
+        // A field comes from one column; the column it was on is cleared
+        if field != ImportField::Ignore {
+            for mapped in self.import_mapping.iter_mut().filter(|mapped| **mapped == field) {
+                *mapped = ImportField::Ignore;
+            }
+        }
+        if let Some(mapped) = self.import_mapping.get_mut(column) {
+            *mapped = field;
+        }
+        self.refresh_import_preview();
+    }
+
+    pub fn handle_import_mode_changed(&mut self, mode: ImportMode) {
+        self.import_mode = mode;
+        self.refresh_import_preview();
+    }
+
+    fn refresh_import_preview(&mut self) {
+        self.import_preview = match &self.import_table {
+            Some(table) => import::preview(table, &self.import_mapping, self.import_mode, &self.items),
+            None => Vec::new(),
+        };
+    }
+
+    pub fn handle_confirm_items_import(&mut self) -> Task<Message> {
+        let Some(session) = self.session.as_ref().filter(|s| s.role.can_edit()) else {
+            self.import_status = Some("You don't have permission to import items".to_string());
+            return Task::none();
+        };
+        let Some(table) = self.import_table.take() else {
+            return Task::none();
+        };
+
+        // Checked again in case the items changed since the preview was made
+        let rows = import::preview(&table, &self.import_mapping, self.import_mode, &self.items);
+        let summary: ImportSummary = import::apply(&rows, &mut self.items, &self.settings.default_category);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::DataImported,
+            "inventory".to_string(),
+            None,
+            format!("Imported items from {}: {}", table.file_name, summary),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.import_status = Some(format!("Imported {}: {}", table.file_name, summary));
+        self.import_preview.clear();
+        self.import_mapping.clear();
+        self.update_alerts_from_inventory();
+        self.auto_save()
+    }
+
+    pub fn handle_cancel_items_import(&mut self) {
+        self.import_table = None;
+        self.import_preview.clear();
+        self.import_mapping.clear();
+        self.import_status = None;
+        self.current_view = View::Inventory;
+    }
+}
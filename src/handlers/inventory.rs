@@ -4,6 +4,8 @@ use crate::messages::ItemDialogMode;
 use crate::inventory::InventoryItem;
 use crate::audit::{AuditAction, AuditEntry};
 use crate::currency;
+use crate::export::inventory_csv;
+use crate::spreadsheet::{self, SheetFormat};
 
 impl InventoryApp {
     pub fn handle_open_add_dialog(&mut self) {
@@ -28,9 +30,13 @@ impl InventoryApp {
                     self.sku_input = item.sku.clone();
                     self.category_input = item.category.clone();
                     self.supplier_input = item.supplier.clone();
+                    self.location_input = item.location.clone();
                     self.description_input = item.description.clone();
                     self.quantity_input = item.quantity.to_string();
                     self.price_input = item.price.to_string();
+                    self.reorder_point_input = count_input(item.reorder_point);
+                    self.reorder_quantity_input = count_input(item.reorder_quantity);
+                    self.lead_time_input = count_input(item.lead_time_days);
                 }
             }
         }
@@ -68,6 +74,11 @@ impl InventoryApp {
         self.item_validation_error = None;
     }
 
+    pub fn handle_location_changed(&mut self, value: String) {
+        self.location_input = value;
+        self.item_validation_error = None;
+    }
+
     pub fn handle_description_changed(&mut self, value: String) {
         self.description_input = value;
         self.item_validation_error = None;
@@ -83,6 +94,21 @@ impl InventoryApp {
         self.item_validation_error = None;
     }
 
+    pub fn handle_reorder_point_changed(&mut self, value: String) {
+        self.reorder_point_input = value;
+        self.item_validation_error = None;
+    }
+
+    pub fn handle_reorder_quantity_changed(&mut self, value: String) {
+        self.reorder_quantity_input = value;
+        self.item_validation_error = None;
+    }
+
+    pub fn handle_lead_time_changed(&mut self, value: String) {
+        self.lead_time_input = value;
+        self.item_validation_error = None;
+    }
+
     pub fn handle_submit_item(&mut self) -> Task<Message> {
         use crate::errors::*;
         
@@ -132,10 +158,34 @@ impl InventoryApp {
             }
         };
         
+        // Validate reorder settings (optional)
+        let reorder_point = match validate_optional_count("Reorder point", &self.reorder_point_input) {
+            Ok(v) => v,
+            Err(e) => {
+                self.item_validation_error = Some(e.to_string());
+                return Task::none();
+            }
+        };
+        let reorder_quantity =
+            match validate_optional_count("Reorder quantity", &self.reorder_quantity_input) {
+                Ok(v) => v,
+                Err(e) => {
+                    self.item_validation_error = Some(e.to_string());
+                    return Task::none();
+                }
+            };
+        let lead_time_days = match validate_optional_count("Lead time", &self.lead_time_input) {
+            Ok(v) => v,
+            Err(e) => {
+                self.item_validation_error = Some(e.to_string());
+                return Task::none();
+            }
+        };
+        
         // All validations passed
         match &self.item_dialog_mode {
             Some(ItemDialogMode::Add) => {
-                let new_item = InventoryItem::new(
+                let mut new_item = InventoryItem::new(
                     self.name_input.clone(),
                     self.sku_input.clone(),
                     self.category_input.clone(),
@@ -144,6 +194,10 @@ impl InventoryApp {
                     quantity,
                     price,
                 );
+                new_item.reorder_point = reorder_point;
+                new_item.reorder_quantity = reorder_quantity;
+                new_item.lead_time_days = lead_time_days;
+                new_item.location = self.location_input.trim().to_string();
                 
                 // Log item creation
                 if let Some(session) = &self.session {
@@ -179,6 +233,10 @@ impl InventoryApp {
                     item.description = self.description_input.clone();
                     item.quantity = quantity;
                     item.price = price;
+                    item.reorder_point = reorder_point;
+                    item.reorder_quantity = reorder_quantity;
+                    item.lead_time_days = lead_time_days;
+                    item.location = self.location_input.trim().to_string();
                     item.update_timestamp();
                     
                     let new_values = format!(
@@ -253,6 +311,7 @@ impl InventoryApp {
     pub fn handle_export_inventory_csv(&mut self) -> Task<Message> {
         let items = self.filtered_items.clone();
         let currency_code = self.settings.preferred_currency.clone();
+        let columns = self.settings.computed_columns.clone();
 
         if let Some(session) = &self.session {
             let audit_entry = AuditEntry::new(
@@ -279,39 +338,7 @@ impl InventoryApp {
                     return;
                 };
 
-                let mut csv = String::new();
-                let header = format!(
-                    "Name,SKU,Category,Supplier,Description,Quantity,Price ({currency}),Total Value ({currency}),Created At,Updated At\n",
-                    currency = currency_code
-                );
-                csv.push_str(&header);
-
-                for item in items {
-                    let price = currency::format_amount(item.price, &currency_code);
-                    let total = currency::format_amount(item.total_value(), &currency_code);
-                    let created_at = chrono::DateTime::from_timestamp(item.created_at, 0)
-                        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                        .unwrap_or_else(|| "Unknown".to_string());
-                    let updated_at = chrono::DateTime::from_timestamp(item.updated_at, 0)
-                        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                        .unwrap_or_else(|| "Unknown".to_string());
-
-                    let row = format!(
-                        "{},{},{},{},{},{},{},{},{},{}\n",
-                        csv_escape(&item.name),
-                        csv_escape(&item.sku),
-                        csv_escape(&item.category),
-                        csv_escape(&item.supplier),
-                        csv_escape(&item.description),
-                        item.quantity,
-                        csv_escape(&price),
-                        csv_escape(&total),
-                        csv_escape(&created_at),
-                        csv_escape(&updated_at),
-                    );
-                    csv.push_str(&row);
-                }
-
+                let csv = inventory_csv(&items, &currency_code, &columns);
                 std::fs::write(file_path, csv).ok();
             },
             |_| Message::Save,
@@ -319,12 +346,68 @@ impl InventoryApp {
 
         Task::batch(vec![self.auto_save(), task])
     }
+
+    pub fn handle_export_inventory_spreadsheet(&mut self) -> Task<Message> {
+        let items = self.filtered_items.clone();
+        let currency_code = self.settings.preferred_currency.clone();
+
+        Task::perform(
+            async move {
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+                let filename = format!("inventory_list_{}.xlsx", timestamp);
+                let file_path = rfd::FileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("Excel workbook", &["xlsx"])
+                    .add_filter("OpenDocument spreadsheet", &["ods"])
+                    .save_file();
+
+                let Some(file_path) = file_path else {
+                    return Ok(None);
+                };
+
+                let format = SheetFormat::from_file_name(&file_path.to_string_lossy());
+                let bytes = spreadsheet::inventory_workbook(&items, &currency_code, format)?;
+                std::fs::write(file_path, bytes).map_err(|e| e.to_string())?;
+                Ok(Some((format, items.len())))
+            },
+            Message::InventorySpreadsheetExported,
+        )
+    }
+
+    /// Logs a written spreadsheet; nothing is logged when the save dialog was
+    /// cancelled or the workbook could not be built or written
+    pub fn handle_inventory_spreadsheet_exported(
+        &mut self,
+        result: Result<Option<(SheetFormat, usize)>, String>,
+    ) -> Task<Message> {
+        let (format, count) = match result {
+            Ok(Some(exported)) => exported,
+            Ok(None) => return Task::none(),
+            Err(e) => {
+                self.inventory_export_error = Some(format!("Could not export the spreadsheet: {}", e));
+                return Task::none();
+            }
+        };
+        self.inventory_export_error = None;
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "inventory".to_string(),
+                None,
+                format!("Exported inventory list ({} items) to {}", count, format),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
 }
 
-fn csv_escape(value: &str) -> String {
-    if value.contains(',') || value.contains('"') || value.contains('\n') || value.contains('\r') {
-        let escaped = value.replace('"', "\"\"");
-        format!("\"{}\"", escaped)
+fn count_input(value: u32) -> String {
+    if value == 0 {
+        String::new()
     } else {
         value.to_string()
     }
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use iced::Task;
+use iced::widget::text_editor;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::merge::{add_missing, MergeOption, RecordKind, Resolution};
+use crate::messages::View;
+
+impl InventoryApp {
+    pub fn handle_merge_resolution_changed(&mut self, kind: RecordKind, index: usize, resolution: Resolution) {
+        if let Some(plan) = &mut self.merge_plan {
+            plan.set_resolution(kind, index, resolution);
+        }
+    }
+
+    pub fn handle_resolve_all_conflicts(&mut self, resolution: Resolution) {
+        if let Some(plan) = &mut self.merge_plan {
+            plan.resolve_all(resolution);
+        }
+    }
+
+    pub fn handle_merge_option_toggled(&mut self, option: MergeOption) {
+        if let Some(plan) = &mut self.merge_plan {
+            plan.options.toggle(option);
+        }
+    }
+
+    /// Leaves the preview, dropping the plan if it was not applied
+    pub fn handle_close_merge(&mut self) {
+        self.merge_plan = None;
+        self.merge_status = None;
+        self.current_view = View::Settings;
+    }
+
+    pub fn handle_confirm_merge(&mut self) -> Task<Message> {
+        let Some(mut plan) = self.merge_plan.take() else {
+            return Task::none();
+        };
+
+        let mut summary = plan.apply_records(&mut self.items, &mut self.notes);
+        self.filtered_items = self.search_filter.apply(&self.items);
+
+        let theirs = plan.state;
+        summary.other = add_missing(&mut self.purchase_orders, theirs.purchase_orders, |order| &order.id)
+            + add_missing(&mut self.customers, theirs.customers, |customer| &customer.id)
+            + add_missing(&mut self.sales_orders, theirs.sales_orders, |order| &order.id)
+            + add_missing(&mut self.stock_movements, theirs.stock_movements, |movement| &movement.id)
+            + add_missing(&mut self.returns, theirs.returns, |rma| &rma.id)
+            + add_missing(&mut self.stocktakes, theirs.stocktakes, |session| &session.id);
+
+        let mut extras = Vec::new();
+        if plan.options.settings {
+            // The storage backend and integrations belong to this installation, not to the data
+            let storage_backend = self.settings.storage_backend;
+            let api = std::mem::take(&mut self.settings.api);
+            let webhooks = std::mem::take(&mut self.settings.webhooks);
+            *self.settings = theirs.settings;
+            self.settings.storage_backend = storage_backend;
+            self.settings.api = api;
+            self.settings.webhooks = webhooks;
+            self.settings_interval_input = self.settings.auto_save_interval.to_string();
+            self.settings_category_input = self.settings.default_category.clone();
+            self.settings_notification_throttle_input = self.settings.notification_throttle_seconds.to_string();
+            if let Some(pos) = theirs.calculator_position {
+                self.calculator.set_position(pos.0, pos.1);
+            }
+            extras.push("settings");
+        }
+        if plan.options.alert_settings {
+            *self.alert_manager.settings_mut() = theirs.alert_manager.settings().clone();
+            extras.push("alert settings");
+        }
+        if plan.options.audit {
+            let existing: HashSet<String> = self.audit_log.get_entries().iter().map(|e| e.id.clone()).collect();
+            for entry in theirs.audit_log.get_entries() {
+                if !existing.contains(&entry.id) {
+                    self.audit_log.add_entry(entry.clone());
+                }
+            }
+            extras.push("audit entries");
+        }
+
+        // Users are never imported, but the default admin must exist
+        self.auth_store.ensure_default_admin();
+        self.update_alerts_from_inventory();
+        self.refresh_note_selection();
+
+        let mut details = format!("Imported data from {}: {}", plan.file_name, summary);
+        if !extras.is_empty() {
+            details.push_str(&format!("; also imported {}", extras.join(", ")));
+        }
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataImported,
+                "data".to_string(),
+                None,
+                details.clone(),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+
+        self.merge_status = Some(details);
+        self.auto_save()
+    }
+
+    /// Keeps the open note if it still exists, otherwise opens the first one
+    fn refresh_note_selection(&mut self) {
+        let selected = self
+            .selected_note_id
+            .as_ref()
+            .and_then(|id| self.notes.iter().find(|note| note.id == *id))
+            .or_else(|| self.notes.first())
+            .cloned();
+        match selected {
+            Some(note) => {
+                self.selected_note_id = Some(note.id);
+                self.note_title_input = note.title;
+                self.editor_content = text_editor::Content::with_text(&note.content);
+            }
+            None => {
+                self.selected_note_id = None;
+                self.note_title_input.clear();
+                self.editor_content = text_editor::Content::new();
+            }
+        }
+    }
+}
@@ -7,4 +7,22 @@ pub mod search;
 pub mod alerts;
 pub mod users;
 pub mod audit;
-
+pub mod reorder;
+pub mod returns;
+pub mod sales;
+pub mod stocktake;
+pub mod analysis;
+pub mod backups;
+pub mod encryption;
+pub mod workspaces;
+pub mod data_file;
+pub mod saving;
+pub mod import;
+pub mod merge;
+pub mod reports;
+pub mod export;
+pub mod export_jobs;
+pub mod api;
+pub mod webhooks;
+pub mod rules;
+pub mod scripting;
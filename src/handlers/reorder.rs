@@ -0,0 +1,211 @@
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::export::csv_escape;
+use crate::purchasing::{self, PurchaseOrder, PurchaseOrderLine};
+use crate::reorder::{self, ReorderSuggestion};
+
+impl InventoryApp {
+    /// Current reorder worksheet, built from active alerts and item reorder settings
+    pub fn reorder_worksheet(&self) -> Vec<ReorderSuggestion> {
+        reorder::build_worksheet(
+            self.alert_manager.get_active_alerts(),
+            &self.items,
+            self.alert_manager.settings(),
+        )
+    }
+
+    /// Quantity to order for a worksheet line, honouring any value typed by the user
+    pub fn reorder_line_quantity(&self, suggestion: &ReorderSuggestion) -> u32 {
+        self.reorder_quantity_inputs
+            .get(&suggestion.item_id)
+            .and_then(|value| value.trim().parse::<u32>().ok())
+            .unwrap_or(suggestion.suggested_quantity)
+    }
+
+    pub fn handle_toggle_reorder_line(&mut self, item_id: String) {
+        if !self.reorder_selection.remove(&item_id) {
+            self.reorder_selection.insert(item_id);
+        }
+        self.reorder_status = None;
+    }
+
+    pub fn handle_reorder_line_quantity_changed(&mut self, item_id: String, value: String) {
+        if value.is_empty() || value.chars().all(|c| c.is_ascii_digit()) {
+            self.reorder_quantity_inputs.insert(item_id, value);
+        }
+        self.reorder_status = None;
+    }
+
+    pub fn handle_select_all_reorder_lines(&mut self) {
+        self.reorder_selection = self
+            .reorder_worksheet()
+            .into_iter()
+            .map(|suggestion| suggestion.item_id)
+            .collect();
+        self.reorder_status = None;
+    }
+
+    pub fn handle_clear_reorder_selection(&mut self) {
+        self.reorder_selection.clear();
+        self.reorder_status = None;
+    }
+
+    /// Selected worksheet lines with a non-zero order quantity, grouped by supplier
+    fn selected_reorder_lines(&self) -> Vec<(String, Vec<PurchaseOrderLine>)> {
+        let worksheet = self.reorder_worksheet();
+        let selected: Vec<ReorderSuggestion> = worksheet
+            .into_iter()
+            .filter(|s| self.reorder_selection.contains(&s.item_id))
+            .collect();
+
+        reorder::group_by_supplier(&selected)
+            .into_iter()
+            .map(|(supplier, suggestions)| {
+                let lines = suggestions
+                    .into_iter()
+                    .map(|s| PurchaseOrderLine {
+                        item_id: s.item_id.clone(),
+                        item_name: s.item_name.clone(),
+                        item_sku: s.item_sku.clone(),
+                        quantity: self.reorder_line_quantity(s),
+                        unit_price: s.unit_price,
+                    })
+                    .filter(|line| line.quantity > 0)
+                    .collect::<Vec<_>>();
+                (supplier, lines)
+            })
+            .filter(|(_, lines)| !lines.is_empty())
+            .collect()
+    }
+
+    pub fn handle_create_draft_purchase_orders(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_create() {
+            self.reorder_status = Some("You do not have permission to create purchase orders".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let groups = self.selected_reorder_lines();
+        if groups.is_empty() {
+            self.reorder_status = Some("Select at least one line to order".to_string());
+            return Task::none();
+        }
+
+        let mut created = Vec::new();
+        for (supplier, lines) in groups {
+            let number = purchasing::next_order_number(&self.purchase_orders);
+            let order = PurchaseOrder::new_draft(number, supplier, lines, username.clone());
+
+            let audit_entry = AuditEntry::new(
+                user_id.clone(),
+                username.clone(),
+                AuditAction::PurchaseOrderCreated,
+                "purchase_order".to_string(),
+                Some(order.id.clone()),
+                format!(
+                    "Created draft {} for {} ({} lines)",
+                    order.number,
+                    reorder::supplier_label(&order.supplier),
+                    order.lines.len()
+                ),
+            );
+            self.audit_log.add_entry(audit_entry);
+
+            created.push(order.number.clone());
+            self.purchase_orders.push(order);
+        }
+
+        self.reorder_selection.clear();
+        self.reorder_quantity_inputs.clear();
+        self.reorder_status = Some(format!("Created draft purchase orders: {}", created.join(", ")));
+        self.auto_save()
+    }
+
+    pub fn handle_export_reorder_csv(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_create() {
+            self.reorder_status = Some("You do not have permission to export purchase orders".to_string());
+            return Task::none();
+        }
+        let groups = self.selected_reorder_lines();
+        if groups.is_empty() {
+            self.reorder_status = Some("Select at least one line to export".to_string());
+            return Task::none();
+        }
+
+        Task::perform(
+            async move {
+                let Some(folder) = rfd::FileDialog::new().pick_folder() else {
+                    return Ok(None);
+                };
+                let suppliers = groups.len();
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+
+                for (supplier, lines) in groups {
+                    let mut csv = String::from("Item,SKU,Quantity,Unit Price,Line Total\n");
+                    for line in &lines {
+                        csv.push_str(&format!(
+                            "{},{},{},{:.2},{:.2}\n",
+                            csv_escape(&line.item_name),
+                            csv_escape(&line.item_sku),
+                            line.quantity,
+                            line.unit_price,
+                            line.total(),
+                        ));
+                    }
+
+                    let filename = format!(
+                        "reorder_{}_{}.csv",
+                        file_safe_name(reorder::supplier_label(&supplier)),
+                        timestamp
+                    );
+                    std::fs::write(folder.join(&filename), csv)
+                        .map_err(|e| format!("Could not write {}: {}", filename, e))?;
+                }
+                Ok(Some(suppliers))
+            },
+            Message::ReorderCsvExported,
+        )
+    }
+
+    /// Logs a finished worksheet export; nothing is logged when the folder
+    /// picker was cancelled or a file could not be written
+    pub fn handle_reorder_csv_exported(&mut self, result: Result<Option<usize>, String>) -> Task<Message> {
+        let suppliers = match result {
+            Ok(Some(suppliers)) => suppliers,
+            Ok(None) => return Task::none(),
+            Err(e) => {
+                self.reorder_status = Some(format!("Export failed: {}", e));
+                return Task::none();
+            }
+        };
+        self.reorder_status = Some(format!("Exported {} supplier files", suppliers));
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "reorder".to_string(),
+                None,
+                format!("Exported reorder worksheet for {} suppliers to CSV", suppliers),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+}
+
+fn file_safe_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::reports::{self, ReportContext, ReportData, ReportKind};
+
+impl InventoryApp {
+    pub fn handle_generate_report(&mut self, kind: ReportKind) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+
+        let data = match kind {
+            ReportKind::Valuation => ReportData::Valuation(self.items.to_vec()),
+            ReportKind::StockList => ReportData::StockList {
+                items: self.filtered_items.clone(),
+                filter: self.search_filter.describe(),
+            },
+            ReportKind::LowStock => ReportData::LowStock {
+                alerts: self.alert_manager.get_active_alerts().to_vec(),
+                items: self.items.to_vec(),
+            },
+            ReportKind::AuditTrail => {
+                if !session.role.can_view_audit() {
+                    self.report_status = Some("You don't have permission to view the audit log".to_string());
+                    return Task::none();
+                }
+                let (from, to) = match self.report_date_range() {
+                    Ok(range) => range,
+                    Err(e) => {
+                        self.report_status = Some(e);
+                        return Task::none();
+                    }
+                };
+                let start = from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp();
+                let end = to.and_hms_opt(23, 59, 59).unwrap_or_default().and_utc().timestamp();
+                ReportData::AuditTrail {
+                    entries: self.audit_log.filter_by_date_range(start, end).into_iter().cloned().collect(),
+                    from,
+                    to,
+                }
+            }
+        };
+
+        let context = ReportContext {
+            settings: self.settings.reports.clone(),
+            generated_at: chrono::Local::now(),
+            generated_by: session.username.clone(),
+            currency: self.settings.preferred_currency.clone(),
+        };
+        self.report_status = None;
+
+        Task::perform(
+            async move {
+                let timestamp = context.generated_at.format("%Y%m%d_%H%M%S");
+                let filename = format!("{}_{}.pdf", kind.file_stem(), timestamp);
+                let Some(file) = rfd::AsyncFileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("PDF", &["pdf"])
+                    .save_file()
+                    .await
+                else {
+                    return Ok(None);
+                };
+
+                let bytes = reports::render(&context, &data)?;
+                tokio::fs::write(file.path(), bytes)
+                    .await
+                    .map_err(|e| format!("Could not save the report: {}", e))?;
+                Ok(Some(kind))
+            },
+            Message::ReportSaved,
+        )
+    }
+
+    fn report_date_range(&self) -> Result<(NaiveDate, NaiveDate), String> {
+        let parse = |value: &str, name: &str| {
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("Enter the {} date as YYYY-MM-DD", name))
+        };
+        let from = parse(&self.report_from_input, "start")?;
+        let to = parse(&self.report_to_input, "end")?;
+        if from > to {
+            return Err("The start date is after the end date".to_string());
+        }
+        Ok((from, to))
+    }
+
+    pub fn handle_report_saved(&mut self, result: Result<Option<ReportKind>, String>) -> Task<Message> {
+        match result {
+            Ok(None) => Task::none(),
+            Ok(Some(kind)) => {
+                self.report_status = Some(format!("{} saved", kind));
+                if let Some(session) = &self.session {
+                    let audit_entry = AuditEntry::new(
+                        session.user_id.clone(),
+                        session.username.clone(),
+                        AuditAction::DataExported,
+                        "report".to_string(),
+                        None,
+                        format!("Generated report: {}", kind),
+                    );
+                    self.audit_log.add_entry(audit_entry);
+                }
+                self.auto_save()
+            }
+            Err(e) => {
+                self.report_status = Some(e);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_report_from_changed(&mut self, value: String) {
+        self.report_from_input = value;
+    }
+
+    pub fn handle_report_to_changed(&mut self, value: String) {
+        self.report_to_input = value;
+    }
+
+    pub fn handle_report_company_name_changed(&mut self, value: String) -> Task<Message> {
+        self.settings.reports.company_name = value;
+        self.auto_save()
+    }
+
+    pub fn handle_choose_report_logo(&mut self) -> Task<Message> {
+        Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a logo")
+                    .add_filter("Image", &["png", "jpg", "jpeg"])
+                    .pick_file()
+                    .await
+                    .map(|file| file.path().to_path_buf())
+            },
+            Message::ReportLogoChosen,
+        )
+    }
+
+    pub fn handle_report_logo_chosen(&mut self, path: Option<PathBuf>) -> Task<Message> {
+        let Some(path) = path else {
+            return Task::none();
+        };
+        self.settings.reports.logo_path = Some(path);
+        self.auto_save()
+    }
+
+    pub fn handle_clear_report_logo(&mut self) -> Task<Message> {
+        self.settings.reports.logo_path = None;
+        self.auto_save()
+    }
+}
@@ -0,0 +1,414 @@
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::export::csv_escape;
+use crate::returns::{self, Disposition, ReturnAuthorization, ReturnLine, ReturnReason};
+use crate::sales::SalesOrderStatus;
+use crate::stock::StockMovement;
+
+impl InventoryApp {
+    pub fn handle_return_sales_order_selected(&mut self, order_id: String) {
+        self.return_draft_lines.clear();
+        self.return_line_item_id = None;
+        self.returns_error = None;
+
+        if order_id.is_empty() {
+            self.return_sales_order_id = None;
+            return;
+        }
+        if let Some(order) = self.sales_orders.iter().find(|o| o.id == order_id) {
+            self.return_customer_input = order.customer_name.clone();
+        }
+        self.return_sales_order_id = Some(order_id);
+    }
+
+    pub fn handle_return_customer_changed(&mut self, value: String) {
+        self.return_customer_input = value;
+        self.returns_error = None;
+    }
+
+    pub fn handle_return_reason_selected(&mut self, reason: ReturnReason) {
+        self.return_reason = Some(reason);
+        self.returns_error = None;
+    }
+
+    pub fn handle_return_notes_changed(&mut self, value: String) {
+        self.return_notes_input = value;
+    }
+
+    pub fn handle_return_line_item_selected(&mut self, item_id: String) {
+        self.return_line_item_id = Some(item_id);
+        self.returns_error = None;
+    }
+
+    pub fn handle_return_line_quantity_changed(&mut self, value: String) {
+        self.return_line_quantity_input = value;
+        self.returns_error = None;
+    }
+
+    pub fn handle_return_line_disposition_selected(&mut self, disposition: Disposition) {
+        self.return_line_disposition = Some(disposition);
+        self.returns_error = None;
+    }
+
+    pub fn handle_add_return_line(&mut self) {
+        let Some(item_id) = self.return_line_item_id.clone() else {
+            self.returns_error = Some("Select an item to return".to_string());
+            return;
+        };
+        let Some(disposition) = self.return_line_disposition else {
+            self.returns_error = Some("Select a disposition".to_string());
+            return;
+        };
+        let quantity = match crate::errors::validate_quantity(&self.return_line_quantity_input) {
+            Ok(0) => {
+                self.returns_error = Some("Quantity must be at least 1".to_string());
+                return;
+            }
+            Ok(q) => q,
+            Err(e) => {
+                self.returns_error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let line = if let Some(order_id) = &self.return_sales_order_id {
+            // Returns against an order are limited to what was shipped and not yet returned
+            let Some(order) = self.sales_orders.iter().find(|o| o.id == *order_id) else {
+                self.returns_error = Some("The selected sales order no longer exists".to_string());
+                return;
+            };
+            if order.status != SalesOrderStatus::Fulfilled {
+                self.returns_error = Some(format!("{} has not been shipped yet", order.number));
+                return;
+            }
+            let Some(order_line) = order.lines.iter().find(|l| l.item_id == item_id) else {
+                self.returns_error = Some("Item is not on the selected sales order".to_string());
+                return;
+            };
+            let drafted: u32 = self
+                .return_draft_lines
+                .iter()
+                .filter(|l| l.item_id == item_id)
+                .map(|l| l.quantity)
+                .sum();
+            let remaining = order_line
+                .quantity
+                .saturating_sub(returns::returned_quantity(&self.returns, order_id, &item_id))
+                .saturating_sub(drafted);
+            if quantity > remaining {
+                self.returns_error = Some(format!(
+                    "Only {} of {} can still be returned on this order",
+                    remaining, order_line.item_name
+                ));
+                return;
+            }
+            ReturnLine {
+                item_id: order_line.item_id.clone(),
+                item_name: order_line.item_name.clone(),
+                item_sku: order_line.item_sku.clone(),
+                quantity,
+                unit_price: order_line.unit_price,
+                disposition,
+            }
+        } else {
+            let Some(item) = self.items.iter().find(|i| i.id == item_id) else {
+                self.returns_error = Some("Selected item no longer exists".to_string());
+                return;
+            };
+            ReturnLine {
+                item_id: item.id.clone(),
+                item_name: item.name.clone(),
+                item_sku: item.sku.clone(),
+                quantity,
+                unit_price: item.price,
+                disposition,
+            }
+        };
+
+        self.return_draft_lines.push(line);
+        self.return_line_quantity_input.clear();
+        self.returns_error = None;
+    }
+
+    pub fn handle_remove_return_line(&mut self, index: usize) {
+        if index < self.return_draft_lines.len() {
+            self.return_draft_lines.remove(index);
+        }
+    }
+
+    pub fn handle_create_return(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.returns_error = Some("You do not have permission to record returns".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let Some(reason) = self.return_reason else {
+            self.returns_error = Some("Select a return reason".to_string());
+            return Task::none();
+        };
+        if let Err(e) = crate::errors::validate_required("Customer", &self.return_customer_input) {
+            self.returns_error = Some(e.to_string());
+            return Task::none();
+        }
+        if self.return_draft_lines.is_empty() {
+            self.returns_error = Some("Add at least one line".to_string());
+            return Task::none();
+        }
+        if let Some(order) = self
+            .return_sales_order_id
+            .as_ref()
+            .and_then(|id| self.sales_orders.iter().find(|o| o.id == *id))
+            && order.status != SalesOrderStatus::Fulfilled
+        {
+            self.returns_error = Some(format!("{} has not been shipped yet", order.number));
+            return Task::none();
+        }
+        if let Some(line) = self
+            .return_draft_lines
+            .iter()
+            .find(|l| !self.items.iter().any(|i| i.id == l.item_id))
+        {
+            self.returns_error = Some(format!("{} no longer exists in inventory", line.item_name));
+            return Task::none();
+        }
+
+        let mut rma = ReturnAuthorization::new(
+            returns::next_return_number(&self.returns),
+            self.return_customer_input.trim().to_string(),
+            reason,
+            self.return_notes_input.trim().to_string(),
+            std::mem::take(&mut self.return_draft_lines),
+            username.clone(),
+        );
+        if let Some(order) = self
+            .return_sales_order_id
+            .as_ref()
+            .and_then(|id| self.sales_orders.iter().find(|o| o.id == *id))
+        {
+            rma.sales_order_id = Some(order.id.clone());
+            rma.sales_order_number = Some(order.number.clone());
+        }
+
+        let audit_entry = AuditEntry::new(
+            user_id.clone(),
+            username.clone(),
+            AuditAction::ReturnCreated,
+            "return".to_string(),
+            Some(rma.id.clone()),
+            format!(
+                "Created {} for {} ({}, {} units{})",
+                rma.number,
+                rma.customer_name,
+                rma.reason,
+                rma.total_units(),
+                rma.sales_order_number
+                    .as_ref()
+                    .map(|n| format!(", order {}", n))
+                    .unwrap_or_default()
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        // Post one stock movement and audit entry per line disposition
+        for line in &rma.lines {
+            let Some(item) = self.items.iter_mut().find(|i| i.id == line.item_id) else {
+                continue;
+            };
+            let kind = line.disposition.movement_kind();
+            let old_quantity = item.quantity;
+            if kind.affects_on_hand() {
+                item.quantity += line.quantity;
+                item.update_timestamp();
+            }
+            self.stock_movements.push(StockMovement::new(
+                item,
+                kind,
+                line.quantity as i64,
+                rma.number.clone(),
+                username.clone(),
+            ));
+
+            let action = match line.disposition {
+                Disposition::Restock => AuditAction::ReturnRestocked,
+                Disposition::Quarantine => AuditAction::ReturnQuarantined,
+                Disposition::ReturnToVendor => AuditAction::ReturnedToVendor,
+                Disposition::Scrap => AuditAction::ReturnScrapped,
+            };
+            let mut audit_entry = AuditEntry::new(
+                user_id.clone(),
+                username.clone(),
+                action,
+                "item".to_string(),
+                Some(item.id.clone()),
+                format!(
+                    "{}: {} × {} ({})",
+                    rma.number, line.quantity, item.name, line.disposition
+                ),
+            );
+            if kind.affects_on_hand() {
+                audit_entry = audit_entry.with_values(
+                    Some(format!("Qty: {}", old_quantity)),
+                    Some(format!("Qty: {}", item.quantity)),
+                );
+            }
+            self.audit_log.add_entry(audit_entry);
+        }
+
+        self.returns.push(rma);
+        self.return_sales_order_id = None;
+        self.return_customer_input.clear();
+        self.return_reason = None;
+        self.return_notes_input.clear();
+        self.return_line_item_id = None;
+        self.returns_error = None;
+        self.update_alerts_from_inventory();
+        self.auto_save()
+    }
+
+    pub fn handle_quarantine_quantity_changed(&mut self, item_id: String, value: String) {
+        if value.is_empty() || value.chars().all(|c| c.is_ascii_digit()) {
+            self.quarantine_quantity_inputs.insert(item_id, value);
+        }
+        self.returns_error = None;
+    }
+
+    /// Releases inspected units from quarantine, returns them to the vendor
+    /// or writes them off. Without a typed quantity all held units go.
+    pub fn handle_resolve_quarantine(&mut self, item_id: String, disposition: Disposition) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.returns_error = Some("You do not have permission to handle quarantined stock".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let held = returns::quarantined_by_item(&self.stock_movements)
+            .get(&item_id)
+            .copied()
+            .unwrap_or(0);
+        let quantity = match self.quarantine_quantity_inputs.get(&item_id).map(|v| v.trim()) {
+            Some(value) if !value.is_empty() => match value.parse::<u32>() {
+                Ok(quantity) => quantity,
+                Err(_) => {
+                    self.returns_error = Some("Enter a whole number of units".to_string());
+                    return Task::none();
+                }
+            },
+            _ => u32::try_from(held).unwrap_or(0),
+        };
+        let Some(item) = self.items.iter_mut().find(|i| i.id == item_id) else {
+            self.returns_error = Some("Selected item no longer exists".to_string());
+            return Task::none();
+        };
+
+        let old_quantity = item.quantity;
+        let resolved =
+            returns::resolve_quarantine(item, &self.stock_movements, quantity, disposition, username.clone());
+        let movements = match resolved {
+            Ok(movements) => movements,
+            Err(e) => {
+                self.returns_error = Some(e);
+                return Task::none();
+            }
+        };
+
+        let action = match disposition {
+            Disposition::Restock => AuditAction::ReturnRestocked,
+            Disposition::ReturnToVendor => AuditAction::ReturnedToVendor,
+            Disposition::Scrap | Disposition::Quarantine => AuditAction::ReturnScrapped,
+        };
+        let mut audit_entry = AuditEntry::new(
+            user_id,
+            username,
+            action,
+            "item".to_string(),
+            Some(item.id.clone()),
+            format!("From quarantine: {} × {} ({})", quantity, item.name, disposition),
+        );
+        if item.quantity != old_quantity {
+            audit_entry = audit_entry.with_values(
+                Some(format!("Qty: {}", old_quantity)),
+                Some(format!("Qty: {}", item.quantity)),
+            );
+        }
+        self.audit_log.add_entry(audit_entry);
+        self.stock_movements.extend(movements);
+        self.quarantine_quantity_inputs.remove(&item_id);
+        self.returns_error = None;
+        self.update_alerts_from_inventory();
+        self.auto_save()
+    }
+
+    pub fn handle_export_returns_report(&mut self) -> Task<Message> {
+        let rows = returns::returns_report(&self.returns);
+
+        Task::perform(
+            async move {
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+                let filename = format!("returns_report_{}.csv", timestamp);
+                let file_path = rfd::FileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("CSV", &["csv"])
+                    .save_file();
+
+                let Some(file_path) = file_path else {
+                    return Ok(None);
+                };
+
+                let count = rows.len();
+                let mut csv = String::from("SKU,Item,Reason,Returns,Quantity,Value\n");
+                for row in rows {
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{:.2}\n",
+                        csv_escape(&row.item_sku),
+                        csv_escape(&row.item_name),
+                        csv_escape(&row.reason.to_string()),
+                        row.returns,
+                        row.quantity,
+                        row.value,
+                    ));
+                }
+
+                std::fs::write(file_path, csv).map_err(|e| e.to_string())?;
+                Ok(Some(count))
+            },
+            Message::ReturnsReportExported,
+        )
+    }
+
+    /// Logs a written returns report; nothing is logged when the save dialog
+    /// was cancelled or the file could not be written
+    pub fn handle_returns_report_exported(&mut self, result: Result<Option<usize>, String>) -> Task<Message> {
+        let count = match result {
+            Ok(Some(count)) => count,
+            Ok(None) => return Task::none(),
+            Err(e) => {
+                self.returns_error = Some(format!("Could not export the returns report: {}", e));
+                return Task::none();
+            }
+        };
+        self.returns_error = None;
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "returns".to_string(),
+                None,
+                format!("Exported returns report ({} rows) to CSV", count),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+}
@@ -0,0 +1,580 @@
+use std::collections::BTreeMap;
+
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::alerts::{AlertType, StockAlert};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::inventory::InventoryItem;
+use crate::note::Note;
+use crate::purchasing::{self, PurchaseOrder, PurchaseOrderLine, PurchaseOrderStatus};
+use crate::reorder;
+use crate::rules::{self, ActionDraft, ActionKind, ItemField, Rule, RuleAction, RuleDraft, RuleNotice, RuleTrigger, TriggerKind};
+use crate::schedule::Frequency;
+
+/// Items listed in a preview or a firing's audit entry before the rest are counted
+const LISTED_ITEMS: usize = 25;
+
+impl InventoryApp {
+    /// Fires alert-raised rules for the alerts just raised, and item-changed
+    /// rules for items that started to meet their condition since the last check
+    pub(crate) fn run_rules(&mut self) -> Task<Message> {
+        if self.session.is_none() || self.settings.rules.is_empty() {
+            return Task::none();
+        }
+        let mut firings: Vec<(usize, Vec<String>, String)> = Vec::new();
+
+        // Alerts raised by rules don't trigger rules, so rules can't set each other off
+        for alert in self.raised_alerts.iter().filter(|alert| !matches!(alert.alert_type, AlertType::Rule(_))) {
+            let Some(item) = self.items.iter().find(|item| item.id == alert.item_id) else {
+                continue;
+            };
+            for (index, rule) in self.settings.rules.iter().enumerate() {
+                if rule.active && rule.trigger == RuleTrigger::AlertRaised && rule.matches(item) {
+                    firings.push((index, vec![item.id.clone()], format!("{} alert", alert.alert_type)));
+                }
+            }
+        }
+
+        if self.rules_checked_change != Some(self.change_count) {
+            // Changes made by the firings below are checked on the next pass
+            self.rules_checked_change = Some(self.change_count);
+            for (index, rule) in self.settings.rules.iter_mut().enumerate() {
+                if rule.active && rule.trigger == RuleTrigger::ItemChanged {
+                    let started = rule.update_matching(&self.items);
+                    if !started.is_empty() {
+                        firings.push((index, started, "item changed".to_string()));
+                    }
+                }
+            }
+        }
+
+        let tasks: Vec<Task<Message>> = firings
+            .into_iter()
+            .map(|(index, item_ids, cause)| self.fire_rule(index, &item_ids, &cause))
+            .collect();
+        Task::batch(tasks)
+    }
+
+    /// Runs scheduled rules whose schedule has fired since they last ran
+    pub fn handle_rules_tick(&mut self) -> Task<Message> {
+        if self.session.is_none() {
+            return Task::none();
+        }
+        let now = chrono::Local::now();
+        let mut tasks = Vec::new();
+        for index in 0..self.settings.rules.len() {
+            let rule = &mut self.settings.rules[index];
+            if !rule.active || !rule.is_due(now) {
+                continue;
+            }
+            rule.last_run = Some(now.timestamp());
+            let item_ids: Vec<String> = rule.matching_items(&self.items).iter().map(|item| item.id.clone()).collect();
+            if !item_ids.is_empty() {
+                tasks.push(self.fire_rule(index, &item_ids, "schedule"));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Runs a rule's actions for items, or only records them for a dry-run rule
+    fn fire_rule(&mut self, index: usize, item_ids: &[String], cause: &str) -> Task<Message> {
+        let Some(rule) = self.settings.rules.get(index).cloned() else {
+            return Task::none();
+        };
+        let mut done = Vec::new();
+        let mut problems = Vec::new();
+        let mut orders: BTreeMap<String, Vec<PurchaseOrderLine>> = BTreeMap::new();
+        let mut items_changed = false;
+
+        for item_id in item_ids {
+            let Some(item) = self.items.iter().find(|item| item.id == *item_id).cloned() else {
+                continue;
+            };
+            let label = format!("{} ({})", item.name, item.sku);
+            for action in rule.actions.iter().filter(|action| !matches!(action, RuleAction::RunExport { .. })) {
+                if rule.dry_run {
+                    done.push(format!("{}: {}", label, action.describe(Some(&item), &self.settings.export_jobs)));
+                    continue;
+                }
+                match self.apply_rule_action(&rule, action, &item, &mut orders) {
+                    Ok(true) => {
+                        items_changed |= matches!(action, RuleAction::SetField { .. });
+                        done.push(format!("{}: {}", label, action.describe(Some(&item), &self.settings.export_jobs)));
+                    }
+                    Ok(false) => {}
+                    Err(e) => problems.push(format!("{}: {}", label, e)),
+                }
+            }
+        }
+
+        for (supplier, lines) in orders {
+            let number = purchasing::next_order_number(&self.purchase_orders);
+            let order = PurchaseOrder::new_draft(number, supplier, lines, rules::AUTOMATION_USERNAME.to_string());
+            self.audit_log.add_entry(automation_entry(
+                AuditAction::PurchaseOrderCreated,
+                "purchase_order",
+                order.id.clone(),
+                format!(
+                    "Rule '{}' created draft {} for {} ({} lines)",
+                    rule.name,
+                    order.number,
+                    reorder::supplier_label(&order.supplier),
+                    order.lines.len()
+                ),
+            ));
+            self.purchase_orders.push(order);
+        }
+
+        // Exports run once per firing, however many items it was for
+        let mut tasks = Vec::new();
+        for action in rule.actions.iter().filter(|action| matches!(action, RuleAction::RunExport { .. })) {
+            let RuleAction::RunExport { job_id } = action else {
+                continue;
+            };
+            let description = action.describe(None, &self.settings.export_jobs);
+            if !self.settings.export_jobs.iter().any(|job| job.id == *job_id) {
+                problems.push(format!("{}: the export job was removed", description));
+            } else if rule.dry_run {
+                done.push(description);
+            } else if !self.running_export_jobs.contains(job_id) {
+                tasks.push(self.start_export_job(job_id.clone()));
+                done.push(description);
+            }
+        }
+
+        if items_changed {
+            self.filtered_items = self.search_filter.apply(&self.items);
+            self.update_alerts_from_inventory();
+        }
+        if done.is_empty() && problems.is_empty() {
+            return Task::batch(tasks);
+        }
+
+        let mut details = if rule.dry_run {
+            format!("Dry run of rule '{}' ({}), would have done: {}", rule.name, cause, summarize(&done))
+        } else if done.is_empty() {
+            format!("Rule '{}' ({}) did nothing", rule.name, cause)
+        } else {
+            format!("Rule '{}' ({}): {}", rule.name, cause, summarize(&done))
+        };
+        if !problems.is_empty() {
+            details.push_str(&format!(". Problems: {}", summarize(&problems)));
+        }
+        self.audit_log
+            .add_entry(automation_entry(AuditAction::RuleFired, "rule", rule.id.clone(), details));
+        if let Some(fired) = self.settings.rules.get_mut(index) {
+            fired.last_fired = Some(chrono::Utc::now().timestamp());
+        }
+
+        tasks.push(self.auto_save());
+        Task::batch(tasks)
+    }
+
+    /// Applies one action to an item; `Ok(false)` when there was nothing to do
+    fn apply_rule_action(
+        &mut self,
+        rule: &Rule,
+        action: &RuleAction,
+        item: &InventoryItem,
+        orders: &mut BTreeMap<String, Vec<PurchaseOrderLine>>,
+    ) -> Result<bool, String> {
+        match action {
+            RuleAction::Notify { user, message } => {
+                self.send_rule_notice(&rule.name, user.as_deref(), rules::expand(message, item));
+                Ok(true)
+            }
+            RuleAction::SetField { field, value } => {
+                let value = rules::expand(value, item);
+                let Some(target) = self.items.iter_mut().find(|target| target.id == item.id) else {
+                    return Ok(false);
+                };
+                let Some(old) = field.set(target, &value)? else {
+                    return Ok(false);
+                };
+                target.update_timestamp();
+                let audit_entry = automation_entry(
+                    AuditAction::ItemUpdated,
+                    "item",
+                    item.id.clone(),
+                    format!("Rule '{}' set {} of {}", rule.name, field.to_string().to_lowercase(), item.name),
+                )
+                .with_values(Some(old), Some(value));
+                self.audit_log.add_entry(audit_entry);
+                Ok(true)
+            }
+            RuleAction::CreateNote { title, content } => {
+                let mut note = Note::new(rules::expand(title, item));
+                note.content = rules::expand(content, item);
+                self.audit_log.add_entry(automation_entry(
+                    AuditAction::NoteCreated,
+                    "note",
+                    note.id.clone(),
+                    format!("Rule '{}' created note: {}", rule.name, note.title),
+                ));
+                self.notes.push(note);
+                Ok(true)
+            }
+            RuleAction::RaiseAlert { message } => {
+                let alert = StockAlert::new(item, AlertType::Rule(rules::expand(message, item)), 0);
+                if !self.alert_manager.raise(alert.clone()) {
+                    return Ok(false);
+                }
+                self.raised_alerts.push(alert);
+                Ok(true)
+            }
+            RuleAction::DraftPurchaseOrder => {
+                // An item already on a draft order is not ordered twice
+                let on_draft = self.purchase_orders.iter().any(|order| {
+                    order.status == PurchaseOrderStatus::Draft && order.lines.iter().any(|line| line.item_id == item.id)
+                });
+                if on_draft || orders.values().flatten().any(|line| line.item_id == item.id) {
+                    return Ok(false);
+                }
+                orders.entry(item.supplier.trim().to_string()).or_default().push(PurchaseOrderLine {
+                    item_id: item.id.clone(),
+                    item_name: item.name.clone(),
+                    item_sku: item.sku.clone(),
+                    quantity: reorder::suggested_quantity(item, self.alert_manager.settings()),
+                    unit_price: item.price,
+                });
+                Ok(true)
+            }
+            RuleAction::RunExport { .. } => Ok(false),
+        }
+    }
+
+    /// Notifies the user now if they are logged in, otherwise at their next login
+    fn send_rule_notice(&mut self, rule_name: &str, user: Option<&str>, message: String) {
+        let logged_in = self.session.as_ref().map(|session| session.username.as_str());
+        match user {
+            Some(user) if !logged_in.is_some_and(|name| name.eq_ignore_ascii_case(user)) => {
+                self.settings.rule_notices.push(RuleNotice {
+                    username: user.to_string(),
+                    rule_name: rule_name.to_string(),
+                    message,
+                    created_at: chrono::Utc::now().timestamp(),
+                });
+            }
+            _ => crate::notifications::send_notification(&format!("Rule: {}", rule_name), &message),
+        }
+    }
+
+    /// Shows the notifications rules left for the user who just logged in
+    pub(crate) fn deliver_rule_notices(&mut self) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let (mine, others): (Vec<RuleNotice>, Vec<RuleNotice>) = std::mem::take(&mut self.settings.rule_notices)
+            .into_iter()
+            .partition(|notice| notice.username.eq_ignore_ascii_case(&session.username));
+        self.settings.rule_notices = others;
+        for notice in mine {
+            crate::notifications::send_notification(&format!("Rule: {}", notice.rule_name), &notice.message);
+        }
+    }
+
+    /// What the rule in the editor would do with the current items
+    fn preview_rule(&self, rule: &Rule) -> Vec<String> {
+        let mut items = rule.matching_items(&self.items);
+        let mut lines = vec![match &rule.trigger {
+            RuleTrigger::ItemChanged => format!(
+                "{} items meet the condition now. The rule fires for an item when a change makes it meet the condition; for these items it would:",
+                items.len()
+            ),
+            RuleTrigger::AlertRaised => {
+                let alerts = self.alert_manager.get_active_alerts();
+                items.retain(|item| {
+                    alerts
+                        .iter()
+                        .any(|alert| alert.item_id == item.id && !matches!(alert.alert_type, AlertType::Rule(_)))
+                });
+                format!(
+                    "{} items meeting the condition have a stock alert now. For a new alert on any of them the rule would:",
+                    items.len()
+                )
+            }
+            RuleTrigger::Schedule(schedule) => format!(
+                "Next run {}. {} items meet the condition now; a run would:",
+                schedule
+                    .next_occurrence(chrono::Local::now())
+                    .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                items.len()
+            ),
+        }];
+        if items.is_empty() {
+            lines.push("Nothing, as no items qualify.".to_string());
+            return lines;
+        }
+
+        for item in items.iter().take(LISTED_ITEMS) {
+            let actions: Vec<String> = rule
+                .actions
+                .iter()
+                .filter(|action| !matches!(action, RuleAction::RunExport { .. }))
+                .map(|action| action.describe(Some(item), &self.settings.export_jobs))
+                .collect();
+            if !actions.is_empty() {
+                lines.push(format!("{} ({}): {}", item.name, item.sku, actions.join("; ")));
+            }
+        }
+        if items.len() > LISTED_ITEMS {
+            lines.push(format!("...and the same for {} more items", items.len() - LISTED_ITEMS));
+        }
+        for action in rule.actions.iter().filter(|action| matches!(action, RuleAction::RunExport { .. })) {
+            lines.push(format!("Once per firing: {}", action.describe(None, &self.settings.export_jobs)));
+        }
+        lines
+    }
+
+    pub fn handle_new_rule(&mut self) {
+        self.rule_draft = RuleDraft::default();
+        self.rule_status = None;
+        self.rule_preview = None;
+    }
+
+    pub fn handle_edit_rule(&mut self, rule_id: String) {
+        if let Some(rule) = self.settings.rules.iter().find(|rule| rule.id == rule_id) {
+            self.rule_draft = RuleDraft::from_rule(rule);
+            self.rule_status = None;
+            self.rule_preview = None;
+        }
+    }
+
+    pub fn handle_rule_name_changed(&mut self, value: String) {
+        self.rule_draft.name = value;
+    }
+
+    pub fn handle_rule_trigger_changed(&mut self, trigger: TriggerKind) {
+        self.rule_draft.trigger = trigger;
+        self.rule_preview = None;
+    }
+
+    pub fn handle_rule_frequency_changed(&mut self, frequency: Frequency) {
+        // Hourly rules take a minute, the others a time of day
+        if (frequency == Frequency::Hourly) != (self.rule_draft.frequency == Frequency::Hourly) {
+            self.rule_draft.time = if frequency == Frequency::Hourly { "0" } else { "08:00" }.to_string();
+        }
+        self.rule_draft.frequency = frequency;
+    }
+
+    pub fn handle_rule_time_changed(&mut self, value: String) {
+        self.rule_draft.time = value;
+    }
+
+    pub fn handle_rule_weekday_changed(&mut self, weekday: chrono::Weekday) {
+        self.rule_draft.weekday = weekday;
+    }
+
+    pub fn handle_rule_condition_changed(&mut self, value: String) {
+        self.rule_draft.condition = value;
+        self.rule_preview = None;
+    }
+
+    pub fn handle_rule_dry_run_toggled(&mut self, dry_run: bool) {
+        self.rule_draft.dry_run = dry_run;
+    }
+
+    pub fn handle_add_rule_action(&mut self) {
+        self.rule_draft.actions.push(ActionDraft::new(ActionKind::Notify));
+    }
+
+    pub fn handle_remove_rule_action(&mut self, index: usize) {
+        if index < self.rule_draft.actions.len() {
+            self.rule_draft.actions.remove(index);
+        }
+    }
+
+    pub fn handle_rule_action_kind_changed(&mut self, index: usize, kind: ActionKind) {
+        if let Some(action) = self.rule_draft.actions.get_mut(index) {
+            if action.kind != kind {
+                // The target means something else for each kind of action
+                action.target.clear();
+            }
+            action.kind = kind;
+        }
+    }
+
+    pub fn handle_rule_action_field_changed(&mut self, index: usize, field: ItemField) {
+        if let Some(action) = self.rule_draft.actions.get_mut(index) {
+            action.field = field;
+        }
+    }
+
+    pub fn handle_rule_action_target_changed(&mut self, index: usize, value: String) {
+        if let Some(action) = self.rule_draft.actions.get_mut(index) {
+            action.target = value;
+        }
+    }
+
+    pub fn handle_rule_action_text_changed(&mut self, index: usize, value: String) {
+        if let Some(action) = self.rule_draft.actions.get_mut(index) {
+            action.text = value;
+        }
+    }
+
+    pub fn handle_preview_rule(&mut self) {
+        match self.rule_draft.build() {
+            Ok(rule) => {
+                self.rule_preview = Some(self.preview_rule(&rule));
+                self.rule_status = None;
+            }
+            Err(e) => {
+                self.rule_preview = None;
+                self.rule_status = Some(e);
+            }
+        }
+    }
+
+    pub fn handle_save_rule(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_view_audit() {
+            self.rule_status = Some("Only managers and administrators can manage rules".to_string());
+            return Task::none();
+        }
+        let mut rule = match self.rule_draft.build() {
+            Ok(rule) => rule,
+            Err(e) => {
+                self.rule_status = Some(e);
+                return Task::none();
+            }
+        };
+        let removed_job = rule.actions.iter().any(|action| {
+            matches!(action, RuleAction::RunExport { job_id } if !self.settings.export_jobs.iter().any(|job| job.id == *job_id))
+        });
+        if removed_job {
+            self.rule_status = Some("Choose an export job that still exists".to_string());
+            return Task::none();
+        }
+        // Items meeting the condition already don't fire the rule when it is saved
+        rule.update_matching(&self.items);
+
+        let existing = self
+            .rule_draft
+            .editing
+            .as_ref()
+            .and_then(|id| self.settings.rules.iter().position(|rule| rule.id == *id));
+        let verb = match existing {
+            Some(index) => {
+                let old = &self.settings.rules[index];
+                rule.id = old.id.clone();
+                rule.active = old.active;
+                rule.created_at = old.created_at;
+                rule.last_run = old.last_run;
+                rule.last_fired = old.last_fired;
+                "Updated"
+            }
+            None => "Added",
+        };
+        let actions: Vec<String> = rule
+            .actions
+            .iter()
+            .map(|action| action.describe(None, &self.settings.export_jobs))
+            .collect();
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "rule".to_string(),
+            Some(rule.id.clone()),
+            format!(
+                "{} rule '{}': {}, if {}, then {}{}",
+                verb,
+                rule.name,
+                rule.trigger,
+                if rule.condition.is_empty() { "any item" } else { rule.condition.as_str() },
+                actions.join(", "),
+                if rule.dry_run { " (dry run)" } else { "" }
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        match existing {
+            Some(index) => self.settings.rules[index] = rule,
+            None => self.settings.rules.push(rule),
+        }
+        self.rule_draft = RuleDraft::default();
+        self.rule_status = None;
+        self.rule_preview = None;
+        self.auto_save()
+    }
+
+    pub fn handle_toggle_rule(&mut self, rule_id: String, active: bool) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_view_audit() {
+            return Task::none();
+        }
+        let Some(rule) = self.settings.rules.iter_mut().find(|rule| rule.id == rule_id) else {
+            return Task::none();
+        };
+        rule.active = active;
+        if active {
+            // Items that started to match while the rule was paused don't fire it
+            rule.update_matching(&self.items);
+        }
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "rule".to_string(),
+            Some(rule.id.clone()),
+            format!("{} rule '{}'", if active { "Resumed" } else { "Paused" }, rule.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+
+    pub fn handle_remove_rule(&mut self, rule_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_view_audit() {
+            return Task::none();
+        }
+        let Some(index) = self.settings.rules.iter().position(|rule| rule.id == rule_id) else {
+            return Task::none();
+        };
+        let rule = self.settings.rules.remove(index);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "rule".to_string(),
+            Some(rule.id.clone()),
+            format!("Removed rule '{}'", rule.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        if self.rule_draft.editing.as_deref() == Some(rule.id.as_str()) {
+            self.rule_draft = RuleDraft::default();
+        }
+        self.auto_save()
+    }
+}
+
+/// Audit entry for a change made by a rule rather than a user
+fn automation_entry(action: AuditAction, entity_type: &str, entity_id: String, details: String) -> AuditEntry {
+    AuditEntry::new(
+        rules::AUTOMATION_USER_ID.to_string(),
+        rules::AUTOMATION_USERNAME.to_string(),
+        action,
+        entity_type.to_string(),
+        Some(entity_id),
+        details,
+    )
+}
+
+/// Joins what a firing did, counting whatever is past the first few
+fn summarize(parts: &[String]) -> String {
+    let mut summary = parts.iter().take(LISTED_ITEMS).cloned().collect::<Vec<_>>().join("; ");
+    if parts.len() > LISTED_ITEMS {
+        summary.push_str(&format!("; and {} more", parts.len() - LISTED_ITEMS));
+    }
+    summary
+}
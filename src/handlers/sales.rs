@@ -0,0 +1,468 @@
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::pdf::PdfWriter;
+use crate::sales::{self, Customer, SalesOrder, SalesOrderLine, SalesOrderStatus};
+use crate::stock::{MovementKind, StockMovement};
+
+impl InventoryApp {
+    pub fn handle_customer_name_changed(&mut self, value: String) {
+        self.customer_name_input = value;
+        self.sales_error = None;
+    }
+
+    pub fn handle_customer_email_changed(&mut self, value: String) {
+        self.customer_email_input = value;
+        self.sales_error = None;
+    }
+
+    pub fn handle_customer_phone_changed(&mut self, value: String) {
+        self.customer_phone_input = value;
+        self.sales_error = None;
+    }
+
+    pub fn handle_customer_address_changed(&mut self, value: String) {
+        self.customer_address_input = value;
+        self.sales_error = None;
+    }
+
+    pub fn handle_create_customer(&mut self) -> Task<Message> {
+        use crate::errors::*;
+
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_create() {
+            self.sales_error = Some("You do not have permission to add customers".to_string());
+            return Task::none();
+        }
+
+        if let Err(e) = validate_required("Customer name", &self.customer_name_input) {
+            self.sales_error = Some(e.to_string());
+            return Task::none();
+        }
+        if let Err(e) = validate_length("Customer name", &self.customer_name_input, 1, 200) {
+            self.sales_error = Some(e.to_string());
+            return Task::none();
+        }
+
+        let customer = Customer::new(
+            self.customer_name_input.trim().to_string(),
+            self.customer_email_input.trim().to_string(),
+            self.customer_phone_input.trim().to_string(),
+            self.customer_address_input.trim().to_string(),
+        );
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::CustomerCreated,
+            "customer".to_string(),
+            Some(customer.id.clone()),
+            format!("Created customer: {}", customer.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        if self.sales_customer_id.is_none() {
+            self.sales_customer_id = Some(customer.id.clone());
+        }
+        self.customers.push(customer);
+        self.customer_name_input.clear();
+        self.customer_email_input.clear();
+        self.customer_phone_input.clear();
+        self.customer_address_input.clear();
+        self.sales_error = None;
+        self.auto_save()
+    }
+
+    pub fn handle_delete_customer(&mut self, customer_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_delete() {
+            self.sales_error = Some("You do not have permission to delete customers".to_string());
+            return Task::none();
+        }
+
+        let has_open_orders = self
+            .sales_orders
+            .iter()
+            .any(|o| o.customer_id == customer_id && o.status == SalesOrderStatus::Open);
+        if has_open_orders {
+            self.sales_error = Some("Customer has open sales orders and cannot be deleted".to_string());
+            return Task::none();
+        }
+
+        if let Some(pos) = self.customers.iter().position(|c| c.id == customer_id) {
+            let customer = self.customers.remove(pos);
+
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::CustomerDeleted,
+                "customer".to_string(),
+                Some(customer.id.clone()),
+                format!("Deleted customer: {}", customer.name),
+            );
+            self.audit_log.add_entry(audit_entry);
+
+            if self.sales_customer_id.as_deref() == Some(customer_id.as_str()) {
+                self.sales_customer_id = None;
+            }
+            return self.auto_save();
+        }
+        Task::none()
+    }
+
+    pub fn handle_sales_customer_selected(&mut self, customer_id: String) {
+        self.sales_customer_id = Some(customer_id);
+        self.sales_error = None;
+    }
+
+    pub fn handle_sales_line_item_selected(&mut self, item_id: String) {
+        self.sales_line_item_id = Some(item_id);
+        self.sales_error = None;
+    }
+
+    pub fn handle_sales_line_quantity_changed(&mut self, value: String) {
+        self.sales_line_quantity_input = value;
+        self.sales_error = None;
+    }
+
+    pub fn handle_add_sales_line(&mut self) {
+        let Some(item_id) = self.sales_line_item_id.clone() else {
+            self.sales_error = Some("Select an item to add".to_string());
+            return;
+        };
+        let Some(item) = self.items.iter().find(|i| i.id == item_id) else {
+            self.sales_error = Some("Selected item no longer exists".to_string());
+            return;
+        };
+
+        let quantity = match crate::errors::validate_quantity(&self.sales_line_quantity_input) {
+            Ok(0) => {
+                self.sales_error = Some("Quantity must be at least 1".to_string());
+                return;
+            }
+            Ok(q) => q,
+            Err(e) => {
+                self.sales_error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let already_drafted: u32 = self
+            .sales_draft_lines
+            .iter()
+            .filter(|line| line.item_id == item_id)
+            .map(|line| line.quantity)
+            .sum();
+        if already_drafted + quantity > item.available() {
+            self.sales_error = Some(format!(
+                "Only {} of {} available to reserve",
+                item.available().saturating_sub(already_drafted),
+                item.name
+            ));
+            return;
+        }
+
+        if let Some(line) = self.sales_draft_lines.iter_mut().find(|l| l.item_id == item_id) {
+            line.quantity += quantity;
+        } else {
+            self.sales_draft_lines.push(SalesOrderLine {
+                item_id: item.id.clone(),
+                item_name: item.name.clone(),
+                item_sku: item.sku.clone(),
+                quantity,
+                unit_price: item.price,
+            });
+        }
+        self.sales_line_quantity_input.clear();
+        self.sales_error = None;
+    }
+
+    pub fn handle_remove_sales_line(&mut self, index: usize) {
+        if index < self.sales_draft_lines.len() {
+            self.sales_draft_lines.remove(index);
+        }
+    }
+
+    pub fn handle_create_sales_order(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_create() {
+            self.sales_error = Some("You do not have permission to create sales orders".to_string());
+            return Task::none();
+        }
+
+        let Some(customer) = self
+            .sales_customer_id
+            .as_ref()
+            .and_then(|id| self.customers.iter().find(|c| c.id == *id))
+        else {
+            self.sales_error = Some("Select a customer".to_string());
+            return Task::none();
+        };
+        if self.sales_draft_lines.is_empty() {
+            self.sales_error = Some("Add at least one line".to_string());
+            return Task::none();
+        }
+
+        // Stock may have moved since the lines were drafted
+        for line in &self.sales_draft_lines {
+            let available = self
+                .items
+                .iter()
+                .find(|i| i.id == line.item_id)
+                .map(|i| i.available())
+                .unwrap_or(0);
+            if line.quantity > available {
+                self.sales_error = Some(format!(
+                    "Only {} of {} available to reserve",
+                    available, line.item_name
+                ));
+                return Task::none();
+            }
+        }
+
+        let number = sales::next_order_number(&self.sales_orders);
+        let order = SalesOrder::new(
+            number,
+            customer,
+            std::mem::take(&mut self.sales_draft_lines),
+            session.username.clone(),
+        );
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SalesOrderCreated,
+            "sales_order".to_string(),
+            Some(order.id.clone()),
+            format!(
+                "Created {} for {} ({} units reserved)",
+                order.number,
+                order.customer_name,
+                order.total_units()
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.sales_orders.push(order);
+        self.sales_error = None;
+        self.update_alerts_from_inventory();
+        self.auto_save()
+    }
+
+    pub fn handle_fulfill_sales_order(&mut self, order_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.sales_error = Some("You do not have permission to fulfill orders".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let Some(order_pos) = self
+            .sales_orders
+            .iter()
+            .position(|o| o.id == order_id && o.status == SalesOrderStatus::Open)
+        else {
+            return Task::none();
+        };
+
+        // Every line must be coverable from on-hand stock before anything is issued
+        for line in &self.sales_orders[order_pos].lines {
+            let on_hand = self
+                .items
+                .iter()
+                .find(|i| i.id == line.item_id)
+                .map(|i| i.quantity);
+            match on_hand {
+                Some(qty) if qty >= line.quantity => {}
+                Some(qty) => {
+                    self.sales_error = Some(format!(
+                        "Cannot fulfill: only {} of {} on hand",
+                        qty, line.item_name
+                    ));
+                    return Task::none();
+                }
+                None => {
+                    self.sales_error = Some(format!(
+                        "Cannot fulfill: {} no longer exists",
+                        line.item_name
+                    ));
+                    return Task::none();
+                }
+            }
+        }
+
+        let order_number = self.sales_orders[order_pos].number.clone();
+        for line in self.sales_orders[order_pos].lines.clone() {
+            if let Some(item) = self.items.iter_mut().find(|i| i.id == line.item_id) {
+                item.quantity -= line.quantity;
+                item.update_timestamp();
+                self.stock_movements.push(StockMovement::new(
+                    item,
+                    MovementKind::Issue,
+                    -(line.quantity as i64),
+                    order_number.clone(),
+                    username.clone(),
+                ));
+            }
+        }
+
+        let order = &mut self.sales_orders[order_pos];
+        order.status = SalesOrderStatus::Fulfilled;
+        order.fulfilled_at = Some(chrono::Utc::now().timestamp());
+
+        let audit_entry = AuditEntry::new(
+            user_id,
+            username,
+            AuditAction::SalesOrderFulfilled,
+            "sales_order".to_string(),
+            Some(order.id.clone()),
+            format!(
+                "Fulfilled {} for {} ({} units issued)",
+                order.number,
+                order.customer_name,
+                order.total_units()
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.sales_error = None;
+        self.update_alerts_from_inventory();
+        self.auto_save()
+    }
+
+    pub fn handle_cancel_sales_order(&mut self, order_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.sales_error = Some("You do not have permission to cancel orders".to_string());
+            return Task::none();
+        }
+
+        if let Some(order) = self
+            .sales_orders
+            .iter_mut()
+            .find(|o| o.id == order_id && o.status == SalesOrderStatus::Open)
+        {
+            order.status = SalesOrderStatus::Cancelled;
+
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::SalesOrderCancelled,
+                "sales_order".to_string(),
+                Some(order.id.clone()),
+                format!("Cancelled {} for {}", order.number, order.customer_name),
+            );
+            self.audit_log.add_entry(audit_entry);
+
+            self.update_alerts_from_inventory();
+            return self.auto_save();
+        }
+        Task::none()
+    }
+
+    pub fn handle_export_packing_slip(&mut self, order_id: String) -> Task<Message> {
+        let Some(order) = self.sales_orders.iter().find(|o| o.id == order_id).cloned() else {
+            return Task::none();
+        };
+        let customer = self.customers.iter().find(|c| c.id == order.customer_id).cloned();
+        self.sales_error = None;
+
+        Task::perform(
+            async move {
+                let filename = format!("packing_slip_{}.pdf", order.number);
+                let file_path = rfd::FileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("PDF", &["pdf"])
+                    .save_file();
+
+                let Some(file_path) = file_path else {
+                    return Ok(None);
+                };
+
+                let bytes = render_packing_slip(&order, customer.as_ref())?;
+                std::fs::write(file_path, bytes).map_err(|e| e.to_string())?;
+                Ok(Some(order.id))
+            },
+            Message::PackingSlipExported,
+        )
+    }
+
+    /// Logs a written packing slip; nothing is logged when the save dialog
+    /// was cancelled or the file could not be written
+    pub fn handle_packing_slip_exported(&mut self, result: Result<Option<String>, String>) -> Task<Message> {
+        let order_id = match result {
+            Ok(Some(order_id)) => order_id,
+            Ok(None) => return Task::none(),
+            Err(e) => {
+                self.sales_error = Some(format!("Could not export the packing slip: {}", e));
+                return Task::none();
+            }
+        };
+        let Some(order) = self.sales_orders.iter().find(|o| o.id == order_id) else {
+            return Task::none();
+        };
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "sales_order".to_string(),
+                Some(order.id.clone()),
+                format!("Exported packing slip for {} to PDF", order.number),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+}
+
+fn render_packing_slip(order: &SalesOrder, customer: Option<&Customer>) -> Result<Vec<u8>, String> {
+    let mut pdf = PdfWriter::new(&format!("Packing Slip {}", order.number))?;
+
+    pdf.title("Packing Slip");
+    pdf.text(&format!("Order: {}", order.number));
+    pdf.text(&format!("Date: {}", order.formatted_timestamp()));
+    pdf.text(&format!("Status: {}", order.status));
+
+    pdf.heading("Ship To");
+    pdf.text(&order.customer_name);
+    if let Some(customer) = customer {
+        for line in customer.address.lines().filter(|l| !l.trim().is_empty()) {
+            pdf.text(line.trim());
+        }
+        if !customer.phone.is_empty() {
+            pdf.text(&format!("Phone: {}", customer.phone));
+        }
+        if !customer.email.is_empty() {
+            pdf.text(&format!("Email: {}", customer.email));
+        }
+    }
+
+    pdf.spacer(4.0);
+    pdf.row(&[("SKU", 0.0), ("Item", 40.0), ("Quantity", 150.0)], true);
+    pdf.rule();
+    for line in &order.lines {
+        let quantity = line.quantity.to_string();
+        pdf.row(
+            &[(&line.item_sku, 0.0), (&line.item_name, 40.0), (&quantity, 150.0)],
+            false,
+        );
+    }
+    pdf.rule();
+    let total_units = order.total_units().to_string();
+    pdf.row(&[("Total units", 40.0), (&total_units, 150.0)], true);
+
+    pdf.into_bytes()
+}
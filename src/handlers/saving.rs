@@ -0,0 +1,81 @@
+use iced::{window, Task};
+use crate::{InventoryApp, Message};
+use crate::persistence;
+
+impl InventoryApp {
+    /// Writes pending changes now, unless a save is already running or the
+    /// data cannot be written from this window
+    pub fn save_now(&mut self) -> Task<Message> {
+        if self.saving.is_some() || self.read_only || self.data_file_changed || !self.has_unsaved_changes() {
+            return Task::none();
+        }
+        self.saving = Some(self.change_count);
+        self.saving_versions = self.section_versions();
+        let changed = self.changed_sections();
+        Task::perform(persistence::save_sections(self.saved_state(), changed), |result| {
+            Message::Saved(result.map_err(|e| e.to_string()))
+        })
+    }
+
+    pub fn handle_auto_save_tick(&mut self) -> Task<Message> {
+        if !self.settings.auto_save_enabled {
+            return Task::none();
+        }
+        self.save_now()
+    }
+
+    pub fn handle_saved(&mut self, result: Result<Option<String>, String>) -> Task<Message> {
+        let saved = self.saving.take();
+        match result {
+            Ok(warning) => {
+                if let Some(count) = saved {
+                    self.saved_change_count = count;
+                    self.saved_versions = Some(self.saving_versions);
+                }
+                self.last_saved = Some(chrono::Local::now());
+                self.save_error = None;
+                self.save_warning = warning;
+            }
+            Err(e) => {
+                self.save_error = Some(match self.closing_window {
+                    Some(_) => format!("Could not save: {}. Close again to quit without saving.", e),
+                    None => format!("Could not save: {}", e),
+                });
+            }
+        }
+
+        // A workspace switch waits for the save in flight, then saves again itself
+        if let Some(name) = self.pending_workspace.take() {
+            return self.handle_workspace_selected(name);
+        }
+        if self.save_error.is_some() {
+            return Task::none();
+        }
+
+        // Finish closing once everything is written
+        match self.closing_window {
+            Some(_) if self.has_unsaved_changes() => self.save_now(),
+            Some(id) => window::close(id),
+            None => Task::none(),
+        }
+    }
+
+    /// Flushes unsaved changes before the window closes. A second close
+    /// request while nothing is being written closes without saving.
+    pub fn handle_window_close_requested(&mut self, id: window::Id) -> Task<Message> {
+        if self.closing_window.is_some() && self.saving.is_none() {
+            return window::close(id);
+        }
+        self.closing_window = Some(id);
+        if !self.has_unsaved_changes() || self.read_only {
+            return window::close(id);
+        }
+        if self.data_file_changed {
+            self.save_error = Some(
+                "The data file was changed outside this window. Reload, merge or keep yours first, or close again to quit without saving.".to_string(),
+            );
+            return Task::none();
+        }
+        self.save_now()
+    }
+}
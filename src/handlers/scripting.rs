@@ -0,0 +1,265 @@
+use iced::Task;
+use iced::widget::text_editor;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::scripting::{self, ComputedColumn, ComputedValues, ReportScript};
+
+impl InventoryApp {
+    /// Works the computed columns out again when the items or columns changed
+    pub(crate) fn refresh_computed_values(&mut self) {
+        if self.computed_values_change == Some(self.change_count) {
+            return;
+        }
+        self.computed_values_change = Some(self.change_count);
+        self.computed_values = ComputedValues::compute(&self.settings.computed_columns, &self.items);
+    }
+
+    pub fn handle_edit_computed_column(&mut self, column_id: String) {
+        if let Some(column) = self.settings.computed_columns.iter().find(|column| column.id == column_id) {
+            self.column_editing = Some(column.id.clone());
+            self.column_name_input = column.name.clone();
+            self.column_script_input = column.script.clone();
+            self.column_status = None;
+        }
+    }
+
+    pub fn handle_computed_column_name_changed(&mut self, value: String) {
+        self.column_name_input = value;
+    }
+
+    pub fn handle_computed_column_script_changed(&mut self, value: String) {
+        self.column_script_input = value;
+    }
+
+    pub fn handle_clear_computed_column(&mut self) {
+        self.column_editing = None;
+        self.column_name_input.clear();
+        self.column_script_input.clear();
+        self.column_status = None;
+    }
+
+    pub fn handle_save_computed_column(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            self.column_status = Some("Only administrators can define computed columns".to_string());
+            return Task::none();
+        }
+        let name = self.column_name_input.trim().to_string();
+        let script = self.column_script_input.trim().to_string();
+        if name.is_empty() {
+            self.column_status = Some("Give the column a name".to_string());
+            return Task::none();
+        }
+        let existing = self
+            .column_editing
+            .as_ref()
+            .and_then(|id| self.settings.computed_columns.iter().position(|column| column.id == *id));
+        if self
+            .settings
+            .computed_columns
+            .iter()
+            .enumerate()
+            .any(|(index, column)| Some(index) != existing && column.name.eq_ignore_ascii_case(&name))
+        {
+            self.column_status = Some(format!("There is already a column called '{}'", name));
+            return Task::none();
+        }
+        if let Err(e) = scripting::check_column(&script, &self.items) {
+            self.column_status = Some(e);
+            return Task::none();
+        }
+
+        let verb = match existing {
+            Some(index) => {
+                let column = &mut self.settings.computed_columns[index];
+                column.name = name;
+                column.script = script;
+                "Updated"
+            }
+            None => {
+                self.settings.computed_columns.push(ComputedColumn::new(name, script));
+                "Added"
+            }
+        };
+        let column = &self.settings.computed_columns[existing.unwrap_or(self.settings.computed_columns.len() - 1)];
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "computed_column".to_string(),
+            Some(column.id.clone()),
+            format!("{} computed column '{}': {}", verb, column.name, column.script),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.handle_clear_computed_column();
+        self.auto_save()
+    }
+
+    pub fn handle_remove_computed_column(&mut self, column_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let Some(index) = self.settings.computed_columns.iter().position(|column| column.id == column_id) else {
+            return Task::none();
+        };
+        let column = self.settings.computed_columns.remove(index);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "computed_column".to_string(),
+            Some(column.id.clone()),
+            format!("Removed computed column '{}'", column.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        if self.column_editing.as_deref() == Some(column.id.as_str()) {
+            self.handle_clear_computed_column();
+        }
+        self.auto_save()
+    }
+
+    pub fn handle_edit_report_script(&mut self, script_id: String) {
+        if let Some(script) = self.settings.report_scripts.iter().find(|script| script.id == script_id) {
+            self.report_script_editing = Some(script.id.clone());
+            self.report_script_name_input = script.name.clone();
+            self.report_script_content = text_editor::Content::with_text(&script.script);
+            self.report_script_status = None;
+        }
+    }
+
+    pub fn handle_report_script_name_changed(&mut self, value: String) {
+        self.report_script_name_input = value;
+    }
+
+    pub fn handle_report_script_action(&mut self, action: text_editor::Action) {
+        self.report_script_content.perform(action);
+    }
+
+    pub fn handle_clear_report_script(&mut self) {
+        self.report_script_editing = None;
+        self.report_script_name_input.clear();
+        self.report_script_content = text_editor::Content::new();
+        self.report_script_status = None;
+    }
+
+    pub fn handle_save_report_script(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            self.report_script_status = Some("Only administrators can write report scripts".to_string());
+            return Task::none();
+        }
+        let name = self.report_script_name_input.trim().to_string();
+        let script = self.report_script_content.text().trim().to_string();
+        if name.is_empty() {
+            self.report_script_status = Some("Give the report a name".to_string());
+            return Task::none();
+        }
+        if let Err(e) = scripting::check_report(&script) {
+            self.report_script_status = Some(e);
+            return Task::none();
+        }
+
+        let existing = self
+            .report_script_editing
+            .as_ref()
+            .and_then(|id| self.settings.report_scripts.iter().position(|script| script.id == *id));
+        let verb = match existing {
+            Some(index) => {
+                let report = &mut self.settings.report_scripts[index];
+                report.name = name;
+                report.script = script;
+                "Updated"
+            }
+            None => {
+                self.settings.report_scripts.push(ReportScript::new(name, script));
+                "Added"
+            }
+        };
+        let report = &self.settings.report_scripts[existing.unwrap_or(self.settings.report_scripts.len() - 1)];
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "report_script".to_string(),
+            Some(report.id.clone()),
+            format!("{} report script '{}'", verb, report.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.handle_clear_report_script();
+        self.auto_save()
+    }
+
+    pub fn handle_remove_report_script(&mut self, script_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let Some(index) = self.settings.report_scripts.iter().position(|script| script.id == script_id) else {
+            return Task::none();
+        };
+        let report = self.settings.report_scripts.remove(index);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "report_script".to_string(),
+            Some(report.id.clone()),
+            format!("Removed report script '{}'", report.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        if self.report_script_editing.as_deref() == Some(report.id.as_str()) {
+            self.handle_clear_report_script();
+        }
+        self.auto_save()
+    }
+
+    /// Runs the script in the editor without saving it
+    pub fn handle_run_report_draft(&mut self) {
+        let name = match self.report_script_name_input.trim() {
+            "" => "Untitled report".to_string(),
+            name => name.to_string(),
+        };
+        let script = self.report_script_content.text();
+        self.run_report(name, &script);
+    }
+
+    pub fn handle_run_report_script(&mut self, script_id: String) {
+        if let Some(report) = self.settings.report_scripts.iter().find(|script| script.id == script_id) {
+            let (name, script) = (report.name.clone(), report.script.clone());
+            self.run_report(name, &script);
+        }
+    }
+
+    fn run_report(&mut self, name: String, script: &str) {
+        if !self.session.as_ref().is_some_and(|session| session.role.can_view_audit()) {
+            self.report_script_status = Some("Only managers and administrators can run report scripts".to_string());
+            return;
+        }
+        match scripting::run_report(script, &self.items, &self.notes, self.audit_log.get_entries()) {
+            Ok(lines) => {
+                self.report_script_status = None;
+                self.report_script_output = Some((name, lines));
+            }
+            Err(e) => {
+                self.report_script_status = Some(format!("{} failed: {}", name, e));
+                self.report_script_output = None;
+            }
+        }
+    }
+}
+
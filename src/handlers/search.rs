@@ -1,5 +1,6 @@
 use iced::Task;
 use crate::{InventoryApp, Message};
+use crate::analysis::AbcClass;
 use crate::search::SortField;
 
 impl InventoryApp {
@@ -31,6 +32,13 @@ impl InventoryApp {
         self.filtered_items = self.search_filter.apply(&self.items);
     }
 
+    pub fn handle_abc_class_filter_changed(&mut self, class: String) {
+        self.search_filter.abc_filter = AbcClass::ALL
+            .into_iter()
+            .find(|c| c.to_string() == class);
+        self.filtered_items = self.search_filter.apply(&self.items);
+    }
+
     pub fn handle_min_quantity_changed(&mut self, value: String) {
         self.search_filter.min_quantity = value.parse().ok();
         self.filtered_items = self.search_filter.apply(&self.items);
@@ -1,9 +1,10 @@
 use iced::Task;
 use iced::widget::markdown;
-use std::collections::HashSet;
 use crate::{InventoryApp, Message};
-use crate::messages::{AppTheme, LayoutStyle, SavedState};
+use crate::merge::MergePlan;
+use crate::messages::{AppTheme, LayoutStyle, LoadError, SavedState, View};
 use crate::audit::{AuditAction, AuditEntry};
+use crate::persistence::{self, StorageBackend};
 
 impl InventoryApp {
     pub fn handle_toggle_auto_save(&mut self) -> Task<Message> {
@@ -77,56 +78,6 @@ impl InventoryApp {
         Task::none()
     }
 
-    pub fn handle_export_data(&mut self) -> Task<Message> {
-        // Log export
-        if let Some(session) = &self.session {
-            let audit_entry = AuditEntry::new(
-                session.user_id.clone(),
-                session.username.clone(),
-                AuditAction::DataExported,
-                "data".to_string(),
-                None,
-                "Exported all data to JSON file".to_string(),
-            );
-            self.audit_log.add_entry(audit_entry);
-        }
-        
-        let state = SavedState {
-            items: self.items.clone(),
-            notes: self.notes.clone(),
-            calculator_position: self.calculator.position,
-            settings: self.settings.clone(),
-            auth_store: self.auth_store.clone(),
-            audit_log: self.audit_log.clone(),
-            alert_manager: self.alert_manager.clone(),
-            sidebar_collapsed: self.sidebar_collapsed,
-            show_alerts_panel: self.show_alerts_panel,
-            show_search_panel: self.show_search_panel,
-            current_view: self.current_view.clone(),
-        };
-        let task = Task::perform(
-            async move {
-                let json = serde_json::to_string_pretty(&state).unwrap_or_default();
-                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-                let filename = format!("inventory_export_{}.json", timestamp);
-                
-                // Get desktop path or fallback to home
-                let export_path = directories::UserDirs::new()
-                    .and_then(|dirs| dirs.desktop_dir().map(|p| p.to_path_buf()))
-                    .or_else(|| {
-                        directories::UserDirs::new()
-                            .map(|dirs| dirs.home_dir().to_path_buf())
-                    })
-                    .unwrap_or_else(|| std::path::PathBuf::from("."));
-                
-                let file_path = export_path.join(filename);
-                std::fs::write(file_path, json).ok();
-            },
-            |_| Message::Save,
-        );
-        Task::batch(vec![self.auto_save(), task])
-    }
-
     pub fn handle_open_import_file_picker(&mut self) -> Task<Message> {
         self.import_file_picker_open = true;
         Task::perform(
@@ -161,8 +112,8 @@ impl InventoryApp {
                 let import_path = home.join("Downloads").join("inventory_import.json");
                 
                 if let Ok(contents) = std::fs::read_to_string(&import_path) {
-                    if let Ok(state) = serde_json::from_str::<SavedState>(&contents) {
-                        persistence::save_state(&state).await.ok();
+                    if let Ok(state) = persistence::parse_state(&contents) {
+                        persistence::save_state(state).await.ok();
                     }
                 }
             },
@@ -193,142 +144,66 @@ impl InventoryApp {
             }
         };
         
-        let imported_state: SavedState = match serde_json::from_str(&file_contents) {
-            Ok(state) => state,
-            Err(e) => {
-                // Provide more helpful error messages
-                let error_msg = if e.to_string().contains("password_hash") {
-                    format!("Import error: The file format is incompatible. Missing required field 'password_hash'. This may be an old export format. Please export a new file from the current version and try again.")
-                } else {
-                    format!("Invalid JSON format: {}. Please ensure the file is a valid export from this application.", e)
-                };
-                self.import_error = Some(error_msg);
+        // Encrypted files are opened with the data key or the file passphrase
+        let file_contents = match persistence::crypto::decode(&file_contents) {
+            Ok(contents) => contents.into_owned(),
+            Err(LoadError::Locked) if self.file_passphrase_input.is_empty() => {
+                self.import_error = Some("This file is encrypted. Enter its passphrase under File passphrase and import again.".to_string());
                 return Task::none();
             }
-        };
-        
-        // Log import action
-        if let Some(session) = &self.session {
-            let audit_entry = AuditEntry::new(
-                session.user_id.clone(),
-                session.username.clone(),
-                AuditAction::DataImported,
-                "data".to_string(),
-                None,
-                format!("Imported data from {}", path.display()),
-            );
-            self.audit_log.add_entry(audit_entry);
-        }
-        
-        // Merge imported data with existing data
-        // Items: Add items that don't exist (check by ID and SKU to avoid duplicates)
-        let existing_item_ids: HashSet<String> = 
-            self.items.iter().map(|item| item.id.clone()).collect();
-        let existing_skus: HashSet<String> = 
-            self.items.iter().map(|item| item.sku.to_lowercase()).collect();
-        
-        for imported_item in imported_state.items {
-            // Skip if item with same ID already exists
-            if existing_item_ids.contains(&imported_item.id) {
-                continue;
-            }
-            // Skip if item with same SKU already exists (case-insensitive)
-            if existing_skus.contains(&imported_item.sku.to_lowercase()) {
-                continue;
+            Err(LoadError::Locked) => {
+                let opened = persistence::crypto::Envelope::parse(&file_contents)
+                    .ok_or(persistence::crypto::CryptoError::Corrupted)
+                    .and_then(|envelope| {
+                        persistence::crypto::open_with_passphrase(&envelope, &self.file_passphrase_input)
+                    });
+                match opened {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        self.import_error = Some(format!("Could not decrypt the file: {}", e));
+                        return Task::none();
+                    }
+                }
             }
-            // Add the new item
-            self.items.push(imported_item);
-        }
-        self.filtered_items = self.search_filter.apply(&self.items);
-        
-        // Notes: Add notes that don't exist (check by ID)
-        let existing_note_ids: HashSet<String> = 
-            self.notes.iter().map(|note| note.id.clone()).collect();
-        
-        for imported_note in imported_state.notes {
-            if !existing_note_ids.contains(&imported_note.id) {
-                self.notes.push(imported_note);
+            Err(_) => {
+                self.import_error = Some("The encrypted file is damaged.".to_string());
+                return Task::none();
             }
-        }
-        
-        // Settings: Replace with imported settings (user likely wants imported preferences)
-        self.settings = imported_state.settings.clone();
-        
-        // Auth store: Don't merge users from import (security - passwords aren't serialized)
-        // Keep existing users, but ensure default admin exists
-        self.auth_store.ensure_default_admin();
-        
-        // Audit log: Merge entries (append imported entries)
-        // Check existing entry IDs to avoid duplicates
-        let existing_audit_ids: HashSet<String> = 
-            self.audit_log.get_entries().iter().map(|e| e.id.clone()).collect();
-        
-        for entry in imported_state.audit_log.get_entries() {
-            // Only add if entry ID doesn't already exist
-            if !existing_audit_ids.contains(&entry.id) {
-                self.audit_log.add_entry(entry.clone());
+        };
+
+        // Older exports are upgraded to the current format before merging
+        let mut value: serde_json::Value = match serde_json::from_str(&file_contents) {
+            Ok(value) => value,
+            Err(e) => {
+                self.import_error = Some(format!("Invalid JSON format: {}. Please ensure the file is a valid export from this application.", e));
+                return Task::none();
             }
+        };
+        if let Err(e) = persistence::migrate(&mut value) {
+            self.import_error = Some(match e {
+                LoadError::NewerVersion(_) => e.to_string(),
+                _ => "The file is not an export from this application.".to_string(),
+            });
+            return Task::none();
         }
-        
-        // Alert manager: Replace settings, then regenerate alerts from merged inventory
-        *self.alert_manager.settings_mut() = imported_state.alert_manager.settings().clone();
-        self.update_alerts_from_inventory();
-        
-        // UI state: Keep current state (don't import UI preferences)
-        // self.sidebar_collapsed stays as is
-        // self.show_alerts_panel stays as is
-        // self.show_search_panel stays as is
-        // self.current_view stays as is
-        
-        // Update settings inputs
-        self.settings_interval_input = self.settings.auto_save_interval.to_string();
-        self.settings_category_input = self.settings.default_category.clone();
-        self.settings_notification_throttle_input =
-            self.settings.notification_throttle_seconds.to_string();
-        
-        // Update calculator position if present
-        if let Some(pos) = imported_state.calculator_position {
-            self.calculator.set_position(pos.0, pos.1);
-        }
-        
-        // Update note selection: keep current selection if it still exists, otherwise select first note
-        use iced::widget::text_editor;
-        if let Some(ref current_note_id) = self.selected_note_id {
-            // Check if current note still exists after merge
-            if self.notes.iter().any(|n| n.id == *current_note_id) {
-                // Current note still exists, update editor content
-                if let Some(note) = self.notes.iter().find(|n| n.id == *current_note_id) {
-                    self.note_title_input = note.title.clone();
-                    self.editor_content = text_editor::Content::with_text(&note.content);
-                }
-            } else if !self.notes.is_empty() {
-                // Current note doesn't exist, select first note
-                self.selected_note_id = Some(self.notes[0].id.clone());
-                self.note_title_input = self.notes[0].title.clone();
-                self.editor_content = text_editor::Content::with_text(&self.notes[0].content);
-            } else {
-                // No notes exist
-                self.selected_note_id = None;
-                self.note_title_input.clear();
-                self.editor_content = text_editor::Content::new();
+        let imported_state: SavedState = match serde_json::from_value(value) {
+            Ok(state) => state,
+            Err(e) => {
+                self.import_error = Some(format!("The file format is incompatible: {}.", e));
+                return Task::none();
             }
-        } else if !self.notes.is_empty() {
-            // No note was selected, select first note
-            self.selected_note_id = Some(self.notes[0].id.clone());
-            self.note_title_input = self.notes[0].title.clone();
-            self.editor_content = text_editor::Content::with_text(&self.notes[0].content);
-        } else {
-            // No notes exist
-            self.selected_note_id = None;
-            self.note_title_input.clear();
-            self.editor_content = text_editor::Content::new();
-        }
+        };
         
-        // Clear error on success
+        // Nothing is merged until the conflicts have been reviewed
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        self.merge_plan = Some(MergePlan::new(file_name, imported_state, &self.items, &self.notes));
+        self.merge_status = None;
         self.import_error = None;
-        
-        // Save the imported state
-        self.auto_save()
+        self.current_view = View::MergeImport;
+        Task::none()
     }
 
     pub fn handle_clear_all_data(&mut self) {
@@ -357,6 +232,20 @@ impl InventoryApp {
         
         self.items.clear();
         self.notes.clear();
+        self.purchase_orders.clear();
+        self.customers.clear();
+        self.sales_orders.clear();
+        self.stock_movements.clear();
+        self.returns.clear();
+        self.return_draft_lines.clear();
+        self.return_sales_order_id = None;
+        self.stocktakes.clear();
+        self.active_stocktake_id = None;
+        self.sales_draft_lines.clear();
+        self.sales_customer_id = None;
+        self.sales_line_item_id = None;
+        self.reorder_selection.clear();
+        self.reorder_quantity_inputs.clear();
         self.selected_note_id = None;
         self.note_title_input.clear();
         self.editor_content = text_editor::Content::new();
@@ -452,5 +341,45 @@ impl InventoryApp {
     pub fn handle_close_update_notification(&mut self) {
         self.show_update_notification = false;
     }
-}
 
+    pub fn handle_storage_backend_changed(&mut self, backend: StorageBackend) -> Task<Message> {
+        if backend == self.settings.storage_backend {
+            return Task::none();
+        }
+
+        self.storage_status = Some(format!("Copying data to the {}...", backend));
+        let mut state = self.saved_state();
+        state.settings.storage_backend = backend;
+        Task::perform(persistence::switch_backend(state, backend), move |result| {
+            Message::StorageBackendSwitched(result.map(|_| backend).map_err(|e| e.to_string()))
+        })
+    }
+
+    pub fn handle_storage_backend_switched(&mut self, result: Result<StorageBackend, String>) {
+        match result {
+            Ok(backend) => {
+                let previous = self.settings.storage_backend;
+                self.settings.storage_backend = backend;
+                self.storage_status = Some(format!(
+                    "Now storing data in {}",
+                    persistence::data_location().display()
+                ));
+
+                if let Some(session) = &self.session {
+                    let audit_entry = AuditEntry::new(
+                        session.user_id.clone(),
+                        session.username.clone(),
+                        AuditAction::SettingsChanged,
+                        "settings".to_string(),
+                        None,
+                        format!("Switched storage from {} to {}", previous, backend),
+                    );
+                    self.audit_log.add_entry(audit_entry);
+                }
+            }
+            Err(e) => {
+                self.storage_status = Some(format!("Failed to switch storage: {}", e));
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::pdf::PdfWriter;
+use crate::stock::{MovementKind, StockMovement};
+use crate::stocktake::{self, CountScope, CountSession, CountStatus, ScopeKind};
+
+impl InventoryApp {
+    fn active_stocktake_mut(&mut self) -> Option<&mut CountSession> {
+        let id = self.active_stocktake_id.as_ref()?;
+        self.stocktakes
+            .iter_mut()
+            .find(|s| s.id == *id && s.status == CountStatus::InProgress)
+    }
+
+    fn selected_count_scope(&self) -> Option<CountScope> {
+        match self.stocktake_scope_kind {
+            ScopeKind::All => Some(CountScope::All),
+            kind => self
+                .stocktake_scope_value
+                .clone()
+                .map(|value| kind.with_value(value)),
+        }
+    }
+
+    pub fn handle_stocktake_scope_kind_selected(&mut self, kind: ScopeKind) {
+        self.stocktake_scope_kind = kind;
+        self.stocktake_scope_value = None;
+        self.stocktake_message = None;
+    }
+
+    pub fn handle_stocktake_scope_value_selected(&mut self, value: String) {
+        self.stocktake_scope_value = Some(value);
+        self.stocktake_message = None;
+    }
+
+    pub fn handle_start_stocktake(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.stocktake_message = Some("You do not have permission to start a count".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let Some(scope) = self.selected_count_scope() else {
+            self.stocktake_message =
+                Some(format!("Select a {} to count", self.stocktake_scope_kind.to_string().to_lowercase()));
+            return Task::none();
+        };
+
+        let count = CountSession::new(
+            stocktake::next_session_number(&self.stocktakes),
+            scope,
+            &self.items,
+            username.clone(),
+        );
+        if count.lines.is_empty() {
+            self.stocktake_message = Some("No items match the selected scope".to_string());
+            return Task::none();
+        }
+
+        let audit_entry = AuditEntry::new(
+            user_id,
+            username,
+            AuditAction::StocktakeStarted,
+            "stocktake".to_string(),
+            Some(count.id.clone()),
+            format!("Started {} ({}, {} items)", count.number, count.scope, count.lines.len()),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.active_stocktake_id = Some(count.id.clone());
+        self.stocktake_message = None;
+        self.stocktakes.push(count);
+        self.auto_save()
+    }
+
+    pub fn handle_select_stocktake(&mut self, session_id: String) {
+        self.active_stocktake_id = Some(session_id);
+        self.stocktake_scan_input.clear();
+        self.stocktake_message = None;
+    }
+
+    pub fn handle_stocktake_count_changed(&mut self, item_id: String, value: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.stocktake_message = Some("You do not have permission to record counts".to_string());
+            return Task::none();
+        }
+        let value = value.trim().to_string();
+        if !value.chars().all(|c| c.is_ascii_digit()) {
+            return Task::none();
+        }
+        let counted = if value.is_empty() {
+            None
+        } else {
+            match value.parse::<u32>() {
+                Ok(n) => Some(n),
+                Err(_) => return Task::none(),
+            }
+        };
+
+        let Some(count) = self.active_stocktake_mut() else {
+            return Task::none();
+        };
+        if let Some(line) = count.lines.iter_mut().find(|l| l.item_id == item_id) {
+            line.counted = counted;
+            // A changed count has to be reviewed again before posting
+            line.approved = false;
+        }
+        self.auto_save()
+    }
+
+    pub fn handle_stocktake_scan_input_changed(&mut self, value: String) {
+        self.stocktake_scan_input = value;
+    }
+
+    pub fn handle_stocktake_scan_submitted(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.stocktake_message = Some("You do not have permission to record counts".to_string());
+            return Task::none();
+        }
+        let code = self.stocktake_scan_input.trim().to_string();
+        if code.is_empty() {
+            return Task::none();
+        }
+        let Some(count) = self.active_stocktake_mut() else {
+            return Task::none();
+        };
+
+        let result = count.record_scan(&code).map(|line| {
+            format!("{} — {}: {}", line.item_sku, line.item_name, line.counted.unwrap_or(0))
+        });
+        self.stocktake_scan_input.clear();
+        match result {
+            Ok(message) => {
+                self.stocktake_message = Some(message);
+                self.auto_save()
+            }
+            Err(e) => {
+                self.stocktake_message = Some(e);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_toggle_stocktake_approval(&mut self, item_id: String) -> Task<Message> {
+        // Approving adjustments is limited to managers and admins
+        if !self.session.as_ref().is_some_and(|s| s.role.can_delete()) {
+            self.stocktake_message = Some("Only managers can approve count variances".to_string());
+            return Task::none();
+        }
+        let Some(count) = self.active_stocktake_mut() else {
+            return Task::none();
+        };
+        if let Some(line) = count
+            .lines
+            .iter_mut()
+            .find(|l| l.item_id == item_id && l.counted.is_some())
+        {
+            line.approved = !line.approved;
+        }
+        self.auto_save()
+    }
+
+    pub fn handle_approve_all_stocktake_variances(&mut self) -> Task<Message> {
+        if !self.session.as_ref().is_some_and(|s| s.role.can_delete()) {
+            self.stocktake_message = Some("Only managers can approve count variances".to_string());
+            return Task::none();
+        }
+        let Some(count) = self.active_stocktake_mut() else {
+            return Task::none();
+        };
+        for line in count.lines.iter_mut().filter(|l| l.has_variance()) {
+            line.approved = true;
+        }
+        self.auto_save()
+    }
+
+    pub fn handle_post_stocktake(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_delete() {
+            self.stocktake_message = Some("Only managers can post count adjustments".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let Some(index) = self
+            .active_stocktake_id
+            .as_ref()
+            .and_then(|id| self.stocktakes.iter().position(|s| s.id == *id))
+        else {
+            return Task::none();
+        };
+        if self.stocktakes[index].status != CountStatus::InProgress {
+            self.stocktake_message = Some("This count has already been closed".to_string());
+            return Task::none();
+        }
+
+        // Post each approved variance as an adjustment against current stock
+        let mut adjusted = 0;
+        let mut units = 0i64;
+        let mut value = 0.0;
+        let number = self.stocktakes[index].number.clone();
+        for line in self.stocktakes[index].lines.iter().filter(|l| l.approved) {
+            let Some(delta) = line.variance().filter(|d| *d != 0) else {
+                continue;
+            };
+            let Some(item) = self.items.iter_mut().find(|i| i.id == line.item_id) else {
+                continue;
+            };
+            let old_quantity = item.quantity;
+            item.quantity = (item.quantity as i64 + delta).max(0) as u32;
+            item.update_timestamp();
+            self.stock_movements.push(StockMovement::new(
+                item,
+                MovementKind::Adjustment,
+                delta,
+                number.clone(),
+                username.clone(),
+            ));
+
+            let audit_entry = AuditEntry::new(
+                user_id.clone(),
+                username.clone(),
+                AuditAction::StocktakeAdjusted,
+                "item".to_string(),
+                Some(item.id.clone()),
+                format!(
+                    "{}: {} counted {} against {} expected ({:+})",
+                    number,
+                    item.name,
+                    line.counted.unwrap_or(0),
+                    line.expected,
+                    delta
+                ),
+            )
+            .with_values(
+                Some(format!("Qty: {}", old_quantity)),
+                Some(format!("Qty: {}", item.quantity)),
+            );
+            self.audit_log.add_entry(audit_entry);
+
+            adjusted += 1;
+            units += delta;
+            value += line.variance_value();
+        }
+
+        let count = &mut self.stocktakes[index];
+        count.status = CountStatus::Posted;
+        count.posted_at = Some(chrono::Utc::now().timestamp());
+        let audit_entry = AuditEntry::new(
+            user_id,
+            username,
+            AuditAction::StocktakePosted,
+            "stocktake".to_string(),
+            Some(count.id.clone()),
+            format!(
+                "Posted {} ({}): {} of {} items counted, {} adjusted, net {:+} units / {:+.2} value",
+                count.number,
+                count.scope,
+                count.counted_lines(),
+                count.lines.len(),
+                adjusted,
+                units,
+                value
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.stocktake_message = Some(format!("{} posted with {} adjustment(s)", number, adjusted));
+        self.update_alerts_from_inventory();
+        self.auto_save()
+    }
+
+    pub fn handle_cancel_stocktake(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_edit() {
+            self.stocktake_message = Some("You do not have permission to cancel a count".to_string());
+            return Task::none();
+        }
+        let user_id = session.user_id.clone();
+        let username = session.username.clone();
+
+        let Some(count) = self.active_stocktake_mut() else {
+            return Task::none();
+        };
+        count.status = CountStatus::Cancelled;
+        let audit_entry = AuditEntry::new(
+            user_id,
+            username,
+            AuditAction::StocktakeCancelled,
+            "stocktake".to_string(),
+            Some(count.id.clone()),
+            format!(
+                "Cancelled {} ({}, {} of {} items counted)",
+                count.number,
+                count.scope,
+                count.counted_lines(),
+                count.lines.len()
+            ),
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.stocktake_message = None;
+        self.auto_save()
+    }
+
+    pub fn handle_print_blank_count_sheet(&mut self) -> Task<Message> {
+        let Some(scope) = self.selected_count_scope() else {
+            self.stocktake_message =
+                Some(format!("Select a {} to print", self.stocktake_scope_kind.to_string().to_lowercase()));
+            return Task::none();
+        };
+        // Sheets list what to count but never the expected quantities
+        let sheet = CountSession::new(String::new(), scope, &self.items, String::new());
+        if sheet.lines.is_empty() {
+            self.stocktake_message = Some("No items match the selected scope".to_string());
+            return Task::none();
+        }
+
+        let details = format!("Exported blank count sheet ({}, {} items) to PDF", sheet.scope, sheet.lines.len());
+
+        Task::perform(
+            async move {
+                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+                let filename = format!("count_sheet_{}.pdf", timestamp);
+                let file_path = rfd::FileDialog::new()
+                    .set_file_name(&filename)
+                    .add_filter("PDF", &["pdf"])
+                    .save_file();
+
+                let Some(file_path) = file_path else {
+                    return Ok(None);
+                };
+
+                let bytes = render_count_sheet(&sheet)?;
+                std::fs::write(file_path, bytes).map_err(|e| e.to_string())?;
+                Ok(Some(details))
+            },
+            Message::CountSheetExported,
+        )
+    }
+
+    /// Logs a written count sheet; nothing is logged when the save dialog was
+    /// cancelled or the sheet could not be rendered or written
+    pub fn handle_count_sheet_exported(&mut self, result: Result<Option<String>, String>) -> Task<Message> {
+        let details = match result {
+            Ok(Some(details)) => details,
+            Ok(None) => return Task::none(),
+            Err(e) => {
+                self.stocktake_message = Some(format!("Could not export the count sheet: {}", e));
+                return Task::none();
+            }
+        };
+
+        if let Some(session) = &self.session {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::DataExported,
+                "stocktake".to_string(),
+                None,
+                details,
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+        self.auto_save()
+    }
+}
+
+fn render_count_sheet(sheet: &CountSession) -> Result<Vec<u8>, String> {
+    let mut pdf = PdfWriter::new("Count Sheet")?;
+
+    pdf.title("Count Sheet");
+    pdf.text(&format!("Scope: {}", sheet.scope));
+    pdf.text(&format!("Printed: {}", sheet.formatted_timestamp()));
+    pdf.text("Counted by: ______________________    Date: ______________");
+
+    pdf.spacer(4.0);
+    pdf.row(&[("SKU", 0.0), ("Item", 35.0), ("Location", 110.0), ("Counted", 150.0)], true);
+    pdf.rule();
+    for line in &sheet.lines {
+        pdf.row(
+            &[
+                (&line.item_sku, 0.0),
+                (&line.item_name, 35.0),
+                (&line.location, 110.0),
+                ("__________", 150.0),
+            ],
+            false,
+        );
+    }
+    pdf.rule();
+
+    pdf.into_bytes()
+}
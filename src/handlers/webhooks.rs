@@ -0,0 +1,237 @@
+use std::collections::BTreeSet;
+
+use iced::Task;
+use crate::{InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::messages::View;
+use crate::webhooks::{self, Delivery, DeliveryStatus, PendingEvent, Webhook, WebhookEvent};
+
+impl InventoryApp {
+    /// Turns audit entries recorded since the last call, and alerts raised,
+    /// into deliveries for the webhooks that follow them
+    pub(crate) fn queue_webhook_events(&mut self) -> Task<Message> {
+        let mut events: Vec<PendingEvent> = std::mem::take(&mut self.raised_alerts)
+            .iter()
+            .map(|alert| PendingEvent::new(WebhookEvent::AlertRaised, serde_json::to_value(alert).unwrap_or_default()))
+            .collect();
+
+        let entries = self.audit_log.get_entries();
+        let last_id = entries.last().map(|entry| entry.id.clone());
+        if last_id != self.webhook_audit_cursor {
+            // A cursor no longer in the log means the log was replaced; nothing new was recorded
+            let start = match &self.webhook_audit_cursor {
+                None => Some(0),
+                Some(cursor) => entries.iter().position(|entry| entry.id == *cursor).map(|index| index + 1),
+            };
+            if let Some(start) = start {
+                // Entries merged in from another file are older than the last one seen
+                let since = start.checked_sub(1).map_or(i64::MIN, |index| entries[index].timestamp);
+                for entry in entries[start..].iter().filter(|entry| entry.timestamp >= since) {
+                    if let Some(event) = WebhookEvent::from_audit(&entry.action) {
+                        let record = match entry.entity_type.as_str() {
+                            "item" => entry
+                                .entity_id
+                                .as_ref()
+                                .and_then(|id| self.items.iter().find(|item| item.id == *id))
+                                .and_then(|item| serde_json::to_value(item).ok()),
+                            "alert" => entry
+                                .entity_id
+                                .as_ref()
+                                .and_then(|id| self.alert_manager.get_active_alerts().iter().find(|alert| alert.id == *id))
+                                .and_then(|alert| serde_json::to_value(alert).ok()),
+                            _ => None,
+                        };
+                        events.push(PendingEvent::from_audit(event, entry, record));
+                    }
+                }
+            }
+            self.webhook_audit_cursor = last_id;
+        }
+
+        let mut tasks = Vec::new();
+        for pending in &events {
+            for webhook in self.settings.webhooks.iter().filter(|webhook| webhook.wants(pending.event)) {
+                let delivery = Delivery::new(webhook, pending);
+                tasks.push(send_delivery(webhook, &delivery));
+                self.webhook_deliveries.push(delivery);
+            }
+        }
+        self.trim_webhook_log();
+        Task::batch(tasks)
+    }
+
+    /// Drops the oldest finished deliveries once the log is full
+    fn trim_webhook_log(&mut self) {
+        while self.webhook_deliveries.len() > webhooks::LOG_SIZE {
+            let Some(index) = self
+                .webhook_deliveries
+                .iter()
+                .position(|delivery| matches!(delivery.status, DeliveryStatus::Delivered | DeliveryStatus::Failed))
+            else {
+                break;
+            };
+            self.webhook_deliveries.remove(index);
+        }
+    }
+
+    pub fn handle_webhook_delivered(&mut self, delivery_id: String, result: Result<u16, String>) {
+        let now = chrono::Utc::now().timestamp();
+        if let Some(delivery) = self.webhook_deliveries.iter_mut().find(|delivery| delivery.id == delivery_id) {
+            delivery.record(result, now);
+        }
+    }
+
+    /// Sends the deliveries whose next attempt is due
+    pub fn handle_webhook_retry_tick(&mut self) -> Task<Message> {
+        let now = chrono::Utc::now().timestamp();
+        let due: Vec<String> = self
+            .webhook_deliveries
+            .iter()
+            .filter(|delivery| delivery.is_due(now))
+            .map(|delivery| delivery.id.clone())
+            .collect();
+        Task::batch(due.into_iter().map(|delivery_id| self.resend_delivery(&delivery_id)))
+    }
+
+    pub fn handle_retry_webhook_delivery(&mut self, delivery_id: String) -> Task<Message> {
+        self.resend_delivery(&delivery_id)
+    }
+
+    fn resend_delivery(&mut self, delivery_id: &str) -> Task<Message> {
+        let Some(delivery) = self.webhook_deliveries.iter_mut().find(|delivery| delivery.id == delivery_id) else {
+            return Task::none();
+        };
+        let Some(webhook) = self.settings.webhooks.iter().find(|webhook| webhook.id == delivery.webhook_id) else {
+            delivery.status = DeliveryStatus::Failed;
+            delivery.last_result = Some("The webhook was removed".to_string());
+            return Task::none();
+        };
+        delivery.status = DeliveryStatus::Sending;
+        send_delivery(webhook, delivery)
+    }
+
+    pub fn handle_show_webhooks(&mut self) {
+        self.current_view = View::Webhooks;
+        self.webhook_status = None;
+    }
+
+    pub fn handle_webhook_name_changed(&mut self, value: String) {
+        self.webhook_name_input = value;
+    }
+
+    pub fn handle_webhook_url_changed(&mut self, value: String) {
+        self.webhook_url_input = value;
+    }
+
+    pub fn handle_webhook_event_toggled(&mut self, event: WebhookEvent) {
+        if !self.webhook_events.remove(&event) {
+            self.webhook_events.insert(event);
+        }
+    }
+
+    pub fn handle_add_webhook(&mut self) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            self.webhook_status = Some("Only administrators can register webhooks".to_string());
+            return Task::none();
+        }
+        let name = self.webhook_name_input.trim().to_string();
+        let url = self.webhook_url_input.trim().to_string();
+        if name.is_empty() {
+            self.webhook_status = Some("Give the webhook a name".to_string());
+            return Task::none();
+        }
+        if let Err(e) = webhooks::validate_url(&url) {
+            self.webhook_status = Some(e);
+            return Task::none();
+        }
+        if self.webhook_events.is_empty() {
+            self.webhook_status = Some("Choose at least one event to send".to_string());
+            return Task::none();
+        }
+
+        let webhook = Webhook::new(name, url, self.webhook_events.clone());
+        let events: Vec<&str> = webhook.events.iter().map(|event| event.key()).collect();
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "webhook".to_string(),
+            Some(webhook.id.clone()),
+            format!("Registered webhook '{}' to {} for {}", webhook.name, webhook.url, events.join(", ")),
+        );
+        self.audit_log.add_entry(audit_entry);
+
+        self.settings.webhooks.push(webhook);
+        self.webhook_name_input.clear();
+        self.webhook_url_input.clear();
+        self.webhook_events = BTreeSet::new();
+        self.webhook_status = None;
+        self.auto_save()
+    }
+
+    pub fn handle_toggle_webhook(&mut self, webhook_id: String, active: bool) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let Some(webhook) = self.settings.webhooks.iter_mut().find(|webhook| webhook.id == webhook_id) else {
+            return Task::none();
+        };
+        webhook.active = active;
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "webhook".to_string(),
+            Some(webhook.id.clone()),
+            format!("{} webhook '{}'", if active { "Resumed" } else { "Paused" }, webhook.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+
+    pub fn handle_remove_webhook(&mut self, webhook_id: String) -> Task<Message> {
+        let Some(session) = &self.session else {
+            return Task::none();
+        };
+        if !session.role.can_manage_users() {
+            return Task::none();
+        }
+        let Some(index) = self.settings.webhooks.iter().position(|webhook| webhook.id == webhook_id) else {
+            return Task::none();
+        };
+        let webhook = self.settings.webhooks.remove(index);
+
+        let audit_entry = AuditEntry::new(
+            session.user_id.clone(),
+            session.username.clone(),
+            AuditAction::SettingsChanged,
+            "webhook".to_string(),
+            Some(webhook.id),
+            format!("Removed webhook '{}'", webhook.name),
+        );
+        self.audit_log.add_entry(audit_entry);
+        self.auto_save()
+    }
+
+    pub fn handle_copy_webhook_secret(&mut self, webhook_id: String) -> Task<Message> {
+        match self.settings.webhooks.iter().find(|webhook| webhook.id == webhook_id) {
+            Some(webhook) => iced::clipboard::write(webhook.secret.clone()),
+            None => Task::none(),
+        }
+    }
+}
+
+fn send_delivery(webhook: &Webhook, delivery: &Delivery) -> Task<Message> {
+    let delivery_id = delivery.id.clone();
+    Task::perform(
+        webhooks::send(webhook.url.clone(), webhook.secret.clone(), delivery.clone()),
+        move |result| Message::WebhookDelivered(delivery_id.clone(), result),
+    )
+}
@@ -0,0 +1,135 @@
+use iced::Task;
+use crate::{AppState, InventoryApp, Message};
+use crate::audit::{AuditAction, AuditEntry};
+use crate::persistence::{self, WorkspaceList};
+
+impl InventoryApp {
+    /// Logs the current user out before the workspace is left
+    fn end_session_for_workspace_switch(&mut self) {
+        if matches!(self.state, AppState::Login | AppState::Loaded) {
+            self.workspace_data_loaded = true;
+        }
+        if let Some(session) = self.session.take() {
+            let audit_entry = AuditEntry::new(
+                session.user_id.clone(),
+                session.username.clone(),
+                AuditAction::UserLogout,
+                "user".to_string(),
+                Some(session.user_id.clone()),
+                format!("User logged out to switch from workspace '{}'", self.workspaces.active),
+            );
+            self.audit_log.add_entry(audit_entry);
+        }
+    }
+
+    pub fn handle_show_workspaces(&mut self) -> Task<Message> {
+        if persistence::workspace::fixed_data_dir().is_some() {
+            return Task::none();
+        }
+        self.end_session_for_workspace_switch();
+        self.workspace_error = None;
+        self.state = AppState::ChoosingWorkspace;
+        Task::none()
+    }
+
+    pub fn handle_workspace_selected(&mut self, name: String) -> Task<Message> {
+        self.end_session_for_workspace_switch();
+        if name == self.workspaces.active && self.workspace_data_loaded {
+            // Back to the workspace that is already open
+            self.state = AppState::Login;
+            return self.auto_save();
+        }
+
+        self.workspace_error = None;
+        self.state = AppState::Loading;
+        if self.saving.is_some() {
+            // Switched once the save in flight has finished
+            self.pending_workspace = Some(name);
+            return Task::none();
+        }
+        // The open workspace is saved before its folder is let go; no other
+        // save starts meanwhile
+        let current = (self.workspace_data_loaded && !self.read_only).then(|| self.saved_state());
+        self.saving = Some(self.change_count);
+        Task::perform(
+            async move {
+                if let Some(state) = current {
+                    persistence::save_state(state)
+                        .await
+                        .map_err(|e| format!("Could not save the open workspace: {}", e))?;
+                }
+                persistence::switch_workspace(name).await
+            },
+            Message::WorkspaceSwitched,
+        )
+    }
+
+    pub fn handle_workspace_switched(&mut self, result: Result<WorkspaceList, String>) -> Task<Message> {
+        match result {
+            Ok(workspaces) => {
+                let theme = self.settings.theme.clone();
+                *self = Self::empty();
+                self.workspaces = workspaces;
+                self.settings.theme = theme;
+                Self::load_workspace()
+            }
+            Err(e) => {
+                self.saving = None;
+                self.workspace_error = Some(e);
+                self.state = AppState::ChoosingWorkspace;
+                Task::none()
+            }
+        }
+    }
+
+    pub fn handle_new_workspace_name_changed(&mut self, value: String) {
+        self.new_workspace_name_input = value;
+        self.workspace_error = None;
+    }
+
+    pub fn handle_pick_workspace_folder(&mut self) -> Task<Message> {
+        Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a folder for the workspace data")
+                    .pick_folder()
+                    .await
+                    .map(|folder| folder.path().to_path_buf())
+            },
+            Message::WorkspaceFolderPicked,
+        )
+    }
+
+    pub fn handle_workspace_folder_picked(&mut self, folder: Option<std::path::PathBuf>) {
+        if folder.is_some() {
+            self.new_workspace_folder = folder;
+        }
+    }
+
+    pub fn handle_create_workspace(&mut self) -> Task<Message> {
+        let mut workspaces = self.workspaces.clone();
+        if let Err(e) = workspaces.add(&self.new_workspace_name_input, self.new_workspace_folder.clone()) {
+            self.workspace_error = Some(e);
+            return Task::none();
+        }
+        self.new_workspace_name_input.clear();
+        self.new_workspace_folder = None;
+        Task::perform(persistence::save_workspaces(workspaces), Message::WorkspacesSaved)
+    }
+
+    pub fn handle_remove_workspace(&mut self, name: String) -> Task<Message> {
+        let mut workspaces = self.workspaces.clone();
+        if let Err(e) = workspaces.remove(&name) {
+            self.workspace_error = Some(e);
+            return Task::none();
+        }
+        Task::perform(persistence::save_workspaces(workspaces), Message::WorkspacesSaved)
+    }
+
+    pub fn handle_workspaces_saved(&mut self, result: Result<WorkspaceList, String>) {
+        match result {
+            Ok(workspaces) => self.workspaces = workspaces,
+            Err(e) => self.workspace_error = Some(format!("Could not save the workspace list: {}", e)),
+        }
+    }
+}
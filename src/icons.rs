@@ -91,8 +91,11 @@ pub enum Icon {
     Chart,
     Dollar,
     Box,
+    Return,
+    Clipboard,
     Lock,
     Lightbulb,
+    Report,
 }
 
 impl Icon {
@@ -133,8 +136,11 @@ impl Icon {
             Icon::Chart => base.join("misc/chart.svg"),
             Icon::Dollar => base.join("misc/dollar.svg"),
             Icon::Box => base.join("misc/box.svg"),
+            Icon::Return => base.join("misc/box.svg"), // Reuse box icon as placeholder
+            Icon::Clipboard => base.join("misc/chart.svg"), // Reuse chart icon as placeholder
             Icon::Lock => base.join("misc/user.svg"), // Reuse user icon as placeholder
             Icon::Lightbulb => base.join("status/info.svg"), // Reuse info icon
+            Icon::Report => base.join("actions/audit-log.svg"), // Reuse audit log icon
         }
     }
     
@@ -174,8 +180,11 @@ impl Icon {
             Icon::Chart => "CHT",
             Icon::Dollar => "$",
             Icon::Box => "BOX",
+            Icon::Return => "RET",
+            Icon::Clipboard => "CNT",
             Icon::Lock => "LCK",
             Icon::Lightbulb => "*",
+            Icon::Report => "RPT",
         }
     }
     
@@ -5,33 +5,34 @@
 
 use iced::window;
 
-mod alerts;
+mod api;
 mod app_state;
-mod audit;
-mod auth;
 mod calculator;
 mod calculator_window;
-mod currency;
-mod errors;
+mod cli;
 mod handlers;
 mod icon;
 mod icons;
-mod inventory;
 mod messages;
-mod note;
 mod notifications;
-mod persistence;
-mod search;
 mod theme;
 mod update_checker;
-mod user;
 mod views;
 
+use inventory_core::{
+    alerts, analysis, audit, auth, currency, errors, export, forecast, import, inventory, merge, note, pdf,
+    persistence, purchasing, reorder, reports, returns, rules, sales, schedule, scripting, search, spreadsheet,
+    stock, stocktake, user, webhooks,
+};
 use app_state::{AppState, InventoryApp};
 pub use messages::Message;
 
 fn main() -> iced::Result {
     let args: Vec<String> = std::env::args().collect();
+    persistence::configure(persistence::LaunchOptions::from_args(&args));
+    if cli::is_command(&args) {
+        std::process::exit(cli::run(&args));
+    }
     if args.iter().any(|arg| arg == "--calculator") {
         return calculator_window::run();
     }
@@ -47,6 +48,8 @@ fn main() -> iced::Result {
         size: iced::Size::new(1200.0, 800.0),
         min_size: Some(iced::Size::new(900.0, 600.0)),
         icon: icon::load_icon(),
+        // Unsaved changes are written before the window closes
+        exit_on_close_request: false,
         ..Default::default()
     })
     .run_with(InventoryApp::new)
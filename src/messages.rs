@@ -1,9 +1,12 @@
 use iced::widget::text_editor;
 
+pub use inventory_core::persistence::{LoadError, SavedState};
+pub use inventory_core::settings::{AppSettings, AppTheme, InventoryViewMode, LayoutStyle, View};
+
 #[derive(Debug, Clone)]
 pub enum Message {
     // Loading messages
-    Loaded(Result<SavedState, LoadError>),
+    Loaded(Result<Box<SavedState>, LoadError>),
 
     // Inventory messages
     OpenAddDialog,
@@ -13,12 +16,19 @@ pub enum Message {
     SkuChanged(String),
     CategoryChanged(String),
     SupplierChanged(String),
+    LocationChanged(String),
     DescriptionChanged(String),
     QuantityChanged(String),
     PriceChanged(String),
+    ReorderPointChanged(String),
+    ReorderQuantityChanged(String),
+    LeadTimeChanged(String),
     SubmitItem,
     DeleteItem(String), // Changed to use ID
     ExportInventoryCsv,
+    ExportInventorySpreadsheet,
+    /// Format and number of items written, or `None` when cancelled
+    InventorySpreadsheetExported(Result<Option<(crate::spreadsheet::SheetFormat, usize)>, String>),
     InventoryViewModeChanged(InventoryViewMode),
 
     // Editor/Notes messages
@@ -69,6 +79,7 @@ pub enum Message {
     SearchQueryChanged(String),
     CategoryFilterChanged(String),
     SupplierFilterChanged(String),
+    AbcClassFilterChanged(String),
     MinQuantityChanged(String),
     MaxQuantityChanged(String),
     MinPriceChanged(String),
@@ -88,6 +99,80 @@ pub enum Message {
     ToggleAlertsEnabled,
     ToggleAlertNotifications,
 
+    // Reorder worksheet messages
+    ToggleReorderLine(String),
+    ReorderLineQuantityChanged(String, String),
+    SelectAllReorderLines,
+    ClearReorderSelection,
+    CreateDraftPurchaseOrders,
+    ExportReorderCsv,
+    /// Number of supplier files written, or `None` when cancelled
+    ReorderCsvExported(Result<Option<usize>, String>),
+
+    // Sales messages
+    CustomerNameChanged(String),
+    CustomerEmailChanged(String),
+    CustomerPhoneChanged(String),
+    CustomerAddressChanged(String),
+    CreateCustomer,
+    DeleteCustomer(String),
+    SalesCustomerSelected(String),
+    SalesLineItemSelected(String),
+    SalesLineQuantityChanged(String),
+    AddSalesLine,
+    RemoveSalesLine(usize),
+    CreateSalesOrder,
+    FulfillSalesOrder(String),
+    CancelSalesOrder(String),
+    ExportPackingSlip(String),
+    /// Order the slip was written for, or `None` when cancelled
+    PackingSlipExported(Result<Option<String>, String>),
+
+    // Returns messages
+    ReturnSalesOrderSelected(String),
+    ReturnCustomerChanged(String),
+    ReturnReasonSelected(crate::returns::ReturnReason),
+    ReturnNotesChanged(String),
+    ReturnLineItemSelected(String),
+    ReturnLineQuantityChanged(String),
+    ReturnLineDispositionSelected(crate::returns::Disposition),
+    AddReturnLine,
+    RemoveReturnLine(usize),
+    CreateReturn,
+    ExportReturnsReport,
+    /// Number of report rows written, or `None` when cancelled
+    ReturnsReportExported(Result<Option<usize>, String>),
+    QuarantineQuantityChanged(String, String),
+    /// Takes an item's quarantined units out to the given disposition
+    ResolveQuarantine(String, crate::returns::Disposition),
+
+    // Stocktake messages
+    StocktakeScopeKindSelected(crate::stocktake::ScopeKind),
+    StocktakeScopeValueSelected(String),
+    StartStocktake,
+    PrintBlankCountSheet,
+    /// Audit details of a written count sheet, or `None` when cancelled
+    CountSheetExported(Result<Option<String>, String>),
+    SelectStocktake(String),
+    StocktakeCountChanged(String, String),
+    StocktakeScanInputChanged(String),
+    StocktakeScanSubmitted,
+    ToggleStocktakeApproval(String),
+    ApproveAllStocktakeVariances,
+    PostStocktake,
+    CancelStocktake,
+
+    // Analysis messages
+    AbcACutoffChanged(String),
+    AbcBCutoffChanged(String),
+    SlowMovingDaysChanged(String),
+    DeadStockDaysChanged(String),
+    ApplyAnalysisSettings,
+    ExportAbcReport,
+    /// Audit details of a written analysis report, or `None` when cancelled
+    AnalysisReportExported(Result<Option<String>, String>),
+    ExportSlowMovingReport,
+
     // Settings messages
     ToggleAutoSave,
     AutoSaveIntervalChanged(String),
@@ -107,6 +192,173 @@ pub enum Message {
     ClearAllData,
     ConfirmClearAllData,
     CancelClearAllData,
+    StorageBackendChanged(crate::persistence::StorageBackend),
+    StorageBackendSwitched(Result<crate::persistence::StorageBackend, String>),
+
+    // Encryption messages
+    UnlockPassphraseChanged(String),
+    Unlock,
+    Unlocked(Result<(), String>),
+    CurrentPassphraseChanged(String),
+    NewPassphraseChanged(String),
+    ConfirmPassphraseChanged(String),
+    ApplyPassphrase,
+    DisableEncryption,
+    EncryptionUpdated(Result<bool, String>),
+    FilePassphraseChanged(String),
+
+    // Backup messages
+    ShowBackups,
+    BackupsListed(Vec<crate::persistence::BackupInfo>),
+    BackupFrequencyChanged(crate::persistence::BackupFrequency),
+    BackupKeepChanged(String),
+    CreateBackup,
+    BackupCreated(Result<(), String>),
+    RestoreBackup(std::path::PathBuf),
+    BackupRestored(Result<Box<SavedState>, String>),
+    StartFresh,
+
+    // Item import messages
+    ImportItemsFile,
+    ItemsFileRead(Option<Result<crate::import::ImportTable, String>>),
+    ImportColumnMapped(usize, crate::import::ImportField),
+    ImportModeChanged(crate::import::ImportMode),
+    ConfirmItemsImport,
+    CancelItemsImport,
+
+    // Data import preview messages
+    MergeResolutionChanged(crate::merge::RecordKind, usize, crate::merge::Resolution),
+    ResolveAllConflicts(crate::merge::Resolution),
+    MergeOptionToggled(crate::merge::MergeOption),
+    ConfirmMerge,
+    CloseMerge,
+
+    // Report messages
+    GenerateReport(crate::reports::ReportKind),
+    ReportSaved(Result<Option<crate::reports::ReportKind>, String>),
+    ReportFromChanged(String),
+    ReportToChanged(String),
+    ReportCompanyNameChanged(String),
+    ChooseReportLogo,
+    ReportLogoChosen(Option<std::path::PathBuf>),
+    ClearReportLogo,
+
+    // Data export messages
+    ExportEntityToggled(crate::export::ExportEntity),
+    ExportItemScopeChanged(crate::export::ItemScope),
+    ExportItemToggled(String),
+    ExportFromChanged(String),
+    ExportToChanged(String),
+    ExportStripSensitiveToggled(bool),
+    ConfirmExport,
+    CancelExport,
+    DataExported(Result<Option<String>, String>),
+
+    // Scheduled export messages
+    ExportJobsTick,
+    ExportJobNameChanged(String),
+    ExportJobFormatChanged(crate::export::JobFormat),
+    ExportJobFrequencyChanged(crate::schedule::Frequency),
+    ExportJobTimeChanged(String),
+    ExportJobWeekdayChanged(chrono::Weekday),
+    ExportJobKeepChanged(String),
+    ToggleExportJobFilter,
+    ToggleExportJobStripSensitive,
+    PickExportJobFolder,
+    ExportJobFolderPicked(Option<std::path::PathBuf>),
+    AddExportJob,
+    RunExportJob(String),
+    RemoveExportJob(String),
+    ExportJobFinished(String, Result<String, String>),
+
+    // HTTP API messages
+    ApiServerStarted(Result<u16, String>),
+    ApiRequest(crate::api::ApiRequest),
+    ToggleApi(bool),
+    ApiPortChanged(String),
+    ApplyApiPort,
+    ApiTokenNameChanged(String),
+    ApiTokenUserChanged(String),
+    CreateApiToken,
+    CopyApiToken,
+    DismissApiToken,
+    RevokeApiToken(String),
+
+    // Webhook messages
+    ShowWebhooks,
+    WebhookNameChanged(String),
+    WebhookUrlChanged(String),
+    WebhookEventToggled(crate::webhooks::WebhookEvent),
+    AddWebhook,
+    ToggleWebhook(String, bool),
+    RemoveWebhook(String),
+    CopyWebhookSecret(String),
+    WebhookDelivered(String, Result<u16, String>),
+    WebhookRetryTick,
+    RetryWebhookDelivery(String),
+
+    // Rule messages
+    RulesTick,
+    NewRule,
+    EditRule(String),
+    RuleNameChanged(String),
+    RuleTriggerChanged(crate::rules::TriggerKind),
+    RuleFrequencyChanged(crate::schedule::Frequency),
+    RuleTimeChanged(String),
+    RuleWeekdayChanged(chrono::Weekday),
+    RuleConditionChanged(String),
+    RuleDryRunToggled(bool),
+    AddRuleAction,
+    RemoveRuleAction(usize),
+    RuleActionKindChanged(usize, crate::rules::ActionKind),
+    RuleActionFieldChanged(usize, crate::rules::ItemField),
+    RuleActionTargetChanged(usize, String),
+    RuleActionTextChanged(usize, String),
+    PreviewRule,
+    SaveRule,
+    ToggleRule(String, bool),
+    RemoveRule(String),
+
+    // Scripting messages
+    EditComputedColumn(String),
+    ComputedColumnNameChanged(String),
+    ComputedColumnScriptChanged(String),
+    SaveComputedColumn,
+    ClearComputedColumn,
+    RemoveComputedColumn(String),
+    EditReportScript(String),
+    ReportScriptNameChanged(String),
+    ReportScriptAction(text_editor::Action),
+    SaveReportScript,
+    ClearReportScript,
+    RemoveReportScript(String),
+    RunReportDraft,
+    RunReportScript(String),
+
+    // Workspace messages
+    ShowWorkspaces,
+    WorkspaceSelected(String),
+    WorkspaceSwitched(Result<crate::persistence::WorkspaceList, String>),
+    NewWorkspaceNameChanged(String),
+    PickWorkspaceFolder,
+    WorkspaceFolderPicked(Option<std::path::PathBuf>),
+    CreateWorkspace,
+    RemoveWorkspace(String),
+    WorkspacesSaved(Result<crate::persistence::WorkspaceList, String>),
+
+    // Data file messages
+    WorkspaceLocked(Result<(), String>),
+    OpenReadOnly,
+    RetryWorkspaceLock,
+    ReopenForEditing,
+    ReopenedForEditing(Result<(), String>),
+    CheckDataFile,
+    DataFileChecked(bool),
+    ReloadDataFile,
+    DataFileReloaded(Result<Box<SavedState>, LoadError>),
+    MergeDataFile,
+    DataFileMerged(Result<Box<SavedState>, LoadError>),
+    OverwriteDataFile,
 
     // Update messages
     CheckForUpdates,
@@ -118,194 +370,26 @@ pub enum Message {
 
     // App actions
     Save,
+    AutoSaveTick,
+    /// A successful save may carry a warning, such as a failed backup
+    Saved(Result<Option<String>, String>),
+    WindowCloseRequested(iced::window::Id),
     ShowAbout,
     CloseAbout,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum AppTheme {
-    Dark,
-    Light,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum LayoutStyle {
-    Header,
-    Sidebar,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum InventoryViewMode {
-    Cards,
-    Table,
-}
-
-impl Default for InventoryViewMode {
-    fn default() -> Self {
-        InventoryViewMode::Cards
-    }
-}
-
-impl serde::Serialize for InventoryViewMode {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(match self {
-            InventoryViewMode::Cards => "cards",
-            InventoryViewMode::Table => "table",
-        })
-    }
-}
-
-impl<'de> serde::Deserialize<'de> for InventoryViewMode {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(match s.as_str() {
-            "table" => InventoryViewMode::Table,
-            _ => InventoryViewMode::Cards,
-        })
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NoteExportFormat {
     Txt,
     Markdown,
 }
 
-impl Default for LayoutStyle {
-    fn default() -> Self {
-        LayoutStyle::Header
-    }
-}
-
-impl serde::Serialize for LayoutStyle {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(match self {
-            LayoutStyle::Header => "header",
-            LayoutStyle::Sidebar => "sidebar",
-        })
-    }
-}
-
-impl<'de> serde::Deserialize<'de> for LayoutStyle {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(match s.as_str() {
-            "sidebar" => LayoutStyle::Sidebar,
-            _ => LayoutStyle::Header,
-        })
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum ItemDialogMode {
     Add,
     Edit(String), // Store item ID being edited
 }
 
-#[derive(Debug, Clone)]
-pub enum LoadError {
-    FileNotFound,
-    FormatError,
-}
-
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
-pub struct SavedState {
-    pub items: Vec<crate::inventory::InventoryItem>,
-    pub notes: Vec<crate::note::Note>,
-    pub calculator_position: Option<(f32, f32)>,
-    pub settings: AppSettings,
-    #[serde(default)]
-    pub auth_store: crate::auth::AuthStore,
-    #[serde(default)]
-    pub audit_log: crate::audit::AuditLog,
-    #[serde(default)]
-    pub alert_manager: crate::alerts::AlertManager,
-    #[serde(default)]
-    pub sidebar_collapsed: bool,
-    #[serde(default)]
-    pub show_alerts_panel: bool,
-    #[serde(default)]
-    pub show_search_panel: bool,
-    #[serde(default)]
-    pub current_view: View,
-}
-
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct AppSettings {
-    pub auto_save_enabled: bool,
-    pub auto_save_interval: u32, // seconds
-    pub default_category: String,
-    #[serde(default)]
-    pub preferred_currency: String,
-    pub theme: AppTheme,
-    pub show_loading_screen: bool,
-    #[serde(default)]
-    pub layout_style: LayoutStyle,
-    #[serde(default)]
-    pub inventory_view_mode: InventoryViewMode,
-    #[serde(default)]
-    pub device_notifications_enabled: bool,
-    #[serde(default)]
-    pub update_notifications_enabled: bool,
-    #[serde(default)]
-    pub notification_throttle_seconds: u32,
-}
-
-impl Default for AppSettings {
-    fn default() -> Self {
-        Self {
-            auto_save_enabled: true,
-            auto_save_interval: 5,
-            default_category: String::from("General"),
-            preferred_currency: String::from("USD"),
-            theme: AppTheme::Dark,
-            show_loading_screen: true,
-            layout_style: LayoutStyle::default(),
-            inventory_view_mode: InventoryViewMode::default(),
-            device_notifications_enabled: true,
-            update_notifications_enabled: true,
-            notification_throttle_seconds: 30,
-        }
-    }
-}
-
-impl serde::Serialize for AppTheme {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(match self {
-            AppTheme::Dark => "dark",
-            AppTheme::Light => "light",
-        })
-    }
-}
-
-impl<'de> serde::Deserialize<'de> for AppTheme {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(match s.as_str() {
-            "light" => AppTheme::Light,
-            _ => AppTheme::Dark,
-        })
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum CalculatorOp {
     Add,
@@ -314,51 +398,3 @@ pub enum CalculatorOp {
     Divide,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum View {
-    Inventory,
-    Editor,
-    Settings,
-    UserManagement,
-    AuditLog,
-    Alerts,
-}
-
-impl Default for View {
-    fn default() -> Self {
-        View::Inventory
-    }
-}
-
-impl serde::Serialize for View {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(match self {
-            View::Inventory => "inventory",
-            View::Editor => "editor",
-            View::Settings => "settings",
-            View::UserManagement => "user_management",
-            View::AuditLog => "audit_log",
-            View::Alerts => "alerts",
-        })
-    }
-}
-
-impl<'de> serde::Deserialize<'de> for View {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Ok(match s.as_str() {
-            "editor" => View::Editor,
-            "settings" => View::Settings,
-            "user_management" => View::UserManagement,
-            "audit_log" => View::AuditLog,
-            "alerts" => View::Alerts,
-            _ => View::Inventory,
-        })
-    }
-}
@@ -1,4 +1,5 @@
 use iced::Color;
+use crate::alerts::AlertType;
 use crate::messages::AppTheme;
 
 // ============================================================================
@@ -203,6 +204,17 @@ pub fn stock_status_color(status: &str, theme: &AppTheme) -> Color {
     }
 }
 
+// Alert colors
+pub fn alert_color(alert_type: &AlertType) -> Color {
+    match alert_type {
+        AlertType::OutOfStock => Color::from_rgb(0.9, 0.3, 0.3),
+        AlertType::LowStock => Color::from_rgb(0.9, 0.7, 0.3),
+        AlertType::CriticallyLow => Color::from_rgb(0.9, 0.5, 0.2),
+        AlertType::StockoutRisk => Color::from_rgb(0.9, 0.8, 0.3),
+        AlertType::Rule(_) => Color::from_rgb(0.4, 0.6, 0.9),
+    }
+}
+
 // Category color helpers (return different colors for different categories)
 pub fn category_color(category: &str, theme: &AppTheme) -> Color {
     let hash = category.bytes().fold(0u32, |acc, b| acc.wrapping_add(b as u32));
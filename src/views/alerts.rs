@@ -194,10 +194,12 @@ fn build_alert_card<'a>(alert: &'a StockAlert, theme: &'a crate::messages::AppTh
         crate::alerts::AlertType::OutOfStock => icons::Icon::XCircle,
         crate::alerts::AlertType::LowStock => icons::Icon::AlertCircle,
         crate::alerts::AlertType::CriticallyLow => icons::Icon::AlertTriangle,
+        crate::alerts::AlertType::StockoutRisk => icons::Icon::Chart,
+        crate::alerts::AlertType::Rule(_) => icons::Icon::Lightbulb,
     };
     let icon_widget = alert_icon.view_with_color(
         icons::IconSize::Large,
-        Some(alert.alert_type.color()),
+        Some(theme::alert_color(&alert.alert_type)),
         theme,
     );
 
@@ -210,7 +212,7 @@ fn build_alert_card<'a>(alert: &'a StockAlert, theme: &'a crate::messages::AppTh
     )
     .padding(5)
     .style(move |_theme: &iced::Theme| container::Style {
-        background: Some(iced::Background::Color(alert.alert_type.color())),
+        background: Some(iced::Background::Color(theme::alert_color(&alert.alert_type))),
         border: iced::Border {
             radius: 3.0.into(),
             ..Default::default()
@@ -261,7 +263,13 @@ fn build_alert_card<'a>(alert: &'a StockAlert, theme: &'a crate::messages::AppTh
                 text("").size(5),
                 row![
                     text(format!("Current Stock: {} items", alert.current_quantity)).size(14),
-                    text(format!("Threshold: {} items", alert.threshold))
+                    text(match alert.alert_type {
+                        crate::alerts::AlertType::StockoutRisk => {
+                            format!("Lead time: {} days", alert.threshold)
+                        }
+                        crate::alerts::AlertType::Rule(_) => "Raised by a rule".to_string(),
+                        _ => format!("Threshold: {} items", alert.threshold),
+                    })
                         .size(12)
                         .style(move |_iced_theme: &iced::Theme| iced::widget::text::Style {
                             color: Some(crate::theme::text_secondary_color(theme)),
@@ -0,0 +1,243 @@
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::analysis::{AbcClass, AbcRow, Movement, SlowMovingRow};
+use crate::currency;
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::views::sales::{muted, panel_style};
+
+/// Current contents of the analysis settings inputs
+pub struct AnalysisForm<'a> {
+    pub a_cutoff: &'a str,
+    pub b_cutoff: &'a str,
+    pub slow_moving_days: &'a str,
+    pub dead_stock_days: &'a str,
+    pub error: Option<&'a str>,
+}
+
+pub fn view<'a>(
+    abc_rows: Vec<AbcRow>,
+    slow_rows: Vec<SlowMovingRow>,
+    form: AnalysisForm<'a>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Chart.view(icons::IconSize::Large, app_theme),
+        text("Inventory Analysis").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+
+    if let Some(error) = form.error {
+        content = content.push(
+            text(error)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::danger_color(app_theme)),
+                }),
+        );
+    }
+
+    content = content
+        .push(build_settings(&form, app_theme))
+        .push(build_abc(abc_rows, currency_code, app_theme))
+        .push(build_slow_moving(slow_rows, currency_code, app_theme));
+
+    scrollable(content).into()
+}
+
+fn build_settings<'a>(form: &AnalysisForm<'a>, app_theme: &'a AppTheme) -> Element<'a, Message> {
+    let field = |label: &'a str, value: &'a str, on_input: fn(String) -> Message| {
+        column![
+            text(label).size(14),
+            text_input("", value)
+                .on_input(on_input)
+                .on_submit(Message::ApplyAnalysisSettings)
+                .padding(8)
+                .width(Length::Fixed(120.0)),
+        ]
+        .spacing(5)
+    };
+
+    container(
+        column![
+            text("Settings").size(20),
+            row![
+                field("Class A up to (%)", form.a_cutoff, Message::AbcACutoffChanged),
+                field("Class B up to (%)", form.b_cutoff, Message::AbcBCutoffChanged),
+                field("Slow-moving after (days)", form.slow_moving_days, Message::SlowMovingDaysChanged),
+                field("Dead stock after (days)", form.dead_stock_days, Message::DeadStockDaysChanged),
+                button("Apply").on_press(Message::ApplyAnalysisSettings).padding(8),
+            ]
+            .spacing(20)
+            .align_y(iced::Alignment::End),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+    .into()
+}
+
+fn build_abc<'a>(
+    rows: Vec<AbcRow>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut export_button = button("Export CSV").padding(6);
+    if !rows.is_empty() {
+        export_button = export_button.on_press(Message::ExportAbcReport);
+    }
+
+    let total: f64 = rows.iter().map(|r| r.consumption_value).sum();
+    let mut summary = row![].spacing(theme::SPACING_XL);
+    for class in AbcClass::ALL {
+        let class_rows: Vec<&AbcRow> = rows.iter().filter(|r| r.class == class).collect();
+        let value: f64 = class_rows.iter().map(|r| r.consumption_value).sum();
+        let share = if total > 0.0 { value / total * 100.0 } else { 0.0 };
+        summary = summary.push(
+            column![
+                text(format!("Class {}", class)).size(theme::TEXT_CAPTION),
+                text(format!("{} items", class_rows.len())).size(theme::TEXT_H3),
+                text(format!(
+                    "{} ({:.1}%)",
+                    currency::format_currency_with_exp(value, currency_code),
+                    share
+                ))
+                .size(theme::TEXT_CAPTION),
+            ]
+            .spacing(theme::SPACING_XS),
+        );
+    }
+
+    let mut report = column![
+        row![text("ABC Analysis").size(20), export_button]
+            .spacing(20)
+            .align_y(iced::Alignment::Center),
+        muted("Ranked by consumption value (units issued in the last 365 days × price)", app_theme),
+        summary,
+        row![
+            text("Class").size(theme::TEXT_CAPTION).width(Length::Fixed(50.0)),
+            text("SKU").size(theme::TEXT_CAPTION).width(Length::FillPortion(1)),
+            text("Item").size(theme::TEXT_CAPTION).width(Length::FillPortion(2)),
+            text("Units").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Value").size(theme::TEXT_CAPTION).width(Length::Fixed(120.0)),
+            text("Cumulative").size(theme::TEXT_CAPTION).width(Length::Fixed(90.0)),
+        ]
+        .spacing(theme::SPACING_LG),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    if rows.is_empty() {
+        report = report.push(muted("No items to analyse", app_theme));
+    }
+
+    for row_data in rows {
+        report = report.push(
+            row![
+                text(row_data.class.to_string())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(50.0)),
+                text(row_data.item_sku).size(theme::TEXT_BODY).width(Length::FillPortion(1)),
+                text(row_data.item_name).size(theme::TEXT_BODY).width(Length::FillPortion(2)),
+                text(row_data.units).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+                text(currency::format_currency_with_exp(row_data.consumption_value, currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(120.0)),
+                text(format!("{:.1}%", row_data.cumulative_share))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(90.0)),
+            ]
+            .spacing(theme::SPACING_LG),
+        );
+    }
+
+    container(report.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
+
+fn build_slow_moving<'a>(
+    rows: Vec<SlowMovingRow>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut export_button = button("Export CSV").padding(6);
+    if !rows.is_empty() {
+        export_button = export_button.on_press(Message::ExportSlowMovingReport);
+    }
+
+    let tied_up = |status: Movement| -> f64 {
+        rows.iter().filter(|r| r.status == status).map(|r| r.value).sum()
+    };
+    let summary = text(format!(
+        "Capital tied up: {} slow-moving, {} dead stock",
+        currency::format_currency_with_exp(tied_up(Movement::SlowMoving), currency_code),
+        currency::format_currency_with_exp(tied_up(Movement::Dead), currency_code),
+    ))
+    .size(theme::TEXT_BODY);
+
+    let mut report = column![
+        row![text("Slow-moving and Dead Stock").size(20), export_button]
+            .spacing(20)
+            .align_y(iced::Alignment::Center),
+        summary,
+        row![
+            text("Status").size(theme::TEXT_CAPTION).width(Length::Fixed(100.0)),
+            text("SKU").size(theme::TEXT_CAPTION).width(Length::FillPortion(1)),
+            text("Item").size(theme::TEXT_CAPTION).width(Length::FillPortion(2)),
+            text("Last Outbound").size(theme::TEXT_CAPTION).width(Length::Fixed(100.0)),
+            text("Idle").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Qty").size(theme::TEXT_CAPTION).width(Length::Fixed(60.0)),
+            text("Value").size(theme::TEXT_CAPTION).width(Length::Fixed(120.0)),
+        ]
+        .spacing(theme::SPACING_LG),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    if rows.is_empty() {
+        report = report.push(muted("All stocked items have moved recently", app_theme));
+    }
+
+    for row_data in rows {
+        let status_color = match row_data.status {
+            Movement::Dead => theme::danger_color(app_theme),
+            Movement::SlowMoving => theme::text_secondary_color(app_theme),
+        };
+        report = report.push(
+            row![
+                text(row_data.status.to_string())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(100.0))
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(status_color),
+                    }),
+                text(row_data.item_sku.clone()).size(theme::TEXT_BODY).width(Length::FillPortion(1)),
+                text(row_data.item_name.clone()).size(theme::TEXT_BODY).width(Length::FillPortion(2)),
+                text(row_data.formatted_last_outbound())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(100.0)),
+                text(format!("{}d", row_data.days_idle))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(70.0)),
+                text(row_data.quantity).size(theme::TEXT_BODY).width(Length::Fixed(60.0)),
+                text(currency::format_currency_with_exp(row_data.value, currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(120.0)),
+            ]
+            .spacing(theme::SPACING_LG),
+        );
+    }
+
+    container(report.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
@@ -0,0 +1,224 @@
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::persistence::{BackupFrequency, BackupInfo, BackupSettings};
+use crate::theme;
+use crate::user::UserRole;
+use crate::views::sales::{muted, panel_style};
+
+pub fn view<'a>(
+    backups: Option<&'a [BackupInfo]>,
+    settings: &'a BackupSettings,
+    keep_input: &'a str,
+    status: Option<&'a str>,
+    restoring: bool,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Save.view(icons::IconSize::Large, app_theme),
+        text("Backups").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let settings_panel = container(
+        column![
+            text("Automatic Backups").size(20),
+            row![
+                column![
+                    text("Frequency").size(14),
+                    pick_list(
+                        BackupFrequency::ALL,
+                        Some(settings.frequency),
+                        Message::BackupFrequencyChanged
+                    )
+                    .width(Length::Fixed(150.0)),
+                ]
+                .spacing(5),
+                column![
+                    text("Backups to keep").size(14),
+                    text_input("", keep_input)
+                        .on_input(Message::BackupKeepChanged)
+                        .padding(8)
+                        .width(Length::Fixed(120.0)),
+                ]
+                .spacing(5),
+                button("Back Up Now").on_press(Message::CreateBackup).padding(8),
+            ]
+            .spacing(20)
+            .align_y(iced::Alignment::End),
+            muted(
+                "A snapshot is taken on the first save after each interval. Older backups are deleted.",
+                app_theme
+            ),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme));
+
+    let can_restore = role.can_manage_users() && !restoring;
+    let mut list = column![
+        text("Restore from Backup").size(20),
+        muted(
+            "Restoring replaces all current data. The current data is backed up first, and you will need to log in again.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_SM);
+    if !role.can_manage_users() {
+        list = list.push(muted("Only administrators can restore backups", app_theme));
+    }
+    list = list.push(backup_list(backups, can_restore, app_theme));
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+    if let Some(status) = status {
+        content = content.push(text(status).size(theme::TEXT_BODY));
+    }
+    content = content.push(settings_panel).push(
+        container(list.padding(15))
+            .width(Length::Fill)
+            .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+    );
+
+    scrollable(content).into()
+}
+
+/// Shown instead of the login screen when the data file cannot be read
+pub fn view_recovery<'a>(
+    reason: &'a str,
+    backups: Option<&'a [BackupInfo]>,
+    status: Option<&'a str>,
+    restoring: bool,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let latest = backups.and_then(|list| list.iter().find(|b| !b.unreadable));
+
+    let mut card = column![
+        row![
+            icons::Icon::AlertTriangle.view(icons::IconSize::Large, app_theme),
+            text("Your data could not be opened").size(theme::TEXT_H2),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        muted(reason, app_theme),
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(theme::SPACING_3XL)
+    .width(700);
+
+    match (backups, latest) {
+        (None, _) => card = card.push(muted("Looking for backups...", app_theme)),
+        (Some(_), Some(latest)) => {
+            let mut restore = button(text(format!(
+                "Restore Latest Backup ({})",
+                latest.formatted_timestamp()
+            )))
+            .padding(theme::SPACING_MD);
+            if !restoring {
+                restore = restore.on_press(Message::RestoreBackup(latest.path.clone()));
+            }
+            card = card.push(restore);
+        }
+        (Some(_), None) => {
+            card = card.push(muted("No usable backups were found.", app_theme));
+        }
+    }
+
+    if let Some(status) = status {
+        card = card.push(text(status).size(theme::TEXT_BODY));
+    }
+
+    if backups.is_some_and(|list| list.len() > 1) {
+        card = card
+            .push(text("Other Backups").size(theme::TEXT_H3))
+            .push(backup_list(backups, !restoring, app_theme));
+    }
+
+    let mut start_fresh = button("Start Fresh").padding(theme::SPACING_MD);
+    if !restoring {
+        start_fresh = start_fresh.on_press(Message::StartFresh);
+    }
+    card = card
+        .push(start_fresh)
+        .push(muted("Starting fresh keeps the current data file next to the new one.", app_theme));
+
+    container(
+        container(scrollable(card)).style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .style(move |_theme: &iced::Theme| container::Style {
+        background: Some(iced::Background::Color(theme::bg_color(app_theme))),
+        ..Default::default()
+    })
+    .into()
+}
+
+fn backup_list<'a>(
+    backups: Option<&'a [BackupInfo]>,
+    can_restore: bool,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let Some(backups) = backups else {
+        return muted("Loading backups...", app_theme);
+    };
+    if backups.is_empty() {
+        return muted("No backups yet", app_theme);
+    }
+
+    let mut list = column![
+        row![
+            text("Created").size(theme::TEXT_CAPTION).width(Length::Fixed(150.0)),
+            text("Items").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Notes").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Users").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+        ]
+        .spacing(theme::SPACING_LG),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    for backup in backups {
+        let details: Element<'a, Message> = if backup.unreadable {
+            text("Unreadable")
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::danger_color(app_theme)),
+                })
+                .into()
+        } else {
+            row![
+                text(backup.items).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+                text(backup.notes).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+                text(backup.users).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+            ]
+            .spacing(theme::SPACING_LG)
+            .into()
+        };
+
+        let mut restore = button("Restore").padding(6);
+        if can_restore && !backup.unreadable {
+            restore = restore.on_press(Message::RestoreBackup(backup.path.clone()));
+        }
+
+        list = list.push(
+            row![
+                text(backup.formatted_timestamp())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(150.0)),
+                details,
+                restore,
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    list.into()
+}
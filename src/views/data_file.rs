@@ -0,0 +1,107 @@
+use iced::widget::{button, column, container, row, text};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::views::sales::{muted, panel_style};
+
+/// Shown when another instance has the workspace open
+pub fn view_in_use<'a>(
+    owner: &'a str,
+    can_switch_workspace: bool,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut actions = row![
+        button("Open Read-Only").on_press(Message::OpenReadOnly).padding(theme::SPACING_MD),
+        button("Try Again").on_press(Message::RetryWorkspaceLock).padding(theme::SPACING_MD),
+    ]
+    .spacing(theme::SPACING_SM);
+    if can_switch_workspace {
+        actions = actions.push(
+            button("Choose Another Workspace")
+                .on_press(Message::ShowWorkspaces)
+                .padding(theme::SPACING_MD),
+        );
+    }
+
+    let card = column![
+        row![
+            icons::Icon::Lock.view(icons::IconSize::Large, app_theme),
+            text("This data is already open").size(theme::TEXT_H2),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        text(format!("In use by {}.", owner)).size(theme::TEXT_BODY),
+        muted(
+            "Saving from two windows at once would overwrite one window's changes. You can browse the data read-only, or close the other window and try again.",
+            app_theme
+        ),
+        actions,
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(theme::SPACING_3XL)
+    .width(600);
+
+    container(container(card).style(move |_iced_theme: &iced::Theme| panel_style(app_theme)))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .style(move |_theme: &iced::Theme| container::Style {
+            background: Some(iced::Background::Color(theme::bg_color(app_theme))),
+            ..Default::default()
+        })
+        .into()
+}
+
+/// Strip above the content for read-only mode and changes made elsewhere
+pub fn banner<'a>(
+    read_only: bool,
+    changed: bool,
+    status: Option<&'a str>,
+    app_theme: &'a AppTheme,
+) -> Option<Element<'a, Message>> {
+    let mut content = row![].spacing(theme::SPACING_SM).align_y(iced::Alignment::Center);
+    if changed {
+        content = content
+            .push(icons::Icon::AlertTriangle.view(icons::IconSize::Small, app_theme))
+            .push(text("The data file was changed outside this window.").size(theme::TEXT_BODY))
+            .push(iced::widget::horizontal_space())
+            .push(button("Reload").on_press(Message::ReloadDataFile).padding(6));
+        if !read_only {
+            content = content
+                .push(button("Merge").on_press(Message::MergeDataFile).padding(6))
+                .push(button("Keep Mine").on_press(Message::OverwriteDataFile).padding(6));
+        }
+    } else if read_only {
+        content = content
+            .push(icons::Icon::Lock.view(icons::IconSize::Small, app_theme))
+            .push(
+                text("Read-only: this workspace is open in another window. Changes are not saved.")
+                    .size(theme::TEXT_BODY),
+            )
+            .push(iced::widget::horizontal_space())
+            .push(button("Edit Here").on_press(Message::ReopenForEditing).padding(6));
+    } else if let Some(status) = status {
+        content = content.push(muted(status, app_theme));
+    } else {
+        return None;
+    }
+    if (changed || read_only)
+        && let Some(status) = status
+    {
+        content = content.push(muted(status, app_theme));
+    }
+
+    Some(
+        container(content)
+            .padding([theme::SPACING_SM, theme::SPACING_LG])
+            .width(Length::Fill)
+            .style(move |_theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(theme::surface_elevated_color(app_theme))),
+                ..Default::default()
+            })
+            .into(),
+    )
+}
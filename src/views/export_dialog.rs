@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use iced::widget::{button, checkbox, column, container, pick_list, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+
+use crate::export::{ExportEntity, ExportOptions, ItemScope};
+use crate::inventory::InventoryItem;
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::views::sales::muted;
+
+pub fn view<'a>(
+    options: &'a ExportOptions,
+    items: &'a [InventoryItem],
+    selection: &'a HashSet<String>,
+    from: &'a str,
+    to: &'a str,
+    status: Option<&'a str>,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut entities = Column::new().spacing(6);
+    for pair in ExportEntity::ALL.chunks(2) {
+        let mut line = row![].spacing(theme::SPACING_LG);
+        for &entity in pair {
+            line = line.push(
+                checkbox(entity.to_string(), options.includes(entity))
+                    .on_toggle(move |_| Message::ExportEntityToggled(entity))
+                    .width(Length::Fixed(200.0)),
+            );
+        }
+        entities = entities.push(line);
+    }
+
+    let mut scope = column![
+        row![
+            text("Items to include").size(14).width(Length::Fixed(140.0)),
+            pick_list(ItemScope::ALL, Some(options.item_scope), Message::ExportItemScopeChanged)
+                .width(Length::Fixed(200.0)),
+        ]
+        .spacing(theme::SPACING_LG)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(theme::SPACING_SM);
+    match options.item_scope {
+        ItemScope::All => {}
+        ItemScope::Filtered => {
+            scope = scope.push(muted("The items shown on the inventory page with its current search and filters", app_theme));
+        }
+        ItemScope::Selected => {
+            let mut list = Column::new().spacing(4);
+            for item in items {
+                let id = item.id.clone();
+                list = list.push(
+                    checkbox(format!("{} ({})", item.name, item.sku), selection.contains(&item.id))
+                        .on_toggle(move |_| Message::ExportItemToggled(id.clone())),
+                );
+            }
+            scope = scope
+                .push(muted("Choose from the items on the inventory page", app_theme))
+                .push(scrollable(list).height(Length::Fixed(160.0)));
+        }
+    }
+
+    let dates = row![
+        text("Audit and alerts from").size(14),
+        text_input("YYYY-MM-DD", from)
+            .on_input(Message::ExportFromChanged)
+            .padding(6)
+            .width(Length::Fixed(120.0)),
+        text("to").size(14),
+        text_input("YYYY-MM-DD", to)
+            .on_input(Message::ExportToChanged)
+            .padding(6)
+            .width(Length::Fixed(120.0)),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let strip = column![
+        checkbox("Remove sensitive data", options.strip_sensitive).on_toggle(Message::ExportStripSensitiveToggled),
+        muted(
+            "Leaves out user accounts, who made each change, and all prices, for sharing with outside parties.",
+            app_theme
+        ),
+    ]
+    .spacing(4);
+
+    let mut content = column![
+        text("Export Data").size(24),
+        text("Include").size(16),
+        entities,
+        scope,
+        dates,
+        muted("Leave a date empty for no limit. Dates are UTC.", app_theme),
+        strip,
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(30);
+    if let Some(status) = status {
+        content = content.push(text(status).size(14).color(theme::danger_color(app_theme)));
+    }
+    content = content.push(
+        row![
+            button("Export...").on_press(Message::ConfirmExport).padding(10),
+            button("Cancel").on_press(Message::CancelExport).padding(10),
+        ]
+        .spacing(10),
+    );
+
+    container(
+        container(scrollable(content))
+            .width(520)
+            .max_height(640)
+            .style(move |_iced_theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(theme::surface_elevated_color(app_theme))),
+                border: iced::Border {
+                    color: theme::border_color(app_theme),
+                    width: 1.0,
+                    radius: 10.0.into(),
+                },
+                ..Default::default()
+            }),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| container::Style {
+        background: Some(iced::Background::Color(iced::Color::from_rgba(
+            theme::surface_color(app_theme).r,
+            theme::surface_color(app_theme).g,
+            theme::surface_color(app_theme).b,
+            0.7,
+        ))),
+        ..Default::default()
+    })
+    .into()
+}
@@ -0,0 +1,177 @@
+use iced::widget::{button, column, container, pick_list, row, scrollable, text};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::import::{ImportField, ImportMode, ImportSummary, ImportTable, RowAction, RowPreview};
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::views::sales::{muted, panel_style};
+
+/// Rows shown in the preview; the rest are summarised
+const PREVIEW_ROWS: usize = 200;
+
+pub fn view<'a>(
+    table: Option<&'a ImportTable>,
+    mapping: &'a [ImportField],
+    mode: ImportMode,
+    preview: &'a [RowPreview],
+    status: Option<&'a str>,
+    can_edit: bool,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Clipboard.view(icons::IconSize::Large, app_theme),
+        text("Import Items").size(28),
+        iced::widget::horizontal_space(),
+        button("Back to Inventory").on_press(Message::CancelItemsImport).padding(8),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+    if let Some(status) = status {
+        content = content.push(text(status).size(theme::TEXT_BODY));
+    }
+
+    let Some(table) = table else {
+        let mut choose = button("Choose File...").padding(8);
+        if can_edit {
+            choose = choose.on_press(Message::ImportItemsFile);
+        }
+        return content
+            .push(muted(
+                "Import items from a CSV or TSV file, or from the first sheet of an Excel or OpenDocument workbook. The delimiter and text encoding of text files are detected automatically.",
+                app_theme,
+            ))
+            .push(choose)
+            .into();
+    };
+
+    let mut columns = column![
+        text("Columns").size(20),
+        muted(
+            "Choose the item field each column goes into. Rows are matched to existing items by SKU.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_SM);
+    for (index, header) in table.headers.iter().enumerate() {
+        let sample = table
+            .rows
+            .iter()
+            .filter_map(|row| row.get(index))
+            .find(|value| !value.trim().is_empty())
+            .map(String::as_str)
+            .unwrap_or_default();
+        columns = columns.push(
+            row![
+                text(header).size(theme::TEXT_BODY).width(Length::Fixed(200.0)),
+                pick_list(
+                    ImportField::ALL,
+                    mapping.get(index).copied(),
+                    move |field| Message::ImportColumnMapped(index, field)
+                )
+                .width(Length::Fixed(200.0)),
+                muted(sample, app_theme),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let summary = ImportSummary::of(preview);
+    let errors = preview.iter().filter(|row| !row.errors.is_empty()).count();
+    let mut import_button = button(text(format!(
+        "Import ({} new, {} updated)",
+        summary.created, summary.updated
+    )))
+    .padding(8);
+    if can_edit && summary.created + summary.updated > 0 {
+        import_button = import_button.on_press(Message::ConfirmItemsImport);
+    }
+    let options = column![
+        row![
+            text("Existing SKUs:").size(14),
+            pick_list(ImportMode::ALL, Some(mode), Message::ImportModeChanged).width(Length::Fixed(360.0)),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        text(format!(
+            "Dry run: {}. {} rows have errors and will be skipped.",
+            summary, errors
+        ))
+        .size(theme::TEXT_BODY),
+        row![
+            import_button,
+            button("Cancel").on_press(Message::CancelItemsImport).padding(8),
+        ]
+        .spacing(theme::SPACING_SM),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    content = content
+        .push(text(format!(
+            "{} ({}, {} rows)",
+            table.file_name,
+            table.format,
+            table.rows.len()
+        )))
+        .push(
+            container(column![columns, options].spacing(theme::SPACING_XL).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        )
+        .push(
+            container(preview_table(preview, app_theme).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+
+    scrollable(content).into()
+}
+
+fn preview_table<'a>(preview: &'a [RowPreview], app_theme: &'a AppTheme) -> iced::widget::Column<'a, Message> {
+    let mut list = column![
+        text("Preview").size(20),
+        row![
+            text("Line").size(theme::TEXT_CAPTION).width(Length::Fixed(50.0)),
+            text("SKU").size(theme::TEXT_CAPTION).width(Length::Fixed(140.0)),
+            text("Name").size(theme::TEXT_CAPTION).width(Length::Fixed(220.0)),
+            text("Action").size(theme::TEXT_CAPTION).width(Length::Fixed(80.0)),
+            text("Problems").size(theme::TEXT_CAPTION),
+        ]
+        .spacing(theme::SPACING_LG),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    for row_preview in preview.iter().take(PREVIEW_ROWS) {
+        let action = match row_preview.action {
+            RowAction::Create => "Create",
+            RowAction::Update(_) => "Update",
+            RowAction::Skip => "Skip",
+        };
+        list = list.push(
+            row![
+                text(row_preview.line).size(theme::TEXT_BODY).width(Length::Fixed(50.0)),
+                text(&row_preview.values.sku).size(theme::TEXT_BODY).width(Length::Fixed(140.0)),
+                text(row_preview.values.name.as_deref().unwrap_or_default())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(220.0)),
+                text(action).size(theme::TEXT_BODY).width(Length::Fixed(80.0)),
+                text(row_preview.errors.join("; "))
+                    .size(theme::TEXT_BODY)
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(theme::danger_color(app_theme)),
+                    }),
+            ]
+            .spacing(theme::SPACING_LG),
+        );
+    }
+    if preview.len() > PREVIEW_ROWS {
+        list = list.push(text(format!(
+            "... and {} more rows",
+            preview.len() - PREVIEW_ROWS
+        )).size(theme::TEXT_BODY));
+    }
+    list
+}
@@ -1,22 +1,34 @@
 use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input, Column};
 use iced::{Color, Element, Length};
 
+use crate::analysis::AbcClass;
 use crate::inventory::InventoryItem;
-use crate::messages::{AppTheme, InventoryViewMode, Message};
+use crate::messages::{AppSettings, AppTheme, InventoryViewMode, Message};
+use crate::scripting::{self, ComputedColumn, ComputedValues};
 use crate::search::{SearchFilter, SortField};
 use crate::theme;
 use crate::icons;
 use crate::currency;
 
+/// Current search filter and whether its panel is open
+pub struct SearchPanel<'a> {
+    pub filter: &'a SearchFilter,
+    pub open: bool,
+}
+
 pub fn view<'a>(
     items: &'a [InventoryItem],
     all_items: &'a [InventoryItem],
-    filter: &'a SearchFilter,
-    show_search_panel: bool,
-    currency_code: &'a str,
-    view_mode: InventoryViewMode,
+    search: SearchPanel<'a>,
+    export_error: Option<&'a str>,
+    settings: &'a AppSettings,
+    computed: &'a ComputedValues,
     app_theme: &'a AppTheme,
 ) -> Element<'a, Message> {
+    let SearchPanel { filter, open: show_search_panel } = search;
+    let currency_code = settings.preferred_currency.as_str();
+    let view_mode = settings.inventory_view_mode;
+
     // Calculate statistics
     let total_items = all_items.len();
     let filtered_count = items.len();
@@ -164,11 +176,67 @@ pub fn view<'a>(
             }
         });
 
+    let import_button = button(
+        row![
+            icons::Icon::Clipboard.view(icons::IconSize::Small, app_theme),
+            text("Import").size(theme::TEXT_BODY),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .on_press(Message::ImportItemsFile)
+    .padding([theme::SPACING_MD, theme::SPACING_XL])
+    .style(move |_theme: &iced::Theme, status: button::Status| {
+        let bg_color = match status {
+            button::Status::Hovered => theme::surface_color(app_theme),
+            _ => theme::surface_elevated_color(app_theme),
+        };
+        button::Style {
+            background: Some(iced::Background::Color(bg_color)),
+            text_color: theme::text_color(app_theme),
+            border: iced::Border {
+                color: theme::border_color(app_theme),
+                width: 1.0,
+                    radius: theme::RADIUS_MD.into(),
+                },
+                ..Default::default()
+            }
+        });
+
+    let spreadsheet_button = button(
+        row![
+            icons::Icon::Save.view(icons::IconSize::Small, app_theme),
+            text("Export Excel").size(theme::TEXT_BODY),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .on_press(Message::ExportInventorySpreadsheet)
+    .padding([theme::SPACING_MD, theme::SPACING_XL])
+    .style(move |_theme: &iced::Theme, status: button::Status| {
+        let bg_color = match status {
+            button::Status::Hovered => theme::surface_color(app_theme),
+            _ => theme::surface_elevated_color(app_theme),
+        };
+        button::Style {
+            background: Some(iced::Background::Color(bg_color)),
+            text_color: theme::text_color(app_theme),
+            border: iced::Border {
+                color: theme::border_color(app_theme),
+                width: 1.0,
+                    radius: theme::RADIUS_MD.into(),
+                },
+                ..Default::default()
+            }
+        });
+
     let header = row![
         title,
         iced::widget::horizontal_space(),
         row![
+            import_button,
             export_button,
+            spreadsheet_button,
             make_view_button("Cards", InventoryViewMode::Cards),
             make_view_button("Table", InventoryViewMode::Table),
             search_button,
@@ -206,7 +274,21 @@ pub fn view<'a>(
     .spacing(theme::SPACING_LG)
     .padding([0.0, theme::SPACING_LG]);
 
-    let mut content = column![header, stats_row].spacing(theme::SPACING_LG);
+    let mut content = column![header].spacing(theme::SPACING_LG);
+
+    if let Some(error) = export_error {
+        content = content.push(
+            container(
+                text(error)
+                    .size(theme::TEXT_BODY)
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(theme::danger_color(app_theme)),
+                    }),
+            )
+            .padding([0.0, theme::SPACING_LG]),
+        );
+    }
+    content = content.push(stats_row);
 
     // Search panel
     if show_search_panel {
@@ -258,7 +340,7 @@ pub fn view<'a>(
         content = content.push(items_list);
             }
             InventoryViewMode::Table => {
-                let table_view = build_table_view(items, currency_code, app_theme);
+                let table_view = build_table_view(items, currency_code, (&settings.computed_columns, computed), app_theme);
                 content = content.push(table_view);
             }
         }
@@ -398,6 +480,26 @@ fn build_search_panel<'a>(
     .padding(theme::SPACING_LG)
     .width(Length::Fill);
 
+    let mut abc_options = vec!["All Classes".to_string()];
+    abc_options.extend(AbcClass::ALL.iter().map(|class| class.to_string()));
+
+    let abc_picker = pick_list(
+        abc_options,
+        filter
+            .abc_filter
+            .map(|class| class.to_string())
+            .or(Some("All Classes".to_string())),
+        |val| {
+            if val == "All Classes" {
+                Message::AbcClassFilterChanged(String::new())
+            } else {
+                Message::AbcClassFilterChanged(val)
+            }
+        },
+    )
+    .padding(theme::SPACING_LG)
+    .width(Length::Fixed(140.0));
+
     let min_qty_str = filter.min_quantity.map_or(String::new(), |v| v.to_string());
     let max_qty_str = filter.max_quantity.map_or(String::new(), |v| v.to_string());
     let min_price_str = filter.min_price.map_or(String::new(), |v| format!("{:.2}", v));
@@ -436,6 +538,7 @@ fn build_search_panel<'a>(
         SortField::Price,
         SortField::CreatedAt,
         SortField::UpdatedAt,
+        SortField::DaysOfCover,
     ];
 
     let sort_field_picker = pick_list(
@@ -521,6 +624,15 @@ fn build_search_panel<'a>(
                 ]
                 .spacing(theme::SPACING_XS)
                 .width(Length::Fill),
+                column![
+                    text("ABC Class")
+                        .size(theme::TEXT_CAPTION)
+                        .style(move |_theme: &iced::Theme| text::Style {
+                            color: Some(theme::text_secondary_color(app_theme)),
+                        }),
+                    abc_picker,
+                ]
+                .spacing(theme::SPACING_XS),
             ]
             .spacing(theme::SPACING_LG),
             row![
@@ -590,21 +702,27 @@ fn build_search_panel<'a>(
 fn build_table_view<'a>(
     items: &'a [InventoryItem],
     currency_code: &'a str,
+    (columns, computed): (&'a [ComputedColumn], &'a ComputedValues),
     app_theme: &'a AppTheme,
 ) -> Element<'a, Message> {
-    let header_row = container(
-        row![
-            text("Name").width(Length::FillPortion(3)),
-            text("SKU").width(Length::FillPortion(2)),
-            text("Category").width(Length::FillPortion(2)),
-            text("Supplier").width(Length::FillPortion(2)),
-            text("Qty").width(Length::FillPortion(1)),
-            text("Price").width(Length::FillPortion(2)),
-            text("Actions").width(Length::FillPortion(2)),
-        ]
-        .spacing(10)
-        .padding(10),
-    )
+    let mut header = row![
+        text("Name").width(Length::FillPortion(3)),
+        text("SKU").width(Length::FillPortion(2)),
+        text("Category").width(Length::FillPortion(2)),
+        text("Supplier").width(Length::FillPortion(2)),
+        text("On Hand").width(Length::FillPortion(1)),
+        text("Allocated").width(Length::FillPortion(1)),
+        text("Available").width(Length::FillPortion(1)),
+        text("Cover").width(Length::FillPortion(1)),
+        text("Stock-out").width(Length::FillPortion(2)),
+        text("Price").width(Length::FillPortion(2)),
+    ]
+    .spacing(10)
+    .padding(10);
+    for column in columns {
+        header = header.push(text(&column.name).width(Length::FillPortion(2)));
+    }
+    let header_row = container(header.push(text("Actions").width(Length::FillPortion(2))))
     .style(move |_iced_theme: &iced::Theme| container::Style {
         background: Some(iced::Background::Color(theme::surface_elevated_color(app_theme))),
         border: iced::Border {
@@ -630,20 +748,35 @@ fn build_table_view<'a>(
         .on_press(Message::DeleteItem(item.id.clone()))
         .padding(6);
 
-        let row_content = row![
+        let mut row_content = row![
             text(&item.name).width(Length::FillPortion(3)),
             text(&item.sku).width(Length::FillPortion(2)),
             text(&item.category).width(Length::FillPortion(2)),
             text(&item.supplier).width(Length::FillPortion(2)),
             text(format!("{}", item.quantity)).width(Length::FillPortion(1)),
+            text(format!("{}", item.allocated)).width(Length::FillPortion(1)),
+            text(format!("{}", item.available())).width(Length::FillPortion(1)),
+            text(cover_label(item)).width(Length::FillPortion(1)),
+            text(stockout_label(item)).width(Length::FillPortion(2)),
             text(currency::format_currency_with_exp(item.price, currency_code))
                 .width(Length::FillPortion(2)),
-            row![edit_button, delete_button]
-                .spacing(theme::SPACING_SM)
-                .width(Length::FillPortion(2)),
         ]
         .spacing(10)
         .align_y(iced::Alignment::Center);
+        for index in 0..columns.len() {
+            let value = computed.get(&item.id, index);
+            let cell = text(value).width(Length::FillPortion(2));
+            row_content = row_content.push(if value == scripting::ERROR_VALUE {
+                cell.color(theme::danger_color(app_theme))
+            } else {
+                cell
+            });
+        }
+        let row_content = row_content.push(
+            row![edit_button, delete_button]
+                .spacing(theme::SPACING_SM)
+                .width(Length::FillPortion(2)),
+        );
 
         let row_container = container(row_content)
             .padding(10)
@@ -663,6 +796,19 @@ fn build_table_view<'a>(
     rows.padding([0.0, theme::SPACING_LG]).into()
 }
 
+/// Days of cover at forecast demand, or a dash when there is no demand
+fn cover_label(item: &InventoryItem) -> String {
+    item.days_of_cover()
+        .map(|days| format!("{:.0}d", days))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+fn stockout_label(item: &InventoryItem) -> String {
+    item.projected_stockout()
+        .map(|date| date.format("%b %d, %Y").to_string())
+        .unwrap_or_else(|| "—".to_string())
+}
+
 fn build_item_card<'a>(
     item: &'a InventoryItem,
     currency_code: &'a str,
@@ -676,11 +822,12 @@ fn build_item_card<'a>(
         .map(|dt| dt.format("%b %d, %Y").to_string())
         .unwrap_or_else(|| "Unknown".to_string());
 
-    let (stock_icon, stock_label, stock_color) = if item.quantity == 0 {
+    let available = item.available();
+    let (stock_icon, stock_label, stock_color) = if available == 0 {
         (icons::Icon::XCircle, "OUT OF STOCK", theme::danger_color(app_theme))
-    } else if item.quantity < 5 {
+    } else if available < 5 {
         (icons::Icon::AlertTriangle, "CRITICALLY LOW", theme::danger_color(app_theme))
-    } else if item.quantity < 10 {
+    } else if available < 10 {
         (icons::Icon::AlertCircle, "LOW STOCK", theme::warning_color(app_theme))
     } else {
         (icons::Icon::CheckCircle, "IN STOCK", theme::success_color(app_theme))
@@ -833,12 +980,40 @@ fn build_item_card<'a>(
                     .spacing(theme::SPACING_XS)
                     .width(Length::Fill),
                     column![
-                        text("Quantity")
+                        text("On Hand")
                             .size(theme::TEXT_CAPTION)
                             .style(move |_theme: &iced::Theme| text::Style {
                                 color: Some(theme::text_tertiary_color(app_theme)),
                             }),
                         text(format!("{}", item.quantity))
+                            .size(theme::TEXT_H3)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_color(app_theme)),
+                            }),
+                    ]
+                    .spacing(theme::SPACING_XS)
+                    .align_x(iced::Alignment::End),
+                    column![
+                        text("Allocated")
+                            .size(theme::TEXT_CAPTION)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_tertiary_color(app_theme)),
+                            }),
+                        text(format!("{}", item.allocated))
+                            .size(theme::TEXT_H3)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_secondary_color(app_theme)),
+                            }),
+                    ]
+                    .spacing(theme::SPACING_XS)
+                    .align_x(iced::Alignment::End),
+                    column![
+                        text("Available")
+                            .size(theme::TEXT_CAPTION)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_tertiary_color(app_theme)),
+                            }),
+                        text(format!("{}", available))
                             .size(theme::TEXT_H3)
                             .style(move |_theme: &iced::Theme| text::Style {
                                 color: Some(stock_color),
@@ -846,6 +1021,25 @@ fn build_item_card<'a>(
                     ]
                     .spacing(theme::SPACING_XS)
                     .align_x(iced::Alignment::End),
+                    column![
+                        text("Cover")
+                            .size(theme::TEXT_CAPTION)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_tertiary_color(app_theme)),
+                            }),
+                        text(cover_label(item))
+                            .size(theme::TEXT_H3)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_secondary_color(app_theme)),
+                            }),
+                        text(stockout_label(item))
+                            .size(theme::TEXT_CAPTION)
+                            .style(move |_theme: &iced::Theme| text::Style {
+                                color: Some(theme::text_tertiary_color(app_theme)),
+                            }),
+                    ]
+                    .spacing(theme::SPACING_XS)
+                    .align_x(iced::Alignment::End),
                     column![
                         text("Price")
                             .size(theme::TEXT_CAPTION)
@@ -5,19 +5,39 @@ use crate::messages::{AppTheme, ItemDialogMode, Message};
 use crate::theme;
 use crate::icons;
 
-pub fn view<'a>(
-    mode: &ItemDialogMode,
-    name: &'a str,
-    sku: &'a str,
-    category: &'a str,
-    supplier: &'a str,
-    description: &'a str,
-    quantity: &'a str,
-    price: &'a str,
-    validation_error: Option<&'a str>,
-    similar_items: &'a [String],
-    app_theme: &'a AppTheme,
-) -> Element<'a, Message> {
+/// Current contents of the item dialog inputs
+pub struct ItemForm<'a> {
+    pub name: &'a str,
+    pub sku: &'a str,
+    pub category: &'a str,
+    pub supplier: &'a str,
+    pub location: &'a str,
+    pub description: &'a str,
+    pub quantity: &'a str,
+    pub price: &'a str,
+    pub reorder_point: &'a str,
+    pub reorder_quantity: &'a str,
+    pub lead_time: &'a str,
+    pub validation_error: Option<&'a str>,
+    pub similar_items: &'a [String],
+}
+
+pub fn view<'a>(mode: &ItemDialogMode, form: ItemForm<'a>, app_theme: &'a AppTheme) -> Element<'a, Message> {
+    let ItemForm {
+        name,
+        sku,
+        category,
+        supplier,
+        location,
+        description,
+        quantity,
+        price,
+        reorder_point,
+        reorder_quantity,
+        lead_time,
+        validation_error,
+        similar_items,
+    } = form;
     let title = match mode {
         ItemDialogMode::Add => row![
             icons::Icon::Add.view(icons::IconSize::Medium, app_theme),
@@ -160,6 +180,7 @@ pub fn view<'a>(
         make_input("SKU *", "Enter SKU", sku, Message::SkuChanged),
         make_input("Category *", "e.g., Electronics, Food", category, Message::CategoryChanged),
         make_input("Supplier", "Enter supplier name", supplier, Message::SupplierChanged),
+        make_input("Location", "e.g., Aisle 3, Bin B2", location, Message::LocationChanged),
     ]
     .spacing(theme::SPACING_LG)
     .width(Length::Fill);
@@ -207,6 +228,16 @@ pub fn view<'a>(
 
     form_content = form_content.push(two_col_row);
 
+    // Reordering settings (optional)
+    let reorder_row = row![
+        make_input("Reorder Point", "Use alert threshold", reorder_point, Message::ReorderPointChanged),
+        make_input("Reorder Qty", "Any amount", reorder_quantity, Message::ReorderQuantityChanged),
+        make_input("Lead Time (days)", "Unknown", lead_time, Message::LeadTimeChanged),
+    ]
+    .spacing(theme::SPACING_LG);
+
+    form_content = form_content.push(reorder_row);
+
     // Action buttons
     let submit_btn = button(
         row![
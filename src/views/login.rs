@@ -11,6 +11,7 @@ pub fn view<'a>(
     error_message: Option<&'a str>,
     app_theme: &'a AppTheme,
     logging_in: bool,
+    workspace: Option<&'a str>,
 ) -> Element<'a, Message> {
     // App title with gradient-like effect using multiple colors
     let title = container(
@@ -199,6 +200,25 @@ pub fn view<'a>(
     .width(600)
     .align_x(iced::Alignment::Center);
 
+    // Open workspace, with a way back to the workspace list
+    if let Some(workspace) = workspace {
+        card_content = card_content.push(
+            row![
+                text(format!("Workspace: {}", workspace))
+                    .size(theme::TEXT_BODY)
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(theme::text_secondary_color(app_theme)),
+                    }),
+                button(text("Change").size(theme::TEXT_BODY))
+                    .on_press_maybe((!logging_in).then_some(Message::ShowWorkspaces))
+                    .padding([theme::SPACING_XS, theme::SPACING_SM])
+                    .style(button::text),
+            ]
+            .spacing(theme::SPACING_SM)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
     // Error message if present
     if let Some(error) = error_message {
         card_content = card_content.push(text("").size(theme::SPACING_SM));
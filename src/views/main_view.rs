@@ -1,4 +1,4 @@
-use iced::widget::{button, column, container, row, text};
+use iced::widget::{button, column, container, pick_list, row, text};
 use iced::{Color, Element, Length};
 use crate::{InventoryApp, Message};
 use crate::messages::{LayoutStyle, View};
@@ -145,6 +145,12 @@ impl InventoryApp {
             ("Inventory".to_string(), View::Inventory, icons::Icon::Inventory),
             ("Notes".to_string(), View::Editor, icons::Icon::Notes),
             (alerts_label, View::Alerts, icons::Icon::Alerts),
+            ("Reorder".to_string(), View::Reorder, icons::Icon::Box),
+            ("Sales".to_string(), View::Sales, icons::Icon::Dollar),
+            ("Returns".to_string(), View::Returns, icons::Icon::Return),
+            ("Stocktake".to_string(), View::Stocktake, icons::Icon::Clipboard),
+            ("Analysis".to_string(), View::Analysis, icons::Icon::Chart),
+            ("Reports".to_string(), View::Reports, icons::Icon::Report),
             ("Settings".to_string(), View::Settings, icons::Icon::Settings),
         ];
         
@@ -153,9 +159,11 @@ impl InventoryApp {
             nav_items.push(("Users".to_string(), View::UserManagement, icons::Icon::Users));
         }
         
-        // Only managers and admins can access audit log
+        // Only managers and admins can access audit log, rules and scripts
         if session.role.can_view_audit() {
             nav_items.push(("Audit Log".to_string(), View::AuditLog, icons::Icon::AuditLog));
+            nav_items.push(("Rules".to_string(), View::Rules, icons::Icon::Lightbulb));
+            nav_items.push(("Scripts".to_string(), View::Scripts, icons::Icon::Calculator));
         }
         
         // Create header tabs
@@ -328,6 +336,41 @@ impl InventoryApp {
         // Create user pill and logout button for header
         let user_pill = make_user_pill();
         let logout_btn = make_logout_btn();
+
+        // Whether changes are waiting for the next background save
+        let save_indicator = || -> Element<Message> {
+            let (label, color) = if let Some(error) = &self.save_error {
+                (error.clone(), theme::danger_color(theme))
+            } else if self.read_only {
+                ("Read-only".to_string(), theme::text_tertiary_color(theme))
+            } else if self.saving.is_some() {
+                ("Saving...".to_string(), theme::text_tertiary_color(theme))
+            } else if self.has_unsaved_changes() {
+                ("● Unsaved changes".to_string(), theme::warning_color(theme))
+            } else if let Some(saved) = self.last_saved {
+                match &self.save_warning {
+                    Some(warning) => (
+                        format!("Saved at {}. {}", saved.format("%H:%M"), warning),
+                        theme::warning_color(theme),
+                    ),
+                    None => (format!("Saved at {}", saved.format("%H:%M")), theme::text_tertiary_color(theme)),
+                }
+            } else {
+                ("No unsaved changes".to_string(), theme::text_tertiary_color(theme))
+            };
+            let label = text(label)
+                .size(theme::TEXT_CAPTION)
+                .style(move |_theme: &iced::Theme| text::Style { color: Some(color) });
+            if self.has_unsaved_changes() && self.saving.is_none() && !self.read_only {
+                button(label)
+                    .on_press(Message::Save)
+                    .padding(0)
+                    .style(button::text)
+                    .into()
+            } else {
+                label.into()
+            }
+        };
         
         // Top bar (for sidebar layout) - always visible with user info
         let _top_bar = {
@@ -364,6 +407,21 @@ impl InventoryApp {
         });
 
         // Sidebar with collapse toggle
+        // Workspace switcher, only when there is more than one to switch between
+        let workspace_picker = || -> Option<Element<Message>> {
+            if crate::persistence::workspace::fixed_data_dir().is_some()
+                || self.workspaces.workspaces.len() < 2
+            {
+                return None;
+            }
+            let names: Vec<String> = self.workspaces.workspaces.iter().map(|w| w.name.clone()).collect();
+            Some(
+                pick_list(names, Some(self.workspaces.active.clone()), Message::WorkspaceSelected)
+                    .text_size(theme::TEXT_BODY)
+                    .into(),
+            )
+        };
+
         let sidebar = {
             let collapsed = self.sidebar_collapsed;
             
@@ -519,12 +577,17 @@ impl InventoryApp {
             };
             
             // Create sidebar user section
-            let sidebar_user_section = column![
-                user_pill_sidebar,
-                logout_btn_sidebar,
-            ]
-            .spacing(theme::SPACING_SM)
-            .width(Length::Fill);
+            let mut sidebar_user_section = column![]
+                .spacing(theme::SPACING_SM)
+                .width(Length::Fill);
+            if !collapsed {
+                sidebar_user_section = sidebar_user_section
+                    .push_maybe(workspace_picker())
+                    .push(save_indicator());
+            }
+            sidebar_user_section = sidebar_user_section
+                .push(user_pill_sidebar)
+                .push(logout_btn_sidebar);
             
             container(
                 column![
@@ -578,7 +641,10 @@ impl InventoryApp {
                 // Spacer to push user info to the right
                 iced::widget::horizontal_space(),
                 // User info - fixed width, always on the right
-                row![user_pill, logout_btn]
+                row![save_indicator()]
+                    .push_maybe(workspace_picker())
+                    .push(user_pill)
+                    .push(logout_btn)
                     .spacing(theme::SPACING_LG)
                     .align_y(iced::Alignment::Center)
                     .width(Length::Shrink),
@@ -636,10 +702,13 @@ impl InventoryApp {
             View::Inventory => crate::views::inventory::view(
                 &self.filtered_items,
                 &self.items,
-                &self.search_filter,
-                self.show_search_panel,
-                &self.settings.preferred_currency,
-                self.settings.inventory_view_mode,
+                crate::views::inventory::SearchPanel {
+                    filter: &self.search_filter,
+                    open: self.show_search_panel,
+                },
+                self.inventory_export_error.as_deref(),
+                &self.settings,
+                &self.computed_values,
                 theme,
             ),
             View::Editor => crate::views::editor::view(
@@ -652,16 +721,64 @@ impl InventoryApp {
             ),
             View::Settings => crate::views::settings::view(
                 &self.settings,
-                &self.settings_interval_input,
-                &self.settings_category_input,
-                &self.settings_notification_throttle_input,
-                self.latest_version.as_ref(),
-                self.import_error.as_deref(),
-                self.checking_for_updates,
-                self.downloading_update,
-                self.update_message.as_deref(),
+                crate::views::settings::GeneralForm {
+                    interval: &self.settings_interval_input,
+                    category: &self.settings_category_input,
+                    notification_throttle: &self.settings_notification_throttle_input,
+                    import_error: self.import_error.as_deref(),
+                    storage_status: self.storage_status.as_deref(),
+                },
+                crate::views::settings::UpdateStatus {
+                    latest_version: self.latest_version.as_ref(),
+                    checking: self.checking_for_updates,
+                    downloading: self.downloading_update,
+                    message: self.update_message.as_deref(),
+                },
+                crate::views::settings::EncryptionForm {
+                    enabled: self.encryption_enabled,
+                    current_passphrase: &self.current_passphrase_input,
+                    new_passphrase: &self.new_passphrase_input,
+                    confirm_passphrase: &self.confirm_passphrase_input,
+                    file_passphrase: &self.file_passphrase_input,
+                    status: self.encryption_status.as_deref(),
+                },
+                crate::views::settings::ExportJobsForm {
+                    running: &self.running_export_jobs,
+                    name: &self.export_job_name_input,
+                    format: self.export_job_format,
+                    frequency: self.export_job_frequency,
+                    time: &self.export_job_time_input,
+                    weekday: self.export_job_weekday,
+                    keep: &self.export_job_keep_input,
+                    use_filter: self.export_job_use_filter,
+                    filter: self.search_filter.describe(),
+                    strip_sensitive: self.export_job_strip_sensitive,
+                    folder: self.export_job_folder.as_deref(),
+                    status: self.export_job_status.as_deref(),
+                    can_manage: session.role.can_view_audit(),
+                },
+                crate::views::settings::ApiForm {
+                    server_status: self.api_server_status.as_ref(),
+                    port: &self.api_port_input,
+                    token_name: &self.api_token_name_input,
+                    token_user: self.api_token_user.as_ref(),
+                    users: self
+                        .auth_store
+                        .get_all_users()
+                        .into_iter()
+                        .map(|user| (user.id.clone(), user.username.clone()))
+                        .collect(),
+                    new_token: self.api_new_token.as_deref(),
+                    status: self.api_form_status.as_deref(),
+                    can_manage: session.role.can_manage_users(),
+                },
                 &self.settings.theme,
             ),
+            View::MergeImport => crate::views::merge_import::view(
+                self.merge_plan.as_ref(),
+                self.merge_status.as_deref(),
+                theme,
+            ),
             View::UserManagement => {
                 let users: Vec<_> = self.auth_store.get_all_users();
                 crate::views::user_management::view(
@@ -679,6 +796,177 @@ impl InventoryApp {
                 crate::views::audit_log::view(&entries, session.role, theme)
             }
             View::Alerts => crate::views::alerts::view(&self.alert_manager, session.role, theme),
+            View::Reorder => {
+                let lines = self
+                    .reorder_worksheet()
+                    .into_iter()
+                    .map(|suggestion| {
+                        let quantity = self.reorder_line_quantity(&suggestion);
+                        (suggestion, quantity)
+                    })
+                    .collect();
+                crate::views::reorder::view(
+                    lines,
+                    &self.reorder_selection,
+                    &self.reorder_quantity_inputs,
+                    &self.purchase_orders,
+                    self.reorder_status.as_deref(),
+                    &self.settings.preferred_currency,
+                    theme,
+                )
+            }
+            View::Sales => crate::views::sales::view(
+                &self.customers,
+                &self.sales_orders,
+                &self.items,
+                crate::views::sales::SalesForm {
+                    customer_name: &self.customer_name_input,
+                    customer_email: &self.customer_email_input,
+                    customer_phone: &self.customer_phone_input,
+                    customer_address: &self.customer_address_input,
+                    selected_customer_id: self.sales_customer_id.as_deref(),
+                    selected_item_id: self.sales_line_item_id.as_deref(),
+                    line_quantity: &self.sales_line_quantity_input,
+                    draft_lines: &self.sales_draft_lines,
+                    error: self.sales_error.as_deref(),
+                },
+                &self.settings.preferred_currency,
+                session.role,
+                theme,
+            ),
+            View::Returns => crate::views::returns::view(
+                &self.returns,
+                &self.sales_orders,
+                &self.items,
+                &self.stock_movements,
+                crate::views::returns::ReturnsForm {
+                    sales_order_id: self.return_sales_order_id.as_deref(),
+                    customer: &self.return_customer_input,
+                    reason: self.return_reason,
+                    notes: &self.return_notes_input,
+                    line_item_id: self.return_line_item_id.as_deref(),
+                    line_quantity: &self.return_line_quantity_input,
+                    line_disposition: self.return_line_disposition,
+                    draft_lines: &self.return_draft_lines,
+                    error: self.returns_error.as_deref(),
+                    quarantine_quantities: &self.quarantine_quantity_inputs,
+                },
+                &self.settings.preferred_currency,
+                theme,
+            ),
+            View::Stocktake => crate::views::stocktake::view(
+                &self.stocktakes,
+                &self.items,
+                crate::views::stocktake::StocktakeForm {
+                    scope_kind: self.stocktake_scope_kind,
+                    scope_value: self.stocktake_scope_value.as_deref(),
+                    active_session_id: self.active_stocktake_id.as_deref(),
+                    scan_input: &self.stocktake_scan_input,
+                    message: self.stocktake_message.as_deref(),
+                },
+                &self.settings.preferred_currency,
+                session.role,
+                theme,
+            ),
+            View::Backups => crate::views::backups::view(
+                self.backups.as_deref(),
+                &self.settings.backups,
+                &self.backup_keep_input,
+                self.backup_status.as_deref(),
+                self.restoring_backup,
+                session.role,
+                theme,
+            ),
+            View::Webhooks => crate::views::webhooks::view(
+                &self.settings.webhooks,
+                &self.webhook_deliveries,
+                crate::views::webhooks::WebhookForm {
+                    name: &self.webhook_name_input,
+                    url: &self.webhook_url_input,
+                    events: &self.webhook_events,
+                    status: self.webhook_status.as_deref(),
+                },
+                session.role,
+                theme,
+            ),
+            View::Rules => crate::views::rules::view(
+                &self.settings.rules,
+                crate::views::rules::RuleForm {
+                    draft: &self.rule_draft,
+                    status: self.rule_status.as_deref(),
+                    preview: self.rule_preview.as_deref(),
+                    usernames: self
+                        .auth_store
+                        .get_all_users()
+                        .into_iter()
+                        .map(|user| user.username.clone())
+                        .collect(),
+                    jobs: &self.settings.export_jobs,
+                },
+                self.audit_log
+                    .get_entries()
+                    .iter()
+                    .rev()
+                    .filter(|entry| entry.action == crate::audit::AuditAction::RuleFired)
+                    .take(20)
+                    .collect(),
+                session.role,
+                theme,
+            ),
+            View::Scripts => crate::views::scripts::view(
+                crate::views::scripts::ColumnForm {
+                    columns: &self.settings.computed_columns,
+                    errors: &self.computed_values.errors,
+                    editing: self.column_editing.as_deref(),
+                    name: &self.column_name_input,
+                    script: &self.column_script_input,
+                    status: self.column_status.as_deref(),
+                },
+                crate::views::scripts::ReportForm {
+                    reports: &self.settings.report_scripts,
+                    editing: self.report_script_editing.as_deref(),
+                    name: &self.report_script_name_input,
+                    content: &self.report_script_content,
+                    status: self.report_script_status.as_deref(),
+                    output: self.report_script_output.as_ref(),
+                },
+                session.role,
+                theme,
+            ),
+            View::Import => crate::views::import::view(
+                self.import_table.as_ref(),
+                &self.import_mapping,
+                self.import_mode,
+                &self.import_preview,
+                self.import_status.as_deref(),
+                session.role.can_edit(),
+                theme,
+            ),
+            View::Reports => crate::views::reports::view(
+                &self.settings.reports,
+                crate::views::reports::ReportForm {
+                    from: &self.report_from_input,
+                    to: &self.report_to_input,
+                    status: self.report_status.as_deref(),
+                },
+                self.search_filter.describe(),
+                self.alert_manager.get_active_alerts().len(),
+                session.role,
+                theme,
+            ),
+            View::Analysis => crate::views::analysis::view(
+                self.abc_report(),
+                self.slow_moving_report(),
+                crate::views::analysis::AnalysisForm {
+                    a_cutoff: &self.abc_a_cutoff_input,
+                    b_cutoff: &self.abc_b_cutoff_input,
+                    slow_moving_days: &self.slow_moving_days_input,
+                    dead_stock_days: &self.dead_stock_days_input,
+                    error: self.analysis_error.as_deref(),
+                },
+                &self.settings.preferred_currency,
+                theme,
+            ),
         };
 
         let content_container = container(content)
@@ -690,12 +978,23 @@ impl InventoryApp {
                 ..Default::default()
             });
 
+        let data_file_banner = || {
+            crate::views::data_file::banner(
+                self.read_only,
+                self.data_file_changed,
+                self.data_file_status.as_deref(),
+                theme,
+            )
+        };
+
         // Conditionally render based on layout style
         let main_view: Element<Message> = match layout_style {
             LayoutStyle::Header => {
                 // Header layout: header at top, content below
                 container(
-                    column![header, shortcuts_hint, content_container]
+                    column![header, shortcuts_hint]
+                        .push_maybe(data_file_banner())
+                        .push(content_container)
                         .spacing(0)
                         .width(Length::Fill)
                         .height(Length::Fill)
@@ -709,13 +1008,12 @@ impl InventoryApp {
                 container(
                     row![
                         sidebar,
-                        column![
-                            shortcuts_hint,
-                            content_container
-                        ]
-                        .spacing(0)
-                        .width(Length::Fill)
-                        .height(Length::Fill)
+                        column![shortcuts_hint]
+                            .push_maybe(data_file_banner())
+                            .push(content_container)
+                            .spacing(0)
+                            .width(Length::Fill)
+                            .height(Length::Fill)
                     ]
                     .spacing(0)
                     .width(Length::Fill)
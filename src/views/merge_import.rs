@@ -0,0 +1,135 @@
+use iced::widget::{button, checkbox, column, container, pick_list, row, scrollable, text, Column};
+use iced::{Element, Length};
+
+use crate::merge::{MergeOption, MergePlan, MergeRecord, MergeStatus, RecordKind, Resolution};
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::views::sales::{muted, panel_style};
+
+pub fn view<'a>(plan: Option<&'a MergePlan>, status: Option<&'a str>, app_theme: &'a AppTheme) -> Element<'a, Message> {
+    let Some(plan) = plan else {
+        // The plan is consumed when applied; only the outcome is left to show
+        let content = column![
+            text("Import Complete").size(28),
+            text(status.unwrap_or("Nothing was imported")).size(theme::TEXT_BODY),
+            button("Done").on_press(Message::CloseMerge).padding(10),
+        ]
+        .spacing(theme::SPACING_LG)
+        .padding(20);
+        return scrollable(content).into();
+    };
+
+    let summary = format!(
+        "{} new, {} identical, {} conflicting, {} skipped",
+        plan.count(|s| matches!(s, MergeStatus::New)),
+        plan.count(|s| matches!(s, MergeStatus::Identical)),
+        plan.count(|s| matches!(s, MergeStatus::Conflict)),
+        plan.count(|s| matches!(s, MergeStatus::Skipped(_))),
+    );
+
+    let header = column![
+        text("Review Import").size(28),
+        text(format!("From {}: {}", plan.file_name, summary))
+            .size(theme::TEXT_BODY)
+            .color(theme::text_secondary_color(app_theme)),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    let options = container(
+        column![
+            text("Also import").size(20),
+            checkbox("Settings", plan.options.settings)
+                .on_toggle(|_| Message::MergeOptionToggled(MergeOption::Settings)),
+            checkbox("Alert settings", plan.options.alert_settings)
+                .on_toggle(|_| Message::MergeOptionToggled(MergeOption::AlertSettings)),
+            checkbox("Audit entries", plan.options.audit)
+                .on_toggle(|_| Message::MergeOptionToggled(MergeOption::Audit)),
+            muted("User accounts are never imported.", app_theme),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme));
+
+    let mut resolve_all = row![text("Resolve all conflicts:").size(14)]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center);
+    for resolution in Resolution::ALL {
+        resolve_all = resolve_all.push(
+            button(text(resolution.to_string()).size(14))
+                .on_press(Message::ResolveAllConflicts(resolution))
+                .padding(6),
+        );
+    }
+
+    let actions = row![
+        button("Import").on_press(Message::ConfirmMerge).padding(10),
+        button("Cancel").on_press(Message::CloseMerge).padding(10),
+    ]
+    .spacing(theme::SPACING_LG);
+
+    let content = column![
+        header,
+        options,
+        resolve_all,
+        records_section("Items", RecordKind::Item, &plan.items, app_theme),
+        records_section("Notes", RecordKind::Note, &plan.notes, app_theme),
+        actions,
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(20);
+
+    scrollable(content).into()
+}
+
+fn records_section<'a, T>(
+    title: &'a str,
+    kind: RecordKind,
+    records: &'a [MergeRecord<T>],
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut list = column![text(format!("{} ({})", title, records.len())).size(20)].spacing(10);
+    if records.is_empty() {
+        list = list.push(muted("None in this file", app_theme));
+    }
+
+    for (index, record) in records.iter().enumerate() {
+        let (label, color) = match &record.status {
+            MergeStatus::New => ("New".to_string(), theme::success_color(app_theme)),
+            MergeStatus::Identical => ("Identical".to_string(), theme::text_secondary_color(app_theme)),
+            MergeStatus::Conflict => ("Conflict".to_string(), theme::warning_color(app_theme)),
+            MergeStatus::Skipped(reason) => (format!("Skipped: {}", reason), theme::danger_color(app_theme)),
+        };
+
+        let mut heading = row![
+            text(&record.label).size(16).width(Length::Fill),
+            text(label).size(theme::TEXT_CAPTION).color(color),
+        ]
+        .spacing(theme::SPACING_LG)
+        .align_y(iced::Alignment::Center);
+        if record.status == MergeStatus::Conflict {
+            heading = heading.push(
+                pick_list(Resolution::ALL, Some(record.resolution), move |resolution| {
+                    Message::MergeResolutionChanged(kind, index, resolution)
+                })
+                .width(Length::Fixed(140.0)),
+            );
+        }
+
+        let mut entry = Column::new().push(heading).spacing(4);
+        for diff in &record.diffs {
+            entry = entry.push(
+                text(format!("{}: {} → {}", diff.field, diff.mine, diff.theirs))
+                    .size(theme::TEXT_CAPTION)
+                    .color(theme::text_secondary_color(app_theme)),
+            );
+        }
+        list = list.push(entry);
+    }
+
+    container(list.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
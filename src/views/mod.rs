@@ -1,13 +1,29 @@
 pub mod about;
 pub mod alerts;
+pub mod analysis;
 pub mod audit_log;
+pub mod backups;
 pub mod calculator;
+pub mod data_file;
 pub mod dialogs;
 pub mod editor;
+pub mod export_dialog;
+pub mod import;
 pub mod inventory;
 pub mod item_dialog;
 pub mod loading;
 pub mod login;
 pub mod main_view;
+pub mod merge_import;
+pub mod reorder;
+pub mod reports;
+pub mod returns;
+pub mod rules;
+pub mod sales;
+pub mod scripts;
 pub mod settings;
+pub mod stocktake;
+pub mod unlock;
 pub mod user_management;
+pub mod webhooks;
+pub mod workspaces;
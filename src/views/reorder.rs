@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::currency;
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::purchasing::PurchaseOrder;
+use crate::reorder::{self, ReorderSuggestion};
+use crate::theme;
+
+/// Reorder worksheet: suggested lines grouped by supplier, plus recent draft orders.
+/// Each line is paired with the quantity that will actually be ordered.
+pub fn view<'a>(
+    lines: Vec<(ReorderSuggestion, u32)>,
+    selection: &'a HashSet<String>,
+    quantity_inputs: &'a HashMap<String, String>,
+    purchase_orders: &'a [PurchaseOrder],
+    status: Option<&'a str>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Box.view(icons::IconSize::Large, app_theme),
+        text("Reorder Worksheet").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let subtitle = text("Suggestions from active stock alerts and item reorder points")
+        .size(theme::TEXT_BODY)
+        .style(move |_theme: &iced::Theme| text::Style {
+            color: Some(theme::text_secondary_color(app_theme)),
+        });
+
+    let has_selection = !selection.is_empty();
+
+    let mut create_button = button("Create Draft POs").padding(8);
+    if has_selection {
+        create_button = create_button.on_press(Message::CreateDraftPurchaseOrders);
+    }
+    let mut export_button = button("Export CSV per Supplier").padding(8);
+    if has_selection {
+        export_button = export_button.on_press(Message::ExportReorderCsv);
+    }
+
+    let actions = row![
+        button("Select All").on_press(Message::SelectAllReorderLines).padding(8),
+        button("Clear Selection").on_press(Message::ClearReorderSelection).padding(8),
+        create_button,
+        export_button,
+    ]
+    .spacing(10);
+
+    let mut worksheet = column![].spacing(theme::SPACING_LG);
+
+    if lines.is_empty() {
+        worksheet = worksheet.push(
+            container(
+                text("Nothing to reorder right now")
+                    .size(16)
+                    .style(move |_iced_theme: &iced::Theme| text::Style {
+                        color: Some(theme::border_color(app_theme)),
+                    }),
+            )
+            .padding(20)
+            .width(Length::Fill)
+            .center_x(Length::Fill),
+        );
+    } else {
+        let suggestions: Vec<ReorderSuggestion> = lines.iter().map(|(s, _)| s.clone()).collect();
+        let quantities: HashMap<&str, u32> = lines
+            .iter()
+            .map(|(s, qty)| (s.item_id.as_str(), *qty))
+            .collect();
+
+        for (supplier, group) in reorder::group_by_supplier(&suggestions) {
+            let mut group_column = column![
+                text(reorder::supplier_label(&supplier).to_string()).size(theme::TEXT_H3),
+            ]
+            .spacing(theme::SPACING_SM);
+
+            let mut subtotal = 0.0;
+            for suggestion in group {
+                let quantity = quantities.get(suggestion.item_id.as_str()).copied().unwrap_or(0);
+                let is_selected = selection.contains(&suggestion.item_id);
+                if is_selected {
+                    subtotal += quantity as f64 * suggestion.unit_price;
+                }
+                group_column = group_column.push(build_line(
+                    suggestion,
+                    quantity,
+                    is_selected,
+                    quantity_inputs.get(&suggestion.item_id),
+                    currency_code,
+                    app_theme,
+                ));
+            }
+
+            group_column = group_column.push(
+                text(format!(
+                    "Selected subtotal: {}",
+                    currency::format_currency_with_exp(subtotal, currency_code)
+                ))
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::text_secondary_color(app_theme)),
+                }),
+            );
+
+            worksheet = worksheet.push(
+                container(group_column.padding(15))
+                    .width(Length::Fill)
+                    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+            );
+        }
+    }
+
+    let mut orders_list = column![text("Purchase Orders").size(20)].spacing(theme::SPACING_SM);
+    if purchase_orders.is_empty() {
+        orders_list = orders_list.push(
+            text("No purchase orders yet")
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::text_tertiary_color(app_theme)),
+                }),
+        );
+    } else {
+        for order in purchase_orders.iter().rev().take(20) {
+            orders_list = orders_list.push(
+                row![
+                    text(&order.number).size(theme::TEXT_BODY).width(Length::Fixed(110.0)),
+                    text(reorder::supplier_label(&order.supplier).to_string())
+                        .size(theme::TEXT_BODY)
+                        .width(Length::Fill),
+                    text(format!("{} lines", order.lines.len())).size(theme::TEXT_BODY),
+                    text(currency::format_currency_with_exp(order.total(), currency_code))
+                        .size(theme::TEXT_BODY),
+                    text(order.status.to_string()).size(theme::TEXT_BODY),
+                    text(order.formatted_timestamp())
+                        .size(theme::TEXT_CAPTION)
+                        .style(move |_theme: &iced::Theme| text::Style {
+                            color: Some(theme::text_tertiary_color(app_theme)),
+                        }),
+                ]
+                .spacing(theme::SPACING_LG)
+                .align_y(iced::Alignment::Center),
+            );
+        }
+    }
+
+    let mut content = column![title, subtitle, text("").size(5), actions].spacing(5).padding(20);
+
+    if let Some(message) = status {
+        content = content.push(
+            text(message)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::primary_color(app_theme)),
+                }),
+        );
+    }
+
+    content = content
+        .push(text("").size(10))
+        .push(worksheet)
+        .push(text("").size(10))
+        .push(
+            container(orders_list.padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+
+    scrollable(content).into()
+}
+
+fn build_line<'a>(
+    suggestion: &ReorderSuggestion,
+    quantity: u32,
+    is_selected: bool,
+    quantity_input: Option<&'a String>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let item_id = suggestion.item_id.clone();
+    let input_id = suggestion.item_id.clone();
+
+    let select = checkbox("", is_selected).on_toggle(move |_| Message::ToggleReorderLine(item_id.clone()));
+
+    let input_value = quantity_input
+        .cloned()
+        .unwrap_or_else(|| suggestion.suggested_quantity.to_string());
+    let quantity_field = text_input("Qty", &input_value)
+        .on_input(move |value| Message::ReorderLineQuantityChanged(input_id.clone(), value))
+        .padding(6)
+        .width(Length::Fixed(80.0));
+
+    let status_label = match &suggestion.alert_type {
+        Some(alert_type) => alert_type.to_string(),
+        None => "At reorder point".to_string(),
+    };
+
+    row![
+        select,
+        column![
+            text(suggestion.item_name.clone()).size(theme::TEXT_BODY_LARGE),
+            text(format!("SKU: {} · {}", suggestion.item_sku, status_label))
+                .size(theme::TEXT_CAPTION)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::text_secondary_color(app_theme)),
+                }),
+        ]
+        .width(Length::Fill),
+        text(format!(
+            "Available {} / point {}",
+            suggestion.current_quantity, suggestion.reorder_point
+        ))
+        .size(theme::TEXT_BODY),
+        quantity_field,
+        text(currency::format_currency_with_exp(
+            quantity as f64 * suggestion.unit_price,
+            currency_code,
+        ))
+        .size(theme::TEXT_BODY)
+        .width(Length::Fixed(110.0)),
+    ]
+    .spacing(theme::SPACING_LG)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+fn panel_style(app_theme: &AppTheme) -> container::Style {
+    container::Style {
+        background: Some(iced::Background::Color(theme::surface_color(app_theme))),
+        border: iced::Border {
+            color: theme::border_color(app_theme),
+            width: 1.0,
+            radius: 8.0.into(),
+        },
+        ..Default::default()
+    }
+}
@@ -0,0 +1,138 @@
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::reports::{ReportKind, ReportSettings};
+use crate::theme;
+use crate::user::UserRole;
+use crate::views::sales::{muted, panel_style};
+
+/// Inputs typed on the reports page
+pub struct ReportForm<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub status: Option<&'a str>,
+}
+
+pub fn view<'a>(
+    settings: &'a ReportSettings,
+    form: ReportForm<'a>,
+    stock_list_filter: String,
+    alert_count: usize,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Report.view(icons::IconSize::Large, app_theme),
+        text("Reports").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let logo_name = settings
+        .logo_path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "No logo".to_string());
+    let mut logo_row = row![
+        text(logo_name).size(theme::TEXT_BODY),
+        button("Choose Logo...").on_press(Message::ChooseReportLogo).padding(8),
+    ]
+    .spacing(theme::SPACING_LG)
+    .align_y(iced::Alignment::Center);
+    if settings.logo_path.is_some() {
+        logo_row = logo_row.push(button("Remove").on_press(Message::ClearReportLogo).padding(8));
+    }
+
+    let letterhead = container(
+        column![
+            text("Letterhead").size(20),
+            muted(
+                "Printed at the top of every page, with the time the report was made and who made it.",
+                app_theme
+            ),
+            row![
+                text("Company name").size(14).width(Length::Fixed(150.0)),
+                text_input("Inventory Manager", &settings.company_name)
+                    .on_input(Message::ReportCompanyNameChanged)
+                    .padding(8)
+                    .width(Length::Fixed(320.0)),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+            row![text("Logo").size(14).width(Length::Fixed(150.0)), logo_row]
+                .spacing(theme::SPACING_LG)
+                .align_y(iced::Alignment::Center),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme));
+
+    let mut list = column![text("Reports").size(20)].spacing(theme::SPACING_LG);
+    for kind in ReportKind::ALL {
+        let note = match kind {
+            ReportKind::StockList => Some(stock_list_filter.clone()),
+            ReportKind::LowStock => Some(format!("{} active alerts", alert_count)),
+            _ => None,
+        };
+        let mut details = column![
+            text(kind.to_string()).size(16),
+            muted(kind.description(), app_theme),
+        ]
+        .spacing(4)
+        .width(Length::Fill);
+        if let Some(note) = note {
+            details = details.push(text(note).size(theme::TEXT_CAPTION));
+        }
+
+        let mut save = button("Save PDF...").padding(8);
+        if kind == ReportKind::AuditTrail {
+            details = details.push(
+                row![
+                    text("From").size(14),
+                    text_input("YYYY-MM-DD", form.from)
+                        .on_input(Message::ReportFromChanged)
+                        .padding(6)
+                        .width(Length::Fixed(130.0)),
+                    text("to").size(14),
+                    text_input("YYYY-MM-DD", form.to)
+                        .on_input(Message::ReportToChanged)
+                        .padding(6)
+                        .width(Length::Fixed(130.0)),
+                    muted("(UTC)", app_theme),
+                ]
+                .spacing(theme::SPACING_SM)
+                .align_y(iced::Alignment::Center),
+            );
+            if role.can_view_audit() {
+                save = save.on_press(Message::GenerateReport(kind));
+            } else {
+                details = details.push(muted("Only managers and administrators can print the audit trail", app_theme));
+            }
+        } else {
+            save = save.on_press(Message::GenerateReport(kind));
+        }
+
+        list = list.push(
+            row![details, save]
+                .spacing(theme::SPACING_LG)
+                .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+    if let Some(status) = form.status {
+        content = content.push(text(status).size(theme::TEXT_BODY));
+    }
+    content = content.push(letterhead).push(
+        container(list.padding(15))
+            .width(Length::Fill)
+            .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+    );
+
+    scrollable(content).into()
+}
@@ -0,0 +1,355 @@
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input};
+use iced::{Element, Length};
+use std::collections::HashMap;
+
+use crate::currency;
+use crate::icons;
+use crate::inventory::InventoryItem;
+use crate::messages::{AppTheme, Message};
+use crate::returns::{self, Disposition, ReturnAuthorization, ReturnLine, ReturnReason};
+use crate::sales::{SalesOrder, SalesOrderStatus};
+use crate::stock::StockMovement;
+use crate::theme;
+use crate::views::sales::{muted, panel_style, PickOption};
+
+/// Current contents of the return entry form
+pub struct ReturnsForm<'a> {
+    pub sales_order_id: Option<&'a str>,
+    pub customer: &'a str,
+    pub reason: Option<ReturnReason>,
+    pub notes: &'a str,
+    pub line_item_id: Option<&'a str>,
+    pub line_quantity: &'a str,
+    pub line_disposition: Option<Disposition>,
+    pub draft_lines: &'a [ReturnLine],
+    pub error: Option<&'a str>,
+    /// Units typed per item in the quarantine list
+    pub quarantine_quantities: &'a HashMap<String, String>,
+}
+
+pub fn view<'a>(
+    returns: &'a [ReturnAuthorization],
+    sales_orders: &'a [SalesOrder],
+    items: &'a [InventoryItem],
+    movements: &'a [StockMovement],
+    form: ReturnsForm<'a>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Return.view(icons::IconSize::Large, app_theme),
+        text("Customer Returns (RMA)").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+
+    if let Some(error) = form.error {
+        content = content.push(
+            text(error)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::danger_color(app_theme)),
+                }),
+        );
+    }
+
+    content = content
+        .push(build_return_form(sales_orders, items, &form, currency_code, app_theme))
+        .push(build_return_list(returns, app_theme))
+        .push(build_report(returns, currency_code, app_theme))
+        .push(build_quarantine(items, movements, form.quarantine_quantities, app_theme));
+
+    scrollable(content).into()
+}
+
+fn build_return_form<'a>(
+    sales_orders: &'a [SalesOrder],
+    items: &'a [InventoryItem],
+    form: &ReturnsForm<'a>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let no_order = PickOption { id: String::new(), label: "No sales order".to_string() };
+    let mut order_options = vec![no_order.clone()];
+    order_options.extend(
+        sales_orders
+            .iter()
+            .filter(|o| o.status == SalesOrderStatus::Fulfilled)
+            .rev()
+            .map(|o| PickOption {
+                id: o.id.clone(),
+                label: format!("{} — {}", o.number, o.customer_name),
+            }),
+    );
+    let selected_order = match form.sales_order_id {
+        Some(id) => order_options.iter().find(|o| o.id == id).cloned(),
+        None => Some(no_order),
+    };
+
+    // When linked to an order only its items can be returned
+    let linked_order = form
+        .sales_order_id
+        .and_then(|id| sales_orders.iter().find(|o| o.id == id));
+    let mut item_options: Vec<PickOption> = match linked_order {
+        Some(order) => order
+            .lines
+            .iter()
+            .map(|l| PickOption {
+                id: l.item_id.clone(),
+                label: format!("{} — {} ({} shipped)", l.item_sku, l.item_name, l.quantity),
+            })
+            .collect(),
+        None => items
+            .iter()
+            .map(|i| PickOption { id: i.id.clone(), label: format!("{} — {}", i.sku, i.name) })
+            .collect(),
+    };
+    item_options.sort_by_key(|o| o.label.to_lowercase());
+    let selected_item = form
+        .line_item_id
+        .and_then(|id| item_options.iter().find(|o| o.id == id).cloned());
+
+    let header = row![
+        column![
+            text("Sales Order").size(14),
+            pick_list(order_options, selected_order, |o: PickOption| {
+                Message::ReturnSalesOrderSelected(o.id)
+            })
+            .padding(8)
+            .width(Length::Fixed(240.0)),
+        ]
+        .spacing(5),
+        column![
+            text("Customer").size(14),
+            text_input("Customer name", form.customer)
+                .on_input(Message::ReturnCustomerChanged)
+                .padding(8),
+        ]
+        .spacing(5)
+        .width(Length::Fill),
+        column![
+            text("Reason").size(14),
+            pick_list(ReturnReason::ALL, form.reason, Message::ReturnReasonSelected)
+                .placeholder("Select reason")
+                .padding(8)
+                .width(Length::Fixed(180.0)),
+        ]
+        .spacing(5),
+    ]
+    .spacing(10);
+
+    let line_entry = row![
+        pick_list(item_options, selected_item, |o: PickOption| {
+            Message::ReturnLineItemSelected(o.id)
+        })
+        .placeholder("Select item")
+        .padding(8)
+        .width(Length::Fill),
+        text_input("Qty", form.line_quantity)
+            .on_input(Message::ReturnLineQuantityChanged)
+            .on_submit(Message::AddReturnLine)
+            .padding(8)
+            .width(Length::Fixed(80.0)),
+        pick_list(Disposition::ALL, form.line_disposition, Message::ReturnLineDispositionSelected)
+            .placeholder("Disposition")
+            .padding(8)
+            .width(Length::Fixed(170.0)),
+        button("Add Line").on_press(Message::AddReturnLine).padding(8),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+
+    let mut lines = column![].spacing(theme::SPACING_XS);
+    for (index, line) in form.draft_lines.iter().enumerate() {
+        lines = lines.push(
+            row![
+                text(format!("{} — {}", line.item_sku, line.item_name))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fill),
+                text(format!("× {}", line.quantity)).size(theme::TEXT_BODY),
+                text(line.disposition.to_string())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(130.0)),
+                text(currency::format_currency_with_exp(line.total(), currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(110.0)),
+                button(icons::Icon::Close.view(icons::IconSize::Small, app_theme))
+                    .on_press(Message::RemoveReturnLine(index))
+                    .padding(4),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+    if form.draft_lines.is_empty() {
+        lines = lines.push(muted("No lines added", app_theme));
+    }
+
+    container(
+        column![
+            text("New Return").size(20),
+            header,
+            text_input("Notes (optional)", form.notes)
+                .on_input(Message::ReturnNotesChanged)
+                .padding(8),
+            line_entry,
+            lines,
+            button("Record Return").on_press(Message::CreateReturn).padding(8),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+    .into()
+}
+
+fn build_return_list<'a>(
+    returns: &'a [ReturnAuthorization],
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut list = column![text("Returns").size(20)].spacing(theme::SPACING_SM);
+
+    if returns.is_empty() {
+        list = list.push(muted("No returns recorded", app_theme));
+    }
+
+    for rma in returns.iter().rev().take(50) {
+        let dispositions = rma
+            .lines
+            .iter()
+            .map(|l| format!("{} × {} → {}", l.quantity, l.item_sku, l.disposition))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        list = list.push(
+            row![
+                text(&rma.number).size(theme::TEXT_BODY).width(Length::Fixed(110.0)),
+                text(rma.sales_order_number.clone().unwrap_or_else(|| "—".to_string()))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(100.0)),
+                text(&rma.customer_name).size(theme::TEXT_BODY).width(Length::FillPortion(2)),
+                text(rma.reason.to_string()).size(theme::TEXT_BODY).width(Length::Fixed(130.0)),
+                text(dispositions).size(theme::TEXT_CAPTION).width(Length::FillPortion(3)),
+                text(rma.formatted_timestamp())
+                    .size(theme::TEXT_CAPTION)
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(theme::text_tertiary_color(app_theme)),
+                    }),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    container(list.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
+
+fn build_report<'a>(
+    returns: &'a [ReturnAuthorization],
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let rows = returns::returns_report(returns);
+
+    let mut export_button = button("Export CSV").padding(6);
+    if !rows.is_empty() {
+        export_button = export_button.on_press(Message::ExportReturnsReport);
+    }
+
+    let mut report = column![
+        row![text("Returns by Item and Reason").size(20), export_button]
+            .spacing(20)
+            .align_y(iced::Alignment::Center),
+        row![
+            text("SKU").size(theme::TEXT_CAPTION).width(Length::FillPortion(1)),
+            text("Item").size(theme::TEXT_CAPTION).width(Length::FillPortion(2)),
+            text("Reason").size(theme::TEXT_CAPTION).width(Length::FillPortion(1)),
+            text("Returns").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Units").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Value").size(theme::TEXT_CAPTION).width(Length::Fixed(110.0)),
+        ]
+        .spacing(theme::SPACING_LG),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    if rows.is_empty() {
+        report = report.push(muted("No returns to report", app_theme));
+    }
+
+    for row_data in rows {
+        report = report.push(
+            row![
+                text(row_data.item_sku).size(theme::TEXT_BODY).width(Length::FillPortion(1)),
+                text(row_data.item_name).size(theme::TEXT_BODY).width(Length::FillPortion(2)),
+                text(row_data.reason.to_string()).size(theme::TEXT_BODY).width(Length::FillPortion(1)),
+                text(row_data.returns).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+                text(row_data.quantity).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+                text(currency::format_currency_with_exp(row_data.value, currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(110.0)),
+            ]
+            .spacing(theme::SPACING_LG),
+        );
+    }
+
+    container(report.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
+
+fn build_quarantine<'a>(
+    items: &'a [InventoryItem],
+    movements: &'a [StockMovement],
+    quantities: &'a HashMap<String, String>,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let quarantined = returns::quarantined_by_item(movements);
+
+    let mut list = column![text("In Quarantine").size(20)].spacing(theme::SPACING_SM);
+    if quarantined.is_empty() {
+        list = list.push(muted("Nothing held in quarantine", app_theme));
+    }
+    for (item_id, quantity) in quarantined {
+        let label = items
+            .iter()
+            .find(|i| i.id == item_id)
+            .map(|i| format!("{} — {}", i.sku, i.name))
+            .unwrap_or_else(|| "Deleted item".to_string());
+        let input = quantities.get(&item_id).map(String::as_str).unwrap_or_default();
+        let resolve = |label: &'a str, disposition: Disposition| {
+            button(text(label).size(theme::TEXT_BODY))
+                .on_press(Message::ResolveQuarantine(item_id.clone(), disposition))
+                .padding(6)
+        };
+        list = list.push(
+            row![
+                text(label).size(theme::TEXT_BODY).width(Length::Fill),
+                text(format!("{} units", quantity)).size(theme::TEXT_BODY),
+                text_input("All", input)
+                    .on_input({
+                        let item_id = item_id.clone();
+                        move |value| Message::QuarantineQuantityChanged(item_id.clone(), value)
+                    })
+                    .padding(6)
+                    .width(Length::Fixed(70.0)),
+                resolve("Release", Disposition::Restock),
+                resolve("Return to Vendor", Disposition::ReturnToVendor),
+                resolve("Write Off", Disposition::Scrap),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    container(list.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+use iced::widget::{button, checkbox, column, container, pick_list, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+
+use crate::audit::AuditEntry;
+use crate::export::ExportJob;
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::rules::{ActionDraft, ActionKind, ItemField, Rule, RuleDraft, TriggerKind};
+use crate::schedule::{self, Frequency};
+use crate::theme;
+use crate::user::UserRole;
+use crate::views::sales::{muted, panel_style};
+
+/// Recipient offered for notifying whoever is logged in
+const EVERYONE: &str = "Whoever is logged in";
+
+/// Current contents of the rules editor
+pub struct RuleForm<'a> {
+    pub draft: &'a RuleDraft,
+    pub status: Option<&'a str>,
+    pub preview: Option<&'a [String]>,
+    pub usernames: Vec<String>,
+    pub jobs: &'a [ExportJob],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct JobChoice {
+    id: String,
+    name: String,
+}
+
+impl fmt::Display for JobChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+pub fn view<'a>(
+    rules: &'a [Rule],
+    form: RuleForm<'a>,
+    firings: Vec<&'a AuditEntry>,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Lightbulb.view(icons::IconSize::Large, app_theme),
+        text("Rules").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![
+        title,
+        muted(
+            "When something happens to an item that meets a condition, do something. Rules run while the app is \
+             open and someone is logged in, and every firing is recorded in the audit log.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(20);
+
+    if !role.can_view_audit() {
+        return scrollable(content.push(muted("Only managers and administrators can manage rules", app_theme))).into();
+    }
+
+    content = content.push(
+        container(editor(&form, app_theme))
+            .width(Length::Fill)
+            .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+    );
+    if let Some(preview) = form.preview {
+        let mut lines = column![text("Preview").size(20)].spacing(6);
+        for line in preview {
+            lines = lines.push(text(line).size(theme::TEXT_BODY));
+        }
+        content = content.push(
+            container(lines.padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+    }
+
+    content = content
+        .push(
+            container(rule_list(rules, form.jobs, app_theme).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        )
+        .push(
+            container(firing_log(firings, app_theme).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+
+    scrollable(content).into()
+}
+
+fn editor<'a>(form: &RuleForm<'a>, app_theme: &'a AppTheme) -> Column<'a, Message> {
+    let draft = form.draft;
+    let heading = if draft.editing.is_some() { "Edit Rule" } else { "New Rule" };
+
+    let mut when = row![
+        text("When").size(14),
+        pick_list(TriggerKind::ALL, Some(draft.trigger), Message::RuleTriggerChanged).width(150),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+    if draft.trigger == TriggerKind::Schedule {
+        let time_label = if draft.frequency == Frequency::Hourly { "minute" } else { "at" };
+        when = when
+            .push(pick_list(Frequency::ALL, Some(draft.frequency), Message::RuleFrequencyChanged).width(120))
+            .push(text(time_label).size(14))
+            .push(text_input("08:00", &draft.time).on_input(Message::RuleTimeChanged).padding(5).width(80));
+        if draft.frequency == Frequency::Weekly {
+            when = when
+                .push(text("on").size(14))
+                .push(pick_list(schedule::WEEKDAYS, Some(draft.weekday), Message::RuleWeekdayChanged).width(90));
+        }
+    }
+
+    let mut actions = Column::new().spacing(theme::SPACING_SM);
+    for (index, action) in draft.actions.iter().enumerate() {
+        actions = actions.push(action_row(index, action, form, app_theme));
+    }
+
+    let mut editor = column![
+        text(heading).size(20),
+        text_input("Rule name, e.g. Reorder consumables", &draft.name)
+            .on_input(Message::RuleNameChanged)
+            .padding(8)
+            .width(Length::Fixed(360.0)),
+        when,
+        row![
+            text("If").size(14),
+            text_input("category is Consumables and quantity < 20", &draft.condition)
+                .on_input(Message::RuleConditionChanged)
+                .padding(8)
+                .width(Length::Fill),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        muted(
+            "Test text, category, supplier, class, quantity or price with is, is not, contains, is empty, \
+             =, !=, <, <=, > or >=, joining tests with and. Quote values with spaces. Leave empty for every item.",
+            app_theme
+        ),
+        text("Then").size(14),
+        actions,
+        muted(
+            "Texts can use {name}, {sku}, {category}, {supplier}, {location} and {quantity}. Users who are \
+             not logged in get their notifications when they next log in.",
+            app_theme
+        ),
+        button("Add Action").on_press(Message::AddRuleAction).padding(6),
+        checkbox("Dry run: only record in the audit log what the rule would do", draft.dry_run)
+            .on_toggle(Message::RuleDryRunToggled),
+        row![
+            button("Save Rule").on_press(Message::SaveRule).padding(8),
+            button("Preview").on_press(Message::PreviewRule).padding(8),
+            button(if draft.editing.is_some() { "Cancel" } else { "Clear" }).on_press(Message::NewRule).padding(8),
+        ]
+        .spacing(theme::SPACING_SM),
+    ]
+    .spacing(10)
+    .padding(15);
+    if let Some(status) = form.status {
+        editor = editor.push(text(status).size(12).color(theme::danger_color(app_theme)));
+    }
+    editor
+}
+
+fn action_row<'a>(
+    index: usize,
+    action: &'a ActionDraft,
+    form: &RuleForm<'a>,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let text_box = |placeholder: &'a str| {
+        text_input(placeholder, &action.text)
+            .on_input(move |value| Message::RuleActionTextChanged(index, value))
+            .padding(5)
+            .width(Length::Fill)
+    };
+
+    let details: Element<'a, Message> = match action.kind {
+        ActionKind::Notify => {
+            let mut recipients = vec![EVERYONE.to_string()];
+            recipients.extend(form.usernames.iter().cloned());
+            let selected = if action.target.is_empty() { EVERYONE.to_string() } else { action.target.clone() };
+            row![
+                pick_list(recipients, Some(selected), move |name: String| {
+                    Message::RuleActionTargetChanged(index, if name == EVERYONE { String::new() } else { name })
+                })
+                .width(200),
+                text_box("Message, e.g. {name} is down to {quantity}"),
+            ]
+            .spacing(theme::SPACING_SM)
+            .into()
+        }
+        ActionKind::SetField => row![
+            pick_list(ItemField::ALL, Some(action.field), move |field| {
+                Message::RuleActionFieldChanged(index, field)
+            })
+            .width(200),
+            text_box("Value, e.g. needs-review"),
+        ]
+        .spacing(theme::SPACING_SM)
+        .into(),
+        ActionKind::CreateNote => row![
+            text_input("Note title, e.g. Check {name}", &action.target)
+                .on_input(move |value| Message::RuleActionTargetChanged(index, value))
+                .padding(5)
+                .width(Length::Fixed(200.0)),
+            text_box("Note text"),
+        ]
+        .spacing(theme::SPACING_SM)
+        .into(),
+        ActionKind::RaiseAlert => text_box("Alert message, e.g. Check {name} for damage").into(),
+        ActionKind::DraftPurchaseOrder => muted("Suggested reorder quantity, on one draft per supplier", app_theme),
+        ActionKind::RunExport if form.jobs.is_empty() => {
+            muted("No scheduled export jobs yet; add one in Settings", app_theme)
+        }
+        ActionKind::RunExport => {
+            let jobs: Vec<JobChoice> = form
+                .jobs
+                .iter()
+                .map(|job| JobChoice {
+                    id: job.id.clone(),
+                    name: job.name.clone(),
+                })
+                .collect();
+            let selected = jobs.iter().find(|job| job.id == action.target).cloned();
+            pick_list(jobs, selected, move |job: JobChoice| Message::RuleActionTargetChanged(index, job.id))
+                .placeholder("Choose an export job")
+                .width(260)
+                .into()
+        }
+    };
+
+    row![
+        pick_list(ActionKind::ALL, Some(action.kind), move |kind| Message::RuleActionKindChanged(index, kind))
+            .width(190),
+        details,
+        button("Remove").on_press(Message::RemoveRuleAction(index)).padding(6),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+fn rule_list<'a>(rules: &'a [Rule], jobs: &'a [ExportJob], app_theme: &'a AppTheme) -> Column<'a, Message> {
+    let mut list = column![text("Rules").size(20)].spacing(theme::SPACING_SM);
+    if rules.is_empty() {
+        return list.push(muted("No rules yet", app_theme));
+    }
+    for rule in rules {
+        let name = if rule.dry_run { format!("{} (dry run)", rule.name) } else { rule.name.clone() };
+        let condition = if rule.condition.is_empty() {
+            "If any item".to_string()
+        } else {
+            format!("If {}", rule.condition)
+        };
+        let actions: Vec<String> = rule.actions.iter().map(|action| action.describe(None, jobs)).collect();
+        let id = rule.id.clone();
+        list = list.push(
+            row![
+                column![
+                    text(name).size(16),
+                    text(rule.trigger.to_string()).size(12),
+                    text(condition).size(12),
+                    text(format!("Then {}", actions.join(", "))).size(12),
+                    text(format!("Last fired: {}", rule.formatted_last_fired()))
+                        .size(12)
+                        .color(theme::text_secondary_color(app_theme)),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                checkbox("Active", rule.active).on_toggle(move |active| Message::ToggleRule(id.clone(), active)),
+                button("Edit").on_press(Message::EditRule(rule.id.clone())).padding(6),
+                button("Remove").on_press(Message::RemoveRule(rule.id.clone())).padding(6),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+    list
+}
+
+fn firing_log<'a>(firings: Vec<&'a AuditEntry>, app_theme: &'a AppTheme) -> Column<'a, Message> {
+    let mut log = column![text("Recent Firings").size(20)].spacing(theme::SPACING_SM);
+    if firings.is_empty() {
+        return log.push(muted("No rule has fired yet", app_theme));
+    }
+    for entry in firings {
+        log = log.push(
+            row![
+                text(entry.formatted_timestamp())
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(150.0)),
+                text(&entry.details).size(theme::TEXT_BODY).width(Length::Fill),
+            ]
+            .spacing(theme::SPACING_SM),
+        );
+    }
+    log
+}
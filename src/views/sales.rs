@@ -0,0 +1,328 @@
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::currency;
+use crate::icons;
+use crate::inventory::InventoryItem;
+use crate::messages::{AppTheme, Message};
+use crate::sales::{Customer, SalesOrder, SalesOrderLine, SalesOrderStatus};
+use crate::theme;
+use crate::user::UserRole;
+
+/// Current contents of the customer and order entry forms
+pub struct SalesForm<'a> {
+    pub customer_name: &'a str,
+    pub customer_email: &'a str,
+    pub customer_phone: &'a str,
+    pub customer_address: &'a str,
+    pub selected_customer_id: Option<&'a str>,
+    pub selected_item_id: Option<&'a str>,
+    pub line_quantity: &'a str,
+    pub draft_lines: &'a [SalesOrderLine],
+    pub error: Option<&'a str>,
+}
+
+/// Entry in a pick list that maps a display label back to an entity id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickOption {
+    pub id: String,
+    pub label: String,
+}
+
+impl std::fmt::Display for PickOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+pub fn view<'a>(
+    customers: &'a [Customer],
+    orders: &'a [SalesOrder],
+    items: &'a [InventoryItem],
+    form: SalesForm<'a>,
+    currency_code: &'a str,
+    current_user_role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Dollar.view(icons::IconSize::Large, app_theme),
+        text("Customers & Sales Orders").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+
+    if let Some(error) = form.error {
+        content = content.push(
+            text(error)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::danger_color(app_theme)),
+                }),
+        );
+    }
+
+    if current_user_role.can_create() {
+        content = content.push(build_customer_form(&form, app_theme));
+    }
+    content = content.push(build_customer_list(customers, current_user_role, app_theme));
+    if current_user_role.can_create() {
+        content = content.push(build_order_form(customers, items, &form, currency_code, app_theme));
+    }
+    content = content.push(build_order_list(orders, currency_code, current_user_role, app_theme));
+
+    scrollable(content).into()
+}
+
+fn build_customer_form<'a>(form: &SalesForm<'a>, app_theme: &'a AppTheme) -> Element<'a, Message> {
+    let field = |label: &'a str, value: &str, on_input: fn(String) -> Message| {
+        column![
+            text(label).size(14),
+            text_input(label, value).on_input(on_input).padding(8),
+        ]
+        .spacing(5)
+        .width(Length::Fill)
+    };
+
+    container(
+        column![
+            text("Add Customer").size(20),
+            row![
+                field("Name", form.customer_name, Message::CustomerNameChanged),
+                field("Email", form.customer_email, Message::CustomerEmailChanged),
+                field("Phone", form.customer_phone, Message::CustomerPhoneChanged),
+                field("Address", form.customer_address, Message::CustomerAddressChanged),
+                column![
+                    text("").size(14),
+                    button("Add Customer").on_press(Message::CreateCustomer).padding(8),
+                ]
+                .spacing(5),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+    .into()
+}
+
+fn build_customer_list<'a>(
+    customers: &'a [Customer],
+    current_user_role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut list = column![text(format!("Customers ({})", customers.len())).size(20)]
+        .spacing(theme::SPACING_SM);
+
+    if customers.is_empty() {
+        list = list.push(muted("No customers yet", app_theme));
+    }
+
+    for customer in customers {
+        let contact = [customer.email.as_str(), customer.phone.as_str()]
+            .iter()
+            .filter(|v| !v.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" · ");
+
+        let mut line = row![
+            text(&customer.name).size(theme::TEXT_BODY_LARGE).width(Length::FillPortion(2)),
+            text(contact).size(theme::TEXT_BODY).width(Length::FillPortion(3)),
+            text(&customer.address).size(theme::TEXT_BODY).width(Length::FillPortion(3)),
+        ]
+        .spacing(theme::SPACING_LG)
+        .align_y(iced::Alignment::Center);
+
+        if current_user_role.can_delete() {
+            line = line.push(
+                button(icons::Icon::Delete.view(icons::IconSize::Small, app_theme))
+                    .on_press(Message::DeleteCustomer(customer.id.clone()))
+                    .padding(6),
+            );
+        }
+        list = list.push(line);
+    }
+
+    container(list.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
+
+fn build_order_form<'a>(
+    customers: &'a [Customer],
+    items: &'a [InventoryItem],
+    form: &SalesForm<'a>,
+    currency_code: &'a str,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let customer_options: Vec<PickOption> = customers
+        .iter()
+        .map(|c| PickOption { id: c.id.clone(), label: c.name.clone() })
+        .collect();
+    let selected_customer = form
+        .selected_customer_id
+        .and_then(|id| customer_options.iter().find(|o| o.id == id).cloned());
+
+    let mut item_options: Vec<PickOption> = items
+        .iter()
+        .map(|i| PickOption {
+            id: i.id.clone(),
+            label: format!("{} — {} ({} available)", i.sku, i.name, i.available()),
+        })
+        .collect();
+    item_options.sort_by_key(|o| o.label.to_lowercase());
+    let selected_item = form
+        .selected_item_id
+        .and_then(|id| item_options.iter().find(|o| o.id == id).cloned());
+
+    let customer_picker = pick_list(customer_options, selected_customer, |o: PickOption| {
+        Message::SalesCustomerSelected(o.id)
+    })
+    .placeholder("Select customer")
+    .padding(8)
+    .width(Length::Fixed(220.0));
+
+    let item_picker = pick_list(item_options, selected_item, |o: PickOption| {
+        Message::SalesLineItemSelected(o.id)
+    })
+    .placeholder("Select item")
+    .padding(8)
+    .width(Length::Fill);
+
+    let quantity_input = text_input("Qty", form.line_quantity)
+        .on_input(Message::SalesLineQuantityChanged)
+        .on_submit(Message::AddSalesLine)
+        .padding(8)
+        .width(Length::Fixed(80.0));
+
+    let mut lines = column![].spacing(theme::SPACING_XS);
+    let mut total = 0.0;
+    for (index, line) in form.draft_lines.iter().enumerate() {
+        total += line.total();
+        lines = lines.push(
+            row![
+                text(format!("{} — {}", line.item_sku, line.item_name))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fill),
+                text(format!("× {}", line.quantity)).size(theme::TEXT_BODY),
+                text(currency::format_currency_with_exp(line.total(), currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(110.0)),
+                button(icons::Icon::Close.view(icons::IconSize::Small, app_theme))
+                    .on_press(Message::RemoveSalesLine(index))
+                    .padding(4),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+    if form.draft_lines.is_empty() {
+        lines = lines.push(muted("No lines added", app_theme));
+    } else {
+        lines = lines.push(
+            text(format!("Order total: {}", currency::format_currency_with_exp(total, currency_code)))
+                .size(theme::TEXT_BODY),
+        );
+    }
+
+    container(
+        column![
+            text("New Sales Order").size(20),
+            row![
+                customer_picker,
+                item_picker,
+                quantity_input,
+                button("Add Line").on_press(Message::AddSalesLine).padding(8),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            lines,
+            button("Create Order (reserve stock)")
+                .on_press(Message::CreateSalesOrder)
+                .padding(8),
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+    .into()
+}
+
+fn build_order_list<'a>(
+    orders: &'a [SalesOrder],
+    currency_code: &'a str,
+    current_user_role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut list = column![text("Sales Orders").size(20)].spacing(theme::SPACING_SM);
+
+    if orders.is_empty() {
+        list = list.push(muted("No sales orders yet", app_theme));
+    }
+
+    for order in orders.iter().rev() {
+        let mut actions = row![].spacing(theme::SPACING_SM);
+        if order.status == SalesOrderStatus::Open && current_user_role.can_edit() {
+            actions = actions
+                .push(button("Fulfill").on_press(Message::FulfillSalesOrder(order.id.clone())).padding(6))
+                .push(button("Cancel").on_press(Message::CancelSalesOrder(order.id.clone())).padding(6));
+        }
+        actions = actions.push(
+            button("Packing Slip")
+                .on_press(Message::ExportPackingSlip(order.id.clone()))
+                .padding(6),
+        );
+
+        list = list.push(
+            row![
+                text(&order.number).size(theme::TEXT_BODY).width(Length::Fixed(100.0)),
+                text(&order.customer_name).size(theme::TEXT_BODY).width(Length::Fill),
+                text(order.status.to_string()).size(theme::TEXT_BODY).width(Length::Fixed(80.0)),
+                text(format!("{} units", order.total_units())).size(theme::TEXT_BODY),
+                text(currency::format_currency_with_exp(order.total(), currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(110.0)),
+                text(order.formatted_timestamp())
+                    .size(theme::TEXT_CAPTION)
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(theme::text_tertiary_color(app_theme)),
+                    }),
+                actions,
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    container(list.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
+
+pub(crate) fn muted<'a>(value: &'a str, app_theme: &'a AppTheme) -> Element<'a, Message> {
+    text(value)
+        .size(theme::TEXT_BODY)
+        .style(move |_theme: &iced::Theme| text::Style {
+            color: Some(theme::text_tertiary_color(app_theme)),
+        })
+        .into()
+}
+
+pub(crate) fn panel_style(app_theme: &AppTheme) -> container::Style {
+    container::Style {
+        background: Some(iced::Background::Color(theme::surface_color(app_theme))),
+        border: iced::Border {
+            color: theme::border_color(app_theme),
+            width: 1.0,
+            radius: 8.0.into(),
+        },
+        ..Default::default()
+    }
+}
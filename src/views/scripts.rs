@@ -0,0 +1,229 @@
+use iced::widget::{button, column, container, row, scrollable, text, text_editor, text_input, Column};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::scripting::{ComputedColumn, ReportScript};
+use crate::theme;
+use crate::user::UserRole;
+use crate::views::sales::{muted, panel_style};
+
+/// Computed columns and the column editor
+pub struct ColumnForm<'a> {
+    pub columns: &'a [ComputedColumn],
+    /// Per column: items it failed for, and the first error
+    pub errors: &'a [Option<(usize, String)>],
+    pub editing: Option<&'a str>,
+    pub name: &'a str,
+    pub script: &'a str,
+    pub status: Option<&'a str>,
+}
+
+/// Report scripts, the report editor and the last report run
+pub struct ReportForm<'a> {
+    pub reports: &'a [ReportScript],
+    pub editing: Option<&'a str>,
+    pub name: &'a str,
+    pub content: &'a text_editor::Content,
+    pub status: Option<&'a str>,
+    pub output: Option<&'a (String, Vec<String>)>,
+}
+
+pub fn view<'a>(
+    columns: ColumnForm<'a>,
+    reports: ReportForm<'a>,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Calculator.view(icons::IconSize::Large, app_theme),
+        text("Scripts").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![
+        title,
+        muted(
+            "Scripts are written in Rhai. They can read the data but not change it, and can't reach files or \
+             the network. A script that runs too long is stopped.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(20);
+
+    if !role.can_view_audit() {
+        return scrollable(content.push(muted("Only managers and administrators can use scripts", app_theme))).into();
+    }
+
+    let can_edit = role.can_manage_users();
+    content = content
+        .push(
+            container(column_panel(&columns, can_edit, app_theme).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        )
+        .push(
+            container(report_panel(&reports, can_edit, app_theme).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+
+    if let Some((name, lines)) = reports.output {
+        let mut output = column![text(name.as_str()).size(20)].spacing(4);
+        if lines.is_empty() {
+            output = output.push(muted("The report printed nothing", app_theme));
+        }
+        for line in lines {
+            output = output.push(text(line).size(theme::TEXT_BODY).font(iced::Font::MONOSPACE));
+        }
+        content = content.push(
+            container(output.padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+    }
+
+    scrollable(content).into()
+}
+
+fn column_panel<'a>(form: &ColumnForm<'a>, can_edit: bool, app_theme: &'a AppTheme) -> Column<'a, Message> {
+    let mut panel = column![
+        text("Computed Columns").size(20),
+        muted(
+            "Shown in the inventory table and CSV exports. A column is worked out for each item, which is \
+             available as item: e.g. item.price * 1.08 + 0.35 for a landed cost.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    if form.columns.is_empty() {
+        panel = panel.push(muted("No computed columns yet", app_theme));
+    }
+    for (index, column) in form.columns.iter().enumerate() {
+        let mut details = column![
+            text(&column.name).size(16),
+            text(&column.script).size(12).font(iced::Font::MONOSPACE),
+        ]
+        .spacing(2)
+        .width(Length::Fill);
+        if let Some(Some((failures, error))) = form.errors.get(index) {
+            let items = if *failures == 1 { "item" } else { "items" };
+            details = details.push(
+                text(format!("Fails for {} {}, first {}", failures, items, error))
+                    .size(12)
+                    .color(theme::danger_color(app_theme)),
+            );
+        }
+        let mut entry = row![details].spacing(theme::SPACING_LG).align_y(iced::Alignment::Center);
+        if can_edit {
+            entry = entry
+                .push(button("Edit").on_press(Message::EditComputedColumn(column.id.clone())).padding(6))
+                .push(button("Remove").on_press(Message::RemoveComputedColumn(column.id.clone())).padding(6));
+        }
+        panel = panel.push(entry);
+    }
+
+    if !can_edit {
+        return panel.push(muted("Only administrators can define computed columns", app_theme));
+    }
+    panel = panel
+        .push(text(if form.editing.is_some() { "Edit Column" } else { "New Column" }).size(16))
+        .push(
+            row![
+                text_input("Column name, e.g. Landed cost", form.name)
+                    .on_input(Message::ComputedColumnNameChanged)
+                    .padding(8)
+                    .width(Length::Fixed(240.0)),
+                text_input("item.price * 1.08 + 0.35", form.script)
+                    .on_input(Message::ComputedColumnScriptChanged)
+                    .on_submit(Message::SaveComputedColumn)
+                    .font(iced::Font::MONOSPACE)
+                    .padding(8)
+                    .width(Length::Fill),
+            ]
+            .spacing(theme::SPACING_SM),
+        )
+        .push(muted(
+            "Items have name, sku, category, supplier, description, location, quantity, allocated, available, \
+             price, reorder_point, reorder_quantity, lead_time_days, daily_demand, class, created_at and updated_at.",
+            app_theme,
+        ))
+        .push(
+            row![
+                button("Save Column").on_press(Message::SaveComputedColumn).padding(8),
+                button(if form.editing.is_some() { "Cancel" } else { "Clear" })
+                    .on_press(Message::ClearComputedColumn)
+                    .padding(8),
+            ]
+            .spacing(theme::SPACING_SM),
+        );
+    if let Some(status) = form.status {
+        panel = panel.push(text(status).size(12).color(theme::danger_color(app_theme)));
+    }
+    panel
+}
+
+fn report_panel<'a>(form: &ReportForm<'a>, can_edit: bool, app_theme: &'a AppTheme) -> Column<'a, Message> {
+    let mut panel = column![
+        text("Report Scripts").size(20),
+        muted(
+            "A report reads items, notes and audit, and shows what it prints followed by its last value.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    if form.reports.is_empty() {
+        panel = panel.push(muted("No report scripts yet", app_theme));
+    }
+    for report in form.reports {
+        let mut entry = row![
+            text(&report.name).size(16).width(Length::Fill),
+            button("Run").on_press(Message::RunReportScript(report.id.clone())).padding(6),
+        ]
+        .spacing(theme::SPACING_LG)
+        .align_y(iced::Alignment::Center);
+        if can_edit {
+            entry = entry
+                .push(button("Edit").on_press(Message::EditReportScript(report.id.clone())).padding(6))
+                .push(button("Remove").on_press(Message::RemoveReportScript(report.id.clone())).padding(6));
+        }
+        panel = panel.push(entry);
+    }
+
+    if can_edit {
+        panel = panel
+            .push(text(if form.editing.is_some() { "Edit Report" } else { "New Report" }).size(16))
+            .push(
+                text_input("Report name, e.g. Stock by supplier", form.name)
+                    .on_input(Message::ReportScriptNameChanged)
+                    .padding(8)
+                    .width(Length::Fixed(360.0)),
+            )
+            .push(
+                text_editor(form.content)
+                    .placeholder("for item in items { if item.available == 0 { print(item.name); } }")
+                    .on_action(Message::ReportScriptAction)
+                    .font(iced::Font::MONOSPACE)
+                    .padding(10)
+                    .height(Length::Fixed(200.0)),
+            )
+            .push(
+                row![
+                    button("Save Report").on_press(Message::SaveReportScript).padding(8),
+                    button("Run").on_press(Message::RunReportDraft).padding(8),
+                    button(if form.editing.is_some() { "Cancel" } else { "Clear" })
+                        .on_press(Message::ClearReportScript)
+                        .padding(8),
+                ]
+                .spacing(theme::SPACING_SM),
+            );
+    }
+    if let Some(status) = form.status {
+        panel = panel.push(text(status).size(12).color(theme::danger_color(app_theme)));
+    }
+    panel
+}
@@ -1,23 +1,94 @@
 use iced::{Element, Length};
 use iced::widget::{button, checkbox, column, container, pick_list, row, scrollable, text, text_input};
 
+use crate::export::{ExportJob, JobFormat};
 use crate::messages::{AppSettings, AppTheme, LayoutStyle, Message};
+use crate::schedule::{self, Frequency};
+use crate::persistence::StorageBackend;
 use crate::theme;
 use crate::icons;
 use crate::currency;
 
+/// Current contents of the encryption inputs
+pub struct EncryptionForm<'a> {
+    pub enabled: bool,
+    pub current_passphrase: &'a str,
+    pub new_passphrase: &'a str,
+    pub confirm_passphrase: &'a str,
+    pub file_passphrase: &'a str,
+    pub status: Option<&'a str>,
+}
+
+/// Current contents of the scheduled export inputs
+pub struct ExportJobsForm<'a> {
+    pub running: &'a std::collections::HashSet<String>,
+    pub name: &'a str,
+    pub format: JobFormat,
+    pub frequency: Frequency,
+    pub time: &'a str,
+    pub weekday: chrono::Weekday,
+    pub keep: &'a str,
+    pub use_filter: bool,
+    /// The inventory filter a new job would save
+    pub filter: String,
+    pub strip_sensitive: bool,
+    pub folder: Option<&'a std::path::Path>,
+    pub status: Option<&'a str>,
+    pub can_manage: bool,
+}
+
+/// Current contents of the HTTP API inputs
+pub struct ApiForm<'a> {
+    pub server_status: Option<&'a Result<u16, String>>,
+    pub port: &'a str,
+    pub token_name: &'a str,
+    pub token_user: Option<&'a String>,
+    /// Id and username of every user
+    pub users: Vec<(String, String)>,
+    pub new_token: Option<&'a str>,
+    pub status: Option<&'a str>,
+    pub can_manage: bool,
+}
+
+/// Current contents of the general settings inputs and their status messages
+pub struct GeneralForm<'a> {
+    pub interval: &'a str,
+    pub category: &'a str,
+    pub notification_throttle: &'a str,
+    pub import_error: Option<&'a str>,
+    pub storage_status: Option<&'a str>,
+}
+
+/// State of the update check
+pub struct UpdateStatus<'a> {
+    pub latest_version: Option<&'a crate::update_checker::UpdateInfo>,
+    pub checking: bool,
+    pub downloading: bool,
+    pub message: Option<&'a str>,
+}
+
 pub fn view<'a>(
     settings: &'a AppSettings,
-    interval_input: &'a str,
-    category_input: &'a str,
-    notification_throttle_input: &'a str,
-    latest_version: Option<&'a crate::update_checker::UpdateInfo>,
-    import_error: Option<&'a str>,
-    checking_for_updates: bool,
-    downloading_update: bool,
-    update_message: Option<&'a str>,
+    general: GeneralForm<'a>,
+    updates: UpdateStatus<'a>,
+    encryption: EncryptionForm<'a>,
+    export_jobs: ExportJobsForm<'a>,
+    api: ApiForm<'a>,
     theme: &'a crate::messages::AppTheme,
 ) -> Element<'a, Message> {
+    let GeneralForm {
+        interval: interval_input,
+        category: category_input,
+        notification_throttle: notification_throttle_input,
+        import_error,
+        storage_status,
+    } = general;
+    let UpdateStatus {
+        latest_version,
+        checking: checking_for_updates,
+        downloading: downloading_update,
+        message: update_message,
+    } = updates;
     let title = text("Settings").size(32);
     let currency_options = currency::options();
     let selected_currency = currency_options
@@ -42,7 +113,7 @@ pub fn view<'a>(
         ]
         .spacing(10)
         .align_y(iced::Alignment::Center),
-        text("Note: Changes are saved automatically when auto-save is enabled")
+        text("Changes are collected and saved in the background once per interval, and always when you log out or close the app")
             .size(12)
             .style(move |_iced_theme: &iced::Theme| {
                 iced::widget::text::Style {
@@ -287,7 +358,7 @@ pub fn view<'a>(
                 }
             }),
         row![
-            button("Export Data")
+            button("Export Data...")
                 .on_press(Message::ExportData)
                 .padding(10)
                 .style(
@@ -321,14 +392,14 @@ pub fn view<'a>(
                 ),
         ]
         .spacing(10),
-        text("Export saves data to Desktop as JSON file")
+        text("Export lets you choose what to include and where to save the JSON file")
             .size(12)
             .style(move |_iced_theme: &iced::Theme| {
                 iced::widget::text::Style {
                     color: Some(crate::theme::text_secondary_color(theme)),
                 }
             }),
-        text("Import opens a file picker to select a JSON file")
+        text("Import opens a file picker to select a JSON file, then shows what will be merged before anything changes")
             .size(12)
             .style(move |_iced_theme: &iced::Theme| {
                 iced::widget::text::Style {
@@ -347,6 +418,66 @@ pub fn view<'a>(
             text("").size(1)
         },
         text("").size(10),
+        row![
+            button("Backups...").on_press(Message::ShowBackups).padding(10),
+            text(match settings.backups.frequency {
+                crate::persistence::BackupFrequency::Off => "Automatic backups are off".to_string(),
+                frequency => format!("{} backups, keeping the last {}", frequency, settings.backups.keep),
+            })
+            .size(12)
+            .style(move |_iced_theme: &iced::Theme| {
+                iced::widget::text::Style {
+                    color: Some(crate::theme::text_secondary_color(theme)),
+                }
+            }),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center),
+        row![
+            button("Workspaces...")
+                .on_press_maybe(
+                    crate::persistence::workspace::fixed_data_dir()
+                        .is_none()
+                        .then_some(Message::ShowWorkspaces)
+                )
+                .padding(10),
+            text(if crate::persistence::workspace::fixed_data_dir().is_some() {
+                "The data folder was set with --data-dir".to_string()
+            } else if crate::persistence::workspace::is_portable() {
+                "Portable mode: data is kept next to the program".to_string()
+            } else {
+                "Switching workspaces logs you out".to_string()
+            })
+            .size(12)
+            .style(move |_iced_theme: &iced::Theme| {
+                iced::widget::text::Style {
+                    color: Some(crate::theme::text_secondary_color(theme)),
+                }
+            }),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center),
+        row![
+            text("Storage backend:").size(14),
+            pick_list(
+                StorageBackend::ALL,
+                Some(settings.storage_backend),
+                Message::StorageBackendChanged
+            )
+            .width(200),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center),
+        text(storage_status.unwrap_or(
+            "Switching copies all data into the new backend. SQLite only writes the records that changed."
+        ))
+            .size(12)
+            .style(move |_iced_theme: &iced::Theme| {
+                iced::widget::text::Style {
+                    color: Some(crate::theme::text_secondary_color(theme)),
+                }
+            }),
+        text("").size(10),
         button("Clear All Data")
             .on_press(Message::ClearAllData)
             .padding(10)
@@ -520,7 +651,7 @@ pub fn view<'a>(
             }),
         text(format!(
             "{}",
-            crate::persistence::data_file_path().display()
+            crate::persistence::data_location().display()
         ))
         .size(11)
         .style(move |_iced_theme: &iced::Theme| {
@@ -590,6 +721,39 @@ pub fn view<'a>(
                     ..Default::default()
                 }
             }),
+            container(encryption_section(encryption, theme)).style(move |_iced_theme: &iced::Theme| {
+                container::Style {
+                    background: Some(iced::Background::Color(crate::theme::surface_color(theme))),
+                    border: iced::Border {
+                        color: crate::theme::border_color(theme),
+                        width: 1.0,
+                        radius: 5.0.into(),
+                    },
+                    ..Default::default()
+                }
+            }),
+            container(export_jobs_section(&settings.export_jobs, export_jobs, theme)).style(move |_iced_theme: &iced::Theme| {
+                container::Style {
+                    background: Some(iced::Background::Color(crate::theme::surface_color(theme))),
+                    border: iced::Border {
+                        color: crate::theme::border_color(theme),
+                        width: 1.0,
+                        radius: 5.0.into(),
+                    },
+                    ..Default::default()
+                }
+            }),
+            container(api_section(settings, api, theme)).style(move |_iced_theme: &iced::Theme| {
+                container::Style {
+                    background: Some(iced::Background::Color(crate::theme::surface_color(theme))),
+                    border: iced::Border {
+                        color: crate::theme::border_color(theme),
+                        width: 1.0,
+                        radius: 5.0.into(),
+                    },
+                    ..Default::default()
+                }
+            }),
             container(update_section).style(move |_iced_theme: &iced::Theme| {
                 container::Style {
                     background: Some(iced::Background::Color(crate::theme::surface_color(theme))),
@@ -619,3 +783,342 @@ pub fn view<'a>(
 
     content
 }
+
+fn encryption_section<'a>(form: EncryptionForm<'a>, theme: &'a AppTheme) -> Element<'a, Message> {
+    let secondary = move |_iced_theme: &iced::Theme| iced::widget::text::Style {
+        color: Some(crate::theme::text_secondary_color(theme)),
+    };
+    let passphrase_input = |placeholder: &'a str, value: &'a str, on_input: fn(String) -> Message| {
+        text_input(placeholder, value)
+            .secure(true)
+            .on_input(on_input)
+            .padding(8)
+            .width(300)
+    };
+
+    let mut section = column![
+        text("Encryption").size(20).style(move |_iced_theme: &iced::Theme| {
+            iced::widget::text::Style {
+                color: Some(crate::theme::text_color(theme)),
+            }
+        }),
+        text(if form.enabled {
+            "Data and backups are encrypted. The passphrase is asked for at startup."
+        } else {
+            "Data is stored unencrypted. Set a passphrase to encrypt the data and all backups."
+        })
+        .size(12)
+        .style(secondary),
+    ]
+    .spacing(10)
+    .padding(20);
+
+    if form.enabled {
+        section = section.push(passphrase_input(
+            "Current passphrase",
+            form.current_passphrase,
+            Message::CurrentPassphraseChanged,
+        ));
+    }
+    section = section
+        .push(passphrase_input("New passphrase", form.new_passphrase, Message::NewPassphraseChanged))
+        .push(passphrase_input(
+            "Confirm new passphrase",
+            form.confirm_passphrase,
+            Message::ConfirmPassphraseChanged,
+        ));
+
+    let mut actions = row![
+        button(if form.enabled { "Change Passphrase" } else { "Encrypt Data" })
+            .on_press(Message::ApplyPassphrase)
+            .padding(10),
+    ]
+    .spacing(10);
+    if form.enabled {
+        actions = actions.push(
+            button("Turn Off Encryption")
+                .on_press(Message::DisableEncryption)
+                .padding(10),
+        );
+    }
+    section = section.push(actions);
+
+    if let Some(status) = form.status {
+        section = section.push(text(status).size(12));
+    }
+
+    section = section
+        .push(text("").size(10))
+        .push(text("File passphrase:").size(14))
+        .push(passphrase_input(
+            "Optional",
+            form.file_passphrase,
+            Message::FilePassphraseChanged,
+        ))
+        .push(
+            text("When set, exports are encrypted with this passphrase, and encrypted files are opened with it on import.")
+                .size(12)
+                .style(secondary),
+        );
+
+    section.into()
+}
+
+fn export_jobs_section<'a>(jobs: &'a [ExportJob], form: ExportJobsForm<'a>, theme: &'a AppTheme) -> Element<'a, Message> {
+    let secondary = move |_iced_theme: &iced::Theme| iced::widget::text::Style {
+        color: Some(crate::theme::text_secondary_color(theme)),
+    };
+
+    let mut section = column![
+        text("Scheduled Exports").size(20).style(move |_iced_theme: &iced::Theme| {
+            iced::widget::text::Style {
+                color: Some(crate::theme::text_color(theme)),
+            }
+        }),
+        text("Jobs run while the app is open and someone is logged in. A run missed while the app was closed happens once at the next start. Files are not encrypted.")
+            .size(12)
+            .style(secondary),
+    ]
+    .spacing(10)
+    .padding(20);
+
+    let now = chrono::Local::now();
+    for job in jobs {
+        let next = job
+            .schedule
+            .next_occurrence(now)
+            .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let mut details = column![
+            text(&job.name).size(16),
+            text(format!("{}, {}, keeping {} files", job.format, job.schedule, job.keep)).size(12),
+            text(format!("To {}", job.folder.display())).size(12).style(secondary),
+        ]
+        .spacing(2)
+        .width(Length::Fill);
+        if let Some(filter) = &job.filter {
+            details = details.push(text(format!("Filter: {}", filter.describe())).size(12).style(secondary));
+        }
+        let last_run = if form.running.contains(&job.id) {
+            text("Running...").size(12)
+        } else {
+            match &job.last_run {
+                Some(run) if run.ok => text(format!("Last run {}: {}", run.formatted_timestamp(), run.message))
+                    .size(12)
+                    .color(crate::theme::success_color(theme)),
+                Some(run) => text(format!("Last run {} failed: {}", run.formatted_timestamp(), run.message))
+                    .size(12)
+                    .color(crate::theme::danger_color(theme)),
+                None => text("Not run yet").size(12).style(secondary),
+            }
+        };
+        details = details
+            .push(last_run)
+            .push(text(format!("Next run {}", next)).size(12).style(secondary));
+
+        let mut actions = row![button("Run Now").on_press(Message::RunExportJob(job.id.clone())).padding(8)].spacing(10);
+        if form.can_manage {
+            actions = actions.push(button("Remove").on_press(Message::RemoveExportJob(job.id.clone())).padding(8));
+        }
+        section = section.push(row![details, actions].spacing(10).align_y(iced::Alignment::Center));
+    }
+
+    if !form.can_manage {
+        return section
+            .push(text("Only managers and administrators can schedule exports.").size(12).style(secondary))
+            .into();
+    }
+
+    let time_label = if form.frequency == Frequency::Hourly { "Minute past the hour:" } else { "Time (HH:MM):" };
+    let mut schedule_row = row![
+        pick_list(Frequency::ALL, Some(form.frequency), Message::ExportJobFrequencyChanged).width(120),
+        text(time_label).size(14),
+        text_input("02:00", form.time).on_input(Message::ExportJobTimeChanged).padding(5).width(80),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+    if form.frequency == Frequency::Weekly {
+        schedule_row = schedule_row
+            .push(text("on").size(14))
+            .push(pick_list(schedule::WEEKDAYS, Some(form.weekday), Message::ExportJobWeekdayChanged).width(90));
+    }
+
+    let folder_name = form
+        .folder
+        .map(|folder| folder.display().to_string())
+        .unwrap_or_else(|| "No folder chosen".to_string());
+
+    section = section
+        .push(text("").size(10))
+        .push(text("New job").size(16))
+        .push(
+            row![
+                text_input("Job name, e.g. Nightly BI copy", form.name)
+                    .on_input(Message::ExportJobNameChanged)
+                    .padding(5)
+                    .width(260),
+                pick_list(JobFormat::ALL, Some(form.format), Message::ExportJobFormatChanged).width(220),
+            ]
+            .spacing(10),
+        )
+        .push(schedule_row)
+        .push(
+            row![
+                button("Choose Folder...").on_press(Message::PickExportJobFolder).padding(8),
+                text(folder_name).size(12),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        )
+        .push(
+            row![
+                text("Keep last").size(14),
+                text_input("7", form.keep).on_input(Message::ExportJobKeepChanged).padding(5).width(60),
+                text("files").size(14),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    if form.format.is_item_list() {
+        section = section.push(
+            checkbox(format!("Use the current inventory filter ({})", form.filter), form.use_filter)
+                .on_toggle(|_| Message::ToggleExportJobFilter),
+        );
+    } else {
+        section = section.push(
+            checkbox("Remove sensitive data (users, who made changes, prices)", form.strip_sensitive)
+                .on_toggle(|_| Message::ToggleExportJobStripSensitive),
+        );
+    }
+    section = section.push(button("Add Job").on_press(Message::AddExportJob).padding(10));
+    if let Some(status) = form.status {
+        section = section.push(text(status).size(12).color(crate::theme::danger_color(theme)));
+    }
+
+    section.into()
+}
+
+fn api_section<'a>(settings: &'a AppSettings, form: ApiForm<'a>, theme: &'a AppTheme) -> Element<'a, Message> {
+    let secondary = move |_iced_theme: &iced::Theme| iced::widget::text::Style {
+        color: Some(crate::theme::text_secondary_color(theme)),
+    };
+
+    let mut section = column![
+        text("HTTP API").size(20).style(move |_iced_theme: &iced::Theme| {
+            iced::widget::text::Style {
+                color: Some(crate::theme::text_color(theme)),
+            }
+        }),
+        text("Lets scripts on this computer read items, alerts and notes, add items and notes, and adjust stock while the app is open. Each script uses a token that acts as one user, with that user's role. Changes are recorded in the audit log.")
+            .size(12)
+            .style(secondary),
+    ]
+    .spacing(10)
+    .padding(20);
+
+    if !form.can_manage {
+        return section
+            .push(text("Only administrators can change API settings.").size(12).style(secondary))
+            .into();
+    }
+
+    section = section.push(
+        checkbox("Enable the HTTP API", settings.api.enabled).on_toggle(Message::ToggleApi),
+    );
+    section = section.push(
+        row![
+            text("Port on 127.0.0.1:").size(14),
+            text_input("8787", form.port).on_input(Message::ApiPortChanged).padding(5).width(80),
+            button("Apply").on_press(Message::ApplyApiPort).padding(8),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center),
+    );
+    if settings.api.enabled {
+        section = section.push(match form.server_status {
+            Some(Ok(port)) => text(format!("Listening on http://127.0.0.1:{}/api", port))
+                .size(12)
+                .color(crate::theme::success_color(theme)),
+            Some(Err(e)) => text(e.as_str()).size(12).color(crate::theme::danger_color(theme)),
+            None => text("Starting...").size(12).style(secondary),
+        });
+    }
+
+    section = section.push(text("").size(10)).push(text("Tokens").size(16));
+    if settings.api.tokens.is_empty() {
+        section = section.push(text("No tokens yet").size(12).style(secondary));
+    }
+    for token in &settings.api.tokens {
+        let username = form
+            .users
+            .iter()
+            .find(|(id, _)| *id == token.user_id)
+            .map_or("a removed user", |(_, username)| username.as_str());
+        section = section.push(
+            row![
+                column![
+                    text(&token.name).size(14),
+                    text(format!("Acts as {}, created {}", username, token.formatted_timestamp()))
+                        .size(12)
+                        .style(secondary),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                button("Revoke").on_press(Message::RevokeApiToken(token.id.clone())).padding(8),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    if let Some(secret) = form.new_token {
+        section = section.push(
+            column![
+                text("Copy the new token now; it is not shown again:").size(12),
+                text_input("", secret).padding(5).width(Length::Fill),
+                row![
+                    button("Copy").on_press(Message::CopyApiToken).padding(8),
+                    button("Done").on_press(Message::DismissApiToken).padding(8),
+                ]
+                .spacing(10),
+            ]
+            .spacing(6),
+        );
+    }
+
+    let usernames: Vec<String> = form.users.iter().map(|(_, username)| username.clone()).collect();
+    section = section.push(
+        row![
+            text_input("Token name, e.g. Front till", form.token_name)
+                .on_input(Message::ApiTokenNameChanged)
+                .padding(5)
+                .width(220),
+            pick_list(usernames, form.token_user.cloned(), Message::ApiTokenUserChanged)
+                .placeholder("Acts as user")
+                .width(160),
+            button("Create Token").on_press(Message::CreateApiToken).padding(8),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center),
+    );
+    if let Some(status) = form.status {
+        section = section.push(text(status).size(12).color(crate::theme::danger_color(theme)));
+    }
+
+    section = section.push(text("").size(10)).push(
+        row![
+            button("Webhooks...").on_press(Message::ShowWebhooks).padding(10),
+            text(match settings.webhooks.len() {
+                0 => "Post item, alert and import events to other systems".to_string(),
+                1 => "1 webhook registered".to_string(),
+                count => format!("{} webhooks registered", count),
+            })
+            .size(12)
+            .style(secondary),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center),
+    );
+
+    section.into()
+}
@@ -0,0 +1,275 @@
+use iced::widget::{button, checkbox, column, container, pick_list, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::currency;
+use crate::icons;
+use crate::inventory::InventoryItem;
+use crate::messages::{AppTheme, Message};
+use crate::stocktake::{CountSession, CountStatus, ScopeKind};
+use crate::theme;
+use crate::user::UserRole;
+use crate::views::sales::{muted, panel_style, PickOption};
+
+/// Current selections on the stocktake screen
+pub struct StocktakeForm<'a> {
+    pub scope_kind: ScopeKind,
+    pub scope_value: Option<&'a str>,
+    pub active_session_id: Option<&'a str>,
+    pub scan_input: &'a str,
+    pub message: Option<&'a str>,
+}
+
+pub fn view<'a>(
+    sessions: &'a [CountSession],
+    items: &'a [InventoryItem],
+    form: StocktakeForm<'a>,
+    currency_code: &'a str,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Clipboard.view(icons::IconSize::Large, app_theme),
+        text("Stocktake").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![title].spacing(theme::SPACING_LG).padding(20);
+
+    if let Some(message) = form.message {
+        content = content.push(
+            text(message)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::text_secondary_color(app_theme)),
+                }),
+        );
+    }
+
+    content = content
+        .push(build_new_count(items, &form, app_theme))
+        .push(build_session_list(sessions, &form, app_theme));
+
+    if let Some(session) = form
+        .active_session_id
+        .and_then(|id| sessions.iter().find(|s| s.id == id))
+    {
+        content = content.push(build_session(session, form.scan_input, currency_code, role, app_theme));
+    }
+
+    scrollable(content).into()
+}
+
+fn build_new_count<'a>(
+    items: &'a [InventoryItem],
+    form: &StocktakeForm<'a>,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut scope_row = row![
+        pick_list(ScopeKind::ALL, Some(form.scope_kind), Message::StocktakeScopeKindSelected)
+            .padding(8)
+            .width(Length::Fixed(160.0)),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+
+    if form.scope_kind != ScopeKind::All {
+        let values = form.scope_kind.values(items);
+        let selected = form.scope_value.map(str::to_string);
+        scope_row = scope_row.push(
+            pick_list(values, selected, Message::StocktakeScopeValueSelected)
+                .placeholder(format!("Select {}", form.scope_kind.to_string().to_lowercase()))
+                .padding(8)
+                .width(Length::Fixed(240.0)),
+        );
+    }
+
+    scope_row = scope_row
+        .push(button("Start Count").on_press(Message::StartStocktake).padding(8))
+        .push(button("Print Blank Sheet").on_press(Message::PrintBlankCountSheet).padding(8));
+
+    container(
+        column![
+            text("New Count").size(20),
+            muted("Expected quantities are snapshotted when the count starts", app_theme),
+            scope_row,
+        ]
+        .spacing(10)
+        .padding(15),
+    )
+    .width(Length::Fill)
+    .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+    .into()
+}
+
+fn build_session_list<'a>(
+    sessions: &'a [CountSession],
+    form: &StocktakeForm<'a>,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut list = column![text("Count Sessions").size(20)].spacing(theme::SPACING_SM);
+
+    if sessions.is_empty() {
+        list = list.push(muted("No counts recorded", app_theme));
+    }
+
+    let options: Vec<PickOption> = sessions
+        .iter()
+        .rev()
+        .map(|s| PickOption {
+            id: s.id.clone(),
+            label: format!("{} — {} ({})", s.number, s.scope, s.status),
+        })
+        .collect();
+    if !options.is_empty() {
+        let selected = form
+            .active_session_id
+            .and_then(|id| options.iter().find(|o| o.id == id).cloned());
+        list = list.push(
+            pick_list(options, selected, |o: PickOption| Message::SelectStocktake(o.id))
+                .placeholder("Open a count session")
+                .padding(8)
+                .width(Length::Fixed(420.0)),
+        );
+    }
+
+    container(list.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
+
+fn build_session<'a>(
+    session: &'a CountSession,
+    scan_input: &'a str,
+    currency_code: &'a str,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let in_progress = session.status == CountStatus::InProgress;
+    let can_approve = in_progress && role.can_delete();
+
+    let mut panel = column![
+        text(format!("{} — {}", session.number, session.scope)).size(20),
+        text(format!(
+            "{} · started {} by {} · {} of {} counted",
+            session.status,
+            session.formatted_timestamp(),
+            session.created_by,
+            session.counted_lines(),
+            session.lines.len()
+        ))
+        .size(theme::TEXT_BODY)
+        .style(move |_theme: &iced::Theme| text::Style {
+            color: Some(theme::text_tertiary_color(app_theme)),
+        }),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    if in_progress {
+        panel = panel.push(
+            text_input("Scan or type a SKU and press Enter", scan_input)
+                .on_input(Message::StocktakeScanInputChanged)
+                .on_submit(Message::StocktakeScanSubmitted)
+                .padding(8)
+                .width(Length::Fixed(320.0)),
+        );
+    }
+
+    panel = panel.push(
+        row![
+            text("SKU").size(theme::TEXT_CAPTION).width(Length::FillPortion(1)),
+            text("Item").size(theme::TEXT_CAPTION).width(Length::FillPortion(2)),
+            text("Location").size(theme::TEXT_CAPTION).width(Length::FillPortion(1)),
+            text("Expected").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Counted").size(theme::TEXT_CAPTION).width(Length::Fixed(80.0)),
+            text("Variance").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Value").size(theme::TEXT_CAPTION).width(Length::Fixed(110.0)),
+            text("Approve").size(theme::TEXT_CAPTION).width(Length::Fixed(60.0)),
+        ]
+        .spacing(theme::SPACING_LG),
+    );
+
+    for line in &session.lines {
+        let counted_value = line.counted.map(|c| c.to_string()).unwrap_or_default();
+        let counted: Element<'a, Message> = if in_progress {
+            let item_id = line.item_id.clone();
+            text_input("—", &counted_value)
+                .on_input(move |value| Message::StocktakeCountChanged(item_id.clone(), value))
+                .padding(4)
+                .width(Length::Fixed(80.0))
+                .into()
+        } else {
+            text(if counted_value.is_empty() { "—".to_string() } else { counted_value })
+                .size(theme::TEXT_BODY)
+                .width(Length::Fixed(80.0))
+                .into()
+        };
+
+        let variance_color = match line.variance() {
+            Some(v) if v < 0 => theme::danger_color(app_theme),
+            Some(v) if v > 0 => theme::primary_color(app_theme),
+            _ => theme::text_secondary_color(app_theme),
+        };
+        let variance = line
+            .variance()
+            .map(|v| format!("{:+}", v))
+            .unwrap_or_else(|| "—".to_string());
+
+        let mut approve = checkbox("", line.approved);
+        if can_approve && line.counted.is_some() {
+            let item_id = line.item_id.clone();
+            approve = approve.on_toggle(move |_| Message::ToggleStocktakeApproval(item_id.clone()));
+        }
+
+        panel = panel.push(
+            row![
+                text(&line.item_sku).size(theme::TEXT_BODY).width(Length::FillPortion(1)),
+                text(&line.item_name).size(theme::TEXT_BODY).width(Length::FillPortion(2)),
+                text(&line.location).size(theme::TEXT_BODY).width(Length::FillPortion(1)),
+                text(line.expected).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+                counted,
+                text(variance)
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(70.0))
+                    .style(move |_theme: &iced::Theme| text::Style {
+                        color: Some(variance_color),
+                    }),
+                text(currency::format_currency_with_exp(line.variance_value(), currency_code))
+                    .size(theme::TEXT_BODY)
+                    .width(Length::Fixed(110.0)),
+                container(approve).width(Length::Fixed(60.0)),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    panel = panel.push(
+        text(format!(
+            "Net variance: {:+} units · {}",
+            session.variance_units(),
+            currency::format_currency_with_exp(session.variance_value(), currency_code)
+        ))
+        .size(theme::TEXT_BODY),
+    );
+
+    if in_progress {
+        let mut approve_all = button("Approve All Variances").padding(8);
+        let mut post = button("Post Adjustments").padding(8);
+        if can_approve {
+            approve_all = approve_all.on_press(Message::ApproveAllStocktakeVariances);
+            post = post.on_press(Message::PostStocktake);
+        }
+        let mut actions = row![approve_all, post].spacing(10);
+        if role.can_edit() {
+            actions = actions.push(button("Cancel Count").on_press(Message::CancelStocktake).padding(8));
+        }
+        panel = panel.push(actions);
+    }
+
+    container(panel.padding(15))
+        .width(Length::Fill)
+        .style(move |_iced_theme: &iced::Theme| panel_style(app_theme))
+        .into()
+}
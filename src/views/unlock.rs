@@ -0,0 +1,75 @@
+use iced::widget::{button, column, container, row, text, text_input};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::views::sales::panel_style;
+
+/// Asks for the data passphrase before the login screen
+pub fn view<'a>(
+    passphrase: &'a str,
+    error_message: Option<&'a str>,
+    unlocking: bool,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let mut input = text_input("Passphrase", passphrase)
+        .secure(true)
+        .padding(theme::SPACING_LG)
+        .size(theme::TEXT_BODY);
+    if !unlocking {
+        input = input
+            .on_input(Message::UnlockPassphraseChanged)
+            .on_submit(Message::Unlock);
+    }
+
+    let mut unlock_button = button(
+        container(text(if unlocking { "Unlocking..." } else { "Unlock" }).size(theme::TEXT_BODY_LARGE))
+            .center_x(Length::Fill),
+    )
+    .padding(theme::SPACING_LG)
+    .width(Length::Fill);
+    if !unlocking {
+        unlock_button = unlock_button.on_press(Message::Unlock);
+    }
+
+    let mut card = column![
+        row![
+            icons::Icon::Lock.view(icons::IconSize::Large, app_theme),
+            text("Data is encrypted").size(theme::TEXT_H2),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        text("Enter the passphrase to unlock your inventory data.")
+            .size(theme::TEXT_BODY)
+            .style(move |_theme: &iced::Theme| text::Style {
+                color: Some(theme::text_secondary_color(app_theme)),
+            }),
+        input,
+        unlock_button,
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(theme::SPACING_3XL)
+    .width(500);
+
+    if let Some(error) = error_message {
+        card = card.push(
+            text(error)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::danger_color(app_theme)),
+                }),
+        );
+    }
+
+    container(container(card).style(move |_iced_theme: &iced::Theme| panel_style(app_theme)))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .style(move |_theme: &iced::Theme| container::Style {
+            background: Some(iced::Background::Color(theme::bg_color(app_theme))),
+            ..Default::default()
+        })
+        .into()
+}
@@ -208,14 +208,3 @@ pub fn view<'a>(
 
     scrollable(container(content).width(Length::Fill)).into()
 }
-
-impl std::fmt::Display for UserRole {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            UserRole::Admin => write!(f, "Admin"),
-            UserRole::Manager => write!(f, "Manager"),
-            UserRole::User => write!(f, "User"),
-            UserRole::Viewer => write!(f, "Viewer"),
-        }
-    }
-}
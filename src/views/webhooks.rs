@@ -0,0 +1,181 @@
+use std::collections::BTreeSet;
+
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::theme;
+use crate::user::UserRole;
+use crate::views::sales::{muted, panel_style};
+use crate::webhooks::{Delivery, DeliveryStatus, Webhook, WebhookEvent};
+
+/// Current contents of the new webhook inputs
+pub struct WebhookForm<'a> {
+    pub name: &'a str,
+    pub url: &'a str,
+    pub events: &'a BTreeSet<WebhookEvent>,
+    pub status: Option<&'a str>,
+}
+
+pub fn view<'a>(
+    webhooks: &'a [Webhook],
+    deliveries: &'a [Delivery],
+    form: WebhookForm<'a>,
+    role: UserRole,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let title = row![
+        icons::Icon::Alerts.view(icons::IconSize::Large, app_theme),
+        text("Webhooks").size(28),
+    ]
+    .spacing(theme::SPACING_SM)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![
+        title,
+        muted(
+            "Posts inventory events as JSON to other systems. Each request carries an X-Inventory-Signature header, \
+             sha256= followed by the HMAC-SHA256 of the body keyed with the webhook's secret.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(20);
+
+    if !role.can_manage_users() {
+        return scrollable(content.push(muted("Only administrators can manage webhooks", app_theme))).into();
+    }
+
+    let mut events = Column::new().spacing(6);
+    for pair in WebhookEvent::ALL.chunks(3) {
+        let mut line = row![].spacing(theme::SPACING_LG);
+        for &event in pair {
+            line = line.push(
+                checkbox(event.to_string(), form.events.contains(&event))
+                    .on_toggle(move |_| Message::WebhookEventToggled(event))
+                    .width(Length::Fixed(180.0)),
+            );
+        }
+        events = events.push(line);
+    }
+    let mut register = column![
+        text("Register a Webhook").size(20),
+        row![
+            text_input("Name, e.g. Ordering system", form.name)
+                .on_input(Message::WebhookNameChanged)
+                .padding(8)
+                .width(Length::Fixed(240.0)),
+            text_input("https://example.com/hooks/inventory", form.url)
+                .on_input(Message::WebhookUrlChanged)
+                .padding(8)
+                .width(Length::Fill),
+        ]
+        .spacing(theme::SPACING_SM),
+        text("Send these events").size(14),
+        events,
+        button("Add Webhook").on_press(Message::AddWebhook).padding(8),
+    ]
+    .spacing(10)
+    .padding(15);
+    if let Some(status) = form.status {
+        register = register.push(text(status).size(12).color(theme::danger_color(app_theme)));
+    }
+
+    let mut list = column![text("Registered Webhooks").size(20)].spacing(theme::SPACING_SM);
+    if webhooks.is_empty() {
+        list = list.push(muted("No webhooks yet", app_theme));
+    }
+    for webhook in webhooks {
+        let events: Vec<&str> = webhook.events.iter().map(|event| event.key()).collect();
+        let id = webhook.id.clone();
+        list = list.push(
+            row![
+                column![
+                    text(&webhook.name).size(16),
+                    text(&webhook.url).size(12),
+                    text(events.join(", ")).size(12).color(theme::text_secondary_color(app_theme)),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                checkbox("Active", webhook.active).on_toggle(move |active| Message::ToggleWebhook(id.clone(), active)),
+                button("Copy Secret").on_press(Message::CopyWebhookSecret(webhook.id.clone())).padding(6),
+                button("Remove").on_press(Message::RemoveWebhook(webhook.id.clone())).padding(6),
+            ]
+            .spacing(theme::SPACING_LG)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    content = content
+        .push(
+            container(register)
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        )
+        .push(
+            container(list.padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        )
+        .push(
+            container(delivery_log(deliveries, app_theme).padding(15))
+                .width(Length::Fill)
+                .style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+        );
+
+    scrollable(content).into()
+}
+
+fn delivery_log<'a>(deliveries: &'a [Delivery], app_theme: &'a AppTheme) -> Column<'a, Message> {
+    let mut log = column![
+        text("Delivery Log").size(20),
+        muted(
+            "Failed deliveries are retried after 30 seconds, then after twice as long each time, up to 6 attempts. \
+             The log covers deliveries since the app was started.",
+            app_theme
+        ),
+    ]
+    .spacing(theme::SPACING_SM);
+    if deliveries.is_empty() {
+        return log.push(muted("Nothing sent yet", app_theme));
+    }
+
+    log = log.push(
+        row![
+            text("Created").size(theme::TEXT_CAPTION).width(Length::Fixed(150.0)),
+            text("Webhook").size(theme::TEXT_CAPTION).width(Length::Fixed(140.0)),
+            text("Event").size(theme::TEXT_CAPTION).width(Length::Fixed(130.0)),
+            text("Status").size(theme::TEXT_CAPTION).width(Length::Fixed(140.0)),
+            text("Attempts").size(theme::TEXT_CAPTION).width(Length::Fixed(70.0)),
+            text("Last result").size(theme::TEXT_CAPTION).width(Length::Fill),
+        ]
+        .spacing(theme::SPACING_SM),
+    );
+    for delivery in deliveries.iter().rev() {
+        let status_color = match delivery.status {
+            DeliveryStatus::Delivered => theme::success_color(app_theme),
+            DeliveryStatus::Failed => theme::danger_color(app_theme),
+            DeliveryStatus::Retrying(_) => theme::warning_color(app_theme),
+            DeliveryStatus::Sending => theme::text_secondary_color(app_theme),
+        };
+        let mut line = row![
+            text(delivery.formatted_timestamp()).size(theme::TEXT_BODY).width(Length::Fixed(150.0)),
+            text(&delivery.webhook_name).size(theme::TEXT_BODY).width(Length::Fixed(140.0)),
+            text(delivery.event.key()).size(theme::TEXT_BODY).width(Length::Fixed(130.0)),
+            text(delivery.status.to_string())
+                .size(theme::TEXT_BODY)
+                .color(status_color)
+                .width(Length::Fixed(140.0)),
+            text(delivery.attempts).size(theme::TEXT_BODY).width(Length::Fixed(70.0)),
+            text(delivery.last_result.as_deref().unwrap_or("")).size(theme::TEXT_BODY).width(Length::Fill),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center);
+        if delivery.status == DeliveryStatus::Failed {
+            line = line.push(button("Retry").on_press(Message::RetryWebhookDelivery(delivery.id.clone())).padding(6));
+        }
+        log = log.push(line);
+    }
+    log
+}
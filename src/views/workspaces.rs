@@ -0,0 +1,113 @@
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::icons;
+use crate::messages::{AppTheme, Message};
+use crate::persistence::WorkspaceList;
+use crate::theme;
+use crate::views::sales::{muted, panel_style};
+
+/// Lists the workspaces to open and lets new ones be added
+pub fn view<'a>(
+    workspaces: &'a WorkspaceList,
+    data_loaded: bool,
+    new_name: &'a str,
+    new_folder: Option<&'a std::path::Path>,
+    error_message: Option<&'a str>,
+    app_theme: &'a AppTheme,
+) -> Element<'a, Message> {
+    let secondary = move |_theme: &iced::Theme| text::Style {
+        color: Some(theme::text_secondary_color(app_theme)),
+    };
+
+    let mut list = column![].spacing(theme::SPACING_SM);
+    for workspace in &workspaces.workspaces {
+        let is_open = workspace.name == workspaces.active && data_loaded;
+        let mut remove = button("Remove").padding(6);
+        if workspace.name != workspaces.active {
+            remove = remove.on_press(Message::RemoveWorkspace(workspace.name.clone()));
+        }
+
+        list = list.push(
+            row![
+                column![
+                    text(&workspace.name).size(theme::TEXT_BODY_LARGE),
+                    text(workspace.directory().display().to_string())
+                        .size(theme::TEXT_CAPTION)
+                        .style(secondary),
+                ]
+                .spacing(theme::SPACING_XS)
+                .width(Length::Fill),
+                button(text(if is_open { "Return" } else { "Open" }))
+                    .on_press(Message::WorkspaceSelected(workspace.name.clone()))
+                    .padding(6),
+                remove,
+            ]
+            .spacing(theme::SPACING_MD)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let folder_label = match new_folder {
+        Some(folder) => folder.display().to_string(),
+        None => "Default location".to_string(),
+    };
+    let create = column![
+        text("New Workspace").size(theme::TEXT_H3),
+        row![
+            text_input("Name", new_name)
+                .on_input(Message::NewWorkspaceNameChanged)
+                .on_submit(Message::CreateWorkspace)
+                .padding(8)
+                .width(Length::Fill),
+            button("Choose Folder...").on_press(Message::PickWorkspaceFolder).padding(8),
+            button("Create").on_press(Message::CreateWorkspace).padding(8),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        text(folder_label).size(theme::TEXT_CAPTION).style(secondary),
+    ]
+    .spacing(theme::SPACING_SM);
+
+    let mut card = column![
+        row![
+            icons::Icon::Clipboard.view(icons::IconSize::Large, app_theme),
+            text("Choose a Workspace").size(theme::TEXT_H2),
+        ]
+        .spacing(theme::SPACING_SM)
+        .align_y(iced::Alignment::Center),
+        muted(
+            "Each workspace keeps its own items, users, settings and backups.",
+            app_theme
+        ),
+        list,
+        create,
+        muted("Removing a workspace only takes it off this list; its files are kept.", app_theme),
+    ]
+    .spacing(theme::SPACING_LG)
+    .padding(theme::SPACING_3XL)
+    .width(700);
+
+    if let Some(error) = error_message {
+        card = card.push(
+            text(error)
+                .size(theme::TEXT_BODY)
+                .style(move |_theme: &iced::Theme| text::Style {
+                    color: Some(theme::danger_color(app_theme)),
+                }),
+        );
+    }
+
+    container(
+        container(scrollable(card)).style(move |_iced_theme: &iced::Theme| panel_style(app_theme)),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .style(move |_theme: &iced::Theme| container::Style {
+        background: Some(iced::Background::Color(theme::bg_color(app_theme))),
+        ..Default::default()
+    })
+    .into()
+}